@@ -4,13 +4,14 @@
 use error_iter::ErrorIter as _;
 use log::error;
 use pixels::{Pixels, SurfaceTexture};
-use std::rc::Rc;
+use std::future::Future;
+use std::sync::Arc;
+use winit::application::ApplicationHandler;
 use winit::dpi::LogicalSize;
-use winit::event::{Event, WindowEvent};
-use winit::event_loop::EventLoop;
-use winit::keyboard::KeyCode;
-use winit::window::WindowBuilder;
-use winit_input_helper::WinitInputHelper;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, EventLoop, EventLoopProxy};
+use winit::keyboard::{KeyCode, PhysicalKey};
+use winit::window::{Window, WindowId};
 
 const WIDTH: u32 = 320;
 const HEIGHT: u32 = 240;
@@ -24,129 +25,184 @@ struct World {
     velocity_y: i16,
 }
 
+/// The window and pixel buffer, which are created when the application is resumed.
+///
+/// Creating the pixel buffer is asynchronous: the browser cannot block while the GPU adapter and
+/// device are requested. The finished pixel buffer is sent back to the event loop as a user event.
+struct App {
+    proxy: EventLoopProxy<Pixels<'static>>,
+    window: Option<Arc<Window>>,
+    pixels: Option<Pixels<'static>>,
+    world: World,
+}
+
 fn main() {
     #[cfg(target_arch = "wasm32")]
     {
         std::panic::set_hook(Box::new(console_error_panic_hook::hook));
         console_log::init_with_level(log::Level::Trace).expect("error initializing logger");
-
-        wasm_bindgen_futures::spawn_local(run());
     }
 
     #[cfg(not(target_arch = "wasm32"))]
+    env_logger::init();
+
+    let event_loop = EventLoop::with_user_event().build().unwrap();
+    let app = App {
+        proxy: event_loop.create_proxy(),
+        window: None,
+        pixels: None,
+        world: World::new(),
+    };
+
+    // The browser runs the event loop, so `spawn_app` returns immediately instead of blocking
+    #[cfg(target_arch = "wasm32")]
     {
-        env_logger::init();
+        use winit::platform::web::EventLoopExtWebSys;
+
+        event_loop.spawn_app(app);
+    }
 
-        pollster::block_on(run());
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let mut app = app;
+        event_loop.run_app(&mut app).unwrap();
     }
 }
 
-async fn run() {
-    let event_loop = EventLoop::new().unwrap();
-    let window = {
+/// Run `future` to completion: in the background on the Web, or by blocking on native targets.
+fn spawn(future: impl Future<Output = ()> + 'static) {
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(future);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pollster::block_on(future);
+}
+
+impl ApplicationHandler<Pixels<'static>> for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+
         let size = LogicalSize::new(WIDTH as f64, HEIGHT as f64);
-        WindowBuilder::new()
+        let attributes = Window::default_attributes()
             .with_title("Hello Pixels + Web")
             .with_inner_size(size)
-            .with_min_inner_size(size)
-            .build(&event_loop)
-            .expect("WindowBuilder error")
-    };
+            .with_min_inner_size(size);
 
-    let window = Rc::new(window);
+        #[cfg(target_arch = "wasm32")]
+        let attributes = {
+            use winit::platform::web::WindowAttributesExtWebSys;
 
-    #[cfg(target_arch = "wasm32")]
-    {
-        use wasm_bindgen::JsCast;
-        use winit::platform::web::WindowExtWebSys;
-
-        // Retrieve current width and height dimensions of browser client window
-        let get_window_size = || {
-            let client_window = web_sys::window().unwrap();
-            LogicalSize::new(
-                client_window.inner_width().unwrap().as_f64().unwrap(),
-                client_window.inner_height().unwrap().as_f64().unwrap(),
-            )
+            // Attach the winit canvas to the body element, and fill the browser client window
+            attributes
+                .with_append(true)
+                .with_inner_size(get_window_size())
         };
 
-        let window = Rc::clone(&window);
-
-        // Initialize winit window with current dimensions of browser client
-        window.set_inner_size(get_window_size());
-
-        let client_window = web_sys::window().unwrap();
-
-        // Attach winit canvas to body element
-        web_sys::window()
-            .and_then(|win| win.document())
-            .and_then(|doc| doc.body())
-            .and_then(|body| {
-                body.append_child(&web_sys::Element::from(window.canvas()))
-                    .ok()
-            })
-            .expect("couldn't append canvas to document body");
-
-        // Listen for resize event on browser client. Adjust winit window dimensions
-        // on event trigger
-        let closure = wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: web_sys::Event| {
-            let size = get_window_size();
-            window.set_inner_size(size)
-        }) as Box<dyn FnMut(_)>);
-        client_window
-            .add_event_listener_with_callback("resize", closure.as_ref().unchecked_ref())
-            .unwrap();
-        closure.forget();
-    }
+        let window = Arc::new(
+            event_loop
+                .create_window(attributes)
+                .expect("create_window error"),
+        );
+
+        #[cfg(target_arch = "wasm32")]
+        {
+            use wasm_bindgen::JsCast;
+
+            // Listen for resize event on browser client. Adjust winit window dimensions
+            // on event trigger
+            let window = Arc::clone(&window);
+            let closure =
+                wasm_bindgen::closure::Closure::wrap(Box::new(move |_e: web_sys::Event| {
+                    let _ = window.request_inner_size(get_window_size());
+                }) as Box<dyn FnMut(_)>);
+            web_sys::window()
+                .unwrap()
+                .add_event_listener_with_callback("resize", closure.as_ref().unchecked_ref())
+                .unwrap();
+            closure.forget();
+        }
 
-    let mut input = WinitInputHelper::new();
-    let mut pixels = {
         let window_size = window.inner_size();
         let surface_texture =
-            SurfaceTexture::new(window_size.width, window_size.height, window.as_ref());
-        Pixels::new_async(WIDTH, HEIGHT, surface_texture)
-            .await
-            .expect("Pixels error")
-    };
-    let mut world = World::new();
-
-    let res = event_loop.run(|event, elwt| {
-        // Draw the current frame
-        if let Event::WindowEvent {
-            event: WindowEvent::RedrawRequested,
-            ..
-        } = event
-        {
-            world.draw(pixels.frame_mut());
-            if let Err(err) = pixels.render() {
-                log_error("pixels.render", err);
-                elwt.exit();
-                return;
-            }
+            SurfaceTexture::new(window_size.width, window_size.height, Arc::clone(&window));
+        let proxy = self.proxy.clone();
+        spawn(async move {
+            let pixels = Pixels::new_async(WIDTH, HEIGHT, surface_texture)
+                .await
+                .expect("Pixels error");
+
+            // Sending fails only when the event loop has already exited
+            let _ = proxy.send_event(pixels);
+        });
+
+        self.window = Some(window);
+    }
+
+    fn user_event(&mut self, _event_loop: &ActiveEventLoop, pixels: Pixels<'static>) {
+        self.pixels = Some(pixels);
+        if let Some(window) = &self.window {
+            window.request_redraw();
         }
+    }
 
-        // Handle input events
-        if input.update(&event) {
-            // Close events
-            if input.key_pressed(KeyCode::Escape) || input.close_requested() {
-                elwt.exit();
-                return;
-            }
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested
+            | WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(KeyCode::Escape),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                ..
+            } => event_loop.exit(),
 
             // Resize the window
-            if let Some(size) = input.window_resized() {
-                if let Err(err) = pixels.resize_surface(size.width, size.height) {
-                    log_error("pixels.resize_surface", err);
-                    elwt.exit();
+            WindowEvent::Resized(size) => {
+                if let Some(pixels) = &mut self.pixels {
+                    if let Err(err) = pixels.resize_surface(size.width, size.height) {
+                        log_error("pixels.resize_surface", err);
+                        event_loop.exit();
+                    }
+                }
+            }
+
+            // Draw the current frame, until the pixel buffer is ready
+            WindowEvent::RedrawRequested => {
+                let Some(pixels) = &mut self.pixels else {
+                    return;
+                };
+
+                self.world.draw(pixels.frame_mut());
+                if let Err(err) = pixels.render() {
+                    log_error("pixels.render", err);
+                    event_loop.exit();
                     return;
                 }
+
+                // Update internal state and request a redraw
+                self.world.update();
+                if let Some(window) = &self.window {
+                    window.request_redraw();
+                }
             }
 
-            // Update internal state and request a redraw
-            world.update();
-            window.request_redraw();
+            _ => (),
         }
-    });
-    res.unwrap();
+    }
+}
+
+/// Retrieve current width and height dimensions of browser client window.
+#[cfg(target_arch = "wasm32")]
+fn get_window_size() -> LogicalSize<f64> {
+    let client_window = web_sys::window().unwrap();
+    LogicalSize::new(
+        client_window.inner_width().unwrap().as_f64().unwrap(),
+        client_window.inner_height().unwrap().as_f64().unwrap(),
+    )
 }
 
 fn log_error<E: std::error::Error + 'static>(method_name: &str, err: E) {
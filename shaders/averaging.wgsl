@@ -0,0 +1,33 @@
+// Draws the average of the last frames, which are kept in the layers of a texture array.
+//
+// sRGB textures decode on load and encode on write, so the frames are averaged in linear light.
+
+struct Locals {
+    // The number of layers holding a frame
+    count: u32,
+}
+@group(0) @binding(0) var r_tex_history: texture_2d_array<f32>;
+@group(0) @binding(1) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<u32>(position.xy);
+
+    var sum = vec4<f32>(0.0);
+    for (var layer = 0u; layer < r_locals.count; layer += 1u) {
+        sum += textureLoad(r_tex_history, pixel, layer, 0);
+    }
+
+    return sum / f32(max(r_locals.count, 1u));
+}
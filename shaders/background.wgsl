@@ -0,0 +1,46 @@
+// Fills the letterbox bars with a background image, scaled to the render target by its fit, or
+// tiled without filtering.
+
+struct Locals {
+    // Maps render target pixels to texture coordinates: xy scale, zw offset
+    uv_transform: vec4<f32>,
+    // The scaled pixel buffer in render target pixels: left, top, right, bottom
+    clip_rect: vec4<f32>,
+    // The size of one image pixel in render target pixels, and whether the image is tiled
+    tile: vec4<f32>,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let clip = r_locals.clip_rect;
+    if all(position.xy >= clip.xy) && all(position.xy < clip.zw) {
+        discard;
+    }
+
+    if r_locals.tile.z != 0.0 {
+        let texel = vec2<u32>(position.xy / r_locals.tile.xy) % textureDimensions(r_tex_color);
+        return textureLoad(r_tex_color, texel, 0);
+    }
+
+    // Outside of a contained image, the clear color shows
+    let uv = fma(position.xy, r_locals.uv_transform.xy, r_locals.uv_transform.zw);
+    if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) {
+        discard;
+    }
+
+    return textureSampleLevel(r_tex_color, r_tex_sampler, uv, 0.0);
+}
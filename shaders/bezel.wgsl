@@ -0,0 +1,46 @@
+// Fills the letterbox bars with a dimmed, blurred copy of the pixel buffer stretched to cover the
+// whole render target.
+
+struct Locals {
+    // Maps render target pixels to texture coordinates: xy scale, zw offset
+    uv_transform: vec4<f32>,
+    // The scaled pixel buffer in render target pixels: left, top, right, bottom
+    clip_rect: vec4<f32>,
+    brightness: f32,
+    // The distance between blur taps in texture coordinates
+    radius: f32,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let clip = r_locals.clip_rect;
+    if all(position.xy >= clip.xy) && all(position.xy < clip.zw) {
+        discard;
+    }
+
+    // A 5x5 box of bilinear taps, which is blurry enough once the taps are spread out
+    let uv = fma(position.xy, r_locals.uv_transform.xy, r_locals.uv_transform.zw);
+    var sum = vec3<f32>(0.0);
+    for (var y = -2; y <= 2; y++) {
+        for (var x = -2; x <= 2; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * r_locals.radius;
+            sum += textureSampleLevel(r_tex_color, r_tex_sampler, uv + offset, 0.0).rgb;
+        }
+    }
+
+    return vec4<f32>(sum / 25.0 * r_locals.brightness, 1.0);
+}
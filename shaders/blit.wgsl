@@ -0,0 +1,90 @@
+// A minimal scaling shader for downlevel targets.
+//
+// The full-screen triangle is generated from the vertex index, so no vertex buffer is bound, and
+// the sampled color is written unchanged.
+
+struct VertexOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+struct Locals {
+    transform: mat4x4<f32>,
+    // Unused; the minimal pipeline does not attenuate
+    attenuation: vec4<f32>,
+    // The sampled rectangle of the texture, see `Pixels::set_view_offset`
+    tex_rect: vec4<f32>,
+}
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    var out: VertexOutput;
+    let view_coord = fma(position, vec2<f32>(0.5, -0.5), vec2<f32>(0.5, 0.5));
+    out.tex_coord = r_locals.tex_rect.xy + view_coord * r_locals.tex_rect.zw;
+    out.position = r_locals.transform * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+// Draws a distortion mesh instead of the full-screen triangle
+@vertex
+fn vs_mesh(
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coord: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = r_locals.tex_rect.xy + tex_coord * r_locals.tex_rect.zw;
+    out.position = r_locals.transform * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, tex_coord);
+}
+
+// Indexed color, see `scale.wgsl`
+struct Palette {
+    colors: array<vec4<f32>, 256>,
+}
+@group(0) @binding(3) var<uniform> r_palette: Palette;
+
+@fragment
+fn fs_main_palette(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(r_tex_color));
+    let texel = clamp(vec2<i32>(floor(tex_coord * vec2<f32>(size))), vec2<i32>(0), size - 1);
+    let index = u32(round(textureLoad(r_tex_color, texel, 0).r * 255.0));
+    return r_palette.colors[index];
+}
+
+// Packed formats, see `scale.wgsl`
+@fragment
+fn fs_main_luma(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let luma = textureSample(r_tex_color, r_tex_sampler, tex_coord).r;
+    return vec4<f32>(srgb_to_linear(vec3<f32>(luma)), 1.0);
+}
+
+@fragment
+fn fs_main_rgb565(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(r_tex_color));
+    let texel = clamp(vec2<i32>(floor(tex_coord * vec2<f32>(size))), vec2<i32>(0), size - 1);
+    let bytes = textureLoad(r_tex_color, texel, 0);
+    let value = u32(round(bytes.r * 255.0)) | (u32(round(bytes.g * 255.0)) << 8u);
+    let rgb = vec3<u32>(value >> 11u, (value >> 5u) & 63u, value & 31u);
+    return vec4<f32>(srgb_to_linear(vec3<f32>(rgb) / vec3<f32>(31.0, 63.0, 31.0)), 1.0);
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
@@ -0,0 +1,22 @@
+// Blends a flat color over the whole render target, for screen flashes and fades.
+
+struct Locals {
+    color: vec4<f32>,
+}
+@group(0) @binding(0) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return r_locals.color;
+}
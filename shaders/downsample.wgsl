@@ -0,0 +1,36 @@
+// Downsamples a supersampled render target with a box filter.
+//
+// Each output pixel is the average of the `factor * factor` block of source texels it covers, so
+// the result is exact for any integer factor.
+
+struct Locals {
+    factor: u32,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let factor = r_locals.factor;
+    let origin = vec2<u32>(position.xy) * factor;
+
+    var sum = vec4<f32>(0.0);
+    for (var y = 0u; y < factor; y++) {
+        for (var x = 0u; x < factor; x++) {
+            sum += textureLoad(r_tex_color, origin + vec2<u32>(x, y), 0);
+        }
+    }
+
+    return sum / f32(factor * factor);
+}
@@ -0,0 +1,66 @@
+// Built-in post-processing effects, each drawn as a full-screen pass over the previous pass.
+//
+// `params` holds the settings of the effect, and `area` the rectangle (x, y, width, height) of
+// the scaled pixel buffer in render target pixels. `rows` is the number of visible pixel buffer
+// rows on the surface: the height of the view, or its width when the buffer is turned sideways.
+
+struct Locals {
+    params: vec4<f32>,
+    area: vec4<f32>,
+    size: vec2<f32>,
+    rows: f32,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+// Barrel distortion and vignette over the scaled pixel buffer, like a curved CRT screen.
+// params.x is the curvature, params.y the vignette strength.
+@fragment
+fn fs_crt(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    // Position relative to the center of the pixel buffer, from -1 to 1
+    let center = r_locals.area.xy + r_locals.area.zw * 0.5;
+    let offset = (position.xy - center) / (r_locals.area.zw * 0.5);
+
+    let warped = offset * (1.0 + r_locals.params.x * dot(offset, offset));
+    if any(abs(warped) > vec2<f32>(1.0)) {
+        return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    }
+
+    let uv = (center + warped * r_locals.area.zw * 0.5) / r_locals.size;
+    let color = textureSampleLevel(r_tex_color, r_tex_sampler, uv, 0.0);
+    let edge = (1.0 - warped * warped).x * (1.0 - warped * warped).y;
+    let vignette = mix(1.0, pow(edge, 0.25), r_locals.params.y);
+
+    return vec4<f32>(color.rgb * vignette, color.a);
+}
+
+// Darkens the bottom part of every pixel buffer row, like the gaps between CRT scanlines.
+// params.x is the darkening of the gaps, params.y the fraction of each row they cover.
+@fragment
+fn fs_scanlines(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = textureLoad(r_tex_color, vec2<u32>(position.xy), 0);
+    let row = (position.y - r_locals.area.y) * r_locals.rows / r_locals.area.w;
+    let gap = step(1.0 - r_locals.params.y, fract(row));
+
+    return vec4<f32>(color.rgb * (1.0 - gap * r_locals.params.x), color.a);
+}
+
+// Raises every color channel to the power of 1 / params.x, brightening midtones above 1.
+@fragment
+fn fs_gamma(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let color = textureLoad(r_tex_color, vec2<u32>(position.xy), 0);
+
+    return vec4<f32>(pow(color.rgb, vec3<f32>(1.0 / r_locals.params.x)), color.a);
+}
@@ -0,0 +1,32 @@
+// Gamma ramp: a per-channel lookup table applied to every output color, like the hardware ramp
+// of a display. Appended to `scale.wgsl` after `const LINEAR_OUTPUT: bool`, which is true when
+// the render target takes linear light, like sRGB and float formats. The ramp maps sRGB-encoded values.
+
+struct GammaRamp {
+    // The red, green, and blue ramp values for each 8-bit input value
+    entries: array<vec4<f32>, 256>,
+}
+@group(0) @binding(5) var<uniform> r_gamma_ramp: GammaRamp;
+
+fn gamma_ramp(color: vec4<f32>) -> vec4<f32> {
+    var encoded = saturate(color.rgb);
+    if LINEAR_OUTPUT {
+        encoded = linear_to_srgb(vec4<f32>(encoded, 1.0)).rgb;
+    }
+
+    // Interpolate between the two nearest entries of each channel
+    let position = encoded * 255.0;
+    let low = vec3<u32>(floor(position));
+    let high = min(low + 1u, vec3<u32>(255u));
+    let blend = fract(position);
+    var ramped = vec3<f32>(
+        mix(r_gamma_ramp.entries[low.r].r, r_gamma_ramp.entries[high.r].r, blend.r),
+        mix(r_gamma_ramp.entries[low.g].g, r_gamma_ramp.entries[high.g].g, blend.g),
+        mix(r_gamma_ramp.entries[low.b].b, r_gamma_ramp.entries[high.b].b, blend.b),
+    );
+
+    if LINEAR_OUTPUT {
+        ramped = srgb_to_linear(ramped);
+    }
+    return vec4<f32>(ramped, color.a);
+}
@@ -0,0 +1,40 @@
+// Draws lines between the pixels of the scaled pixel buffer.
+//
+// The full-screen triangle is generated from the vertex index and clipped to the scaled pixel
+// buffer with a scissor rectangle. Fragments which are not on a grid line are discarded.
+
+struct Locals {
+    color: vec4<f32>,
+    // Top-left corner of the scaled pixel buffer in physical pixels
+    origin: vec2<f32>,
+    // Width and height of one buffer pixel in physical pixels
+    scale: vec2<f32>,
+    // Width of the grid lines in physical pixels
+    line_width: f32,
+}
+@group(0) @binding(0) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let offset = floor(position.xy) - r_locals.origin;
+
+    // Lines are drawn on the leading edge of every buffer pixel except the first row and column
+    let on_line = (offset % r_locals.scale < vec2<f32>(r_locals.line_width))
+        & (offset >= r_locals.scale);
+    if !any(on_line) {
+        discard;
+    }
+
+    return r_locals.color;
+}
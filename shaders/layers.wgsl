@@ -0,0 +1,33 @@
+// Draws a layer over the pixel buffer texture, scrolled by an offset and faded by an opacity.
+//
+// Layers have the size of the pixel buffer, and wrap around at its edges when they are scrolled.
+
+struct Locals {
+    // The position of the top-left corner of the layer in pixels
+    offset: vec2<i32>,
+    opacity: f32,
+}
+@group(0) @binding(0) var r_tex_layer: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(r_tex_layer));
+    let pixel = vec2<i32>(position.xy) - r_locals.offset;
+    let wrapped = (pixel % size + size) % size;
+
+    var color = textureLoad(r_tex_layer, wrapped, 0);
+    color.a *= r_locals.opacity;
+    return color;
+}
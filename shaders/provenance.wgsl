@@ -0,0 +1,54 @@
+// Copies the composited frame to the surface, with a message in the least significant bit of the
+// blue channel.
+//
+// The message is a tile of 16x8 bits repeated over the surface, where bit `i` is bit `i % 32` of
+// `words[i / 32]`. sRGB targets encode on write, so the bit is set on the encoded value and
+// decoded again for the GPU to store it exactly.
+
+struct Locals {
+    words: vec4<u32>,
+    // Non-zero when the target encodes colors as sRGB
+    srgb: u32,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let pixel = vec2<u32>(position.xy);
+    var color = textureLoad(r_tex_color, pixel, 0);
+
+    let index = pixel.x % 16u + pixel.y % 8u * 16u;
+    let bit = (r_locals.words[index / 32u] >> (index % 32u)) & 1u;
+
+    var blue = saturate(color.b);
+    if r_locals.srgb != 0u {
+        blue = linear_to_srgb(blue);
+    }
+    blue = f32((u32(round(blue * 255.0)) & ~1u) | bit) / 255.0;
+    if r_locals.srgb != 0u {
+        blue = srgb_to_linear(blue);
+    }
+    color.b = blue;
+
+    return color;
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    return select(1.055 * pow(value, 1.0 / 2.4) - 0.055, value * 12.92, value <= 0.0031308);
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    return select(pow((value + 0.055) / 1.055, 2.4), value / 12.92, value <= 0.04045);
+}
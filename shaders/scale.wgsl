@@ -7,6 +7,12 @@ struct VertexOutput {
 
 struct Locals {
     transform: mat4x4<f32>,
+    // Output is blended toward `attenuation.rgb`; `attenuation.a == 1.0` presents the sample as-is
+    attenuation: vec4<f32>,
+    // The sampled rectangle of the texture, see `Pixels::set_view_offset`
+    tex_rect: vec4<f32>,
+    // Exposure, tonemapping curve (0 none, 1 extended Reinhard, 2 ACES), and Reinhard white point
+    tonemap: vec4<f32>,
 }
 @group(0) @binding(2) var<uniform> r_locals: Locals;
 
@@ -15,7 +21,20 @@ fn vs_main(
     @location(0) position: vec2<f32>,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.tex_coord = fma(position, vec2<f32>(0.5, -0.5), vec2<f32>(0.5, 0.5));
+    let view_coord = fma(position, vec2<f32>(0.5, -0.5), vec2<f32>(0.5, 0.5));
+    out.tex_coord = r_locals.tex_rect.xy + view_coord * r_locals.tex_rect.zw;
+    out.position = r_locals.transform * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+// Draws a distortion mesh instead of the full-screen triangle
+@vertex
+fn vs_mesh(
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coord: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.tex_coord = r_locals.tex_rect.xy + tex_coord * r_locals.tex_rect.zw;
     out.position = r_locals.transform * vec4<f32>(position, 0.0, 1.0);
     return out;
 }
@@ -27,5 +46,167 @@ fn vs_main(
 
 @fragment
 fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
-    return textureSample(r_tex_color, r_tex_sampler, tex_coord);
+    return attenuate(textureSample(r_tex_color, r_tex_sampler, tex_coord), tex_coord);
+}
+
+// Used when the texture is viewed as sRGB so that filtering happens in linear light. The filtered
+// color is encoded again, so the output matches `fs_main` apart from the filtering.
+@fragment
+fn fs_main_srgb(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let color = textureSample(r_tex_color, r_tex_sampler, tex_coord);
+    return attenuate(linear_to_srgb(color), tex_coord);
+}
+
+// Area averaging: each fragment is the average of the texels it covers, weighted by coverage.
+@fragment
+fn fs_main_area(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return attenuate(sample_area(tex_coord), tex_coord);
+}
+
+@fragment
+fn fs_main_area_srgb(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return attenuate(linear_to_srgb(sample_area(tex_coord)), tex_coord);
+}
+
+// Sharp bilinear: nearest neighbor sampling, with the edges between texels blended over one
+// fragment so that uneven scale factors do not shimmer. Bound with a linear sampler.
+@fragment
+fn fs_main_sharp(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return attenuate(textureSample(r_tex_color, r_tex_sampler, sharp_coord(tex_coord)), tex_coord);
+}
+
+@fragment
+fn fs_main_sharp_srgb(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let color = textureSample(r_tex_color, r_tex_sampler, sharp_coord(tex_coord));
+    return attenuate(linear_to_srgb(color), tex_coord);
+}
+
+// Indexed color: the texture holds one palette index per texel, which is looked up without
+// filtering. Only bound for pixel buffers built with a palette.
+struct Palette {
+    colors: array<vec4<f32>, 256>,
+}
+@group(0) @binding(3) var<uniform> r_palette: Palette;
+
+@fragment
+fn fs_main_palette(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return attenuate(sample_palette(tex_coord), tex_coord);
+}
+
+// Packed formats: the texture holds raw sRGB-encoded values, which are unpacked and decoded.
+// Grayscale in an `R8Unorm` texture is filtered as usual.
+@fragment
+fn fs_main_luma(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return attenuate(unpack_luma(textureSample(r_tex_color, r_tex_sampler, tex_coord)), tex_coord);
+}
+
+// RGB565 in an `Rg8Unorm` texture, low byte first, is loaded without filtering.
+@fragment
+fn fs_main_rgb565(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return attenuate(unpack_rgb565(texel_at(tex_coord)), tex_coord);
+}
+
+// The shader hook of the pixel buffer, or a pass-through, is appended to this file and defines
+// `user_color`. It runs before tonemapping and the attenuation, so flashes and fades still cover
+// its output. `gamma_ramp` is appended the same way, from `gamma_ramp.wgsl` or as a
+// pass-through, and runs last.
+fn attenuate(color: vec4<f32>, tex_coord: vec2<f32>) -> vec4<f32> {
+    let user = user_color(color, tex_coord);
+    let mapped = tonemap(user.rgb);
+    let attenuated = mix(r_locals.attenuation.rgb, mapped, r_locals.attenuation.a);
+    return gamma_ramp(vec4<f32>(attenuated, user.a));
+}
+
+fn tonemap(color: vec3<f32>) -> vec3<f32> {
+    let exposed = color * r_locals.tonemap.x;
+    let curve = u32(r_locals.tonemap.y);
+
+    if curve == 1u {
+        let light = max(exposed, vec3<f32>(0.0));
+        let white = r_locals.tonemap.z;
+        return min(light * (1.0 + light / (white * white)) / (1.0 + light), vec3<f32>(1.0));
+    }
+    if curve == 2u {
+        let light = max(exposed, vec3<f32>(0.0));
+        let numerator = light * (2.51 * light + 0.03);
+        return saturate(numerator / (light * (2.43 * light + 0.59) + 0.14));
+    }
+    return exposed;
+}
+
+fn sample_area(tex_coord: vec2<f32>) -> vec4<f32> {
+    let size = vec2<f32>(textureDimensions(r_tex_color));
+    let center = tex_coord * size;
+    let footprint = fwidth(center);
+
+    // The box covered by this fragment, in texels
+    let low = center - footprint * 0.5;
+    let high = center + footprint * 0.5;
+    let first = vec2<i32>(floor(low));
+    let last = vec2<i32>(ceil(high));
+    let max_texel = vec2<i32>(size) - vec2<i32>(1);
+
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var y = first.y; y < last.y; y++) {
+        for (var x = first.x; x < last.x; x++) {
+            let texel = vec2<f32>(f32(x), f32(y));
+            let overlap = max(min(high, texel + 1.0) - max(low, texel), vec2<f32>(0.0));
+            let weight = overlap.x * overlap.y;
+            let coord = clamp(vec2<i32>(x, y), vec2<i32>(0), max_texel);
+
+            sum += textureLoad(r_tex_color, coord, 0) * weight;
+            total += weight;
+        }
+    }
+
+    return sum / max(total, 1e-6);
+}
+
+// Moves `tex_coord` to the center of its texel, except within one fragment of the next texel,
+// where the linear sampler blends the two
+fn sharp_coord(tex_coord: vec2<f32>) -> vec2<f32> {
+    let size = vec2<f32>(textureDimensions(r_tex_color));
+    let texel = tex_coord * size;
+
+    // The size of a fragment in texels; shrinking blends everywhere, like bilinear filtering
+    let footprint = clamp(fwidth(texel), vec2<f32>(1e-5), vec2<f32>(1.0));
+    let low = texel - footprint * 0.5;
+    let blend = saturate((fract(low) - (1.0 - footprint)) / footprint);
+
+    return (floor(low) + 0.5 + blend) / size;
+}
+
+fn linear_to_srgb(color: vec4<f32>) -> vec4<f32> {
+    let low = color.rgb * 12.92;
+    let high = 1.055 * pow(color.rgb, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return vec4<f32>(select(high, low, color.rgb <= vec3<f32>(0.0031308)), color.a);
+}
+
+fn srgb_to_linear(color: vec3<f32>) -> vec3<f32> {
+    let low = color / 12.92;
+    let high = pow((color + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(high, low, color <= vec3<f32>(0.04045));
+}
+
+// The texel covering `tex_coord`, without filtering
+fn texel_at(tex_coord: vec2<f32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(r_tex_color));
+    let texel = clamp(vec2<i32>(floor(tex_coord * vec2<f32>(size))), vec2<i32>(0), size - 1);
+    return textureLoad(r_tex_color, texel, 0);
+}
+
+fn sample_palette(tex_coord: vec2<f32>) -> vec4<f32> {
+    let index = u32(round(texel_at(tex_coord).r * 255.0));
+    return r_palette.colors[index];
+}
+
+fn unpack_luma(texel: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(srgb_to_linear(vec3<f32>(texel.r)), 1.0);
+}
+
+fn unpack_rgb565(texel: vec4<f32>) -> vec4<f32> {
+    let value = u32(round(texel.r * 255.0)) | (u32(round(texel.g * 255.0)) << 8u);
+    let rgb = vec3<u32>(value >> 11u, (value >> 5u) & 63u, value & 31u);
+    return vec4<f32>(srgb_to_linear(vec3<f32>(rgb) / vec3<f32>(31.0, 63.0, 31.0)), 1.0);
 }
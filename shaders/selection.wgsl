@@ -0,0 +1,77 @@
+// Draws selection rectangles and region highlights over the scaled pixel buffer.
+//
+// Each selection is one instance of a quad generated from the vertex index. Rectangles are given
+// in pixel buffer coordinates and mapped to physical pixels along the axes of the scaled buffer,
+// which may be rotated or flipped.
+
+struct Locals {
+    // Position of the top-left corner of the pixel buffer in physical pixels
+    origin: vec2<f32>,
+    // Size of the surface in physical pixels
+    screen_size: vec2<f32>,
+    // Physical offset of one buffer pixel to the right, and one buffer pixel down
+    x_axis: vec2<f32>,
+    y_axis: vec2<f32>,
+    // Offset of the marching ants dash pattern in physical pixels
+    phase: f32,
+}
+@group(0) @binding(0) var<uniform> r_locals: Locals;
+
+// Length of a marching ants dash in physical pixels
+const DASH_LENGTH: f32 = 4.0;
+
+const STYLE_MARCHING_ANTS: u32 = 0u;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    // Physical rectangle: x, y, width, height
+    @location(0) @interpolate(flat) rect: vec4<f32>,
+    @location(1) @interpolate(flat) color: vec4<f32>,
+    @location(2) @interpolate(flat) style: u32,
+}
+
+@vertex
+fn vs_main(
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) rect: vec4<f32>,
+    @location(1) color: vec4<f32>,
+    @location(2) style: u32,
+) -> VertexOutput {
+    // Two triangles: (0, 0), (1, 0), (0, 1), (0, 1), (1, 0), (1, 1)
+    let corner = vec2<f32>(
+        f32(vertex_index == 1u || vertex_index >= 4u),
+        f32(vertex_index == 2u || vertex_index == 3u || vertex_index == 5u),
+    );
+
+    let start = r_locals.origin + rect.x * r_locals.x_axis + rect.y * r_locals.y_axis;
+    let end = start + rect.z * r_locals.x_axis + rect.w * r_locals.y_axis;
+    let physical = vec4<f32>(min(start, end), abs(end - start));
+    let position = physical.xy + corner * physical.zw;
+    let ndc = position / r_locals.screen_size * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0);
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+    out.rect = physical;
+    out.color = color;
+    out.style = style;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    if in.style != STYLE_MARCHING_ANTS {
+        return in.color;
+    }
+
+    // Only the outermost physical pixels of the rectangle form the outline
+    let pixel = floor(in.position.xy);
+    let local = pixel - in.rect.xy;
+    if all(local >= vec2<f32>(1.0)) && all(local < in.rect.zw - vec2<f32>(1.0)) {
+        discard;
+    }
+
+    // Alternate between black and white dashes which move along the outline with the phase
+    let dash = floor((pixel.x + pixel.y + r_locals.phase) / DASH_LENGTH);
+    let white = f32(dash % 2.0 != 0.0);
+    return vec4<f32>(vec3<f32>(white), 1.0);
+}
@@ -0,0 +1,44 @@
+// Draws instanced sprites from an atlas over the pixel buffer texture.
+
+struct Locals {
+    // The size of the destination and the atlas in pixels
+    dst_size: vec2<f32>,
+    atlas_size: vec2<f32>,
+}
+@group(0) @binding(0) var<uniform> r_locals: Locals;
+@group(0) @binding(1) var r_atlas: texture_2d<f32>;
+@group(0) @binding(2) var r_sampler: sampler;
+
+struct Instance {
+    // The x, y, width, and height in pixels of the destination
+    @location(0) dst: vec4<f32>,
+    // The left, top, right, and bottom in pixels of the atlas, swapped for flipped sprites
+    @location(1) src: vec4<f32>,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coord: vec2<f32>,
+}
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, instance: Instance) -> VertexOutput {
+    // Corners (0, 0), (1, 0), (0, 1), (1, 1) of a triangle strip
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u));
+    let pixel = instance.dst.xy + corner * instance.dst.zw;
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(
+        pixel.x / r_locals.dst_size.x * 2.0 - 1.0,
+        1.0 - pixel.y / r_locals.dst_size.y * 2.0,
+        0.0,
+        1.0,
+    );
+    out.tex_coord = mix(instance.src.xy, instance.src.zw, corner) / r_locals.atlas_size;
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(r_atlas, r_sampler, tex_coord);
+}
@@ -0,0 +1,64 @@
+// Copies a draw target into a rectangle of another texture.
+//
+// The quad is generated from the vertex index and placed with a rectangle in clip space, so parts
+// outside of the destination are clipped by the rasterizer. The area filter averages every texel
+// covered by a fragment, for minimaps and other large reductions.
+
+struct VertexOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+struct Locals {
+    // Left, top, right, and bottom edges of the destination rectangle in clip space
+    rect: vec4<f32>,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Triangle strip: (0, 0), (1, 0), (0, 1), (1, 1)
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u));
+
+    var out: VertexOutput;
+    out.tex_coord = corner;
+    out.position = vec4<f32>(mix(r_locals.rect.xy, r_locals.rect.zw, corner), 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    return textureSample(r_tex_color, r_tex_sampler, tex_coord);
+}
+
+@fragment
+fn fs_area(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(r_tex_color));
+    let center = tex_coord * size;
+    let footprint = fwidth(center);
+
+    // The box covered by this fragment, in texels
+    let low = center - footprint * 0.5;
+    let high = center + footprint * 0.5;
+    let first = vec2<i32>(floor(low));
+    let last = vec2<i32>(ceil(high));
+    let max_texel = vec2<i32>(size) - vec2<i32>(1);
+
+    var sum = vec4<f32>(0.0);
+    var total = 0.0;
+    for (var y = first.y; y < last.y; y++) {
+        for (var x = first.x; x < last.x; x++) {
+            let texel = vec2<f32>(f32(x), f32(y));
+            let overlap = max(min(high, texel + 1.0) - max(low, texel), vec2<f32>(0.0));
+            let weight = overlap.x * overlap.y;
+            let coord = clamp(vec2<i32>(x, y), vec2<i32>(0), max_texel);
+
+            sum += textureLoad(r_tex_color, coord, 0) * weight;
+            total += weight;
+        }
+    }
+
+    return sum / max(total, 1e-6);
+}
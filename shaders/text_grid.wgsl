@@ -0,0 +1,44 @@
+// Draws a grid of character cells into the pixel buffer texture, looking up glyphs in a font
+// atlas and their colors in a palette.
+
+struct Locals {
+    // The top-left corner of the grid in pixels of the destination, and the glyph size
+    rect: vec4<i32>,
+    // The number of glyphs, and the number of glyphs in each row of the atlas
+    atlas: vec4<u32>,
+}
+@group(0) @binding(0) var<uniform> r_locals: Locals;
+// Glyph index low and high bytes, foreground and background palette indices
+@group(0) @binding(1) var r_cells: texture_2d<u32>;
+@group(0) @binding(2) var r_atlas: texture_2d<f32>;
+@group(0) @binding(3) var r_palette: texture_2d<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4<f32> {
+    // Vertices (-1, -1), (3, -1), (-1, 3), clipped to the grid with a scissor rect
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let glyph_size = r_locals.rect.zw;
+    let pixel = vec2<i32>(floor(position.xy)) - r_locals.rect.xy;
+    let cell = textureLoad(r_cells, pixel / glyph_size, 0);
+    let glyph = cell.r | (cell.g << 8u);
+
+    // Glyphs past the end of the font are blank
+    var coverage = 0.0;
+    if glyph < r_locals.atlas.x {
+        let columns = r_locals.atlas.y;
+        let origin = vec2<i32>(vec2<u32>(glyph % columns, glyph / columns)) * glyph_size;
+        coverage = textureLoad(r_atlas, origin + pixel % glyph_size, 0).r;
+    }
+    let index = select(cell.a, cell.b, coverage > 0.5);
+
+    return textureLoad(r_palette, vec2<i32>(i32(index), 0), 0);
+}
@@ -0,0 +1,81 @@
+// Draws a snapshot of the previous frame over the new one, partially uncovered by a transition.
+//
+// The full-screen triangle is generated from the vertex index and placed with the scaling
+// transform of the snapshot. Fragments where the new frame has been revealed are discarded.
+
+struct VertexOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+struct Locals {
+    transform: mat4x4<f32>,
+    // From 0.0 (only the snapshot) to 1.0 (only the new frame)
+    progress: f32,
+    kind: u32,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+const KIND_CROSSFADE: u32 = 0u;
+const KIND_WIPE: u32 = 1u;
+const KIND_DISSOLVE: u32 = 2u;
+const KIND_IRIS: u32 = 3u;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Vertices (-1, -1), (3, -1), (-1, 3)
+    let position = vec2<f32>(
+        f32((vertex_index << 1u) & 2u) * 2.0 - 1.0,
+        f32(vertex_index & 2u) * 2.0 - 1.0,
+    );
+
+    var out: VertexOutput;
+    out.tex_coord = fma(position, vec2<f32>(0.5, -0.5), vec2<f32>(0.5, 0.5));
+    out.position = r_locals.transform * vec4<f32>(position, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let color = textureSample(r_tex_color, r_tex_sampler, tex_coord);
+    let size = vec2<f32>(textureDimensions(r_tex_color));
+    let pixel = floor(tex_coord * size);
+    let progress = r_locals.progress;
+
+    var revealed = false;
+    switch r_locals.kind {
+        case KIND_CROSSFADE: {
+            return vec4<f32>(color.rgb, color.a * (1.0 - progress));
+        }
+        case KIND_WIPE: {
+            // Left to right, one whole pixel column at a time
+            revealed = pixel.x < floor(progress * size.x);
+        }
+        case KIND_DISSOLVE: {
+            revealed = hash(pixel) < progress;
+        }
+        case KIND_IRIS: {
+            // A circle growing from the center until it covers the corners
+            let center = size * 0.5;
+            revealed = distance(pixel + 0.5, center) < progress * length(center);
+        }
+        default: {}
+    }
+
+    if revealed {
+        discard;
+    }
+    return color;
+}
+
+// A uniformly distributed value from 0 to 1 for each pixel
+fn hash(pixel: vec2<f32>) -> f32 {
+    let p = vec2<u32>(pixel);
+    var h = p.x * 1973u + p.y * 9277u + 89173u;
+    h = (h ^ (h >> 15u)) * 0x2c1b3c6du;
+    h = (h ^ (h >> 12u)) * 0x297a2d39u;
+    h = h ^ (h >> 15u);
+    return f32(h) / 4294967295.0;
+}
@@ -0,0 +1,35 @@
+// Stamps a watermark image at a corner of the render target.
+//
+// The quad is generated from the vertex index and placed with a rectangle in clip space. The
+// image is blended over the target with its alpha, multiplied by the opacity.
+
+struct VertexOutput {
+    @location(0) tex_coord: vec2<f32>,
+    @builtin(position) position: vec4<f32>,
+}
+
+struct Locals {
+    // Left, top, right, and bottom edges of the watermark in clip space
+    rect: vec4<f32>,
+    opacity: f32,
+}
+@group(0) @binding(0) var r_tex_color: texture_2d<f32>;
+@group(0) @binding(1) var r_tex_sampler: sampler;
+@group(0) @binding(2) var<uniform> r_locals: Locals;
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    // Triangle strip: (0, 0), (1, 0), (0, 1), (1, 1)
+    let corner = vec2<f32>(f32(vertex_index & 1u), f32(vertex_index >> 1u));
+
+    var out: VertexOutput;
+    out.tex_coord = corner;
+    out.position = vec4<f32>(mix(r_locals.rect.xy, r_locals.rect.zw, corner), 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(@location(0) tex_coord: vec2<f32>) -> @location(0) vec4<f32> {
+    let color = textureSample(r_tex_color, r_tex_sampler, tex_coord);
+    return vec4<f32>(color.rgb, color.a * r_locals.opacity);
+}
@@ -0,0 +1,347 @@
+//! A managed event loop for small games and animations, with a fixed-timestep update.
+//!
+//! [`run`] owns the `winit` [`ApplicationHandler`] boilerplate: it creates the window and the
+//! pixel buffer once the event loop resumes, resizes the surface with the window, follows it to
+//! monitors with other refresh rates, asks for a frame whenever the last one was presented, and
+//! stops with the error of the first call that fails. The application only passes its state and
+//! the callbacks of an [`App`]:
+//!
+//! ```no_run
+//! use pixels::app::App;
+//!
+//! struct World {
+//!     x: u32,
+//! }
+//!
+//! let app = App::new(320, 240, World { x: 0 })
+//!     .title("Bouncing pixel")
+//!     // Called 60 times per second, however fast the display refreshes
+//!     .update(|world, _dt| world.x = (world.x + 1) % 320)
+//!     .draw(|world, frame| {
+//!         frame.fill(0);
+//!         let i = (120 * 320 + world.x as usize) * 4;
+//!         frame[i..i + 4].copy_from_slice(&[0xff; 4]);
+//!     });
+//!
+//! pixels::run(app)?;
+//! # Ok::<(), pixels::app::RunError>(())
+//! ```
+//!
+//! Updates run at a fixed [`App::timestep`], so the simulation behaves the same at any frame rate:
+//! each frame runs as many updates as the time since the previous frame covers, and carries the
+//! rest over to the next one. After a stall, such as dragging the window on some platforms, at
+//! most 8 updates run in one frame and the rest of the time is skipped, instead of trying to
+//! catch up all at once.
+//!
+//! Applications which need more control, e.g. over several windows or the device, implement
+//! [`ApplicationHandler`] themselves; the
+//! [`minimal-winit`](https://github.com/parasyte/pixels/tree/main/examples/minimal-winit) example
+//! shows how. `run` is not available on Web targets, where the pixel buffer can only be created
+//! asynchronously.
+
+use crate::{Error, Pixels, PixelsBuilder, SurfaceTexture, ZeroSizePolicy};
+use std::sync::Arc;
+use web_time::{Duration, Instant};
+use winit::application::ApplicationHandler;
+use winit::dpi::LogicalSize;
+use winit::event::WindowEvent;
+use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::window::{Window, WindowId};
+
+/// The most updates run for one frame, see the [module documentation](self).
+const MAX_UPDATES_PER_FRAME: u32 = 8;
+
+type Setup<S> = Box<dyn FnOnce(&mut S, &mut Pixels<'static>)>;
+type Update<S> = Box<dyn FnMut(&mut S, Duration)>;
+type Draw<S> = Box<dyn FnMut(&mut S, &mut [u8])>;
+type Input<S> = Box<dyn FnMut(&mut S, &WindowEvent, &ActiveEventLoop)>;
+
+/// The ways in which [`run`] can fail.
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum RunError {
+    /// Equivalent to [`winit::error::EventLoopError`]
+    #[error("The event loop failed: {0}")]
+    EventLoop(#[from] winit::error::EventLoopError),
+    /// Equivalent to [`winit::error::OsError`], when the window cannot be created
+    #[error("Unable to create the window: {0}")]
+    Window(#[from] winit::error::OsError),
+    /// Equivalent to [`Error`], when creating, resizing, or rendering the pixel buffer fails
+    #[error("The pixel buffer failed: {0}")]
+    Pixels(#[from] Error),
+}
+
+/// The state and callbacks of an application for [`run`].
+///
+/// Every callback gets the state `S` first. All of them are optional: without any, `run` shows a
+/// window with a black pixel buffer.
+pub struct App<S> {
+    width: u32,
+    height: u32,
+    state: S,
+    title: String,
+    scale: u32,
+    timestep: Duration,
+    setup: Option<Setup<S>>,
+    update: Update<S>,
+    draw: Draw<S>,
+    input: Input<S>,
+}
+
+impl<S> std::fmt::Debug for App<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("App")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("title", &self.title)
+            .field("scale", &self.scale)
+            .field("timestep", &self.timestep)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> App<S> {
+    /// Create an application with a `width` by `height` pixel buffer and its initial `state`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` is 0.
+    pub fn new(width: u32, height: u32, state: S) -> Self {
+        assert!(width > 0 && height > 0);
+
+        Self {
+            width,
+            height,
+            state,
+            title: "pixels".to_string(),
+            scale: 2,
+            timestep: Duration::from_secs(1) / 60,
+            setup: None,
+            update: Box::new(|_, _| ()),
+            draw: Box::new(|_, _| ()),
+            input: Box::new(|_, _, _| ()),
+        }
+    }
+
+    /// Set the window title. The default is `"pixels"`.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Open the window at `scale` times the size of the pixel buffer, in logical pixels. It can
+    /// be resized down to the size of the pixel buffer. The default value is 2.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `scale` is 0.
+    pub fn scale(mut self, scale: u32) -> Self {
+        assert!(scale > 0);
+        self.scale = scale;
+        self
+    }
+
+    /// Set the time simulated by each update. The default is 1/60th of a second.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `timestep` is zero.
+    pub fn timestep(mut self, timestep: Duration) -> Self {
+        assert!(!timestep.is_zero());
+        self.timestep = timestep;
+        self
+    }
+
+    /// Configure the pixel buffer once it is created, before the first frame, e.g. with
+    /// [`Pixels::set_scaling_mode`] or [`Pixels::add_effect`].
+    pub fn setup(mut self, setup: impl FnOnce(&mut S, &mut Pixels<'static>) + 'static) -> Self {
+        self.setup = Some(Box::new(setup));
+        self
+    }
+
+    /// Advance the state by the [timestep](App::timestep), which is passed as the second
+    /// argument.
+    pub fn update(mut self, update: impl FnMut(&mut S, Duration) + 'static) -> Self {
+        self.update = Box::new(update);
+        self
+    }
+
+    /// Draw the state into the pixel buffer of every frame, as returned by [`Pixels::frame_mut`].
+    ///
+    /// The buffer keeps the previous frame; it is not cleared first.
+    pub fn draw(mut self, draw: impl FnMut(&mut S, &mut [u8]) + 'static) -> Self {
+        self.draw = Box::new(draw);
+        self
+    }
+
+    /// Handle the window events, e.g. keyboard and mouse input.
+    ///
+    /// Resizing and closing the window are handled by [`run`] after the callback. Call
+    /// [`ActiveEventLoop::exit`] to quit.
+    pub fn input(
+        mut self,
+        input: impl FnMut(&mut S, &WindowEvent, &ActiveEventLoop) + 'static,
+    ) -> Self {
+        self.input = Box::new(input);
+        self
+    }
+}
+
+/// Open a window for `app`, and run its callbacks until the window is closed or
+/// [`ActiveEventLoop::exit`] is called.
+///
+/// See the [module documentation](crate::app).
+///
+/// # Errors
+///
+/// Returns the first error of creating the event loop, the window, or the pixel buffer, or of
+/// resizing or rendering it. Devices which are lost are recreated once before giving up.
+pub fn run<S>(app: App<S>) -> Result<(), RunError> {
+    let event_loop = EventLoop::new()?;
+    let mut runner = Runner {
+        app,
+        window: None,
+        pixels: None,
+        last_frame: None,
+        accumulator: Duration::ZERO,
+        error: None,
+    };
+    event_loop.run_app(&mut runner)?;
+
+    match runner.error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+/// The [`ApplicationHandler`] of [`run`].
+struct Runner<S> {
+    app: App<S>,
+    window: Option<Arc<Window>>,
+    pixels: Option<Pixels<'static>>,
+    // The time of the previous frame, and the time not yet simulated by updates
+    last_frame: Option<Instant>,
+    accumulator: Duration,
+    error: Option<RunError>,
+}
+
+impl<S> Runner<S> {
+    /// Create the window and the pixel buffer.
+    fn create(&mut self, event_loop: &ActiveEventLoop) -> Result<(), RunError> {
+        let app = &mut self.app;
+        let (width, height) = (f64::from(app.width), f64::from(app.height));
+        let scale = f64::from(app.scale);
+        let attributes = Window::default_attributes()
+            .with_title(app.title.clone())
+            .with_inner_size(LogicalSize::new(width * scale, height * scale))
+            .with_min_inner_size(LogicalSize::new(width, height));
+        let window = Arc::new(event_loop.create_window(attributes)?);
+
+        let size = window.inner_size();
+        let surface_texture = SurfaceTexture::new(size.width, size.height, Arc::clone(&window));
+        let mut pixels = PixelsBuilder::new(app.width, app.height, surface_texture)
+            .zero_size_policy(ZeroSizePolicy::Pause)
+            .build()?;
+        pixels.track_monitor(&window);
+        if let Some(setup) = app.setup.take() {
+            setup(&mut app.state, &mut pixels);
+        }
+
+        self.window = Some(window);
+        self.pixels = Some(pixels);
+
+        Ok(())
+    }
+
+    /// Run the updates which are due, then draw and render a frame.
+    fn frame(&mut self) -> Result<(), RunError> {
+        let Some(pixels) = self.pixels.as_mut() else {
+            return Ok(());
+        };
+        let app = &mut self.app;
+
+        // Minimized windows neither simulate nor catch up when they are restored
+        let now = Instant::now();
+        if pixels.is_paused() {
+            self.last_frame = None;
+            return Ok(());
+        }
+        if let Some(last_frame) = self.last_frame.replace(now) {
+            self.accumulator += now - last_frame;
+        }
+        let mut updates = 0;
+        while self.accumulator >= app.timestep {
+            if updates == MAX_UPDATES_PER_FRAME {
+                self.accumulator = Duration::ZERO;
+                break;
+            }
+            (app.update)(&mut app.state, app.timestep);
+            self.accumulator -= app.timestep;
+            updates += 1;
+        }
+
+        (app.draw)(&mut app.state, pixels.frame_mut());
+        match pixels.render() {
+            Err(Error::DeviceLost) => {
+                pixels.recreate_device()?;
+                pixels.render()?;
+            }
+            result => result?,
+        }
+
+        Ok(())
+    }
+
+    /// Stop the event loop with the first error.
+    fn fail(&mut self, event_loop: &ActiveEventLoop, err: RunError) {
+        self.error.get_or_insert(err);
+        event_loop.exit();
+    }
+}
+
+impl<S> ApplicationHandler for Runner<S> {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_none() {
+            if let Err(err) = self.create(event_loop) {
+                self.fail(event_loop, err);
+            }
+        }
+    }
+
+    fn window_event(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        (self.app.input)(&mut self.app.state, &event, event_loop);
+
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::Resized(size) => {
+                if let Some(pixels) = self.pixels.as_mut() {
+                    if let Err(err) = pixels.resize_surface(size.width, size.height) {
+                        self.fail(event_loop, Error::from(err).into());
+                    }
+                }
+            }
+            WindowEvent::Moved(_) | WindowEvent::ScaleFactorChanged { .. } => {
+                if let (Some(pixels), Some(window)) = (self.pixels.as_mut(), &self.window) {
+                    pixels.track_monitor(window);
+                }
+            }
+            WindowEvent::RedrawRequested => {
+                if let Err(err) = self.frame() {
+                    self.fail(event_loop, err);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
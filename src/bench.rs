@@ -0,0 +1,363 @@
+//! Measurements of pixel buffer costs on the current machine.
+//!
+//! The right buffer size and texture format depend on the target hardware: a 4K buffer uploads
+//! in a fraction of a frame on a desktop GPU, but not on an integrated one. [`Bench`] creates an
+//! [offscreen](crate::Pixels::new_offscreen) pixel buffer with the given options and times the
+//! work done for every frame, so an application can compare configurations, or pick one at
+//! startup.
+//!
+//! ```no_run
+//! use pixels::bench::Bench;
+//!
+//! for (width, height) in [(320, 240), (1920, 1080), (3840, 2160)] {
+//!     let report = Bench::new(width, height).surface_size(3840, 2160).run()?;
+//!     println!("{report}");
+//!     println!("{:.0} MB/s uploaded", report.upload.bytes_per_second() / 1e6);
+//! }
+//! # Ok::<(), pixels::Error>(())
+//! ```
+//!
+//! Times are averages over [`Bench::iterations`] runs after one warm-up frame. GPU work is
+//! waited for at the end of each measurement, so the results include the submission overhead,
+//! but not presentation.
+
+use crate::ingest::{self, Plane};
+use crate::{Error, Pixels, PixelsBuilder, SurfaceTexture, UploadStrategy};
+use std::fmt;
+use web_time::{Duration, Instant};
+
+/// Options for measuring the costs of a pixel buffer configuration.
+///
+/// See the [module documentation](self).
+#[derive(Clone, Debug)]
+pub struct Bench {
+    width: u32,
+    height: u32,
+    surface_size: Option<(u32, u32)>,
+    texture_format: wgpu::TextureFormat,
+    upload_strategy: UploadStrategy,
+    backends: wgpu::Backends,
+    iterations: u32,
+}
+
+/// The results of a [`Bench`] run.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct BenchReport {
+    /// The name of the adapter, e.g. the GPU model.
+    pub adapter_name: String,
+
+    /// The graphics backend the adapter runs on.
+    pub backend: wgpu::Backend,
+
+    /// The pixel buffer size, in pixels.
+    pub buffer_size: (u32, u32),
+
+    /// The render target size the pixel buffer is scaled to, in physical pixels.
+    pub surface_size: (u32, u32),
+
+    /// The pixel buffer texture format.
+    pub texture_format: wgpu::TextureFormat,
+
+    /// How the pixel buffer is copied to its texture.
+    pub upload_strategy: UploadStrategy,
+
+    /// Uploading the whole pixel buffer to its texture.
+    pub upload: Throughput,
+
+    /// Copying a frame with padded rows into an `RGBA` buffer, like
+    /// [`Pixels::copy_strided`](crate::Pixels::copy_strided).
+    pub strided_copy: Throughput,
+
+    /// Converting a YUYV frame to `RGBA` with [`ingest::yuyv_to_rgba`].
+    pub yuyv_to_rgba: Throughput,
+
+    /// Converting an NV12 frame to `RGBA` with [`ingest::nv12_to_rgba`].
+    pub nv12_to_rgba: Throughput,
+
+    /// Drawing the scaling pass to the render target, without the upload.
+    pub scaling: Duration,
+
+    /// A whole [`Pixels::render`](crate::Pixels::render), including the upload.
+    pub render: Duration,
+}
+
+/// The average time to process a number of bytes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Throughput {
+    /// The number of bytes written per run.
+    pub bytes: u64,
+
+    /// The average time per run.
+    pub duration: Duration,
+}
+
+impl Bench {
+    /// Measure a pixel buffer of `width` by `height` pixels.
+    ///
+    /// The defaults are an `Rgba8UnormSrgb` texture scaled to a render target of the same size,
+    /// any backend, and 60 iterations.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0.
+    pub fn new(width: u32, height: u32) -> Self {
+        assert!(width > 0);
+        assert!(height > 0);
+
+        Self {
+            width,
+            height,
+            surface_size: None,
+            texture_format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            upload_strategy: UploadStrategy::WriteTexture,
+            backends: wgpu::Backends::all(),
+            iterations: 60,
+        }
+    }
+
+    /// Scale the pixel buffer to a render target of `width` by `height` physical pixels.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0.
+    pub fn surface_size(mut self, width: u32, height: u32) -> Self {
+        assert!(width > 0);
+        assert!(height > 0);
+        self.surface_size = Some((width, height));
+        self
+    }
+
+    /// Set the texture format of the pixel buffer, see [`PixelsBuilder::texture_format`].
+    pub fn texture_format(mut self, texture_format: wgpu::TextureFormat) -> Self {
+        self.texture_format = texture_format;
+        self
+    }
+
+    /// Set how the pixel buffer is uploaded, see [`PixelsBuilder::upload_strategy`].
+    pub fn upload_strategy(mut self, strategy: UploadStrategy) -> Self {
+        self.upload_strategy = strategy;
+        self
+    }
+
+    /// Set which backends may be used, see [`PixelsBuilder::backends`].
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Set the number of runs averaged for each measurement.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `iterations` is 0.
+    pub fn iterations(mut self, iterations: u32) -> Self {
+        assert!(iterations > 0);
+        self.iterations = iterations;
+        self
+    }
+
+    /// Create the pixel buffer and take the measurements. This blocks for as long as they take.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the pixel buffer cannot be created with these options.
+    pub fn run(&self) -> Result<BenchReport, Error> {
+        let (surface_width, surface_height) =
+            self.surface_size.unwrap_or((self.width, self.height));
+        let mut pixels = PixelsBuilder::new(
+            self.width,
+            self.height,
+            SurfaceTexture::offscreen(surface_width, surface_height),
+        )
+        .texture_format(self.texture_format)
+        .upload_strategy(self.upload_strategy)
+        .backends(self.backends)
+        .build()?;
+
+        // Warm up pipelines and allocations
+        pixels.render()?;
+        wait(&pixels);
+
+        let upload = Throughput {
+            bytes: pixels.frame().len() as u64,
+            duration: self.time_gpu(&mut pixels, upload_pass),
+        };
+        let scaling = self.time_gpu(&mut pixels, render_scaling_pass);
+        let render = {
+            let start = Instant::now();
+            for _ in 0..self.iterations {
+                pixels.frame_mut();
+                pixels.render()?;
+            }
+            wait(&pixels);
+            start.elapsed() / self.iterations
+        };
+
+        let (strided_copy, yuyv_to_rgba, nv12_to_rgba) = self.time_conversions();
+
+        Ok(BenchReport {
+            adapter_name: pixels.adapter().get_info().name,
+            backend: pixels.backend(),
+            buffer_size: (self.width, self.height),
+            surface_size: (surface_width, surface_height),
+            texture_format: self.texture_format,
+            upload_strategy: self.upload_strategy,
+            upload,
+            strided_copy,
+            yuyv_to_rgba,
+            nv12_to_rgba,
+            scaling,
+            render,
+        })
+    }
+
+    /// Get the average time of `f` over all iterations, waiting for the GPU at the end.
+    fn time_gpu<'win>(
+        &self,
+        pixels: &mut Pixels<'win>,
+        mut f: impl FnMut(&mut Pixels<'win>),
+    ) -> Duration {
+        let start = Instant::now();
+        for _ in 0..self.iterations {
+            f(pixels);
+        }
+        wait(pixels);
+
+        start.elapsed() / self.iterations
+    }
+
+    /// Time the CPU conversions from the [`ingest`] module into an `RGBA` frame.
+    fn time_conversions(&self) -> (Throughput, Throughput, Throughput) {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let row_len = width * 4;
+        let mut dst = vec![0; row_len * height];
+
+        // Pad rows to a 256 byte alignment, like many camera drivers
+        let stride = row_len.next_multiple_of(256);
+        let strided = vec![0x80; stride * height];
+        let yuyv = vec![0x80; chroma_width * 4 * height];
+        let luma = vec![0x80; width * height];
+        let chroma = vec![0x80; chroma_width * 2 * chroma_height];
+
+        let mut time = |f: &mut dyn FnMut(&mut [u8]) -> Result<(), ingest::IngestError>| {
+            let start = Instant::now();
+            for _ in 0..self.iterations {
+                f(&mut dst).expect("the benchmark frames are valid");
+            }
+
+            Throughput {
+                bytes: (row_len * height) as u64,
+                duration: start.elapsed() / self.iterations,
+            }
+        };
+
+        let strided_copy = time(&mut |dst| {
+            let src = Plane {
+                data: &strided,
+                stride,
+            };
+            ingest::copy_strided(dst, row_len, src, row_len, height)
+        });
+        let yuyv_to_rgba = time(&mut |dst| {
+            let src = Plane {
+                data: &yuyv,
+                stride: chroma_width * 4,
+            };
+            ingest::yuyv_to_rgba(src, width, height, dst)
+        });
+        let nv12_to_rgba = time(&mut |dst| {
+            let y = Plane {
+                data: &luma,
+                stride: width,
+            };
+            let uv = Plane {
+                data: &chroma,
+                stride: chroma_width * 2,
+            };
+            ingest::nv12_to_rgba(y, uv, width, height, dst)
+        });
+
+        (strided_copy, yuyv_to_rgba, nv12_to_rgba)
+    }
+}
+
+/// Upload only the pixel buffer, with the upload strategy of `pixels`.
+fn upload_pass(pixels: &mut Pixels<'_>) {
+    let mut encoder =
+        pixels
+            .context()
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("pixels_bench_command_encoder"),
+            });
+    pixels.upload_frame(&mut encoder);
+    pixels.submit_upload(encoder);
+}
+
+/// Draw only the scaling pass to the offscreen render target.
+fn render_scaling_pass(pixels: &mut Pixels<'_>) {
+    let texture = pixels
+        .offscreen_texture()
+        .expect("benchmarks render offscreen");
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+        format: Some(pixels.surface_texture_format()),
+        ..Default::default()
+    });
+    let context = pixels.context();
+    let mut encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("pixels_bench_command_encoder"),
+        });
+    context.scaling_renderer.render(&mut encoder, &view);
+    context.queue.submit(Some(encoder.finish()));
+}
+
+/// Block until the GPU has finished all submitted work.
+fn wait(pixels: &Pixels<'_>) {
+    let context = pixels.context();
+    context.queue.submit([]);
+    context.device.poll(wgpu::Maintain::Wait);
+}
+
+impl Throughput {
+    /// Get the throughput in bytes per second, or infinity when the work took no measurable time.
+    pub fn bytes_per_second(&self) -> f64 {
+        self.bytes as f64 / self.duration.as_secs_f64()
+    }
+}
+
+impl fmt::Display for Throughput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} ({:.1} MB/s)",
+            self.duration,
+            self.bytes_per_second() / 1e6,
+        )
+    }
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.buffer_size;
+        let (surface_width, surface_height) = self.surface_size;
+
+        writeln!(f, "adapter: {} ({:?})", self.adapter_name, self.backend)?;
+        writeln!(
+            f,
+            "buffer: {width}x{height} {:?}, scaled to {surface_width}x{surface_height}, {:?}",
+            self.texture_format, self.upload_strategy,
+        )?;
+        writeln!(f, "upload: {}", self.upload)?;
+        writeln!(f, "strided copy: {}", self.strided_copy)?;
+        writeln!(f, "yuyv to rgba: {}", self.yuyv_to_rgba)?;
+        writeln!(f, "nv12 to rgba: {}", self.nv12_to_rgba)?;
+        writeln!(f, "scaling: {:?}", self.scaling)?;
+        write!(f, "render: {:?}", self.render)
+    }
+}
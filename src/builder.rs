@@ -1,21 +1,87 @@
-use crate::renderers::{ScalingMatrix, ScalingRenderer};
-use crate::{Error, Pixels, PixelsContext, SurfaceSize, SurfaceTexture, TextureError};
+use crate::color::{self, ColorSpace};
+use crate::layer;
+use crate::overlay;
+use crate::profiling::{self, Profiler};
+use crate::provenance::ProvenanceMark;
+use crate::renderers::{
+    self, BackgroundRenderer, BezelRenderer, FrameAverager, GridRenderer, LayerCompositor,
+    ProvenanceRenderer, ScalingMatrix, ScalingOptions, ScalingRenderer, SelectionRenderer,
+    ShaderHook, Supersampler, WatermarkRenderer,
+};
+use crate::target;
+use crate::workarounds::{self, Workaround};
+use crate::{
+    AmbientBezel, Background, Error, FilterMode, FlashLimiter, FrameInterpolation, GammaRamp,
+    Layer, NoWindow, PackedFormat, PixelGrid, Pixels, PixelsConfig, PixelsContext, RedrawPolicy,
+    Rotation, ScalingMode, SharedGpu, SurfaceSize, SurfaceTexture, TextureBorder, TextureError,
+    Tonemapping, UploadOrder, UploadStrategy, Watermark, ZeroSizePolicy,
+};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A hook that chooses the backends to use, given the backends requested by the application.
+type BackendOverride = Box<dyn FnOnce(wgpu::Backends) -> wgpu::Backends>;
+
+/// A hook that chooses the driver workarounds to apply, given the adapter and the workarounds
+/// detected for it.
+type WorkaroundOverride = Box<dyn FnOnce(&wgpu::AdapterInfo, Vec<Workaround>) -> Vec<Workaround>>;
 
 /// A builder to help create customized pixel buffers.
 pub struct PixelsBuilder<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> {
     request_adapter_options: Option<wgpu::RequestAdapterOptions<'req, 'win>>,
+    shared_gpu: Option<SharedGpu>,
     device_descriptor: Option<wgpu::DeviceDescriptor<'dev>>,
     backend: wgpu::Backends,
+    backend_override: BackendOverride,
+    workaround_override: WorkaroundOverride,
     width: u32,
     height: u32,
     _pixel_aspect_ratio: f64,
     present_mode: wgpu::PresentMode,
+    zero_size_policy: ZeroSizePolicy,
     surface_texture: SurfaceTexture<W>,
+    // The surface of a pixel buffer being recreated, used instead of the window
+    surface: Option<wgpu::Surface<'win>>,
     texture_format: wgpu::TextureFormat,
+    palette: Option<Box<[[u8; 4]; 256]>>,
+    packed_format: Option<PackedFormat>,
+    shader_hook: Option<ShaderHook>,
+    gamma_ramp: Option<Box<GammaRamp>>,
+    tonemapping: Tonemapping,
+    exposure: f32,
+    hdr: bool,
     render_texture_format: Option<wgpu::TextureFormat>,
     surface_texture_format: Option<wgpu::TextureFormat>,
     clear_color: wgpu::Color,
+    clear_color_space: Option<ColorSpace>,
     blend_state: wgpu::BlendState,
+    texture_ring_size: usize,
+    layers: usize,
+    upload_slices: u32,
+    upload_order: UploadOrder,
+    upload_strategy: UploadStrategy,
+    filter: FilterMode,
+    scaling_mode: ScalingMode,
+    rotation: Rotation,
+    flip: (bool, bool),
+    texture_border: TextureBorder,
+    sampler_descriptor: Option<wgpu::SamplerDescriptor<'static>>,
+    linear_light_filtering: bool,
+    supersampling: u32,
+    frame_averaging: u32,
+    flash_limiter: Option<FlashLimiter>,
+    frame_interpolation: Option<FrameInterpolation>,
+    pixel_grid: Option<PixelGrid>,
+    resize_transition: u32,
+    watermark: Option<Watermark>,
+    provenance_mark: Option<ProvenanceMark>,
+    ambient_bezel: Option<AmbientBezel>,
+    background: Option<Background>,
+    overlay_mode: bool,
+    alpha_mode: Option<wgpu::CompositeAlphaMode>,
+    downlevel: bool,
+    frame_stats: bool,
 }
 
 impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, 'win, W> {
@@ -49,18 +115,59 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
 
         Self {
             request_adapter_options: None,
+            shared_gpu: None,
             device_descriptor: None,
-            backend: wgpu::util::backend_bits_from_env().unwrap_or_else(wgpu::Backends::all),
+            backend: wgpu::Backends::all(),
+            backend_override: Box::new(|backends| {
+                wgpu::util::backend_bits_from_env().unwrap_or(backends)
+            }),
+            workaround_override: Box::new(|_, detected| detected),
             width,
             height,
             _pixel_aspect_ratio: 1.0,
             present_mode: wgpu::PresentMode::AutoVsync,
+            zero_size_policy: ZeroSizePolicy::Error,
             surface_texture,
+            surface: None,
             texture_format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            palette: None,
+            packed_format: None,
+            shader_hook: None,
+            gamma_ramp: None,
+            tonemapping: Tonemapping::None,
+            exposure: 1.0,
+            hdr: false,
             render_texture_format: None,
             surface_texture_format: None,
             clear_color: wgpu::Color::BLACK,
+            clear_color_space: None,
             blend_state: wgpu::BlendState::ALPHA_BLENDING,
+            texture_ring_size: 2,
+            layers: 0,
+            upload_slices: 1,
+            upload_order: UploadOrder::Sequential,
+            upload_strategy: UploadStrategy::WriteTexture,
+            filter: FilterMode::Nearest,
+            scaling_mode: ScalingMode::IntegerOnly,
+            rotation: Rotation::D0,
+            flip: (false, false),
+            texture_border: TextureBorder::Clamp,
+            sampler_descriptor: None,
+            linear_light_filtering: true,
+            supersampling: 1,
+            frame_averaging: 1,
+            flash_limiter: None,
+            frame_interpolation: None,
+            pixel_grid: None,
+            resize_transition: 0,
+            watermark: None,
+            provenance_mark: None,
+            ambient_bezel: None,
+            background: None,
+            overlay_mode: false,
+            alpha_mode: None,
+            downlevel: false,
+            frame_stats: false,
         }
     }
 
@@ -79,11 +186,99 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
         self
     }
 
+    /// Create the pixel buffer on the GPU of another, instead of requesting a new adapter and
+    /// device.
+    ///
+    /// See [`SharedGpu`]. The adapter and device options of this builder are ignored: the
+    /// [backends](PixelsBuilder::backends), the [backend
+    /// override](PixelsBuilder::backend_override), the [adapter
+    /// options](PixelsBuilder::request_adapter_options), and the [device
+    /// descriptor](PixelsBuilder::device_descriptor). Options which need device features fall
+    /// back as usual when the shared device lacks them.
+    ///
+    /// ```no_run
+    /// use pixels::{Pixels, PixelsBuilder};
+    ///
+    /// # let main_window = pixels_mocks::Window;
+    /// # let tool_window = pixels_mocks::Window;
+    /// # let main_surface = pixels::SurfaceTexture::new(640, 480, &main_window);
+    /// # let tool_surface = pixels::SurfaceTexture::new(128, 128, &tool_window);
+    /// let main = Pixels::new(640, 480, main_surface)?;
+    /// let palette = PixelsBuilder::new(16, 16, tool_surface)
+    ///     .shared_gpu(main.shared_gpu())
+    ///     .enable_vsync(false)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn shared_gpu(mut self, gpu: SharedGpu) -> Self {
+        self.shared_gpu = Some(gpu);
+        self
+    }
+
     /// Set which backends wgpu will attempt to use.
     ///
     /// The default enables all backends, including the backends with "best effort" support in wgpu.
-    pub fn wgpu_backend(mut self, backend: wgpu::Backends) -> Self {
-        self.backend = backend;
+    /// The backend that was actually chosen is available from [`Pixels::backend`].
+    ///
+    /// The requested backends are passed through the [backend override
+    /// hook](PixelsBuilder::backend_override) when the pixel buffer is built, which lets the
+    /// `WGPU_BACKEND` environment variable take precedence by default. That allows users to work
+    /// around driver bugs (e.g. forcing DX12 over Vulkan) without recompiling.
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::Backends;
+    ///
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .backends(Backends::DX12 | Backends::VULKAN)
+    ///     .build()?;
+    ///
+    /// println!("Rendering with {:?}", pixels.backend());
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn backends(mut self, backends: wgpu::Backends) -> Self {
+        self.backend = backends;
+        self
+    }
+
+    /// Set which backends wgpu will attempt to use.
+    ///
+    /// This is equivalent to [`PixelsBuilder::backends`].
+    pub fn wgpu_backend(self, backend: wgpu::Backends) -> Self {
+        self.backends(backend)
+    }
+
+    /// Replace the hook that chooses the final backends at build time.
+    ///
+    /// The hook receives the backends requested with [`PixelsBuilder::backends`] and returns the
+    /// backends to use. The default hook returns the backends named by the `WGPU_BACKEND`
+    /// environment variable when it is set, and the requested backends otherwise.
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::Backends;
+    ///
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // Read the backend from the application's own setting instead of `WGPU_BACKEND`
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .backend_override(|requested| match std::env::var("MY_GAME_BACKEND").as_deref() {
+    ///         Ok("dx12") => Backends::DX12,
+    ///         Ok("vulkan") => Backends::VULKAN,
+    ///         _ => requested,
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// Use `.backend_override(|requested| requested)` to ignore the environment entirely.
+    pub fn backend_override<F>(mut self, backend_override: F) -> Self
+    where
+        F: FnOnce(wgpu::Backends) -> wgpu::Backends + 'static,
+    {
+        self.backend_override = Box::new(backend_override);
         self
     }
 
@@ -125,25 +320,560 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
         self
     }
 
+    /// Set a hook which chooses the driver workarounds to apply.
+    ///
+    /// When the pixel buffer is built, the entries of
+    /// [`KNOWN_WORKAROUNDS`](workarounds::KNOWN_WORKAROUNDS) matching the adapter are detected
+    /// and passed to the hook with the adapter info, and the workarounds it returns are applied.
+    /// By default, the detected workarounds are applied unchanged. See the [`workarounds`] module.
+    ///
+    /// ```no_run
+    /// use pixels::workarounds::Workaround;
+    ///
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # let safe_mode = true;
+    /// // Let users opt into the most conservative settings after a black screen
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .workaround_override(move |_info, mut detected| {
+    ///         if safe_mode {
+    ///             detected.extend([Workaround::FifoPresentMode, Workaround::SingleTexture]);
+    ///         }
+    ///         detected
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// Use `.workaround_override(|_, _| Vec::new())` to disable all workarounds.
+    pub fn workaround_override<F>(mut self, workaround_override: F) -> Self
+    where
+        F: FnOnce(&wgpu::AdapterInfo, Vec<Workaround>) -> Vec<Workaround> + 'static,
+    {
+        self.workaround_override = Box::new(workaround_override);
+        self
+    }
+
     /// Set the `wgpu` present mode.
     ///
     /// This differs from [`PixelsBuilder::enable_vsync`] by allowing the present mode to be set to
-    /// any value.
+    /// any value. Unsupported modes fall back to an automatic mode, see
+    /// [`Pixels::set_present_mode`].
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::PresentMode;
+    /// use pixels::PixelsBuilder;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .present_mode(PresentMode::Mailbox)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
     pub fn present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
         self.present_mode = present_mode;
         self
     }
 
+    /// Set what [`Pixels::resize_surface`] does with a zero width or height, such as the size of
+    /// a minimized window.
+    ///
+    /// The default value is [`ZeroSizePolicy::Error`]. [`ZeroSizePolicy::Pause`] lets the event
+    /// loop pass every resize through unchanged and keep calling [`Pixels::render`]:
+    ///
+    /// ```no_run
+    /// use pixels::{PixelsBuilder, ZeroSizePolicy};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .zero_size_policy(ZeroSizePolicy::Pause)
+    ///     .build()?;
+    ///
+    /// // The window was minimized
+    /// pixels.resize_surface(0, 0)?;
+    /// assert!(pixels.is_paused());
+    /// pixels.render()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn zero_size_policy(mut self, policy: ZeroSizePolicy) -> Self {
+        self.zero_size_policy = policy;
+        self
+    }
+
+    /// Set the number of source textures that uploads rotate through.
+    ///
+    /// Each frame is uploaded to the next texture in the ring while the GPU may still be sampling
+    /// the previous one. This keeps `write_texture` from serializing behind the previous frame on
+    /// tile-based and mobile GPUs, at the cost of one texture per ring entry. The default value is
+    /// 2, for double buffering. A size of 1 uses a single texture.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `size` is 0.
+    pub fn texture_ring_size(mut self, size: usize) -> Self {
+        assert!(size > 0);
+        self.texture_ring_size = size;
+        self
+    }
+
+    /// Add `count` [layers](Layer) over the pixel buffer, e.g. for sprites and UI over a
+    /// background.
+    ///
+    /// Each layer is a pixel buffer of the same size and texture format, blended over the pixel
+    /// buffer texture on the GPU after every upload, with its own scroll offset and opacity. Get
+    /// them with [`Pixels::layer_mut`]. Layers are not drawn over an
+    /// [imported texture](Pixels::import_texture).
+    ///
+    /// With layers, the whole pixel buffer is uploaded on every render, because nothing of the
+    /// previous frame may remain under the blended layers. This overrides
+    /// [`PixelsBuilder::upload_slices`] and [`Pixels::set_dirty_tracking`].
+    ///
+    /// The default value is 0. Building fails with [`Error::InvalidTexture`] when the
+    /// [texture format](PixelsBuilder::texture_format) cannot be drawn to and blended, such as
+    /// integer and block compressed formats, and with a [palette](PixelsBuilder::palette) or a
+    /// [packed format](PixelsBuilder::packed_format).
+    ///
+    /// ```no_run
+    /// use pixels::PixelsBuilder;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // Background in the pixel buffer, then sprites, then UI
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .layers(2)
+    ///     .build()?;
+    /// assert_eq!(pixels.layer_count(), 2);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn layers(mut self, count: usize) -> Self {
+        self.layers = count;
+        self
+    }
+
+    /// Split the upload of the pixel buffer into `slices` parts spread across consecutive frames.
+    ///
+    /// Uploading a very large pixel buffer every frame can take longer than a frame on weak GPUs.
+    /// With slicing, each render uploads only `1 / slices` of the rows, so a full frame reaches the
+    /// screen over `slices` renders. This trades temporal coherence for consistent frame times:
+    /// rows from different frames are shown together, split by a tear line or interleaved
+    /// depending on `order`. The first render after creating or resizing the pixel buffer uploads
+    /// the whole frame.
+    ///
+    /// Sliced uploads all go to one texture instead of rotating through the
+    /// [texture ring](PixelsBuilder::texture_ring_size). Block compressed texture formats are
+    /// always uploaded whole.
+    ///
+    /// The default value is 1, which uploads the whole frame on every render. It can be changed
+    /// later with [`Pixels::set_upload_slices`].
+    ///
+    /// ```no_run
+    /// use pixels::{PixelsBuilder, UploadOrder};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(3840, 2160, &window);
+    /// // Spread each 4K frame over four renders
+    /// let mut pixels = PixelsBuilder::new(3840, 2160, surface_texture)
+    ///     .upload_slices(4, UploadOrder::Sequential)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `slices` is 0.
+    pub fn upload_slices(mut self, slices: u32, order: UploadOrder) -> Self {
+        assert!(slices > 0);
+        self.upload_slices = slices;
+        self.upload_order = order;
+        self
+    }
+
+    /// Set how the pixel buffer is copied to its texture on each render.
+    ///
+    /// The default value is [`UploadStrategy::WriteTexture`], which suits most buffer sizes. For
+    /// very large pixel buffers, [`UploadStrategy::StagingRing`] writes each frame into staging
+    /// buffers which stay mapped, and copies them on the GPU, so the upload overlaps with
+    /// rendering. Slicing and [dirty tracking](Pixels::set_dirty_tracking) work with both. It can
+    /// be changed later with [`Pixels::set_upload_strategy`].
+    ///
+    /// ```no_run
+    /// use pixels::{PixelsBuilder, UploadStrategy};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(1920, 1080, &window);
+    /// let mut pixels = PixelsBuilder::new(1920, 1080, surface_texture)
+    ///     .upload_strategy(UploadStrategy::StagingRing)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn upload_strategy(mut self, strategy: UploadStrategy) -> Self {
+        self.upload_strategy = strategy;
+        self
+    }
+
+    /// Set the filter used to sample the pixel buffer when it is scaled to the surface.
+    ///
+    /// The default value is [`FilterMode::Nearest`], which is crisp at integer scale factors.
+    /// At other scale factors, choose between the blur of [`FilterMode::Linear`] and the
+    /// antialiased edges of [`FilterMode::SharpBilinear`].
+    ///
+    /// ```no_run
+    /// use pixels::{FilterMode, PixelsBuilder, ScalingMode};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(1366, 768, &window);
+    /// let pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .scaling_mode(ScalingMode::Fit)
+    ///     .filter(FilterMode::SharpBilinear)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn filter(mut self, filter: FilterMode) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Set how the scaled pixel buffer fills the surface.
+    ///
+    /// The default value is [`ScalingMode::IntegerOnly`], which keeps pixel art crisp at the cost
+    /// of a border around the image. [`ScalingMode::Fit`] and [`ScalingMode::Stretch`] fill more
+    /// of the surface, but buffer pixels then cover different numbers of physical pixels; pair
+    /// them with [`FilterMode::SharpBilinear`], [`FilterMode::Linear`], or [`FilterMode::Area`] to
+    /// hide the uneven sizes.
+    ///
+    /// ```no_run
+    /// use pixels::{FilterMode, PixelsBuilder, ScalingMode};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(1920, 1080, &window);
+    /// let pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .scaling_mode(ScalingMode::Fit)
+    ///     .filter(FilterMode::Area)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn scaling_mode(mut self, mode: ScalingMode) -> Self {
+        self.scaling_mode = mode;
+        self
+    }
+
+    /// Rotate the scaled pixel buffer clockwise on the surface, for displays mounted sideways or
+    /// upside down.
+    ///
+    /// The buffer is fit to the surface with its width and height swapped by [`Rotation::D90`]
+    /// and [`Rotation::D270`]. Window coordinates map to the rotated buffer, so
+    /// [`Pixels::window_pos_to_pixel`] keeps returning upright pixel coordinates.
+    ///
+    /// The default is [`Rotation::D0`]. It can be changed later with [`Pixels::set_rotation`].
+    ///
+    /// ```no_run
+    /// use pixels::{PixelsBuilder, Rotation};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(1080, 1920, &window);
+    /// // A landscape handheld on a portrait panel
+    /// let pixels = PixelsBuilder::new(240, 160, surface_texture)
+    ///     .rotation(Rotation::D90)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// Mirror the scaled pixel buffer on the surface, `horizontal`ly and `vertical`ly, e.g. for
+    /// rear projection.
+    ///
+    /// The mirroring applies to the surface after the [rotation](PixelsBuilder::rotation). The
+    /// default is no flip. It can be changed later with [`Pixels::set_flip`].
+    pub fn flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.flip = (horizontal, vertical);
+        self
+    }
+
+    /// Set what the scaling pass samples outside of the pixel buffer texture.
+    ///
+    /// The default value is [`TextureBorder::Clamp`].
+    pub fn texture_border(mut self, texture_border: TextureBorder) -> Self {
+        self.texture_border = texture_border;
+        self
+    }
+
+    /// Use a custom sampler for the scaling pass, instead of the one derived from
+    /// [`PixelsBuilder::filter`] and [`PixelsBuilder::texture_border`].
+    ///
+    /// This gives full control over the min, mag, and mipmap filters, the address modes, and the
+    /// anisotropy clamp, for advanced scaling setups. The filter still selects the scaling shader,
+    /// e.g. area averaging for [`FilterMode::Area`]. The label is replaced, and `compare` is
+    /// ignored because the pixel buffer is not a depth texture.
+    ///
+    /// [`wgpu::AddressMode::ClampToBorder`] requires a device feature, which the builder requests
+    /// when the adapter supports it, as for [`TextureBorder::Color`]. Without it, the derived
+    /// sampler is used instead. An `anisotropy_clamp` above 1 requires linear filters.
+    ///
+    /// ```no_run
+    /// use pixels::{wgpu, PixelsBuilder};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // Smooth minification, crisp magnification, and a repeating texture
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .sampler_descriptor(wgpu::SamplerDescriptor {
+    ///         address_mode_u: wgpu::AddressMode::Repeat,
+    ///         address_mode_v: wgpu::AddressMode::Repeat,
+    ///         min_filter: wgpu::FilterMode::Linear,
+    ///         mag_filter: wgpu::FilterMode::Nearest,
+    ///         ..Default::default()
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn sampler_descriptor(mut self, descriptor: wgpu::SamplerDescriptor<'static>) -> Self {
+        self.sampler_descriptor = Some(descriptor);
+        self
+    }
+
+    /// Enable or disable filtering in linear light.
+    ///
+    /// Filtering gamma-encoded colors darkens the blended edges between bright and dark pixels.
+    /// With any filter but [`FilterMode::Nearest`], colors are decoded from sRGB before
+    /// filtering and encoded again afterward, avoiding the dark fringes. This is enabled by
+    /// default.
+    ///
+    /// Textures with an sRGB format, such as the default `Rgba8UnormSrgb`, are always filtered in
+    /// linear light by the GPU. For other 8-bit formats the texture is viewed as sRGB, which is
+    /// unsupported on WebGL2 and the [minimal pipeline](Pixels::minimal_pipeline); there, and for
+    /// formats without an sRGB variant, filtering happens on the stored values.
+    pub fn linear_light_filtering(mut self, enable: bool) -> Self {
+        self.linear_light_filtering = enable;
+        self
+    }
+
+    /// Render at a multiple of the surface size and downsample to the surface.
+    ///
+    /// The scaling pass and any custom render passes draw into an intermediate texture `factor`
+    /// times larger than the surface in each dimension, which is then averaged down with a box
+    /// filter. This improves the quality of rotation, CRT, and other shader effects on low-DPI
+    /// displays, and lets the pixel buffer scale by fractional amounts of a physical pixel. It
+    /// costs `factor * factor` times the fill rate and memory of the surface.
+    ///
+    /// The default value is 1, which disables supersampling.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `factor` is 0.
+    pub fn supersampling(mut self, factor: u32) -> Self {
+        assert!(factor > 0);
+        self.supersampling = factor;
+        self
+    }
+
+    /// Show the average of the last `frames` rendered frames, to hide flicker from content which
+    /// alternates every frame, such as interlaced CRT output or multiplexed LED displays.
+    ///
+    /// The default value is 1, which disables averaging. It can be changed later with
+    /// [`Pixels::set_frame_averaging`], which describes the costs.
+    pub fn frame_averaging(mut self, frames: u32) -> Self {
+        self.frame_averaging = frames;
+        self
+    }
+
     /// Set the texture format.
     ///
     /// The default value is `Rgba8UnormSrgb`, which is 4 unsigned bytes in `RGBA` order using the
     /// sRGB color space. This is typically what you want when you are working with color values
     /// from popular image editing tools or web apps.
     ///
-    /// This is the pixel format of the texture that most applications will interact with directly.
-    /// The format influences the structure of byte data that is returned by [`Pixels::frame`].
-    pub fn texture_format(mut self, texture_format: wgpu::TextureFormat) -> Self {
-        self.texture_format = texture_format;
+    /// This is the pixel format of the texture that most applications will interact with directly.
+    /// The format influences the structure of byte data that is returned by [`Pixels::frame`].
+    ///
+    /// Setting a texture format disables a [palette](PixelsBuilder::palette) or a
+    /// [packed format](PixelsBuilder::packed_format) set before.
+    pub fn texture_format(mut self, texture_format: wgpu::TextureFormat) -> Self {
+        self.texture_format = texture_format;
+        self.palette = None;
+        self.packed_format = None;
+        self
+    }
+
+    /// Use a pixel buffer in a packed layout without a matching texture format, which the
+    /// scaling shader unpacks on the GPU.
+    ///
+    /// Emulators which output RGB565 or grayscale frames copy them into the pixel buffer as they
+    /// are, instead of converting every pixel to RGBA on the CPU. The frame has
+    /// [`PackedFormat::texture_format`], which sets the row length of [`Pixels::frame`]: one byte
+    /// per pixel for [`PackedFormat::Luma8`], and two for [`PackedFormat::Rgb565`]. Like
+    /// [palette](PixelsBuilder::palette) colors, packed values are sRGB-encoded.
+    ///
+    /// This replaces the texture format and a palette set before.
+    ///
+    /// ```no_run
+    /// use pixels::{PackedFormat, PixelsBuilder};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(240, 160, &window);
+    /// let mut pixels = PixelsBuilder::new(240, 160, surface_texture)
+    ///     .packed_format(PackedFormat::Rgb565)
+    ///     .build()?;
+    /// assert_eq!(pixels.frame().len(), 240 * 160 * 2);
+    ///
+    /// // Pure red in RGB565, low byte first
+    /// for pixel in pixels.frame_mut().chunks_exact_mut(2) {
+    ///     pixel.copy_from_slice(&0xf800_u16.to_le_bytes());
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn packed_format(mut self, packed_format: PackedFormat) -> Self {
+        self.texture_format = packed_format.texture_format();
+        self.palette = None;
+        self.packed_format = Some(packed_format);
+        self
+    }
+
+    /// Use an indexed pixel buffer, with one palette index per pixel.
+    ///
+    /// The frame holds a single byte per pixel in the `R8Unorm` texture format, and the scaling
+    /// shader looks each index up in `palette` on the GPU. Retro emulators and demos save the
+    /// memory and CPU time of expanding indices to RGBA every frame, and can cycle colors by
+    /// updating the palette with [`Pixels::set_palette`]. Draw indexed sprites into the frame with
+    /// [`blit_indexed`](crate::blit_indexed).
+    ///
+    /// Palette colors are 8-bit RGBA in the sRGB color space, like the default `Rgba8UnormSrgb`
+    /// texture format. Indices are looked up without filtering, so [`PixelsBuilder::filter`] has
+    /// no effect apart from shrinking buffers larger than the surface with [`FilterMode::Area`].
+    /// Effects which sample the pixel buffer texture themselves, such as the
+    /// [ambient bezel](PixelsBuilder::ambient_bezel) and [transitions](Pixels::start_transition),
+    /// see the raw indices.
+    ///
+    /// ```no_run
+    /// use pixels::PixelsBuilder;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // A grayscale ramp
+    /// let palette = std::array::from_fn(|i| [i as u8, i as u8, i as u8, 0xff]);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .palette(&palette)
+    ///     .build()?;
+    ///
+    /// pixels.frame_mut().fill(0x80);
+    /// assert_eq!(pixels.frame().len(), 320 * 240);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn palette(mut self, palette: &[[u8; 4]; 256]) -> Self {
+        self.texture_format = wgpu::TextureFormat::R8Unorm;
+        self.packed_format = None;
+        self.palette = Some(Box::new(*palette));
+        self
+    }
+
+    /// Add WGSL code to the default scaling shader, with a uniform buffer holding `uniforms`.
+    ///
+    /// Simple whole-screen effects, like a tint, a fade, or a wobble, fit in the scaling pass
+    /// without replacing it with [`Pixels::render_with`] or adding a
+    /// [render effect](Pixels::add_effect). The code is appended to the scaling shader and must
+    /// define this function, which is called with every output color:
+    ///
+    /// ```wgsl
+    /// fn user_color(color: vec4<f32>, tex_coord: vec2<f32>) -> vec4<f32>
+    /// ```
+    ///
+    /// `tex_coord` is the pixel buffer coordinate of the fragment, from `(0.0, 0.0)` at the
+    /// top-left corner to `(1.0, 1.0)`. The code may sample the pixel buffer itself, e.g. at an
+    /// offset, with `r_tex_color` and `r_tex_sampler`. The uniforms are bound to
+    /// `@group(0) @binding(4)`, padded to a multiple of 16 bytes, and updated with
+    /// [`Pixels::set_uniform`]. Flashes and fades of the pixel buffer are applied to the returned
+    /// color.
+    ///
+    /// The [minimal pipeline](Pixels::minimal_pipeline) does not run the hook.
+    ///
+    /// ```no_run
+    /// use pixels::PixelsBuilder;
+    ///
+    /// #[repr(C)]
+    /// #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    /// struct Tint {
+    ///     color: [f32; 4],
+    /// }
+    ///
+    /// const TINT: &str = "
+    /// @group(0) @binding(4) var<uniform> tint: vec4<f32>;
+    ///
+    /// fn user_color(color: vec4<f32>, tex_coord: vec2<f32>) -> vec4<f32> {
+    ///     return vec4<f32>(mix(color.rgb, tint.rgb, tint.a), color.a);
+    /// }
+    /// ";
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let no_tint = Tint { color: [0.0; 4] };
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .shader_hook(TINT, &no_tint)
+    ///     .build()?;
+    ///
+    /// // The player took damage
+    /// pixels.set_uniform(&Tint {
+    ///     color: [1.0, 0.0, 0.0, 0.5],
+    /// });
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// [`PixelsBuilder::build`] panics when the code is not valid with the scaling shader.
+    pub fn shader_hook<T: bytemuck::Pod>(mut self, wgsl: &str, uniforms: &T) -> Self {
+        self.shader_hook = Some(ShaderHook::new(wgsl, bytemuck::bytes_of(uniforms)));
+        self
+    }
+
+    /// Map every color of the scaled pixel buffer through a per-channel [`GammaRamp`].
+    ///
+    /// This emulates the display gamma ramps of `SetDeviceGammaRamp` and similar APIs, for ports
+    /// of games which fade or brighten the screen with them. The ramp only affects this pixel
+    /// buffer, not the desktop, and is replaced at runtime with [`Pixels::set_gamma_ramp`]
+    /// without rebuilding any pipelines.
+    ///
+    /// The ramp applies last in the scaling pass, after the [shader hook](Self::shader_hook) and
+    /// flashes, and before [render effects](Pixels::add_effect) and overlays. The
+    /// [minimal pipeline](Pixels::minimal_pipeline) does not apply it.
+    pub fn gamma_ramp(mut self, ramp: &GammaRamp) -> Self {
+        self.gamma_ramp = Some(Box::new(ramp.clone()));
+        self
+    }
+
+    /// Set the [`Tonemapping`] curve of the scaled pixel buffer.
+    ///
+    /// The default keeps colors as they are. Change it at runtime with
+    /// [`Pixels::set_tonemapping`]. The [minimal pipeline](Pixels::minimal_pipeline) does not
+    /// apply it.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the white point of [`Tonemapping::Reinhard`] is not positive.
+    pub fn tonemapping(mut self, tonemapping: Tonemapping) -> Self {
+        tonemapping.validate();
+        self.tonemapping = tonemapping;
+        self
+    }
+
+    /// Multiply the colors of the scaled pixel buffer by `exposure`, before
+    /// [tonemapping](Self::tonemapping).
+    ///
+    /// The default is `1.0`. On [HDR](Self::hdr) surfaces, `1.0` is the SDR white of the
+    /// platform, which is often darker than the desktop: e.g. Windows shows it at 80 nits. Raise
+    /// the exposure to match the SDR brightness setting of the display. Change it at runtime with
+    /// [`Pixels::set_exposure`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when `exposure` is negative or not finite.
+    pub fn exposure(mut self, exposure: f32) -> Self {
+        assert!(exposure.is_finite() && exposure >= 0.0);
+        self.exposure = exposure;
         self
     }
 
@@ -174,11 +904,53 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
     /// texture, but a view is provided to the `render_function` closure by [`Pixels::render_with`].
     /// The render texture can only be used as the final render target at the end of all
     /// post-processing shaders.
+    ///
+    /// Where the adapter supports surface view formats, the format only needs to be supported up
+    /// to its sRGB encoding: a surface which offers `Bgra8Unorm` but not `Bgra8UnormSrgb` is
+    /// configured with the former and viewed as the latter. The automatic choice uses this to
+    /// prefer sRGB views on such surfaces, e.g. on WebGPU.
     pub fn surface_texture_format(mut self, texture_format: wgpu::TextureFormat) -> Self {
         self.surface_texture_format = Some(texture_format);
         self
     }
 
+    /// Prefer an HDR surface, with colors brighter than SDR white and a wider gamut.
+    ///
+    /// When the surface supports `Rgba16Float`, it is chosen as the surface texture format, in
+    /// place of the 8-bit sRGB formats which clamp every color to `1.0`. The platform shows it
+    /// in extended linear sRGB (scRGB), where `1.0` is SDR white and negative values are colors
+    /// outside of the sRGB gamut. Check which format was chosen with [`Pixels::is_hdr`]; other
+    /// surfaces fall back to the usual choice.
+    ///
+    /// 8-bit pixel buffers look the same on HDR surfaces, apart from the
+    /// [exposure](Self::exposure). Use a float [texture format](Self::texture_format), e.g.
+    /// `Rgba16Float`, with values in linear light for brighter colors, and a
+    /// [tonemapping curve](Self::tonemapping) to show them on SDR surfaces as well.
+    ///
+    /// This has no effect with [`PixelsBuilder::surface_texture_format`].
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::TextureFormat;
+    /// use pixels::{PixelsBuilder, Tonemapping};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .texture_format(TextureFormat::Rgba16Float)
+    ///     .hdr(true)
+    ///     .build()?;
+    ///
+    /// // Compress the highlights on SDR displays
+    /// if !pixels.is_hdr() {
+    ///     pixels.set_tonemapping(Tonemapping::Aces);
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn hdr(mut self, hdr: bool) -> Self {
+        self.hdr = hdr;
+        self
+    }
+
     /// Set the blend state.
     ///
     /// Allows customization of how to mix the new and existing pixels in a texture
@@ -227,8 +999,299 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
     ///     .build()?;
     /// # Ok::<(), pixels::Error>(())
     /// ```
+    ///
+    /// The color components are passed to the GPU unchanged, so the resulting shade depends on
+    /// whether the render texture format is sRGB. Use [`PixelsBuilder::clear_color_srgb`] or
+    /// [`PixelsBuilder::clear_color_linear`] for the same shade on every platform.
     pub fn clear_color(mut self, color: wgpu::Color) -> Self {
         self.clear_color = color;
+        self.clear_color_space = None;
+        self
+    }
+
+    /// Set the clear color with components in the sRGB color space.
+    ///
+    /// This is the color space used by image editors and color pickers. The color is converted as
+    /// needed for the render texture format, so the border has the same shade on every platform.
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::Color;
+    ///
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // Set clear color to `#336699`.
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .clear_color_srgb(Color {
+    ///         r: 0.2,
+    ///         g: 0.4,
+    ///         b: 0.6,
+    ///         a: 1.0,
+    ///     })
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn clear_color_srgb(mut self, color: wgpu::Color) -> Self {
+        self.clear_color = color;
+        self.clear_color_space = Some(ColorSpace::Srgb);
+        self
+    }
+
+    /// Set the clear color with components in linear light.
+    ///
+    /// The color is converted as needed for the render texture format, so the border has the
+    /// same shade on every platform.
+    pub fn clear_color_linear(mut self, color: wgpu::Color) -> Self {
+        self.clear_color = color;
+        self.clear_color_space = Some(ColorSpace::Linear);
+        self
+    }
+
+    /// Enable the [`FlashLimiter`] to attenuate large frame-to-frame luminance swings.
+    ///
+    /// The flash limiter is disabled by default. It can be changed later with
+    /// [`Pixels::set_flash_limiter`].
+    pub fn flash_limiter(mut self, flash_limiter: FlashLimiter) -> Self {
+        self.flash_limiter = Some(flash_limiter);
+        self
+    }
+
+    /// Interpolate between the last two frames of the pixel buffer, for content which updates
+    /// slower than the display.
+    ///
+    /// See [`FrameInterpolation`] for the modes. Interpolation needs the previous frame in the
+    /// [texture ring](PixelsBuilder::texture_ring_size), so it has no effect with a ring size of 1,
+    /// [sliced uploads](PixelsBuilder::upload_slices), [dirty region
+    /// tracking](Pixels::set_dirty_tracking), or an [imported texture](Pixels::import_texture).
+    ///
+    /// Interpolation is disabled by default. It can be changed later with
+    /// [`Pixels::set_frame_interpolation`].
+    ///
+    /// ```no_run
+    /// use pixels::{FrameInterpolation, PixelsBuilder};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // Smooth a 30 Hz simulation on a faster display
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .frame_interpolation(FrameInterpolation::Crossfade)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn frame_interpolation(mut self, mode: FrameInterpolation) -> Self {
+        self.frame_interpolation = Some(mode);
+        self
+    }
+
+    /// Draw a [`PixelGrid`] between the pixels of the scaled pixel buffer.
+    ///
+    /// The grid is disabled by default. It can be changed later with [`Pixels::set_pixel_grid`].
+    pub fn pixel_grid(mut self, pixel_grid: PixelGrid) -> Self {
+        self.pixel_grid = Some(pixel_grid);
+        self
+    }
+
+    /// Crossfade from the previous frame to the new one over `frames` frames when
+    /// [`Pixels::resize_buffer`] is called.
+    ///
+    /// The default value is 0, which disables the transition. It can be changed later with
+    /// [`Pixels::set_resize_transition`].
+    pub fn resize_transition(mut self, frames: u32) -> Self {
+        self.resize_transition = frames;
+        self
+    }
+
+    /// Stamp a [`Watermark`] at a corner of the surface.
+    ///
+    /// There is no watermark by default. It can be changed later with [`Pixels::set_watermark`].
+    pub fn watermark(mut self, watermark: Watermark) -> Self {
+        self.watermark = Some(watermark);
+        self
+    }
+
+    /// Mark every presented frame with an invisible [`ProvenanceMark`], which traces screenshots
+    /// to the build and frame.
+    ///
+    /// There is no mark by default. It can be changed later with [`Pixels::set_provenance_mark`].
+    /// See the [`provenance`](crate::provenance) module.
+    pub fn provenance_mark(mut self, mark: ProvenanceMark) -> Self {
+        self.provenance_mark = Some(mark);
+        self
+    }
+
+    /// Fill the letterbox bars around the scaled pixel buffer with a dimmed, blurred copy of the
+    /// frame stretched to cover the surface, instead of the clear color.
+    ///
+    /// This is the "ambient" look used by video players, drawn by one cheap extra pass. It is drawn
+    /// after the [`Pixels::render_with`] closure, over anything custom render passes draw in the
+    /// bars. It has no effect with the [minimal pipeline](Pixels::minimal_pipeline).
+    ///
+    /// ```no_run
+    /// use pixels::{AmbientBezel, PixelsBuilder};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(1920, 1080, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .ambient_bezel(AmbientBezel::default())
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// The bars use the clear color by default. It can be changed later with
+    /// [`Pixels::set_ambient_bezel`].
+    pub fn ambient_bezel(mut self, bezel: AmbientBezel) -> Self {
+        self.ambient_bezel = Some(bezel);
+        self
+    }
+
+    /// Fill the letterbox bars around the scaled pixel buffer with a [`Background`] image,
+    /// instead of the clear color.
+    ///
+    /// Like the [ambient bezel](PixelsBuilder::ambient_bezel), it is drawn after the
+    /// [`Pixels::render_with`] closure, over anything custom render passes draw in the bars. An
+    /// ambient bezel covers the background.
+    ///
+    /// There is no background by default. It can be changed later with
+    /// [`Pixels::set_background`].
+    pub fn background(mut self, background: Background) -> Self {
+        self.background = Some(background);
+        self
+    }
+
+    /// Apply saved video settings, replacing the options they cover.
+    ///
+    /// This is the same as setting each option of the [`PixelsConfig`] with its builder method,
+    /// e.g. [`PixelsBuilder::clear_color_srgb`] for the clear color. Like
+    /// [`PixelsBuilder::texture_format`], the texture format disables a palette or a packed format
+    /// set before, so set those after the config.
+    ///
+    /// ```no_run
+    /// use pixels::{PixelsBuilder, PixelsConfig};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # let load_settings = PixelsConfig::default;
+    /// let config: PixelsConfig = load_settings();
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .config(&config)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn config(self, config: &PixelsConfig) -> Self {
+        let mut builder = self
+            .scaling_mode(config.scaling_mode)
+            .filter(config.filter)
+            .present_mode(config.present_mode)
+            .clear_color_srgb(config.clear_color)
+            .texture_format(config.texture_format);
+        builder.pixel_grid = config.pixel_grid;
+        builder.ambient_bezel = config.ambient_bezel;
+        builder.frame_interpolation = config.frame_interpolation;
+        builder
+    }
+
+    /// Enable or disable overlay mode for transparent windows.
+    ///
+    /// When enabled, the surface uses a transparent composite alpha mode (if the platform supports
+    /// one) and the clear color defaults to transparent black, so the desktop shows through the
+    /// border and through pixels with alpha less than `0xff`. See the [`overlay`](crate::overlay)
+    /// module for creating a suitable window.
+    ///
+    /// Overlay mode is disabled by default.
+    pub fn overlay_mode(mut self, overlay_mode: bool) -> Self {
+        self.overlay_mode = overlay_mode;
+        if overlay_mode {
+            self.clear_color = wgpu::Color::TRANSPARENT;
+        }
+        self
+    }
+
+    /// Request a composite alpha mode for the surface.
+    ///
+    /// The alpha mode controls how the window compositor blends the surface with the windows
+    /// beneath it. By default, the first mode reported by the surface is used, which is usually
+    /// opaque. The modes supported on the current platform are available from
+    /// [`Pixels::supported_alpha_modes`].
+    ///
+    /// This takes precedence over the alpha mode chosen by [`PixelsBuilder::overlay_mode`].
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::CompositeAlphaMode;
+    /// use pixels::{Error, PixelsBuilder};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// match PixelsBuilder::new(320, 240, surface_texture)
+    ///     .alpha_mode(CompositeAlphaMode::PreMultiplied)
+    ///     .build()
+    /// {
+    ///     Ok(pixels) => { /* ... */ }
+    ///     Err(Error::UnsupportedAlphaMode { supported, .. }) => {
+    ///         eprintln!("Transparency is unavailable, supported modes: {supported:?}");
+    ///     }
+    ///     Err(err) => return Err(err),
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn alpha_mode(mut self, alpha_mode: wgpu::CompositeAlphaMode) -> Self {
+        self.alpha_mode = Some(alpha_mode);
+        self
+    }
+
+    /// Configure the pixel buffer for Raspberry Pi and other GLES-class devices.
+    ///
+    /// This preset:
+    ///
+    /// - Selects the OpenGL backend (GLES on embedded Linux, WebGL2 on the Web).
+    /// - Requests [`wgpu::Limits::downlevel_webgl2_defaults`] with the texture size limits of the
+    ///   adapter, instead of every limit the adapter reports. Drivers for these devices commonly
+    ///   advertise limits they cannot actually satisfy.
+    /// - Picks a surface texture format the surface reports as supported, rather than assuming
+    ///   `Bgra8UnormSrgb` is available.
+    /// - Uses the minimal scaling pipeline, see [`Pixels::minimal_pipeline`]. This is also chosen
+    ///   automatically for adapters that only support GLES2-class shaders.
+    ///
+    /// Options given explicitly with [`PixelsBuilder::backends`],
+    /// [`PixelsBuilder::device_descriptor`], or [`PixelsBuilder::surface_texture_format`] take
+    /// precedence when called after this method.
+    ///
+    /// ```no_run
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .downlevel_profile()
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn downlevel_profile(mut self) -> Self {
+        self.backend = wgpu::Backends::GL;
+        self.downlevel = true;
+        self
+    }
+
+    /// Enable the frame statistics of [`Pixels::frame_stats`] and [`Pixels::on_frame_stats`],
+    /// including the GPU time of each frame and of named [scopes](PixelsContext::scope).
+    ///
+    /// GPU time is measured with timestamp queries, so the default device requests
+    /// [`wgpu::Features::TIMESTAMP_QUERY`] and [`wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS`]
+    /// when the adapter supports them. Devices without them, including those from a [custom
+    /// descriptor](PixelsBuilder::device_descriptor) or a [shared GPU](PixelsBuilder::shared_gpu)
+    /// which lack the features, only report CPU timings.
+    ///
+    /// Frame statistics are disabled by default.
+    ///
+    /// ```no_run
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .enable_frame_stats(true)
+    ///     .build()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn enable_frame_stats(mut self, enable: bool) -> Self {
+        self.frame_stats = enable;
         self
     }
 
@@ -239,86 +1302,348 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
     ///
     /// # Errors
     ///
-    /// Returns an error when a [`wgpu::Adapter`] cannot be found.
-    async fn build_impl(self) -> Result<Pixels<'win>, Error> {
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-            backends: self.backend,
-            ..Default::default()
-        });
+    /// Returns an error when a [`wgpu::Adapter`] cannot be found, or a [shared
+    /// adapter](PixelsBuilder::shared_gpu) cannot present to the surface.
+    async fn build_impl(mut self) -> Result<Pixels<'win>, Error> {
+        // Palette indices and packed values cannot be blended by layers
+        if self.layers > 0 && (self.palette.is_some() || self.packed_format.is_some()) {
+            return Err(TextureError::TextureFormat(self.texture_format).into());
+        }
+
+        let instance = match &self.shared_gpu {
+            Some(gpu) => Arc::clone(&gpu.instance),
+            None => Arc::new(wgpu::Instance::new(wgpu::InstanceDescriptor {
+                backends: (self.backend_override)(self.backend),
+                ..Default::default()
+            })),
+        };
 
         // TODO: Use `options.pixel_aspect_ratio` to stretch the scaled texture
-        let surface = instance.create_surface(self.surface_texture.window)?;
-        let compatible_surface = Some(&surface);
-        let request_adapter_options = &self.request_adapter_options;
-        let adapter = match wgpu::util::initialize_adapter_from_env(&instance, compatible_surface) {
-            Some(adapter) => Some(adapter),
-            None => {
-                instance
-                    .request_adapter(&request_adapter_options.as_ref().map_or_else(
-                        || wgpu::RequestAdapterOptions {
-                            compatible_surface,
-                            force_fallback_adapter: false,
-                            power_preference:
-                                wgpu::util::power_preference_from_env().unwrap_or_default(),
-                        },
-                        |rao| wgpu::RequestAdapterOptions {
-                            compatible_surface: rao.compatible_surface.or(compatible_surface),
-                            force_fallback_adapter: rao.force_fallback_adapter,
-                            power_preference: rao.power_preference,
-                        },
-                    ))
-                    .await
-            }
+        let surface = match self.surface.take() {
+            Some(surface) => Some(surface),
+            None => self
+                .surface_texture
+                .window
+                .take()
+                .map(|window| instance.create_surface(window))
+                .transpose()?,
         };
+        let (adapter, device, queue, device_lost) = match self.shared_gpu.take() {
+            Some(gpu) => {
+                // The shared adapter was chosen for another surface
+                if let Some(surface) = &surface {
+                    if surface.get_capabilities(&gpu.adapter).formats.is_empty() {
+                        return Err(Error::AdapterNotFound);
+                    }
+                }
+
+                (gpu.adapter, gpu.device, gpu.queue, gpu.device_lost)
+            }
+            None => {
+                let compatible_surface = surface.as_ref();
+                let request_adapter_options = &self.request_adapter_options;
+                let adapter =
+                    match wgpu::util::initialize_adapter_from_env(&instance, compatible_surface) {
+                        Some(adapter) => Some(adapter),
+                        None => {
+                            instance
+                                .request_adapter(&request_adapter_options.as_ref().map_or_else(
+                                    || {
+                                        wgpu::RequestAdapterOptions {
+                                            compatible_surface,
+                                            force_fallback_adapter: false,
+                                            power_preference:
+                                                wgpu::util::power_preference_from_env()
+                                                    .unwrap_or_default(),
+                                        }
+                                    },
+                                    |rao| wgpu::RequestAdapterOptions {
+                                        compatible_surface:
+                                            rao.compatible_surface.or(compatible_surface),
+                                        force_fallback_adapter: rao.force_fallback_adapter,
+                                        power_preference: rao.power_preference,
+                                    },
+                                ))
+                                .await
+                        }
+                    };
 
-        let adapter = adapter.ok_or(Error::AdapterNotFound)?;
+                let adapter = adapter.ok_or(Error::AdapterNotFound)?;
 
-        let device_descriptor = self
-            .device_descriptor
-            .unwrap_or_else(|| wgpu::DeviceDescriptor {
-                required_limits: adapter.limits(),
-                ..wgpu::DeviceDescriptor::default()
-            });
+                let device_descriptor = self.device_descriptor.unwrap_or_else(|| {
+                    let required_limits = if self.downlevel {
+                        wgpu::Limits::downlevel_webgl2_defaults().using_resolution(adapter.limits())
+                    } else {
+                        adapter.limits()
+                    };
+
+                    wgpu::DeviceDescriptor {
+                        required_features: (self.texture_border.required_feature()
+                            | self.sampler_descriptor.as_ref().map_or(
+                                wgpu::Features::empty(),
+                                renderers::sampler_required_features,
+                            )
+                            | if self.frame_stats {
+                                profiling::TIMESTAMP_FEATURES
+                            } else {
+                                wgpu::Features::empty()
+                            })
+                            & adapter.features(),
+                        required_limits,
+                        ..wgpu::DeviceDescriptor::default()
+                    }
+                });
+
+                // Report exactly which limits are unsupported, instead of an opaque device request
+                // error
+                let mut unsupported_limits = Vec::new();
+                device_descriptor.required_limits.check_limits_with_fail_fn(
+                    &adapter.limits(),
+                    false,
+                    |name, _, _| unsupported_limits.push(name),
+                );
+                if !unsupported_limits.is_empty() {
+                    return Err(Error::UnsupportedLimits(unsupported_limits));
+                }
+
+                let (device, queue) = adapter.request_device(&device_descriptor, None).await?;
+                let device_lost = watch_device_lost(&device);
+
+                (
+                    Arc::new(adapter),
+                    Arc::new(device),
+                    Arc::new(queue),
+                    device_lost,
+                )
+            }
+        };
+        let adapter_info = adapter.get_info();
+        let detected = workarounds::detect(&adapter_info);
+        let workarounds = (self.workaround_override)(&adapter_info, detected);
+        let texture_ring_size = if workarounds.contains(&Workaround::SingleTexture) {
+            1
+        } else {
+            self.texture_ring_size
+        };
 
-        let (device, queue) = adapter.request_device(&device_descriptor, None).await?;
+        // GLES2-class adapters get the minimal scaling pipeline
+        let downlevel_capabilities = adapter.get_downlevel_capabilities();
+        let scaling_options = ScalingOptions {
+            minimal: self.downlevel
+                || downlevel_capabilities.shader_model == wgpu::ShaderModel::Sm2
+                || workarounds.contains(&Workaround::MinimalPipeline),
+            filter: self.filter,
+            linear_light: self.linear_light_filtering
+                && downlevel_capabilities
+                    .flags
+                    .contains(wgpu::DownlevelFlags::VIEW_FORMATS),
+            border: if device
+                .features()
+                .contains(self.texture_border.required_feature())
+            {
+                self.texture_border
+            } else {
+                TextureBorder::Clamp
+            },
+            sampler: self.sampler_descriptor.filter(|descriptor| {
+                device
+                    .features()
+                    .contains(renderers::sampler_required_features(descriptor))
+            }),
+            palette: self.palette,
+            packed: self.packed_format,
+            hook: self.shader_hook,
+            gamma_ramp: self.gamma_ramp,
+            tonemapping: self.tonemapping,
+            exposure: self.exposure,
+            mode: self.scaling_mode,
+            rotation: self.rotation,
+            flip: self.flip,
+        };
 
-        let surface_capabilities = surface.get_capabilities(&adapter);
+        let surface_capabilities = match &surface {
+            Some(surface) => surface.get_capabilities(&adapter),
+            None => offscreen_capabilities(),
+        };
         let present_mode = self.present_mode;
+        let reinterpret = adapter
+            .get_downlevel_capabilities()
+            .flags
+            .contains(wgpu::DownlevelFlags::SURFACE_VIEW_FORMATS);
         let surface_texture_format = self.surface_texture_format.unwrap_or_else(|| {
-            *surface_capabilities
-                .formats
+            let formats = &surface_capabilities.formats;
+            let hdr = wgpu::TextureFormat::Rgba16Float;
+            let hdr = (self.hdr && formats.contains(&hdr)).then_some(hdr);
+            // Surfaces without sRGB formats, like on WebGPU, can still be viewed as sRGB
+            let srgb = formats.iter().copied().find(|format| format.is_srgb());
+            let srgb_view = formats
                 .iter()
-                .find(|format| format.is_srgb())
-                .unwrap_or(&wgpu::TextureFormat::Bgra8UnormSrgb)
+                .map(|format| format.add_srgb_suffix())
+                .find(|format| reinterpret && format.is_srgb());
+            let fallback = if self.downlevel {
+                formats.first().copied()
+            } else {
+                None
+            };
+
+            hdr.or(srgb)
+                .or(srgb_view)
+                .or(fallback)
+                .unwrap_or(wgpu::TextureFormat::Bgra8UnormSrgb)
         });
+        let surface_storage_format = surface_storage_format(
+            &surface_capabilities.formats,
+            surface_texture_format,
+            reinterpret,
+        );
         let render_texture_format = self.render_texture_format.unwrap_or(surface_texture_format);
 
         // Create the backing texture
         let surface_size = self.surface_texture.size;
-        let clear_color = self.clear_color;
+        let render_size = surface_size.scaled(self.supersampling);
+        check_texture_size(&device, render_size.width, render_size.height)?;
+        let clear_color = match self.clear_color_space {
+            Some(color_space) => {
+                color::clear_color_for_target(self.clear_color, color_space, render_texture_format)
+            }
+            None => self.clear_color,
+        };
         let blend_state = self.blend_state;
-        let (scaling_matrix_inverse, texture_extent, texture, scaling_renderer, pixels_buffer_size) =
-            create_backing_texture(
-                &device,
-                // Backing texture values
-                self.width,
-                self.height,
-                self.texture_format,
-                // Render texture values
-                &surface_size,
-                render_texture_format,
-                // Clear color and blending values
-                clear_color,
-                blend_state,
-            )?;
+        let (
+            scaling_matrix_inverse,
+            texture_extent,
+            mut textures,
+            scaling_renderer,
+            pixels_buffer_size,
+        ) = create_backing_texture(
+            &device,
+            // Backing texture values
+            self.width,
+            self.height,
+            self.texture_format,
+            // Render texture values
+            &render_size,
+            render_texture_format,
+            // Clear color and blending values
+            clear_color,
+            blend_state,
+            &scaling_options,
+            texture_ring_size,
+        )?;
+        let texture = textures.pop_front().unwrap();
 
         // Create the pixel buffer
         let mut pixels = Vec::with_capacity(pixels_buffer_size);
         pixels.resize_with(pixels_buffer_size, Default::default);
 
-        let alpha_mode = surface_capabilities.alpha_modes[0];
+        // Create the layers over it
+        let (layers, layer_compositor) = if self.layers > 0 {
+            layer::check_layer_format(&device, self.texture_format)?;
+            let compositor = LayerCompositor::new(&device, self.texture_format);
+            let layers = (0..self.layers)
+                .map(|_| {
+                    Layer::new(
+                        &device,
+                        &compositor,
+                        self.width,
+                        self.height,
+                        self.texture_format,
+                    )
+                })
+                .collect();
+            (layers, Some(compositor))
+        } else {
+            (Vec::new(), None)
+        };
+
+        let supported_alpha_modes = &surface_capabilities.alpha_modes;
+        let alpha_mode = match self.alpha_mode {
+            Some(requested) if !supported_alpha_modes.contains(&requested) => {
+                return Err(Error::UnsupportedAlphaMode {
+                    requested,
+                    supported: supported_alpha_modes.clone(),
+                });
+            }
+            Some(requested) => requested,
+            None => self
+                .overlay_mode
+                .then(|| overlay::transparent_alpha_mode(supported_alpha_modes))
+                .flatten()
+                .unwrap_or(supported_alpha_modes[0]),
+        };
+
+        let grid_renderer = GridRenderer::new(
+            &device,
+            &texture_extent,
+            &render_size,
+            render_texture_format,
+            scaling_options.fit(),
+            self.pixel_grid,
+        );
+        let selection_renderer = SelectionRenderer::new(
+            &device,
+            &texture_extent,
+            &render_size,
+            render_texture_format,
+            scaling_options.fit(),
+        );
+
+        let supersampler = (self.supersampling > 1).then(|| {
+            Supersampler::new(
+                &device,
+                &surface_size,
+                render_texture_format,
+                self.supersampling,
+            )
+        });
+
+        let bezel_renderer = self
+            .ambient_bezel
+            .map(|bezel| BezelRenderer::new(&device, render_texture_format, bezel));
+
+        let background_renderer = self.background.map(|background| {
+            BackgroundRenderer::new(&device, &queue, background, render_texture_format)
+        });
+
+        let frame_averager = (self.frame_averaging > 1).then(|| {
+            FrameAverager::new(
+                &device,
+                &surface_size,
+                render_texture_format,
+                self.frame_averaging,
+            )
+        });
+
+        let provenance_renderer = self.provenance_mark.map(|mark| {
+            ProvenanceRenderer::new(
+                &device,
+                &surface_size,
+                render_texture_format,
+                surface_texture_format,
+                mark,
+            )
+        });
+
+        let watermark_renderer = self.watermark.map(|watermark| {
+            WatermarkRenderer::new(
+                &device,
+                &queue,
+                watermark,
+                &render_size,
+                render_texture_format,
+                self.supersampling,
+            )
+        });
 
         // Instantiate the Pixels struct
+        let frame_metadata = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_frame_metadata_buffer"),
+            size: crate::FRAME_METADATA_SIZE as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let profiler = Profiler::new(&device, &queue, self.frame_stats);
         let context = PixelsContext {
             device,
             queue,
@@ -328,21 +1653,95 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
             texture_format: self.texture_format,
             texture_format_size: texture_format_size(self.texture_format),
             scaling_renderer,
+            grid_renderer,
+            selection_renderer,
+            frame_metadata,
+            profiler,
         };
 
-        let pixels = Pixels {
+        let mut pixels = Pixels {
             context,
+            instance,
             adapter,
+            device_lost,
             surface_size,
             present_mode,
             render_texture_format,
             surface_texture_format,
+            surface_storage_format,
             blend_state,
-            pixels,
+            pixels: Arc::new(pixels),
+            layers,
+            layer_compositor,
+            flash_limiter: self.flash_limiter,
+            spare_textures: textures,
+            texture_ring_size,
+            upload_slices: self.upload_slices,
+            upload_order: self.upload_order,
+            upload_slice: 0,
+            staging_ring: (self.upload_strategy == UploadStrategy::StagingRing)
+                .then(Default::default),
+            full_upload: true,
+            frame_interpolator: None,
+            frame_changed: true,
+            dirty_rects: None,
+            imported_texture: None,
+            workarounds,
+            scaling_options,
+            distortion_mesh: None,
+            supersampler,
+            frame_averager,
+            provenance_renderer,
+            resize_transition: self.resize_transition,
+            window_aspect_locked: false,
+            redraw_policy: RedrawPolicy::default(),
+            zero_size_policy: self.zero_size_policy,
+            surface_paused: false,
+            redraw_pending: true,
+            crossfade: None,
+            transition: None,
+            shake: None,
+            screen_flash: None,
+            color_overlay_renderer: None,
+            watermark_renderer,
+            bezel_renderer,
+            background_renderer,
+            target_blitter: None,
+            pending_copies: Vec::new(),
+            spare_copy_textures: Vec::new(),
+            pending_blits: Vec::new(),
+            text_grid_renderer: None,
+            pending_text_grids: Vec::new(),
+            sprite_renderer: None,
+            pending_sprites: Vec::new(),
+            pending_drawn_rects: Vec::new(),
+            drawn_rects: Vec::new(),
+            output_picker: Default::default(),
+            frame_capture: Default::default(),
+            recorder: None,
+            surface_usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            renderer_size: None,
+            registered_renderer: None,
+            safe_mode: false,
+            effects: Default::default(),
+            pending_metadata: None,
+            frame_index: 0,
+            first_frame_time: None,
+            previous_frame_time: None,
+            last_present_time: None,
+            present_events: None,
+            display_refresh_rate: None,
+            latency_probe: Default::default(),
+            probe_renderer: None,
             scaling_matrix_inverse,
             alpha_mode,
+            offscreen_texture: None,
+            surface_resize_pending: false,
+            view_offset: (0, 0),
+            view_size: None,
         };
         pixels.reconfigure_surface();
+        pixels.set_frame_interpolation(self.frame_interpolation);
 
         Ok(pixels)
     }
@@ -354,7 +1753,8 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
     ///
     /// # Errors
     ///
-    /// Returns an error when a [`wgpu::Adapter`] or [`wgpu::Device`] cannot be found.
+    /// Returns an error when a [`wgpu::Adapter`] or [`wgpu::Device`] cannot be found, or
+    /// [`Error::UnsupportedLimits`] when the adapter cannot satisfy the requested limits.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn build(self) -> Result<Pixels<'win>, Error> {
         pollster::block_on(self.build_impl())
@@ -381,12 +1781,142 @@ impl<'req, 'dev, 'win, W: wgpu::WindowHandle + 'win> PixelsBuilder<'req, 'dev, '
     ///
     /// # Errors
     ///
-    /// Returns an error when a [`wgpu::Adapter`] or [`wgpu::Device`] cannot be found.
+    /// Returns an error when a [`wgpu::Adapter`] or [`wgpu::Device`] cannot be found, or
+    /// [`Error::UnsupportedLimits`] when the adapter cannot satisfy the requested limits.
     pub async fn build_async(self) -> Result<Pixels<'win>, Error> {
         self.build_impl().await
     }
 }
 
+impl<'req, 'dev, 'win> PixelsBuilder<'req, 'dev, 'win, NoWindow> {
+    /// A builder for a pixel buffer with the settings of `pixels` on `gpu`, which takes over the
+    /// surface of `pixels`.
+    pub(crate) fn rebuild(pixels: &mut Pixels<'win>, gpu: SharedGpu) -> Self {
+        let extent = pixels.context.texture_extent;
+        let surface_size = &pixels.surface_size;
+        let surface_texture =
+            SurfaceTexture::offscreen(surface_size.width.max(1), surface_size.height.max(1));
+        let options = &pixels.scaling_options;
+        let (upload_slices, upload_order) = pixels.upload_slices();
+
+        let mut builder = Self::new(extent.width, extent.height, surface_texture);
+        builder.shared_gpu = Some(gpu);
+        builder.surface = pixels.context.surface.take();
+        builder.present_mode = pixels.present_mode;
+        builder.zero_size_policy = pixels.zero_size_policy;
+        builder.texture_format = pixels
+            .imported_texture
+            .unwrap_or(pixels.context.texture_format);
+        builder.palette = options.palette.clone();
+        builder.packed_format = options.packed;
+        builder.shader_hook = options.hook.clone();
+        builder.gamma_ramp = options.gamma_ramp.clone();
+        builder.tonemapping = options.tonemapping;
+        builder.exposure = options.exposure;
+        builder.render_texture_format = Some(pixels.render_texture_format);
+        builder.surface_texture_format = Some(pixels.surface_texture_format);
+        builder.clear_color = pixels.context.scaling_renderer.clear_color;
+        builder.blend_state = pixels.blend_state;
+        builder.texture_ring_size = pixels.texture_ring_size;
+        builder.layers = pixels.layer_count();
+        builder.upload_slices = upload_slices;
+        builder.upload_order = upload_order;
+        builder.upload_strategy = pixels.upload_strategy();
+        builder.filter = options.filter;
+        builder.scaling_mode = options.mode;
+        builder.rotation = options.rotation;
+        builder.flip = options.flip;
+        builder.texture_border = options.border;
+        builder.sampler_descriptor = options.sampler.clone();
+        builder.linear_light_filtering = options.linear_light;
+        builder.supersampling = pixels.supersampling();
+        builder.frame_averaging = pixels.frame_averaging();
+        builder.flash_limiter = pixels.flash_limiter.clone();
+        builder.frame_interpolation = pixels.frame_interpolation();
+        builder.pixel_grid = pixels.pixel_grid();
+        builder.resize_transition = pixels.resize_transition;
+        builder.watermark = pixels.watermark().cloned();
+        builder.provenance_mark = pixels.provenance_mark();
+        builder.ambient_bezel = pixels.ambient_bezel();
+        builder.background = pixels.background().cloned();
+        builder.alpha_mode = Some(pixels.alpha_mode);
+        builder.downlevel = options.minimal;
+        builder.frame_stats = pixels.context.profiler.is_enabled();
+        builder
+    }
+}
+
+/// Request a new adapter and device from `instance`, with the features and limits of a device
+/// which was lost.
+///
+/// # Errors
+///
+/// Returns an error when no adapter can present to `surface`, or
+/// [`Error::UnsupportedLimits`] when it cannot satisfy the limits.
+pub(crate) async fn request_gpu(
+    instance: Arc<wgpu::Instance>,
+    surface: Option<&wgpu::Surface<'_>>,
+    features: wgpu::Features,
+    limits: wgpu::Limits,
+) -> Result<SharedGpu, Error> {
+    let adapter = match wgpu::util::initialize_adapter_from_env(&instance, surface) {
+        Some(adapter) => Some(adapter),
+        None => {
+            instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    compatible_surface: surface,
+                    force_fallback_adapter: false,
+                    power_preference: wgpu::util::power_preference_from_env().unwrap_or_default(),
+                })
+                .await
+        }
+    };
+    let adapter = adapter.ok_or(Error::AdapterNotFound)?;
+
+    let mut unsupported_limits = Vec::new();
+    limits.check_limits_with_fail_fn(&adapter.limits(), false, |name, _, _| {
+        unsupported_limits.push(name)
+    });
+    if !unsupported_limits.is_empty() {
+        return Err(Error::UnsupportedLimits(unsupported_limits));
+    }
+
+    let device_descriptor = wgpu::DeviceDescriptor {
+        required_features: features & adapter.features(),
+        required_limits: limits,
+        ..wgpu::DeviceDescriptor::default()
+    };
+    let (device, queue) = adapter.request_device(&device_descriptor, None).await?;
+    let device_lost = watch_device_lost(&device);
+
+    Ok(SharedGpu {
+        instance,
+        adapter: Arc::new(adapter),
+        device: Arc::new(device),
+        queue: Arc::new(queue),
+        device_lost,
+    })
+}
+
+/// Get a flag which is set when `device` is lost, e.g. after a driver reset.
+fn watch_device_lost(device: &wgpu::Device) -> Arc<AtomicBool> {
+    let device_lost = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&device_lost);
+    device.set_device_lost_callback(move |reason, _| {
+        // The callback also runs when the device is dropped, or the callback replaced
+        if matches!(
+            reason,
+            wgpu::DeviceLostReason::Unknown
+                | wgpu::DeviceLostReason::Destroyed
+                | wgpu::DeviceLostReason::DeviceInvalid
+        ) {
+            flag.store(true, Ordering::Relaxed);
+        }
+    });
+
+    device_lost
+}
+
 /// Compare the given size to the limits defined by `device`.
 ///
 /// # Errors
@@ -419,11 +1949,13 @@ pub(crate) fn create_backing_texture(
     render_texture_format: wgpu::TextureFormat,
     clear_color: wgpu::Color,
     blend_state: wgpu::BlendState,
+    scaling_options: &ScalingOptions,
+    texture_ring_size: usize,
 ) -> Result<
     (
         ultraviolet::Mat4,
         wgpu::Extent3d,
-        wgpu::Texture,
+        VecDeque<wgpu::Texture>,
         ScalingRenderer,
         usize,
     ),
@@ -432,11 +1964,11 @@ pub(crate) fn create_backing_texture(
     check_texture_size(device, width, height)?;
 
     let scaling_matrix_inverse = ScalingMatrix::new(
-        (width as f32, height as f32),
-        (surface_size.width as f32, surface_size.height as f32),
+        (width, height),
+        (surface_size.width, surface_size.height),
+        scaling_options.fit(),
     )
-    .transform
-    .inversed();
+    .window_to_texture();
 
     let texture_extent = wgpu::Extent3d {
         width,
@@ -444,26 +1976,52 @@ pub(crate) fn create_backing_texture(
         depth_or_array_layers: 1,
     };
 
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-        label: Some("pixels_source_texture"),
-        size: texture_extent,
-        mip_level_count: 1,
-        sample_count: 1,
-        dimension: wgpu::TextureDimension::D2,
-        format: backing_texture_format,
-        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
-        view_formats: &[],
-    });
-    let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    // Linear-light filtering views the texture with its sRGB variant
+    let srgb_view_format = scaling_options.srgb_view_format(backing_texture_format);
+    let view_formats = match &srgb_view_format {
+        Some(format) => std::slice::from_ref(format),
+        None => &[],
+    };
+
+    // Uploads rotate through the textures, so writing the next frame does not wait for the GPU to
+    // finish sampling the previous one
+    let textures: VecDeque<_> = (0..texture_ring_size.max(1))
+        .map(|_| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("pixels_source_texture"),
+                size: texture_extent,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: backing_texture_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | wgpu::TextureUsages::COPY_SRC
+                    | target::render_attachment_usage(device, backing_texture_format),
+                view_formats,
+            })
+        })
+        .collect();
+    let texture_views = textures
+        .iter()
+        .map(|texture| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                format: srgb_view_format,
+                ..Default::default()
+            })
+        })
+        .collect();
 
     let scaling_renderer = ScalingRenderer::new(
         device,
-        &texture_view,
+        texture_views,
         &texture_extent,
         surface_size,
         render_texture_format,
         clear_color,
         blend_state,
+        scaling_options,
+        srgb_view_format.is_some(),
     );
 
     let texture_format_size = texture_format_size(backing_texture_format);
@@ -472,15 +2030,67 @@ pub(crate) fn create_backing_texture(
     Ok((
         scaling_matrix_inverse,
         texture_extent,
-        texture,
+        textures,
         scaling_renderer,
         pixels_buffer_size,
     ))
 }
 
+/// The capabilities of the internal texture which offscreen pixel buffers render to in place of a
+/// surface. Formats start with `Rgba8UnormSrgb`, so that the output reads back as RGBA.
+pub(crate) fn offscreen_capabilities() -> wgpu::SurfaceCapabilities {
+    use wgpu::TextureFormat::*;
+
+    wgpu::SurfaceCapabilities {
+        formats: vec![
+            Rgba8UnormSrgb,
+            Bgra8UnormSrgb,
+            Rgba8Unorm,
+            Bgra8Unorm,
+            Rgb10a2Unorm,
+            Rgba16Float,
+        ],
+        present_modes: vec![wgpu::PresentMode::Fifo],
+        alpha_modes: vec![
+            wgpu::CompositeAlphaMode::Opaque,
+            wgpu::CompositeAlphaMode::PreMultiplied,
+            wgpu::CompositeAlphaMode::PostMultiplied,
+        ],
+        usages: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_SRC
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::TEXTURE_BINDING,
+    }
+}
+
+/// Get the format to configure a surface with, so that its textures can be viewed as
+/// `view_format`.
+///
+/// When the surface does not support `view_format` itself, but supports the variant with the
+/// other sRGB encoding and `reinterpret` is true, that variant is configured instead. This avoids
+/// choosing between washed-out and double-gamma output on platforms whose surfaces only offer one
+/// encoding.
+fn surface_storage_format(
+    formats: &[wgpu::TextureFormat],
+    view_format: wgpu::TextureFormat,
+    reinterpret: bool,
+) -> wgpu::TextureFormat {
+    if !reinterpret || formats.contains(&view_format) {
+        return view_format;
+    }
+
+    [
+        view_format.add_srgb_suffix(),
+        view_format.remove_srgb_suffix(),
+    ]
+    .into_iter()
+    .find(|format| formats.contains(format))
+    .unwrap_or(view_format)
+}
+
 #[rustfmt::skip]
 #[inline]
-const fn texture_format_size(texture_format: wgpu::TextureFormat) -> f32 {
+pub(crate) const fn texture_format_size(texture_format: wgpu::TextureFormat) -> f32 {
     use wgpu::{AstcBlock::*, TextureFormat::*};
 
     // TODO: Use constant arithmetic when supported.
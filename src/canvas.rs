@@ -0,0 +1,264 @@
+//! Sparse tiled canvases larger than the pixel buffer.
+
+use std::collections::HashMap;
+
+/// An unbounded image stored as sparse, fixed-size square tiles.
+///
+/// Only tiles which have been drawn to take memory, so a canvas can be far larger than both a
+/// full-frame allocation and the GPU texture limits. A [`CanvasView`] selects the part of the
+/// canvas to show; [`Canvas::render`] copies it into a frame, which the scaling renderer then
+/// scales to the window as usual. Pixels in missing tiles use the background color.
+///
+/// Pixels have the same byte layout as the pixel buffer, e.g. 4 bytes for
+/// `Rgba8UnormSrgb`.
+///
+/// ```
+/// use pixels::{Canvas, CanvasView};
+///
+/// let mut canvas = Canvas::new(256, 4);
+/// canvas.put_pixel(-1000, 5000, &[0xff, 0x00, 0x00, 0xff]);
+/// assert_eq!(canvas.len(), 1);
+///
+/// // Show the pixel at 2x zoom in the top-left corner of a 4x4 frame
+/// let mut frame = vec![0; 4 * 4 * 4];
+/// let view = CanvasView::new(-1000.0, 5000.0, 2.0);
+/// canvas.render(&view, &mut frame, 4, 4);
+/// assert_eq!(&frame[..8], &[0xff, 0x00, 0x00, 0xff, 0xff, 0x00, 0x00, 0xff]);
+/// assert_eq!(&frame[8..12], &[0; 4]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Canvas {
+    tile_size: u32,
+    bytes_per_pixel: usize,
+    tiles: HashMap<(i64, i64), Box<[u8]>>,
+    background: Vec<u8>,
+}
+
+/// The part of a [`Canvas`] shown by [`Canvas::render`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CanvasView {
+    /// The canvas x coordinate at the left edge of the frame.
+    pub x: f64,
+
+    /// The canvas y coordinate at the top edge of the frame.
+    pub y: f64,
+
+    /// The number of frame pixels per canvas pixel. Values above 1 zoom in, and values below 1
+    /// zoom out.
+    pub zoom: f64,
+}
+
+impl Canvas {
+    /// Create an empty canvas of tiles with `tile_size` pixels per side, and `bytes_per_pixel`
+    /// bytes per pixel.
+    ///
+    /// The background is transparent black (all zeros).
+    ///
+    /// # Panics
+    ///
+    /// Panics when `tile_size` or `bytes_per_pixel` are 0.
+    pub fn new(tile_size: u32, bytes_per_pixel: usize) -> Self {
+        assert!(tile_size > 0);
+        assert!(bytes_per_pixel > 0);
+
+        Self {
+            tile_size,
+            bytes_per_pixel,
+            tiles: HashMap::new(),
+            background: vec![0; bytes_per_pixel],
+        }
+    }
+
+    /// Get the number of pixels per side of a tile.
+    pub fn tile_size(&self) -> u32 {
+        self.tile_size
+    }
+
+    /// Get the number of bytes per pixel.
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.bytes_per_pixel
+    }
+
+    /// Get the number of allocated tiles.
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// Check if no tiles are allocated.
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Set the color of pixels in missing tiles, and of newly allocated tiles.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `pixel` is not [`Canvas::bytes_per_pixel`] long.
+    pub fn set_background(&mut self, pixel: &[u8]) {
+        assert_eq!(pixel.len(), self.bytes_per_pixel);
+        self.background.copy_from_slice(pixel);
+    }
+
+    /// Get the tile at tile coordinates `(x, y)`, if it is allocated.
+    ///
+    /// Tile `(x, y)` covers canvas pixels from `x * tile_size` to `(x + 1) * tile_size - 1`
+    /// horizontally, and the same vertically. Rows are stored top to bottom.
+    pub fn tile(&self, x: i64, y: i64) -> Option<&[u8]> {
+        self.tiles.get(&(x, y)).map(AsRef::as_ref)
+    }
+
+    /// Get the tile at tile coordinates `(x, y)` for drawing, allocating it with the background
+    /// color if needed.
+    pub fn tile_mut(&mut self, x: i64, y: i64) -> &mut [u8] {
+        let len = self.tile_size as usize * self.tile_size as usize;
+        let background = &self.background;
+
+        self.tiles
+            .entry((x, y))
+            .or_insert_with(|| background.repeat(len).into_boxed_slice())
+    }
+
+    /// Free the tile at tile coordinates `(x, y)`, returning its pixels.
+    pub fn remove_tile(&mut self, x: i64, y: i64) -> Option<Box<[u8]>> {
+        self.tiles.remove(&(x, y))
+    }
+
+    /// Iterate over the allocated tiles and their tile coordinates, in arbitrary order.
+    pub fn tiles(&self) -> impl Iterator<Item = ((i64, i64), &[u8])> {
+        self.tiles
+            .iter()
+            .map(|(&coords, tile)| (coords, tile.as_ref()))
+    }
+
+    /// Free all tiles.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Get the pixel at canvas coordinates `(x, y)`, which is the background in missing tiles.
+    pub fn pixel(&self, x: i64, y: i64) -> &[u8] {
+        let (tile, offset) = self.locate(x, y);
+
+        self.tiles.get(&tile).map_or(&self.background, |tile| {
+            &tile[offset..offset + self.bytes_per_pixel]
+        })
+    }
+
+    /// Set the pixel at canvas coordinates `(x, y)`, allocating its tile if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `pixel` is not [`Canvas::bytes_per_pixel`] long.
+    pub fn put_pixel(&mut self, x: i64, y: i64, pixel: &[u8]) {
+        assert_eq!(pixel.len(), self.bytes_per_pixel);
+
+        let ((tile_x, tile_y), offset) = self.locate(x, y);
+        let len = pixel.len();
+        self.tile_mut(tile_x, tile_y)[offset..offset + len].copy_from_slice(pixel);
+    }
+
+    /// Copy the part of the canvas selected by `view` into `frame`, which is `width` by `height`
+    /// pixels.
+    ///
+    /// Pixels are sampled with nearest-neighbor filtering.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `frame` is too small, or `view.zoom` is not positive.
+    pub fn render(&self, view: &CanvasView, frame: &mut [u8], width: u32, height: u32) {
+        assert!(view.zoom > 0.0);
+
+        let bpp = self.bytes_per_pixel;
+        let row_len = width as usize * bpp;
+        let tile_size = i64::from(self.tile_size);
+        assert!(frame.len() >= row_len * height as usize);
+        if row_len == 0 {
+            return;
+        }
+
+        for (frame_y, row) in frame
+            .chunks_exact_mut(row_len)
+            .take(height as usize)
+            .enumerate()
+        {
+            let y = view.frame_to_canvas_axis(view.y, frame_y as u32);
+            let (tile_y, local_y) = (y.div_euclid(tile_size), y.rem_euclid(tile_size));
+
+            // Look up each tile once per run of pixels within it
+            let mut current: Option<(i64, Option<&[u8]>)> = None;
+            for (frame_x, dst) in row.chunks_exact_mut(bpp).enumerate() {
+                let x = view.frame_to_canvas_axis(view.x, frame_x as u32);
+                let (tile_x, local_x) = (x.div_euclid(tile_size), x.rem_euclid(tile_size));
+
+                let tile = match current {
+                    Some((cached_x, tile)) if cached_x == tile_x => tile,
+                    _ => {
+                        let tile = self.tile(tile_x, tile_y);
+                        current = Some((tile_x, tile));
+                        tile
+                    }
+                };
+
+                let src = match tile {
+                    Some(tile) => {
+                        let offset = ((local_y * tile_size + local_x) as usize) * bpp;
+                        &tile[offset..offset + bpp]
+                    }
+                    None => &self.background,
+                };
+                dst.copy_from_slice(src);
+            }
+        }
+    }
+
+    /// Find the tile coordinates and byte offset within the tile of a canvas pixel.
+    fn locate(&self, x: i64, y: i64) -> ((i64, i64), usize) {
+        let tile_size = i64::from(self.tile_size);
+        let tile = (x.div_euclid(tile_size), y.div_euclid(tile_size));
+        let offset = (y.rem_euclid(tile_size) * tile_size + x.rem_euclid(tile_size)) as usize;
+
+        (tile, offset * self.bytes_per_pixel)
+    }
+}
+
+impl CanvasView {
+    /// Create a view with the canvas coordinates `(x, y)` at the top-left corner of the frame.
+    pub fn new(x: f64, y: f64, zoom: f64) -> Self {
+        Self { x, y, zoom }
+    }
+
+    /// Get the canvas pixel shown at a frame pixel, e.g. one returned by
+    /// [`Pixels::window_pos_to_pixel`](crate::Pixels::window_pos_to_pixel).
+    pub fn frame_to_canvas(&self, (x, y): (u32, u32)) -> (i64, i64) {
+        (
+            self.frame_to_canvas_axis(self.x, x),
+            self.frame_to_canvas_axis(self.y, y),
+        )
+    }
+
+    /// Move the view by `(dx, dy)` frame pixels.
+    pub fn pan(&mut self, dx: f64, dy: f64) {
+        self.x += dx / self.zoom;
+        self.y += dy / self.zoom;
+    }
+
+    /// Multiply the zoom by `factor`, keeping the canvas pixel under frame position `(x, y)` in
+    /// place.
+    pub fn zoom_at(&mut self, factor: f64, (x, y): (f64, f64)) {
+        let zoom = self.zoom * factor;
+        self.x += x / self.zoom - x / zoom;
+        self.y += y / self.zoom - y / zoom;
+        self.zoom = zoom;
+    }
+
+    fn frame_to_canvas_axis(&self, origin: f64, pixel: u32) -> i64 {
+        // Sample at the pixel center
+        (origin + (f64::from(pixel) + 0.5) / self.zoom).floor() as i64
+    }
+}
+
+impl Default for CanvasView {
+    fn default() -> Self {
+        Self::new(0.0, 0.0, 1.0)
+    }
+}
@@ -0,0 +1,77 @@
+//! Color space conversions.
+
+/// The color space in which color components are specified.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColorSpace {
+    /// Gamma-encoded sRGB components, as used by image editors, CSS, and color pickers.
+    Srgb,
+    /// Linear-light components.
+    Linear,
+}
+
+/// Decode an sRGB component in the range `0.0..=1.0` to linear light.
+///
+/// ```
+/// use pixels::srgb_to_linear;
+///
+/// assert_eq!(srgb_to_linear(0.0), 0.0);
+/// assert_eq!(srgb_to_linear(1.0), 1.0);
+/// assert!((srgb_to_linear(0.5) - 0.214).abs() < 0.001);
+/// ```
+pub fn srgb_to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encode a linear-light component in the range `0.0..=1.0` to sRGB.
+///
+/// This is the inverse of [`srgb_to_linear`].
+pub fn linear_to_srgb(c: f64) -> f64 {
+    if c <= 0.003_130_8 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+#[cfg(feature = "gpu")]
+/// Check if a render target with the given format takes colors in linear light.
+///
+/// sRGB formats encode colors when storing them, and float formats, like HDR surfaces, store them
+/// in linear light. Other formats store colors as-is, which the display then treats as sRGB.
+pub(crate) fn is_linear_target(format: wgpu::TextureFormat) -> bool {
+    use wgpu::TextureFormat::*;
+
+    format.is_srgb()
+        || matches!(
+            format,
+            R16Float | Rg16Float | Rgba16Float | R32Float | Rg32Float | Rgba32Float | Rg11b10Float
+        )
+}
+
+#[cfg(feature = "gpu")]
+/// Convert a color for use as the clear value of a render target with the given format.
+///
+/// Linear render targets, see [`is_linear_target`], expect clear values in linear light; other
+/// formats store the clear value as-is. The alpha component is never converted.
+pub(crate) fn clear_color_for_target(
+    color: wgpu::Color,
+    color_space: ColorSpace,
+    target_format: wgpu::TextureFormat,
+) -> wgpu::Color {
+    let convert: fn(f64) -> f64 = match (color_space, is_linear_target(target_format)) {
+        (ColorSpace::Srgb, true) => srgb_to_linear,
+        (ColorSpace::Linear, false) => linear_to_srgb,
+        _ => return color,
+    };
+
+    wgpu::Color {
+        r: convert(color.r),
+        g: convert(color.g),
+        b: convert(color.b),
+        a: color.a,
+    }
+}
@@ -0,0 +1,76 @@
+//! Video settings which can be saved and applied in one call.
+
+use crate::{AmbientBezel, FilterMode, FrameInterpolation, PixelGrid, ScalingMode};
+
+/// The user-facing video settings of a pixel buffer.
+///
+/// Applications with a settings menu can save these, e.g. with the `serde` feature, and restore
+/// them at startup with [`PixelsBuilder::config`](crate::PixelsBuilder::config), or while running
+/// with [`Pixels::apply_config`](crate::Pixels::apply_config). The settings in use are returned
+/// by [`Pixels::config`](crate::Pixels::config). Missing fields deserialize to their defaults, so
+/// settings files from older versions of an application keep working.
+///
+/// ```no_run
+/// use pixels::{FilterMode, PixelsConfig, ScalingMode};
+///
+/// # use pixels::Pixels;
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+///
+/// // The user picked smooth scaling to fill the window
+/// pixels.apply_config(&PixelsConfig {
+///     scaling_mode: ScalingMode::Fit,
+///     filter: FilterMode::Linear,
+///     ..pixels.config()
+/// })?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PixelsConfig {
+    /// How the scaled pixel buffer fills the surface.
+    pub scaling_mode: ScalingMode,
+
+    /// The filter used to scale the pixel buffer.
+    pub filter: FilterMode,
+
+    /// The requested `wgpu` present mode. Modes the surface does not support fall back as
+    /// described in [`Pixels::set_present_mode`](crate::Pixels::set_present_mode).
+    pub present_mode: wgpu::PresentMode,
+
+    /// The clear color, with components in the sRGB color space, like
+    /// [`PixelsBuilder::clear_color_srgb`](crate::PixelsBuilder::clear_color_srgb).
+    pub clear_color: wgpu::Color,
+
+    /// The pixel buffer texture format.
+    pub texture_format: wgpu::TextureFormat,
+
+    /// The [`PixelGrid`] overlay, if enabled.
+    pub pixel_grid: Option<PixelGrid>,
+
+    /// The [`AmbientBezel`] in the letterbox bars, if enabled.
+    pub ambient_bezel: Option<AmbientBezel>,
+
+    /// The [`FrameInterpolation`] mode, if enabled.
+    pub frame_interpolation: Option<FrameInterpolation>,
+}
+
+impl Default for PixelsConfig {
+    /// The defaults of [`PixelsBuilder`](crate::PixelsBuilder): integer scaling with nearest
+    /// neighbor sampling, vsync, a black clear color, an `Rgba8UnormSrgb` texture, and no
+    /// effects.
+    fn default() -> Self {
+        Self {
+            scaling_mode: ScalingMode::default(),
+            filter: FilterMode::default(),
+            present_mode: wgpu::PresentMode::AutoVsync,
+            clear_color: wgpu::Color::BLACK,
+            texture_format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            pixel_grid: None,
+            ambient_bezel: None,
+            frame_interpolation: None,
+        }
+    }
+}
@@ -0,0 +1,302 @@
+//! Descriptions of the active rendering configuration, for bug reports and debug overlays.
+
+use crate::workarounds::Workaround;
+use crate::{
+    FilterMode, FrameInterpolation, PackedFormat, Rotation, ScalingMode, TextureBorder,
+    UploadStrategy,
+};
+use std::borrow::Cow;
+use std::fmt;
+
+/// A snapshot of the rendering configuration of a [`Pixels`](crate::Pixels) instance.
+///
+/// Returned by [`Pixels::describe_pipeline`](crate::Pixels::describe_pipeline). The
+/// [`Display`](fmt::Display) implementation prints one line per setting and pass, ready to paste
+/// into a bug report.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct PipelineDescription {
+    /// The name of the adapter, e.g. the GPU model.
+    pub adapter_name: String,
+
+    /// The graphics API in use.
+    pub backend: wgpu::Backend,
+
+    /// The driver workarounds applied for the adapter.
+    pub workarounds: Vec<Workaround>,
+
+    /// The size of the pixel buffer texture.
+    pub texture_size: (u32, u32),
+
+    /// The format of the pixel buffer texture.
+    pub texture_format: wgpu::TextureFormat,
+
+    /// Whether the pixel buffer holds [palette](crate::PixelsBuilder::palette) indices.
+    pub palette: bool,
+
+    /// The [packed layout](crate::PixelsBuilder::packed_format) of the pixel buffer, if any.
+    pub packed_format: Option<PackedFormat>,
+
+    /// The number of source textures that uploads rotate through.
+    pub texture_ring_size: usize,
+
+    /// The number of frames that each upload of the pixel buffer is spread across.
+    pub upload_slices: u32,
+
+    /// How the pixel buffer is copied to its texture.
+    pub upload_strategy: UploadStrategy,
+
+    /// Whether a texture imported with [`Pixels::import_texture`](crate::Pixels::import_texture)
+    /// is shown instead of the pixel buffer.
+    pub imported_texture: bool,
+
+    /// The size of the surface in physical pixels.
+    pub surface_size: (u32, u32),
+
+    /// The format of the surface texture views drawn to.
+    pub surface_texture_format: wgpu::TextureFormat,
+
+    /// The format the surface is configured with. It differs from the
+    /// [`surface_texture_format`](Self::surface_texture_format) only in sRGB encoding, when the
+    /// surface is reinterpreted through its view formats.
+    pub surface_storage_format: wgpu::TextureFormat,
+
+    /// The format of the render target passed to render functions.
+    pub render_texture_format: wgpu::TextureFormat,
+
+    /// The present mode of the surface.
+    pub present_mode: wgpu::PresentMode,
+
+    /// The composite alpha mode of the surface.
+    pub alpha_mode: wgpu::CompositeAlphaMode,
+
+    /// The filter used by the scaling pass.
+    pub filter: FilterMode,
+
+    /// How the scaled pixel buffer fills the surface.
+    pub scaling_mode: ScalingMode,
+
+    /// The clockwise rotation of the scaled pixel buffer.
+    pub rotation: Rotation,
+
+    /// Whether the scaled pixel buffer is mirrored horizontally and vertically.
+    pub flip: (bool, bool),
+
+    /// What the scaling pass samples outside of the pixel buffer texture.
+    pub texture_border: TextureBorder,
+
+    /// Whether the scaling pass uses a
+    /// [custom sampler](crate::PixelsBuilder::sampler_descriptor).
+    pub custom_sampler: bool,
+
+    /// Whether the minimal downlevel scaling pipeline is in use.
+    pub minimal_pipeline: bool,
+
+    /// The supersampling factor, which is 1 when supersampling is disabled.
+    pub supersampling: u32,
+
+    /// The number of [averaged frames](crate::Pixels::set_frame_averaging), which is 1 when
+    /// averaging is disabled.
+    pub frame_averaging: u32,
+
+    /// The [frame interpolation](crate::PixelsBuilder::frame_interpolation) mode, if enabled.
+    pub frame_interpolation: Option<FrameInterpolation>,
+
+    /// The passes drawn by [`Pixels::render`](crate::Pixels::render), in order.
+    pub passes: Vec<PassDescription>,
+}
+
+/// One render pass in a [`PipelineDescription`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct PassDescription {
+    /// A short name for the pass, such as `"scaling"`, or the name of an effect.
+    pub name: Cow<'static, str>,
+
+    /// The size of the texture the pass draws to.
+    pub target_size: (u32, u32),
+
+    /// The format of the texture the pass draws to.
+    pub target_format: wgpu::TextureFormat,
+}
+
+impl PassDescription {
+    pub(crate) fn new(
+        name: impl Into<Cow<'static, str>>,
+        target_size: (u32, u32),
+        target_format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            target_size,
+            target_format,
+        }
+    }
+}
+
+/// A summary of what the adapter and surface support, and the configuration chosen from it.
+///
+/// Returned by [`Pixels::capability_report`](crate::Pixels::capability_report). Users can attach
+/// the [`Display`](fmt::Display) output to bug reports; with the `serde` feature, the report can
+/// also be serialized, e.g. to JSON for automated crash reporting.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub struct CapabilityReport {
+    /// The adapter, its driver, and the graphics API in use.
+    pub adapter: wgpu::AdapterInfo,
+
+    /// The features supported by the adapter.
+    pub features: wgpu::Features,
+
+    /// The downlevel capabilities of the adapter.
+    pub downlevel: wgpu::DownlevelCapabilities,
+
+    /// The limits of the device.
+    pub limits: wgpu::Limits,
+
+    /// The texture formats supported by the surface, in order of preference.
+    pub surface_formats: Vec<wgpu::TextureFormat>,
+
+    /// The present modes supported by the surface.
+    pub present_modes: Vec<wgpu::PresentMode>,
+
+    /// The composite alpha modes supported by the surface.
+    pub alpha_modes: Vec<wgpu::CompositeAlphaMode>,
+
+    /// The rendering configuration chosen from these capabilities.
+    pub configuration: PipelineDescription,
+}
+
+impl fmt::Display for CapabilityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let adapter = &self.adapter;
+
+        writeln!(
+            f,
+            "adapter: {} ({:?}, {:?}), vendor {:#06x}, device {:#06x}",
+            adapter.name, adapter.backend, adapter.device_type, adapter.vendor, adapter.device,
+        )?;
+        writeln!(f, "driver: {} {}", adapter.driver, adapter.driver_info)?;
+        writeln!(f, "features: {:?}", self.features)?;
+        writeln!(
+            f,
+            "downlevel: {:?}, shader model {:?}",
+            self.downlevel.flags, self.downlevel.shader_model,
+        )?;
+        writeln!(
+            f,
+            "limits: max texture {}, max bind groups {}, max buffer {}",
+            self.limits.max_texture_dimension_2d,
+            self.limits.max_bind_groups,
+            self.limits.max_buffer_size,
+        )?;
+        writeln!(f, "surface formats: {:?}", self.surface_formats)?;
+        writeln!(f, "present modes: {:?}", self.present_modes)?;
+        writeln!(f, "alpha modes: {:?}", self.alpha_modes)?;
+        write!(f, "{}", self.configuration)
+    }
+}
+
+impl fmt::Display for PipelineDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (texture_width, texture_height) = self.texture_size;
+        let (surface_width, surface_height) = self.surface_size;
+
+        writeln!(f, "adapter: {} ({:?})", self.adapter_name, self.backend)?;
+        if !self.workarounds.is_empty() {
+            writeln!(f, "workarounds: {:?}", self.workarounds)?;
+        }
+        writeln!(
+            f,
+            "texture: {texture_width}x{texture_height} {:?}{}, ring of {}{}{}{}",
+            self.texture_format,
+            match (self.palette, self.packed_format) {
+                (true, _) => " (palette indices)".to_string(),
+                (false, Some(packed_format)) => format!(" ({packed_format:?})"),
+                (false, None) => String::new(),
+            },
+            self.texture_ring_size,
+            if self.upload_slices > 1 {
+                format!(", uploaded in {} slices", self.upload_slices)
+            } else {
+                String::new()
+            },
+            match self.upload_strategy {
+                UploadStrategy::WriteTexture => "",
+                UploadStrategy::StagingRing => ", through a staging ring",
+            },
+            if self.imported_texture {
+                ", imported"
+            } else {
+                ""
+            },
+        )?;
+        writeln!(
+            f,
+            "surface: {surface_width}x{surface_height} {:?}{}, render format {:?}",
+            self.surface_texture_format,
+            if self.surface_storage_format == self.surface_texture_format {
+                String::new()
+            } else {
+                format!(" (viewing {:?})", self.surface_storage_format)
+            },
+            self.render_texture_format,
+        )?;
+        writeln!(
+            f,
+            "present: {:?}, alpha {:?}",
+            self.present_mode, self.alpha_mode,
+        )?;
+        writeln!(
+            f,
+            "scaling: {:?} filter, {:?} mode, {:?} border, {}x supersampling{}{}{}{}{}{}",
+            self.filter,
+            self.scaling_mode,
+            self.texture_border,
+            self.supersampling,
+            match self.rotation {
+                Rotation::D0 => String::new(),
+                rotation => format!(", {rotation:?} rotation"),
+            },
+            match self.flip {
+                (false, false) => "",
+                (true, false) => ", flipped horizontally",
+                (false, true) => ", flipped vertically",
+                (true, true) => ", flipped both ways",
+            },
+            if self.custom_sampler {
+                ", custom sampler"
+            } else {
+                ""
+            },
+            if self.minimal_pipeline {
+                ", minimal pipeline"
+            } else {
+                ""
+            },
+            match self.frame_interpolation {
+                Some(mode) => format!(", {mode:?} frame interpolation"),
+                None => String::new(),
+            },
+            match self.frame_averaging {
+                1 => String::new(),
+                frames => format!(", {frames} frame averaging"),
+            },
+        )?;
+
+        write!(f, "passes:")?;
+        for (index, pass) in self.passes.iter().enumerate() {
+            let (width, height) = pass.target_size;
+            write!(
+                f,
+                "\n  {index}: {} -> {width}x{height} {:?}",
+                pass.name, pass.target_format,
+            )?;
+        }
+
+        Ok(())
+    }
+}
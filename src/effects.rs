@@ -0,0 +1,1277 @@
+//! Full-screen post-processing passes between the scaled pixel buffer and the surface.
+
+use crate::tween::{Easing, ParamTween};
+use crate::{FrameInfo, Pixels, PixelsContext, TextureError};
+use std::collections::BTreeMap;
+use thiserror::Error;
+use web_time::{Duration, Instant};
+
+/// A full-screen pass in the effect chain of [`Pixels::add_effect`](crate::Pixels::add_effect).
+///
+/// Every effect reads the output of the pass before it, starting with the scaled pixel buffer,
+/// and draws the whole render target. Pixels owns the intermediate textures between effects and
+/// recreates them when the render target changes size, so an effect only manages its own
+/// pipeline and bind group. The input texture of an effect stays the same until the next call to
+/// [`RenderEffect::resize`], which is the place to create that bind group.
+///
+/// With the `effects` feature, [`CrtEffect`], [`ScanlineEffect`], and [`GammaEffect`] are
+/// provided.
+pub trait RenderEffect {
+    /// Create the resources which depend on the `input` texture, which is `width` by `height`
+    /// physical pixels in the [render texture format](crate::Pixels::render_texture_format), the
+    /// same as the output.
+    ///
+    /// This is called before the first frame, whenever the render target changes size, and when
+    /// the input texture changes because effects before this one were added, removed,
+    /// [enabled or disabled](crate::Pixels::set_effect_enabled), or
+    /// [moved](crate::Pixels::move_effect).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a texture of the new size cannot be created.
+    fn resize(
+        &mut self,
+        context: &PixelsContext<'_>,
+        input: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError>;
+
+    /// Update per-frame state, such as uniforms, before any effect draws.
+    ///
+    /// The default implementation does nothing.
+    fn prepare(&mut self, info: &FrameInfo, context: &PixelsContext<'_>) {
+        let _ = (info, context);
+    }
+
+    /// Record the pass into `encoder`, drawing every pixel of `output`.
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        context: &PixelsContext<'_>,
+    );
+
+    /// Get the parameter `name`, or `None` when the effect has no such parameter.
+    ///
+    /// Parameters let [tweens](crate::Pixels::tween_effect_param) and settings menus change an
+    /// effect through [`Pixels::effect_mut`](crate::Pixels::effect_mut) without knowing its
+    /// type. The default implementation has no parameters.
+    fn param(&self, name: &str) -> Option<f32> {
+        let _ = name;
+        None
+    }
+
+    /// Set the parameter `name` to `value`. Returns `false` when the effect has no such
+    /// parameter, or rejects the value.
+    ///
+    /// The default implementation has no parameters.
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        let _ = (name, value);
+        false
+    }
+
+    /// The name of the [scope](PixelsContext::scope) the pass is recorded in, which labels it in
+    /// GPU captures and [frame statistics](crate::Pixels::frame_stats).
+    ///
+    /// The default implementation returns `"effect"`.
+    fn name(&self) -> &str {
+        "effect"
+    }
+}
+
+/// Identifies an effect in the effect chain of a [`Pixels`](crate::Pixels), returned by
+/// [`Pixels::add_effect`](crate::Pixels::add_effect).
+///
+/// Ids are not reused, so an id of a removed effect never refers to another one.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct EffectId(u64);
+
+/// An effect in the chain, with the state that lets the chain skip untouched effects.
+struct EffectEntry {
+    id: EffectId,
+    name: String,
+    effect: Box<dyn RenderEffect>,
+    enabled: bool,
+    // The intermediate texture the effect was last resized with
+    input: Option<usize>,
+    tweens: Vec<ParamTween>,
+}
+
+/// The effects added to a [`Pixels`](crate::Pixels), and the textures between them.
+#[derive(Default)]
+pub(crate) struct EffectChain {
+    entries: Vec<EffectEntry>,
+    // Ping-pong textures: the `i`th enabled effect reads `textures[i % 2]`, and the last draws the
+    // render target
+    textures: Vec<wgpu::TextureView>,
+    size: Option<(u32, u32)>,
+    next_id: u64,
+}
+
+impl std::fmt::Debug for EffectChain {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EffectChain")
+            .field("effects", &self.entries.len())
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl EffectChain {
+    pub(crate) fn push(&mut self, name: String, effect: Box<dyn RenderEffect>) -> EffectId {
+        let id = EffectId(self.next_id);
+        self.next_id += 1;
+        self.entries.push(EffectEntry {
+            id,
+            name,
+            effect,
+            enabled: true,
+            input: None,
+            tweens: Vec::new(),
+        });
+
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: EffectId) -> Option<Box<dyn RenderEffect>> {
+        let index = self.index(id)?;
+
+        Some(self.entries.remove(index).effect)
+    }
+
+    pub(crate) fn clear(&mut self) -> Vec<Box<dyn RenderEffect>> {
+        self.textures.clear();
+        self.size = None;
+        self.entries.drain(..).map(|entry| entry.effect).collect()
+    }
+
+    /// The number of enabled effects.
+    pub(crate) fn enabled_len(&self) -> usize {
+        self.entries.iter().filter(|entry| entry.enabled).count()
+    }
+
+    /// The names of the enabled effects, in the order they are drawn.
+    pub(crate) fn enabled_names(&self) -> impl Iterator<Item = &str> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.name.as_str())
+    }
+
+    pub(crate) fn ids(&self) -> impl Iterator<Item = EffectId> + '_ {
+        self.entries.iter().map(|entry| entry.id)
+    }
+
+    pub(crate) fn find(&self, name: &str) -> Option<EffectId> {
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| entry.id)
+    }
+
+    pub(crate) fn name(&self, id: EffectId) -> Option<&str> {
+        self.entry(id).map(|entry| entry.name.as_str())
+    }
+
+    pub(crate) fn get_mut(&mut self, id: EffectId) -> Option<&mut dyn RenderEffect> {
+        let index = self.index(id)?;
+
+        Some(self.entries[index].effect.as_mut())
+    }
+
+    pub(crate) fn is_enabled(&self, id: EffectId) -> Option<bool> {
+        self.entry(id).map(|entry| entry.enabled)
+    }
+
+    pub(crate) fn set_enabled(&mut self, id: EffectId, enabled: bool) -> bool {
+        let Some(index) = self.index(id) else {
+            return false;
+        };
+        self.entries[index].enabled = enabled;
+
+        true
+    }
+
+    /// Move the effect to `index`, or to the end of the chain when `index` is past it.
+    pub(crate) fn move_to(&mut self, id: EffectId, index: usize) -> bool {
+        let Some(from) = self.index(id) else {
+            return false;
+        };
+        let entry = self.entries.remove(from);
+        let index = index.min(self.entries.len());
+        self.entries.insert(index, entry);
+
+        true
+    }
+
+    /// Move `param` of the effect from its current value to `to`, replacing a tween of the same
+    /// parameter.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EffectError::UnknownEffect`] when the effect is not in the chain, or
+    /// [`EffectError::UnknownParameter`] when it has no parameter `param`.
+    pub(crate) fn tween(
+        &mut self,
+        id: EffectId,
+        param: &str,
+        to: f32,
+        duration: Duration,
+        easing: Easing,
+    ) -> Result<(), EffectError> {
+        let index = self
+            .index(id)
+            .ok_or_else(|| EffectError::UnknownEffect(format!("{id:?}")))?;
+        let entry = &mut self.entries[index];
+        let from = entry
+            .effect
+            .param(param)
+            .ok_or_else(|| EffectError::UnknownParameter {
+                effect: entry.name.clone(),
+                parameter: param.to_string(),
+            })?;
+
+        entry.tweens.retain(|tween| tween.param() != param);
+        let tween = ParamTween::new(param, from, to, duration, easing);
+        entry.tweens.push(tween);
+
+        Ok(())
+    }
+
+    /// Whether any effect has a tween in progress.
+    pub(crate) fn is_tweening(&self) -> bool {
+        self.entries.iter().any(|entry| !entry.tweens.is_empty())
+    }
+
+    /// Set the parameters of the tweens for a frame drawn at `now`, and drop the ones which
+    /// ended.
+    pub(crate) fn update_tweens(&mut self, now: Instant) {
+        for entry in &mut self.entries {
+            let effect = &mut entry.effect;
+            entry.tweens.retain(|tween| {
+                let (value, ended) = tween.value(now);
+                // Stop when the effect rejects a value, instead of retrying every frame
+                effect.set_param(tween.param(), value) && !ended
+            });
+        }
+    }
+
+    fn index(&self, id: EffectId) -> Option<usize> {
+        self.entries.iter().position(|entry| entry.id == id)
+    }
+
+    fn entry(&self, id: EffectId) -> Option<&EffectEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// The texture that passes before the effects draw to, when any effect is enabled.
+    pub(crate) fn input(&self) -> Option<&wgpu::TextureView> {
+        self.textures
+            .first()
+            .filter(|_| self.entries.iter().any(|entry| entry.enabled))
+    }
+
+    /// Match the intermediate textures to the render target, resize the enabled effects whose
+    /// input changed, then prepare them.
+    pub(crate) fn prepare(
+        &mut self,
+        info: &FrameInfo,
+        context: &PixelsContext<'_>,
+        size: (u32, u32),
+        format: wgpu::TextureFormat,
+    ) -> Result<(), TextureError> {
+        let enabled = self.enabled_len();
+        if enabled == 0 {
+            return Ok(());
+        }
+
+        if self.size != Some(size) {
+            self.textures.clear();
+            for entry in &mut self.entries {
+                entry.input = None;
+            }
+            self.size = Some(size);
+        }
+        while self.textures.len() < enabled.min(2) {
+            let view = create_effect_view(&context.device, size, format);
+            self.textures.push(view);
+        }
+
+        let entries = self.entries.iter_mut().filter(|entry| entry.enabled);
+        for (index, entry) in entries.enumerate() {
+            let input = index % 2;
+            if entry.input != Some(input) {
+                let view = &self.textures[input];
+                entry.effect.resize(context, view, size.0, size.1)?;
+                entry.input = Some(input);
+            }
+        }
+
+        for entry in self.entries.iter_mut().filter(|entry| entry.enabled) {
+            entry.effect.prepare(info, context);
+        }
+
+        Ok(())
+    }
+
+    /// Draw every enabled effect, the last one to `render_target`.
+    pub(crate) fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        context: &PixelsContext<'_>,
+    ) {
+        let last = self.enabled_len().saturating_sub(1);
+        let entries = self.entries.iter().filter(|entry| entry.enabled);
+        for (index, entry) in entries.enumerate() {
+            let output = if index == last {
+                render_target
+            } else {
+                &self.textures[(index + 1) % 2]
+            };
+            let effect = &entry.effect;
+            context.scope(encoder, effect.name(), |encoder| {
+                effect.render(encoder, output, context);
+            });
+        }
+    }
+}
+
+fn create_effect_view(
+    device: &wgpu::Device,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_effect_texture"),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// All the ways in which creating an effect from an [`EffectPreset`] can fail.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum EffectError {
+    /// No effect is registered with the name
+    #[error("Unknown effect: {0}")]
+    UnknownEffect(String),
+    /// The effect has no parameter with the name
+    #[error("Unknown parameter of effect {effect}: {parameter}")]
+    UnknownParameter {
+        /// The name of the effect.
+        effect: String,
+        /// The name of the parameter.
+        parameter: String,
+    },
+    /// The value of a parameter is out of range for the effect
+    #[error("Invalid value of parameter {parameter} of effect {effect}: {value}")]
+    InvalidParameter {
+        /// The name of the effect.
+        effect: String,
+        /// The name of the parameter.
+        parameter: String,
+        /// The rejected value.
+        value: f32,
+    },
+    /// The device cannot run the effect, e.g. because it lacks a texture feature or limit
+    #[error("Effect {effect} is not supported by the device: {reason}")]
+    Unsupported {
+        /// The name of the effect.
+        effect: String,
+        /// Why the effect cannot run.
+        reason: String,
+    },
+    /// Creating the shaders or pipelines of the effect failed on the device
+    #[error("Unable to create the pipeline of effect {effect}: {message}")]
+    Pipeline {
+        /// The name of the effect.
+        effect: String,
+        /// The error reported by the device.
+        message: String,
+    },
+}
+
+impl EffectError {
+    /// Whether the error comes from the device rather than the preset, such that a simpler
+    /// effect may still work.
+    fn is_device_error(&self) -> bool {
+        matches!(self, Self::Unsupported { .. } | Self::Pipeline { .. })
+    }
+}
+
+/// An effect which [`EffectRegistry::add_effects_with_fallbacks`] could not create, and what
+/// replaced it.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct EffectDegradation {
+    /// The name of the effect which failed.
+    pub effect: String,
+
+    /// Why the effect failed, either [`EffectError::Unsupported`] or [`EffectError::Pipeline`].
+    pub error: EffectError,
+
+    /// The name of the effect tried next, or `None` when the effect was left out of the chain.
+    pub fallback: Option<String>,
+}
+
+impl std::fmt::Display for EffectDegradation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.fallback {
+            Some(fallback) => write!(f, "{}, falling back to {fallback}", self.error),
+            None => write!(f, "{}, disabled", self.error),
+        }
+    }
+}
+
+/// An effect and its parameters, by name, as stored in config and preset files.
+///
+/// With the `serde` feature, a post-processing chain can be loaded from a list of presets in any
+/// format supported by `serde`, and created with an [`EffectRegistry`]. In TOML:
+///
+/// ```toml
+/// [[effects]]
+/// name = "scanlines"
+/// params = { intensity = 0.4 }
+///
+/// [[effects]]
+/// name = "crt"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct EffectPreset {
+    /// The name the effect is registered with.
+    pub name: String,
+
+    /// The parameters of the effect; the effect uses defaults for the missing ones.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub params: BTreeMap<String, f32>,
+}
+
+impl EffectPreset {
+    /// Create a preset of the effect registered as `name`, with default parameters.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    /// Set the parameter `name` to `value`.
+    pub fn with_param(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.params.insert(name.into(), value);
+        self
+    }
+
+    /// Get the parameter `name`, or `default` when it is not set.
+    pub fn param(&self, name: &str, default: f32) -> f32 {
+        self.params.get(name).copied().unwrap_or(default)
+    }
+
+    /// Check that every parameter is one of `known`, so that typos in preset files do not go
+    /// unnoticed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EffectError::UnknownParameter`] for the first parameter which is not known.
+    pub fn check_params(&self, known: &[&str]) -> Result<(), EffectError> {
+        match self
+            .params
+            .keys()
+            .find(|name| !known.contains(&name.as_str()))
+        {
+            Some(parameter) => Err(EffectError::UnknownParameter {
+                effect: self.name.clone(),
+                parameter: parameter.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Creates a [`RenderEffect`] for a pixel buffer from the parameters of an [`EffectPreset`].
+pub type EffectConstructor =
+    Box<dyn Fn(&Pixels<'_>, &EffectPreset) -> Result<Box<dyn RenderEffect>, EffectError>>;
+
+/// Effects registered by name, so that post-processing chains can be created from
+/// [`EffectPreset`]s without knowing every effect at compile time.
+///
+/// Applications and effect libraries register their effects with a constructor, and config or
+/// user-provided preset files then pick effects by name. With the `effects` feature,
+/// [`EffectRegistry::builtin`] holds the built-in effects.
+///
+/// ```no_run
+/// use pixels::{EffectPreset, EffectRegistry, Pixels};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// # #[cfg(feature = "effects")]
+/// # {
+/// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+/// let registry = EffectRegistry::builtin();
+///
+/// // Usually deserialized from a preset file
+/// let presets = [
+///     EffectPreset::new("scanlines").with_param("intensity", 0.4),
+///     EffectPreset::new("crt"),
+/// ];
+/// registry.add_effects(&mut pixels, &presets)?;
+/// # }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Default)]
+pub struct EffectRegistry {
+    constructors: BTreeMap<String, EffectConstructor>,
+    fallbacks: BTreeMap<String, String>,
+}
+
+impl std::fmt::Debug for EffectRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EffectRegistry")
+            .field("names", &self.constructors.keys().collect::<Vec<_>>())
+            .field("fallbacks", &self.fallbacks)
+            .finish()
+    }
+}
+
+impl EffectRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry of the built-in effects:
+    ///
+    /// * `"crt"`: [`CrtEffect`], with the parameters `curvature` and `vignette`.
+    /// * `"scanlines"`: [`ScanlineEffect`], with the parameters `intensity` and `coverage`.
+    /// * `"gamma"`: [`GammaEffect`], with the parameter `gamma`, which defaults to 1.
+    ///
+    /// `"crt"` [falls back](EffectRegistry::set_fallback) to `"scanlines"`.
+    #[cfg(feature = "effects")]
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        registry.register("crt", |pixels, preset| {
+            preset.check_params(&["curvature", "vignette"])?;
+            let mut effect = CrtEffect::new(pixels)?;
+            effect.curvature = preset.param("curvature", effect.curvature);
+            effect.vignette = preset.param("vignette", effect.vignette);
+            Ok(Box::new(effect))
+        });
+        registry.register("scanlines", |pixels, preset| {
+            preset.check_params(&["intensity", "coverage"])?;
+            let mut effect = ScanlineEffect::new(pixels)?;
+            effect.intensity = preset.param("intensity", effect.intensity);
+            effect.coverage = preset.param("coverage", effect.coverage);
+            Ok(Box::new(effect))
+        });
+        registry.register("gamma", |pixels, preset| {
+            preset.check_params(&["gamma"])?;
+            let gamma = preset.param("gamma", 1.0);
+            if gamma.is_nan() || gamma <= 0.0 {
+                return Err(EffectError::InvalidParameter {
+                    effect: preset.name.clone(),
+                    parameter: "gamma".to_string(),
+                    value: gamma,
+                });
+            }
+            Ok(Box::new(GammaEffect::new(pixels, gamma)?))
+        });
+        registry.set_fallback("crt", Some("scanlines"));
+        registry
+    }
+
+    /// Register `constructor` as the effect `name`, replacing any effect of the same name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        constructor: impl Fn(&Pixels<'_>, &EffectPreset) -> Result<Box<dyn RenderEffect>, EffectError>
+            + 'static,
+    ) {
+        self.constructors.insert(name.into(), Box::new(constructor));
+    }
+
+    /// Set the effect to try when the device cannot run the effect `name`, or remove its fallback
+    /// with `None`.
+    ///
+    /// The fallback is created with default parameters by
+    /// [`EffectRegistry::add_effects_with_fallbacks`], and may have a fallback of its own. Use it
+    /// for a simpler version of the effect, which needs fewer device features or limits.
+    pub fn set_fallback(&mut self, name: impl Into<String>, fallback: Option<&str>) {
+        let name = name.into();
+        match fallback {
+            Some(fallback) => self.fallbacks.insert(name, fallback.to_string()),
+            None => self.fallbacks.remove(&name),
+        };
+    }
+
+    /// The effect to try when the device cannot run the effect `name`, see
+    /// [`EffectRegistry::set_fallback`].
+    pub fn fallback(&self, name: &str) -> Option<&str> {
+        self.fallbacks.get(name).map(String::as_str)
+    }
+
+    /// The names of the registered effects, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    /// Create the effect of `preset` for `pixels`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EffectError::UnknownEffect`] when no effect is registered with the name of the
+    /// preset, or the error of its constructor.
+    pub fn create(
+        &self,
+        pixels: &Pixels<'_>,
+        preset: &EffectPreset,
+    ) -> Result<Box<dyn RenderEffect>, EffectError> {
+        let constructor = self
+            .constructors
+            .get(&preset.name)
+            .ok_or_else(|| EffectError::UnknownEffect(preset.name.clone()))?;
+
+        constructor(pixels, preset)
+    }
+
+    /// Create the effects of `presets` and add them to the effect chain of `pixels`, in order.
+    ///
+    /// Either every effect is added, or none: the effect chain is unchanged on errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first preset which fails, see [`EffectRegistry::create`].
+    pub fn add_effects(
+        &self,
+        pixels: &mut Pixels<'_>,
+        presets: &[EffectPreset],
+    ) -> Result<(), EffectError> {
+        let effects = presets
+            .iter()
+            .map(|preset| self.create(pixels, preset))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (preset, effect) in presets.iter().zip(effects) {
+            pixels.push_effect(preset.name.clone(), effect);
+        }
+
+        Ok(())
+    }
+
+    /// Create the effects of `presets` and add them to the effect chain of `pixels`, replacing
+    /// the ones which the device cannot run with their [fallbacks](EffectRegistry::set_fallback).
+    ///
+    /// This is the way to load a chain which works on the best devices and still shows something on
+    /// weak ones, such as WebGL2. An effect is replaced when its constructor returns
+    /// [`EffectError::Unsupported`], or the device reports an error while its shaders and pipelines
+    /// are created, which is returned as [`EffectError::Pipeline`]. The fallback is tried next,
+    /// then its own fallback, and so on: with the [built-in effects](EffectRegistry::builtin),
+    /// `"crt"` degrades to `"scanlines"`, and then to no effect, which is the plain scaled pixel
+    /// buffer. Returns every replacement, in order, to report what was disabled:
+    ///
+    /// ```no_run
+    /// use pixels::{EffectPreset, EffectRegistry, Pixels};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # #[cfg(feature = "effects")]
+    /// # {
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let registry = EffectRegistry::builtin();
+    ///
+    /// let presets = [EffectPreset::new("crt").with_param("curvature", 0.08)];
+    /// for degradation in registry.add_effects_with_fallbacks(&mut pixels, &presets)? {
+    ///     eprintln!("{degradation}");
+    /// }
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// Errors in the presets still fail the whole chain, like [`EffectRegistry::add_effects`],
+    /// so that typos do not pass for missing device features. Errors which only happen once the
+    /// effect [resizes](RenderEffect::resize) for the first frame are not caught.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EffectError::UnknownEffect`], [`EffectError::UnknownParameter`], or
+    /// [`EffectError::InvalidParameter`] for the first preset or fallback which has them. The
+    /// effect chain is unchanged on errors.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn add_effects_with_fallbacks(
+        &self,
+        pixels: &mut Pixels<'_>,
+        presets: &[EffectPreset],
+    ) -> Result<Vec<EffectDegradation>, EffectError> {
+        pollster::block_on(self.add_effects_with_fallbacks_async(pixels, presets))
+    }
+
+    /// Create the effects of `presets` with fallbacks, without blocking the current thread.
+    ///
+    /// Devices report errors asynchronously; this is the only way to use fallbacks on Web
+    /// targets. See [`EffectRegistry::add_effects_with_fallbacks`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error like [`EffectRegistry::add_effects_with_fallbacks`].
+    pub async fn add_effects_with_fallbacks_async(
+        &self,
+        pixels: &mut Pixels<'_>,
+        presets: &[EffectPreset],
+    ) -> Result<Vec<EffectDegradation>, EffectError> {
+        let mut effects = Vec::with_capacity(presets.len());
+        let mut degradations = Vec::new();
+
+        for preset in presets {
+            let mut preset = preset.clone();
+            // Fallbacks which were already tried, so that cycles end with no effect
+            let mut tried = vec![preset.name.clone()];
+            loop {
+                let error = match self.create_checked(pixels, &preset).await {
+                    Ok(effect) => {
+                        effects.push((preset.name.clone(), effect));
+                        break;
+                    }
+                    Err(err) if err.is_device_error() => err,
+                    Err(err) => return Err(err),
+                };
+
+                let fallback = self
+                    .fallback(&preset.name)
+                    .filter(|fallback| !tried.iter().any(|name| name == fallback))
+                    .map(str::to_string);
+                degradations.push(EffectDegradation {
+                    effect: preset.name.clone(),
+                    error,
+                    fallback: fallback.clone(),
+                });
+                match fallback {
+                    Some(fallback) => {
+                        tried.push(fallback.clone());
+                        preset = EffectPreset::new(fallback);
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        for (name, effect) in effects {
+            pixels.push_effect(name, effect);
+        }
+
+        Ok(degradations)
+    }
+
+    /// Create the effect of `preset`, and turn the device errors raised meanwhile into
+    /// [`EffectError::Pipeline`].
+    async fn create_checked(
+        &self,
+        pixels: &Pixels<'_>,
+        preset: &EffectPreset,
+    ) -> Result<Box<dyn RenderEffect>, EffectError> {
+        let device = pixels.device();
+        device.push_error_scope(wgpu::ErrorFilter::Internal);
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let result = self.create(pixels, preset);
+        let validation = device.pop_error_scope().await;
+        let internal = device.pop_error_scope().await;
+
+        match validation.or(internal) {
+            Some(err) => Err(EffectError::Pipeline {
+                effect: preset.name.clone(),
+                message: err.to_string(),
+            }),
+            None => result,
+        }
+    }
+}
+
+/// Check that the built-in effect `effect` can filter the render texture format of `pixels`.
+#[cfg(feature = "effects")]
+fn check_filterable(pixels: &Pixels<'_>, effect: &str) -> Result<(), EffectError> {
+    let format = pixels.render_texture_format();
+    let sample_type = format.sample_type(None, Some(pixels.device().features()));
+    if sample_type == Some(wgpu::TextureSampleType::Float { filterable: true }) {
+        Ok(())
+    } else {
+        Err(EffectError::Unsupported {
+            effect: effect.to_string(),
+            reason: format!("the render texture format {format:?} cannot be filtered"),
+        })
+    }
+}
+
+/// Barrel distortion and a vignette, like the curved glass of a CRT monitor.
+///
+/// ```no_run
+/// use pixels::{CrtEffect, Pixels};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+/// let crt = CrtEffect::new(&pixels)?.with_curvature(0.08);
+/// pixels.add_effect(Box::new(crt));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "effects")]
+#[derive(Debug)]
+pub struct CrtEffect {
+    pass: EffectPass,
+    curvature: f32,
+    vignette: f32,
+}
+
+#[cfg(feature = "effects")]
+impl CrtEffect {
+    /// Create the effect for `pixels`, with a curvature of 0.05 and a vignette strength of 0.5.
+    ///
+    /// # Errors
+    ///
+    /// [`EffectError::Unsupported`] when the render texture format cannot be filtered.
+    pub fn new(pixels: &crate::Pixels<'_>) -> Result<Self, EffectError> {
+        check_filterable(pixels, "crt")?;
+
+        Ok(Self {
+            pass: EffectPass::new(pixels, "crt", "fs_crt"),
+            curvature: 0.05,
+            vignette: 0.5,
+        })
+    }
+
+    /// Set how far the corners bend outward; 0 is flat.
+    pub fn with_curvature(mut self, curvature: f32) -> Self {
+        self.curvature = curvature;
+        self
+    }
+
+    /// Set how much the edges darken, from 0 to 1.
+    pub fn with_vignette(mut self, vignette: f32) -> Self {
+        self.vignette = vignette;
+        self
+    }
+}
+
+/// Dark gaps between the rows of the pixel buffer, like the scanlines of a CRT monitor.
+///
+/// The gaps follow the rows of the scaled pixel buffer, so put this effect before any effect
+/// which moves pixels, like [`CrtEffect`].
+#[cfg(feature = "effects")]
+#[derive(Debug)]
+pub struct ScanlineEffect {
+    pass: EffectPass,
+    intensity: f32,
+    coverage: f32,
+}
+
+#[cfg(feature = "effects")]
+impl ScanlineEffect {
+    /// Create the effect for `pixels`, darkening the bottom third of every row by half.
+    ///
+    /// # Errors
+    ///
+    /// [`EffectError::Unsupported`] when the render texture format cannot be filtered.
+    pub fn new(pixels: &crate::Pixels<'_>) -> Result<Self, EffectError> {
+        check_filterable(pixels, "scanlines")?;
+
+        Ok(Self {
+            pass: EffectPass::new(pixels, "scanline", "fs_scanlines"),
+            intensity: 0.5,
+            coverage: 1.0 / 3.0,
+        })
+    }
+
+    /// Set how much the gaps darken, from 0 to 1.
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Set the fraction of each row the gaps cover, from 0 to 1.
+    pub fn with_coverage(mut self, coverage: f32) -> Self {
+        self.coverage = coverage;
+        self
+    }
+}
+
+/// A gamma adjustment, raising every color channel to the power of `1 / gamma`.
+///
+/// Values above 1 brighten midtones, and values below 1 darken them.
+#[cfg(feature = "effects")]
+#[derive(Debug)]
+pub struct GammaEffect {
+    pass: EffectPass,
+    gamma: f32,
+}
+
+#[cfg(feature = "effects")]
+impl GammaEffect {
+    /// Create the effect for `pixels` with the given `gamma`.
+    ///
+    /// # Errors
+    ///
+    /// [`EffectError::Unsupported`] when the render texture format cannot be filtered.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `gamma` is not positive.
+    pub fn new(pixels: &crate::Pixels<'_>, gamma: f32) -> Result<Self, EffectError> {
+        assert!(gamma > 0.0);
+        check_filterable(pixels, "gamma")?;
+
+        Ok(Self {
+            pass: EffectPass::new(pixels, "gamma", "fs_gamma"),
+            gamma,
+        })
+    }
+}
+
+#[cfg(feature = "effects")]
+impl RenderEffect for CrtEffect {
+    fn resize(
+        &mut self,
+        context: &PixelsContext<'_>,
+        input: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError> {
+        self.pass.resize(context, input, width, height);
+        Ok(())
+    }
+
+    fn prepare(&mut self, info: &FrameInfo, context: &PixelsContext<'_>) {
+        let params = [self.curvature, self.vignette, 0.0, 0.0];
+        self.pass.prepare(info, context, params);
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        _context: &PixelsContext<'_>,
+    ) {
+        self.pass.render(encoder, output);
+    }
+
+    fn param(&self, name: &str) -> Option<f32> {
+        match name {
+            "curvature" => Some(self.curvature),
+            "vignette" => Some(self.vignette),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "curvature" => self.curvature = value,
+            "vignette" => self.vignette = value,
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn name(&self) -> &str {
+        "crt"
+    }
+}
+
+#[cfg(feature = "effects")]
+impl RenderEffect for ScanlineEffect {
+    fn resize(
+        &mut self,
+        context: &PixelsContext<'_>,
+        input: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError> {
+        self.pass.resize(context, input, width, height);
+        Ok(())
+    }
+
+    fn prepare(&mut self, info: &FrameInfo, context: &PixelsContext<'_>) {
+        let params = [self.intensity, self.coverage, 0.0, 0.0];
+        self.pass.prepare(info, context, params);
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        _context: &PixelsContext<'_>,
+    ) {
+        self.pass.render(encoder, output);
+    }
+
+    fn param(&self, name: &str) -> Option<f32> {
+        match name {
+            "intensity" => Some(self.intensity),
+            "coverage" => Some(self.coverage),
+            _ => None,
+        }
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        match name {
+            "intensity" => self.intensity = value,
+            "coverage" => self.coverage = value,
+            _ => return false,
+        }
+
+        true
+    }
+
+    fn name(&self) -> &str {
+        "scanlines"
+    }
+}
+
+#[cfg(feature = "effects")]
+impl RenderEffect for GammaEffect {
+    fn resize(
+        &mut self,
+        context: &PixelsContext<'_>,
+        input: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError> {
+        self.pass.resize(context, input, width, height);
+        Ok(())
+    }
+
+    fn prepare(&mut self, info: &FrameInfo, context: &PixelsContext<'_>) {
+        let params = [self.gamma, 0.0, 0.0, 0.0];
+        self.pass.prepare(info, context, params);
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        output: &wgpu::TextureView,
+        _context: &PixelsContext<'_>,
+    ) {
+        self.pass.render(encoder, output);
+    }
+
+    fn param(&self, name: &str) -> Option<f32> {
+        (name == "gamma").then_some(self.gamma)
+    }
+
+    fn set_param(&mut self, name: &str, value: f32) -> bool {
+        // Like the constructor, only positive gammas are valid
+        if name != "gamma" || value.is_nan() || value <= 0.0 {
+            return false;
+        }
+        self.gamma = value;
+
+        true
+    }
+
+    fn name(&self) -> &str {
+        "gamma"
+    }
+}
+
+/// The pipeline shared by the built-in effects, drawing one fragment entry point of
+/// `effects.wgsl`.
+#[cfg(feature = "effects")]
+#[derive(Debug)]
+struct EffectPass {
+    label: &'static str,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: Option<wgpu::BindGroup>,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+    size: (u32, u32),
+}
+
+#[cfg(feature = "effects")]
+impl EffectPass {
+    fn new(pixels: &crate::Pixels<'_>, name: &'static str, entry_point: &str) -> Self {
+        let device = pixels.device();
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/effects.wgsl"));
+        let label = |kind: &str| format!("pixels_{name}_effect_{kind}");
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some(&label("sampler")),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(&label("uniform_buffer")),
+            size: 48,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some(&label("bind_group_layout")),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(48),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some(&label("pipeline_layout")),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(&label("pipeline")),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: pixels.render_texture_format(),
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            label: name,
+            bind_group_layout,
+            bind_group: None,
+            sampler,
+            uniform_buffer,
+            render_pipeline,
+            size: (0, 0),
+        }
+    }
+
+    fn resize(
+        &mut self,
+        context: &PixelsContext<'_>,
+        input: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) {
+        let label = format!("pixels_{}_effect_bind_group", self.label);
+        self.bind_group = Some(
+            context
+                .device
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some(&label),
+                    layout: &self.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(input),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(&self.sampler),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: self.uniform_buffer.as_entire_binding(),
+                        },
+                    ],
+                }),
+        );
+        self.size = (width, height);
+    }
+
+    fn prepare(&self, info: &FrameInfo, context: &PixelsContext<'_>, params: [f32; 4]) {
+        let (x, y, width, height) = info.clip_rect;
+        // The buffer columns become the rows on the surface after a quarter turn
+        let rows = if info.rotation.is_sideways() {
+            info.view.2
+        } else {
+            info.view.3
+        };
+        let locals: [f32; 12] = [
+            params[0],
+            params[1],
+            params[2],
+            params[3],
+            x as f32,
+            y as f32,
+            width as f32,
+            height as f32,
+            self.size.0 as f32,
+            self.size.1 as f32,
+            rows as f32,
+            0.0,
+        ];
+        context
+            .queue
+            .write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&locals));
+    }
+
+    fn render(&self, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
+        let Some(bind_group) = &self.bind_group else {
+            return;
+        };
+
+        let label = format!("pixels_{}_effect_render_pass", self.label);
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some(&label),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,104 @@
+//! Screen shake and color flashes, for game feel.
+
+use web_time::{Duration, Instant};
+
+/// A decaying random offset of the scaled pixel buffer.
+#[derive(Debug)]
+pub(crate) struct ScreenShake {
+    intensity: f32,
+    duration: Duration,
+    start: Instant,
+    rng: u32,
+}
+
+impl ScreenShake {
+    /// Shake by up to `intensity` physical pixels, decaying to nothing over `duration`.
+    pub(crate) fn new(intensity: f32, duration: Duration, seed: u32) -> Self {
+        Self {
+            intensity,
+            duration,
+            start: Instant::now(),
+            // Xorshift must not start at zero
+            rng: seed | 1,
+        }
+    }
+
+    /// Get the offset for a frame drawn at `now` in whole physical pixels, or `None` once the
+    /// shake has ended.
+    pub(crate) fn offset(&mut self, now: Instant) -> Option<(f32, f32)> {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= self.duration {
+            return None;
+        }
+
+        // Quadratic decay settles quickly without an abrupt stop
+        let remaining = 1.0 - elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        let amplitude = self.intensity * remaining * remaining;
+        let x = (self.next_unit() * amplitude).round();
+        let y = (self.next_unit() * amplitude).round();
+
+        Some((x, y))
+    }
+
+    /// A pseudo-random value from -1 to 1.
+    fn next_unit(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+
+        (self.rng as f32 / u32::MAX as f32) * 2.0 - 1.0
+    }
+}
+
+/// A full-screen color which fades in or out.
+#[derive(Debug)]
+pub(crate) struct ScreenFlash {
+    color: wgpu::Color,
+    fade_in: bool,
+    duration: Duration,
+    start: Instant,
+}
+
+impl ScreenFlash {
+    /// Start at `color` and fade out to nothing over `duration`.
+    pub(crate) fn flash(color: wgpu::Color, duration: Duration) -> Self {
+        Self {
+            color,
+            fade_in: false,
+            duration,
+            start: Instant::now(),
+        }
+    }
+
+    /// Fade in to `color` over `duration`, then hold it.
+    pub(crate) fn fade(color: wgpu::Color, duration: Duration) -> Self {
+        Self {
+            color,
+            fade_in: true,
+            duration,
+            start: Instant::now(),
+        }
+    }
+
+    /// Get the color to blend over a frame drawn at `now`, or `None` once a flash has ended.
+    pub(crate) fn color(&self, now: Instant) -> Option<wgpu::Color> {
+        let elapsed = now.saturating_duration_since(self.start);
+        let progress = if self.duration.is_zero() {
+            1.0
+        } else {
+            (elapsed.as_secs_f64() / self.duration.as_secs_f64()).min(1.0)
+        };
+        let opacity = if self.fade_in {
+            progress
+        } else if progress < 1.0 {
+            1.0 - progress
+        } else {
+            return None;
+        };
+
+        Some(wgpu::Color {
+            a: self.color.a * opacity,
+            ..self.color
+        })
+    }
+}
@@ -0,0 +1,247 @@
+//! Photosensitivity safeguards for the presented image.
+
+use crate::color::srgb_to_linear;
+
+/// The number of bins in a [`LuminanceHistogram`].
+const BINS: usize = 256;
+
+/// A luminance histogram of a single frame.
+///
+/// Each of the 256 bins counts the pixels whose relative luminance falls into the corresponding
+/// `1/256` wide slice of the `0.0..=1.0` range. Luminance is computed with the Rec. 709
+/// coefficients in linear light, decoding sRGB pixel data first when the texture format is sRGB.
+///
+/// ```
+/// use pixels::LuminanceHistogram;
+///
+/// // Two white pixels and two black pixels
+/// let frame = [
+///     0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+///     0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x00, 0xff,
+/// ];
+/// let histogram = LuminanceHistogram::from_rgba(&frame, true);
+///
+/// assert_eq!(histogram.total(), 4);
+/// assert_eq!(histogram.bins()[0], 2);
+/// assert_eq!(histogram.bins()[255], 2);
+/// assert!((histogram.mean() - 0.5).abs() < 0.01);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LuminanceHistogram {
+    bins: [u32; BINS],
+    total: u32,
+    sum: f64,
+}
+
+#[cfg(feature = "gpu")]
+/// Limits large frame-to-frame luminance swings in the presented image.
+///
+/// The limiter measures the average luminance of each frame with a [`LuminanceHistogram`]. When
+/// the average changes by more than [`FlashLimiter::max_delta`] since the previous frame, the
+/// scaling renderer blends the output toward a flat gray at the last presented luminance, so the
+/// visible change is capped to `max_delta` per frame. Content that changes slowly is unaffected.
+///
+/// This helps applications follow photosensitive epilepsy guidance (such as WCAG 2.3.1) without
+/// analyzing frames themselves, but it is not a certification.
+///
+/// The limiter only analyzes 4-byte-per-pixel RGBA and BGRA texture formats. Other formats are
+/// presented unchanged.
+///
+/// ```no_run
+/// use pixels::{FlashLimiter, PixelsBuilder};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+///     .flash_limiter(FlashLimiter::new(0.05))
+///     .build()?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct FlashLimiter {
+    max_delta: f32,
+    presented: Option<f32>,
+}
+
+#[cfg(feature = "gpu")]
+/// Output attenuation applied by the scaling renderer.
+///
+/// The output color is `mix(color, sampled, factor)`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Attenuation {
+    pub(crate) color: [f32; 3],
+    pub(crate) factor: f32,
+}
+
+impl LuminanceHistogram {
+    /// Compute the histogram of a frame with 4 bytes per pixel in `RGBA` order.
+    ///
+    /// Set `srgb` when the pixel data is sRGB encoded (e.g. `Rgba8UnormSrgb`), so that luminance
+    /// is measured in linear light.
+    pub fn from_rgba(frame: &[u8], srgb: bool) -> Self {
+        Self::from_channels(frame, srgb, [0, 1, 2])
+    }
+
+    /// Compute the histogram of a frame with 4 bytes per pixel in `BGRA` order.
+    ///
+    /// See [`LuminanceHistogram::from_rgba`].
+    pub fn from_bgra(frame: &[u8], srgb: bool) -> Self {
+        Self::from_channels(frame, srgb, [2, 1, 0])
+    }
+
+    fn from_channels(frame: &[u8], srgb: bool, [r, g, b]: [usize; 3]) -> Self {
+        let decode = decode_table(srgb);
+        let mut bins = [0; BINS];
+        let mut sum = 0.0;
+        let mut total = 0;
+
+        for pixel in frame.chunks_exact(4) {
+            let luminance = 0.2126 * decode[pixel[r] as usize]
+                + 0.7152 * decode[pixel[g] as usize]
+                + 0.0722 * decode[pixel[b] as usize];
+            let bin = ((luminance * BINS as f32) as usize).min(BINS - 1);
+
+            bins[bin] += 1;
+            sum += luminance as f64;
+            total += 1;
+        }
+
+        Self { bins, total, sum }
+    }
+
+    /// The pixel count for each luminance bin, from darkest to brightest.
+    pub fn bins(&self) -> &[u32; BINS] {
+        &self.bins
+    }
+
+    /// The total number of pixels counted.
+    pub fn total(&self) -> u32 {
+        self.total
+    }
+
+    /// The average relative luminance in the range `0.0..=1.0`.
+    ///
+    /// Returns `0.0` for an empty histogram.
+    pub fn mean(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            (self.sum / self.total as f64) as f32
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl FlashLimiter {
+    /// Create a flash limiter.
+    ///
+    /// `max_delta` is the largest change in average relative luminance (`0.0..=1.0`) allowed from
+    /// one presented frame to the next.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `max_delta` is not within `0.0..=1.0`.
+    pub fn new(max_delta: f32) -> Self {
+        assert!((0.0..=1.0).contains(&max_delta));
+
+        Self {
+            max_delta,
+            presented: None,
+        }
+    }
+
+    /// The largest change in average luminance allowed between frames.
+    pub fn max_delta(&self) -> f32 {
+        self.max_delta
+    }
+
+    /// Forget the previously presented luminance.
+    ///
+    /// The next frame will be presented without attenuation. This is done automatically when the
+    /// pixel buffer is resized.
+    pub fn reset(&mut self) {
+        self.presented = None;
+    }
+
+    /// Analyze a frame and compute the attenuation needed to present it.
+    ///
+    /// Returns `None` when the texture format is not supported by the limiter.
+    pub(crate) fn update(
+        &mut self,
+        frame: &[u8],
+        texture_format: wgpu::TextureFormat,
+    ) -> Option<Attenuation> {
+        use wgpu::TextureFormat::*;
+
+        let histogram = match texture_format {
+            Rgba8Unorm => LuminanceHistogram::from_rgba(frame, false),
+            Rgba8UnormSrgb => LuminanceHistogram::from_rgba(frame, true),
+            Bgra8Unorm => LuminanceHistogram::from_bgra(frame, false),
+            Bgra8UnormSrgb => LuminanceHistogram::from_bgra(frame, true),
+            _ => return None,
+        };
+        let luminance = histogram.mean();
+
+        let previous = match self.presented {
+            Some(previous) => previous,
+            None => {
+                self.presented = Some(luminance);
+                return Some(Attenuation::NONE);
+            }
+        };
+
+        let delta = luminance - previous;
+        if delta.abs() <= self.max_delta {
+            self.presented = Some(luminance);
+            return Some(Attenuation::NONE);
+        }
+
+        // Blending toward a gray of luminance `previous` by `factor` moves the average luminance to
+        // `previous + delta * factor`. Pick the factor that lands exactly on the allowed delta.
+        let factor = self.max_delta / delta.abs();
+        self.presented = Some(previous + self.max_delta.copysign(delta));
+
+        Some(Attenuation {
+            color: [previous; 3],
+            factor,
+        })
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl Default for FlashLimiter {
+    /// A limiter allowing a 2% change in average luminance per frame.
+    ///
+    /// At 60 frames per second, a full black-to-white transition takes almost a second.
+    fn default() -> Self {
+        Self::new(0.02)
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl Attenuation {
+    /// Present the sampled color unchanged.
+    pub(crate) const NONE: Self = Self {
+        color: [0.0; 3],
+        factor: 1.0,
+    };
+}
+
+/// Create a lookup table mapping 8-bit channel values to linear light.
+fn decode_table(srgb: bool) -> [f32; 256] {
+    let mut table = [0.0; 256];
+    for (i, value) in table.iter_mut().enumerate() {
+        let c = i as f64 / 255.0;
+        *value = if srgb { srgb_to_linear(c) } else { c } as f32;
+    }
+
+    table
+}
+
+#[cfg(feature = "gpu")]
+impl From<Attenuation> for [f32; 4] {
+    fn from(attenuation: Attenuation) -> Self {
+        let [r, g, b] = attenuation.color;
+        [r, g, b, attenuation.factor]
+    }
+}
@@ -0,0 +1,232 @@
+//! Bounds-checked drawing into a pixel buffer.
+
+use std::ops::{Deref, DerefMut};
+
+/// A pixel buffer with its dimensions, for drawing without index arithmetic.
+///
+/// Coordinates are in pixels from the top-left corner. Drawing is clipped to the frame, so
+/// shapes may start or end outside of it, e.g. while dragging past the edge of the window.
+/// Pixels have the same byte layout as the pixel buffer, e.g. 4 bytes for `Rgba8UnormSrgb`.
+///
+/// Get one for the pixel buffer with [`Pixels::frame_mut_view`](crate::Pixels::frame_mut_view),
+/// or wrap any byte slice with [`Frame::new`]. The frame dereferences to the bytes of the
+/// buffer for everything else.
+///
+/// ```
+/// use pixels::Frame;
+///
+/// let mut bytes = vec![0; 4 * 3 * 4];
+/// let mut frame = Frame::new(&mut bytes, 4, 3, 4);
+/// frame.fill(&[0x00, 0x00, 0xff, 0xff]);
+/// assert!(frame.set_pixel(3, 2, &[0xff, 0x00, 0x00, 0xff]));
+/// assert!(!frame.set_pixel(4, 2, &[0xff, 0x00, 0x00, 0xff]));
+///
+/// assert_eq!(frame.pixel(3, 2), Some(&[0xff, 0x00, 0x00, 0xff][..]));
+/// assert_eq!(frame.pixel(0, 0), Some(&[0x00, 0x00, 0xff, 0xff][..]));
+/// assert_eq!(frame.pixel(4, 0), None);
+/// ```
+#[derive(Debug)]
+pub struct Frame<'a> {
+    pixels: &'a mut [u8],
+    width: u32,
+    height: u32,
+    bytes_per_pixel: usize,
+}
+
+impl<'a> Frame<'a> {
+    /// Wrap `pixels`, a frame of `width` by `height` pixels with `bytes_per_pixel` bytes each.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `bytes_per_pixel` is 0, or the length of `pixels` does not match the size.
+    pub fn new(pixels: &'a mut [u8], width: u32, height: u32, bytes_per_pixel: usize) -> Self {
+        assert!(bytes_per_pixel > 0);
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize * bytes_per_pixel,
+        );
+
+        Self {
+            pixels,
+            width,
+            height,
+            bytes_per_pixel,
+        }
+    }
+
+    /// Get the width of the frame in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the frame in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the number of bytes per pixel.
+    pub fn bytes_per_pixel(&self) -> usize {
+        self.bytes_per_pixel
+    }
+
+    /// Get the pixel at `(x, y)`, or `None` outside of the frame.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<&[u8]> {
+        let start = self.offset(x, y)?;
+
+        Some(&self.pixels[start..start + self.bytes_per_pixel])
+    }
+
+    /// Get the pixel at `(x, y)` mutably, or `None` outside of the frame.
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut [u8]> {
+        let start = self.offset(x, y)?;
+
+        Some(&mut self.pixels[start..start + self.bytes_per_pixel])
+    }
+
+    /// Set the pixel at `(x, y)`. Returns `false` without drawing when it is outside of the frame.
+    ///
+    /// Unlike [`Frame::pixel`] and [`Frame::pixel_mut`], which can only return pixels inside of
+    /// the frame, this takes signed coordinates like the position of [`Frame::blit`], so shapes
+    /// plotted pixel by pixel may start left of or above the frame and are clipped there.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `pixel` is not [`Frame::bytes_per_pixel`] long.
+    pub fn set_pixel(&mut self, x: i32, y: i32, pixel: &[u8]) -> bool {
+        assert_eq!(pixel.len(), self.bytes_per_pixel);
+
+        let (Ok(x), Ok(y)) = (u32::try_from(x), u32::try_from(y)) else {
+            return false;
+        };
+        match self.pixel_mut(x, y) {
+            Some(dst) => {
+                dst.copy_from_slice(pixel);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Set every pixel of the frame to `pixel`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `pixel` is not [`Frame::bytes_per_pixel`] long.
+    pub fn fill(&mut self, pixel: &[u8]) {
+        assert_eq!(pixel.len(), self.bytes_per_pixel);
+
+        for dst in self.pixels.chunks_exact_mut(self.bytes_per_pixel) {
+            dst.copy_from_slice(pixel);
+        }
+    }
+
+    /// Copy the region `src_rect` (x, y, width, height) of `src`, an image `src_width` pixels
+    /// wide in the same format, to the frame with its top-left corner at `position`.
+    ///
+    /// The region is clipped to both the source image and the frame; parts outside of either are
+    /// skipped.
+    ///
+    /// ```
+    /// use pixels::Frame;
+    ///
+    /// // A 2x2 sprite with one byte per pixel
+    /// let sprite = [1, 2, 3, 4];
+    /// let mut bytes = vec![0; 3 * 3];
+    /// let mut frame = Frame::new(&mut bytes, 3, 3, 1);
+    ///
+    /// // Half of the sprite hangs off the left edge
+    /// frame.blit(&sprite, 2, (0, 0, 2, 2), (-1, 1));
+    /// assert_eq!(&frame[..], &[0, 0, 0, 2, 0, 0, 4, 0, 0]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when the length of `src` is not a whole number of rows of `src_width` pixels.
+    pub fn blit(
+        &mut self,
+        src: &[u8],
+        src_width: u32,
+        src_rect: (u32, u32, u32, u32),
+        position: (i32, i32),
+    ) {
+        let bpp = self.bytes_per_pixel;
+        let src_row_len = src_width as usize * bpp;
+        assert!(src_row_len == 0 || src.len() % src_row_len == 0);
+        let src_height = src.len().checked_div(src_row_len).unwrap_or(0) as i64;
+
+        // Clip the region to the source image, then to the frame
+        let (rect_x, rect_y, rect_width, rect_height) = src_rect;
+        let sx0 = i64::from(rect_x);
+        let sy0 = i64::from(rect_y);
+        let sx1 = (sx0 + i64::from(rect_width)).min(i64::from(src_width));
+        let sy1 = (sy0 + i64::from(rect_height)).min(src_height);
+        let (dx, dy) = (i64::from(position.0) - sx0, i64::from(position.1) - sy0);
+        let x0 = (sx0 + dx).max(0);
+        let y0 = (sy0 + dy).max(0);
+        let x1 = (sx1 + dx).min(i64::from(self.width));
+        let y1 = (sy1 + dy).min(i64::from(self.height));
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        let row_len = (x1 - x0) as usize * bpp;
+        let width = i64::from(self.width);
+        for y in y0..y1 {
+            let src_start = ((y - dy) * i64::from(src_width) + (x0 - dx)) as usize * bpp;
+            let dst_start = (y * width + x0) as usize * bpp;
+            self.pixels[dst_start..dst_start + row_len]
+                .copy_from_slice(&src[src_start..src_start + row_len]);
+        }
+    }
+
+    /// Iterate over the rows of the frame, from top to bottom.
+    ///
+    /// ```
+    /// use pixels::Frame;
+    ///
+    /// let mut bytes = vec![0; 2 * 2];
+    /// let mut frame = Frame::new(&mut bytes, 2, 2, 1);
+    /// for (y, row) in frame.rows_mut().enumerate() {
+    ///     row.fill(y as u8);
+    /// }
+    /// assert_eq!(&frame[..], &[0, 0, 1, 1]);
+    /// ```
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        // Rows of zero-width frames are empty, but `chunks_exact_mut` needs a nonzero size
+        let row_len = (self.width as usize * self.bytes_per_pixel).max(1);
+
+        self.pixels.chunks_exact_mut(row_len)
+    }
+
+    /// Get the byte offset of the pixel at `(x, y)`, or `None` outside of the frame.
+    fn offset(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height)
+            .then(|| (y as usize * self.width as usize + x as usize) * self.bytes_per_pixel)
+    }
+}
+
+impl Deref for Frame<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.pixels
+    }
+}
+
+impl DerefMut for Frame<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.pixels
+    }
+}
+
+impl AsRef<[u8]> for Frame<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self.pixels
+    }
+}
+
+impl AsMut<[u8]> for Frame<'_> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        self.pixels
+    }
+}
@@ -0,0 +1,98 @@
+//! Per-channel gamma ramps, like the hardware lookup tables of older displays.
+
+/// A lookup table for each color channel, applied to every color the scaling pass outputs.
+///
+/// Games from the era of `SetDeviceGammaRamp` and `XF86VidModeSetGammaRamp` brighten, fade, and
+/// tint the whole screen by loading a ramp into the display hardware. A port can keep that code
+/// by passing its ramps to [`PixelsBuilder::gamma_ramp`](crate::PixelsBuilder::gamma_ramp) and
+/// [`Pixels::set_gamma_ramp`](crate::Pixels::set_gamma_ramp) instead.
+///
+/// Each channel maps the 8-bit sRGB-encoded output value at its index to a 16-bit value, like
+/// those APIs, where `0xffff` is full intensity. Output values between two entries are
+/// interpolated.
+///
+/// ```
+/// use pixels::GammaRamp;
+///
+/// let identity = GammaRamp::default();
+/// assert_eq!(identity.red[0x80], 0x8080);
+///
+/// // Fade to black at half brightness
+/// let mut fade = GammaRamp::default();
+/// for channel in [&mut fade.red, &mut fade.green, &mut fade.blue] {
+///     channel.iter_mut().for_each(|value| *value /= 2);
+/// }
+/// assert_eq!(fade.green[0xff], 0x7fff);
+///
+/// let bright = GammaRamp::from_gamma(2.0);
+/// assert!(bright.blue[0x40] > identity.blue[0x40]);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GammaRamp {
+    /// The ramp of the red channel.
+    pub red: [u16; 256],
+
+    /// The ramp of the green channel.
+    pub green: [u16; 256],
+
+    /// The ramp of the blue channel.
+    pub blue: [u16; 256],
+}
+
+impl GammaRamp {
+    /// Create a ramp which keeps every color.
+    pub fn identity() -> Self {
+        let channel = std::array::from_fn(|index| index as u16 * 0x101);
+
+        Self {
+            red: channel,
+            green: channel,
+            blue: channel,
+        }
+    }
+
+    /// Create a ramp which raises every channel to the power of `1.0 / gamma`, the curve of the
+    /// brightness sliders in many older games. Values above `1.0` brighten midtones.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `gamma` is not positive.
+    pub fn from_gamma(gamma: f32) -> Self {
+        assert!(gamma > 0.0, "gamma must be positive");
+
+        let channel = std::array::from_fn(|index| {
+            let value = (index as f32 / 255.0).powf(1.0 / gamma);
+            (value * f32::from(u16::MAX)).round() as u16
+        });
+
+        Self {
+            red: channel,
+            green: channel,
+            blue: channel,
+        }
+    }
+
+    /// The entries of the `GammaRamp` uniform in `gamma_ramp.wgsl`.
+    #[cfg(feature = "gpu")]
+    pub(crate) fn entries(&self) -> Vec<[f32; 4]> {
+        let value = |value: u16| f32::from(value) / f32::from(u16::MAX);
+
+        (0..256)
+            .map(|index| {
+                [
+                    value(self.red[index]),
+                    value(self.green[index]),
+                    value(self.blue[index]),
+                    0.0,
+                ]
+            })
+            .collect()
+    }
+}
+
+impl Default for GammaRamp {
+    /// The [identity](GammaRamp::identity) ramp.
+    fn default() -> Self {
+        Self::identity()
+    }
+}
@@ -0,0 +1,86 @@
+//! GPU handles shared between pixel buffers.
+
+use crate::{Error, Pixels, PixelsBuilder, SurfaceTexture};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// A `wgpu` instance, adapter, device, and queue shared by several [`Pixels`] instances.
+///
+/// Every pixel buffer built without one requests its own adapter and device, which slows down
+/// startup and takes memory for each window. Tools with more than one window, like an editor with
+/// a preview, can share the GPU instead: take the handle of the first pixel buffer with
+/// [`Pixels::shared_gpu`], and pass it to [`PixelsBuilder::shared_gpu`] or
+/// [`SharedGpu::create_pixels`] for the others. Resources created with the shared device, such as
+/// textures for [`Pixels::import_texture`], work with every pixel buffer that shares it.
+///
+/// The handle is cheap to clone, and the GPU is released once the last pixel buffer and handle
+/// are dropped.
+///
+/// ```no_run
+/// use pixels::Pixels;
+///
+/// # let editor_window = pixels_mocks::Window;
+/// # let preview_window = pixels_mocks::Window;
+/// # let editor_surface = pixels::SurfaceTexture::new(640, 480, &editor_window);
+/// # let preview_surface = pixels::SurfaceTexture::new(320, 240, &preview_window);
+/// let editor = Pixels::new(640, 480, editor_surface)?;
+/// let preview = editor
+///     .shared_gpu()
+///     .create_pixels(320, 240, preview_surface)?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct SharedGpu {
+    pub(crate) instance: Arc<wgpu::Instance>,
+    pub(crate) adapter: Arc<wgpu::Adapter>,
+    pub(crate) device: Arc<wgpu::Device>,
+    pub(crate) queue: Arc<wgpu::Queue>,
+    // Set by the device lost callback, which the device holds only one of
+    pub(crate) device_lost: Arc<AtomicBool>,
+}
+
+impl SharedGpu {
+    /// Get the instance, which creates the surfaces of the pixel buffers.
+    pub fn instance(&self) -> &wgpu::Instance {
+        &self.instance
+    }
+
+    /// Get the adapter.
+    pub fn adapter(&self) -> &wgpu::Adapter {
+        &self.adapter
+    }
+
+    /// Get the device.
+    pub fn device(&self) -> &wgpu::Device {
+        &self.device
+    }
+
+    /// Get the queue.
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.queue
+    }
+
+    /// Create a pixel buffer on the shared GPU with the default options.
+    ///
+    /// This is a shortcut for [`PixelsBuilder::shared_gpu`]; use the builder for other options.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the adapter cannot present to the surface, or when the pixel buffer
+    /// cannot be created.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn create_pixels<'win, W: wgpu::WindowHandle + 'win>(
+        &self,
+        width: u32,
+        height: u32,
+        surface_texture: SurfaceTexture<W>,
+    ) -> Result<Pixels<'win>, Error> {
+        PixelsBuilder::new(width, height, surface_texture)
+            .shared_gpu(self.clone())
+            .build()
+    }
+}
@@ -0,0 +1,168 @@
+//! Window icons and cursor images cut from the pixel buffer.
+
+use thiserror::Error;
+
+/// All the ways in which cutting an [`IconImage`] from a frame can fail.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum IconError {
+    /// The frame is not in an 8-bit RGBA or BGRA format
+    #[error("Texture format is unsupported: {0:?}")]
+    TextureFormat(wgpu::TextureFormat),
+    /// The region (x, y, width, height) is empty or extends past the frame
+    #[error("Region is out of bounds: {0:?}")]
+    OutOfBounds((u32, u32, u32, u32)),
+}
+
+/// A region of a frame as 8-bit RGBA with straight alpha, scaled up for use as a window icon or
+/// a cursor.
+///
+/// Create one with [`Pixels::icon_image`](crate::Pixels::icon_image), so that pixel-art games can
+/// draw their iconography with the same code as the rest of the game. With the `winit` feature,
+/// it converts to a window icon with [`IconImage::to_window_icon`] or a cursor with
+/// [`IconImage::to_cursor`].
+///
+/// ```
+/// use pixels::IconImage;
+/// use pixels::wgpu::TextureFormat;
+///
+/// // A 2x1 frame with a red and a blue pixel
+/// let frame = [0xff, 0x00, 0x00, 0xff, 0x00, 0x00, 0xff, 0xff];
+/// let icon =
+///     IconImage::from_frame(&frame, (2, 1), TextureFormat::Rgba8UnormSrgb, (1, 0, 1, 1), 2)?;
+/// assert_eq!((icon.width, icon.height), (2, 2));
+/// assert_eq!(icon.rgba, [0x00, 0x00, 0xff, 0xff].repeat(4));
+/// # Ok::<(), pixels::IconError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct IconImage {
+    /// The width of the image in pixels.
+    pub width: u32,
+
+    /// The height of the image in pixels.
+    pub height: u32,
+
+    /// The scale from frame pixels to image pixels.
+    pub scale: u32,
+
+    /// The pixels as RGBA bytes, in rows from top to bottom.
+    pub rgba: Vec<u8>,
+}
+
+impl IconImage {
+    /// Cut `rect` (x, y, width, height) from `frame`, which is `frame_size` pixels in
+    /// `texture_format`, and scale it by the whole number `scale` with nearest-neighbor
+    /// sampling.
+    ///
+    /// 8-bit RGBA and BGRA formats are supported. The bytes are copied as they are, so sRGB
+    /// frames produce sRGB images, which is what window systems expect.
+    ///
+    /// # Errors
+    ///
+    /// - [`IconError::TextureFormat`] when the format is not 8-bit RGBA or BGRA.
+    /// - [`IconError::OutOfBounds`] when `rect` is empty or extends past the frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `scale` is 0, or `frame` is smaller than `frame_size`.
+    pub fn from_frame(
+        frame: &[u8],
+        frame_size: (u32, u32),
+        texture_format: wgpu::TextureFormat,
+        rect: (u32, u32, u32, u32),
+        scale: u32,
+    ) -> Result<Self, IconError> {
+        assert!(scale > 0);
+
+        let swap_red_blue = match texture_format {
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            _ => return Err(IconError::TextureFormat(texture_format)),
+        };
+
+        let (x, y, width, height) = rect;
+        let in_bounds = |offset: u32, len: u32, limit: u32| {
+            len > 0 && offset.checked_add(len).is_some_and(|end| end <= limit)
+        };
+        if !in_bounds(x, width, frame_size.0) || !in_bounds(y, height, frame_size.1) {
+            return Err(IconError::OutOfBounds(rect));
+        }
+        let frame_row_len = frame_size.0 as usize * 4;
+        assert!(frame.len() >= frame_row_len * frame_size.1 as usize);
+
+        let scale_len = scale as usize;
+        let mut rgba = Vec::with_capacity(width as usize * height as usize * 4 * scale_len.pow(2));
+        for row in frame
+            .chunks_exact(frame_row_len)
+            .skip(y as usize)
+            .take(height as usize)
+        {
+            let start = rgba.len();
+            for pixel in row[x as usize * 4..(x + width) as usize * 4].chunks_exact(4) {
+                let pixel = if swap_red_blue {
+                    [pixel[2], pixel[1], pixel[0], pixel[3]]
+                } else {
+                    [pixel[0], pixel[1], pixel[2], pixel[3]]
+                };
+                for _ in 0..scale {
+                    rgba.extend_from_slice(&pixel);
+                }
+            }
+
+            // Repeat the scaled row
+            let end = rgba.len();
+            for _ in 1..scale {
+                rgba.extend_from_within(start..end);
+            }
+        }
+
+        Ok(Self {
+            width: width * scale,
+            height: height * scale,
+            scale,
+            rgba,
+        })
+    }
+
+    /// Create a window icon, e.g. for [`winit::window::Window::set_window_icon`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the platform rejects the image.
+    #[cfg(feature = "winit")]
+    pub fn to_window_icon(&self) -> Result<winit::window::Icon, winit::window::BadIcon> {
+        winit::window::Icon::from_rgba(self.rgba.clone(), self.width, self.height)
+    }
+
+    /// Create a cursor for [`winit::event_loop::ActiveEventLoop::create_custom_cursor`].
+    ///
+    /// `hotspot` is the position of the click point in frame pixels, relative to the top-left
+    /// corner of the region; it is scaled with the image to the center of that pixel.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the image is larger than [`winit::window::MAX_CURSOR_SIZE`], or the
+    /// hotspot is outside of the image.
+    #[cfg(feature = "winit")]
+    pub fn to_cursor(
+        &self,
+        hotspot: (u32, u32),
+    ) -> Result<winit::window::CustomCursorSource, winit::window::BadImage> {
+        let to_u16 = |value: u32| u16::try_from(value).unwrap_or(u16::MAX);
+        let center = |value: u32| {
+            value
+                .saturating_mul(self.scale)
+                .saturating_add(self.scale / 2)
+        };
+        let (hotspot_x, hotspot_y) = (center(hotspot.0), center(hotspot.1));
+
+        winit::window::CustomCursor::from_rgba(
+            self.rgba.clone(),
+            to_u16(self.width),
+            to_u16(self.height),
+            to_u16(hotspot_x),
+            to_u16(hotspot_y),
+        )
+    }
+}
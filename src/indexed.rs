@@ -0,0 +1,184 @@
+//! Blitting indexed-color sprites with palette remapping.
+
+/// A table which replaces palette indices as a sprite is drawn.
+///
+/// Remapping is the classic way to recolor one sprite many ways without storing a copy for each:
+/// team colors swap a few indices, and a damage flash maps every index to white.
+///
+/// ```
+/// use pixels::PaletteRemap;
+///
+/// let mut team_red = PaletteRemap::default();
+/// team_red.set(4, 12).set(5, 13);
+/// assert_eq!(team_red.get(4), 12);
+/// assert_eq!(team_red.get(6), 6);
+///
+/// let flash = PaletteRemap::fill(15);
+/// assert_eq!(flash.get(4), 15);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaletteRemap([u8; 256]);
+
+impl PaletteRemap {
+    /// Create a remap table which keeps every index.
+    pub fn identity() -> Self {
+        Self(std::array::from_fn(|index| index as u8))
+    }
+
+    /// Create a remap table which maps every index to `index`.
+    pub fn fill(index: u8) -> Self {
+        Self([index; 256])
+    }
+
+    /// Replace index `from` with `to`.
+    pub fn set(&mut self, from: u8, to: u8) -> &mut Self {
+        self.0[from as usize] = to;
+        self
+    }
+
+    /// Get the index which replaces `index`.
+    pub fn get(&self, index: u8) -> u8 {
+        self.0[index as usize]
+    }
+}
+
+impl Default for PaletteRemap {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// An indexed-color image with one palette index per pixel, drawn with [`blit_indexed`] or
+/// [`blit_indexed_rgba`].
+#[derive(Clone, Copy, Debug)]
+pub struct IndexedSprite<'a> {
+    /// The palette indices, in rows from top to bottom.
+    pub indices: &'a [u8],
+
+    /// The width of the sprite in pixels.
+    pub width: u32,
+
+    /// The height of the sprite in pixels.
+    pub height: u32,
+
+    /// The index drawn as a hole, before remapping.
+    pub transparent: Option<u8>,
+}
+
+impl<'a> IndexedSprite<'a> {
+    /// Create a sprite without a transparent index.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `indices` is not `width * height` long.
+    pub fn new(indices: &'a [u8], width: u32, height: u32) -> Self {
+        assert_eq!(indices.len(), width as usize * height as usize);
+
+        Self {
+            indices,
+            width,
+            height,
+            transparent: None,
+        }
+    }
+
+    /// Skip pixels with the palette index `transparent`.
+    pub fn with_transparent(mut self, transparent: u8) -> Self {
+        self.transparent = Some(transparent);
+        self
+    }
+}
+
+/// Draw `sprite` into an indexed frame of `dst_size` pixels with its top-left corner at
+/// `position`, replacing indices through `remap`.
+///
+/// Pixels outside of `dst` are skipped, as are transparent pixels.
+///
+/// ```
+/// use pixels::{blit_indexed, IndexedSprite, PaletteRemap};
+///
+/// let sprite = IndexedSprite::new(&[1, 0, 2, 1], 2, 2).with_transparent(0);
+/// let mut remap = PaletteRemap::default();
+/// remap.set(1, 7);
+///
+/// let mut frame = [9; 3 * 2];
+/// blit_indexed(&mut frame, (3, 2), &sprite, (1, 0), &remap);
+/// assert_eq!(frame, [9, 7, 9, 9, 2, 7]);
+/// ```
+///
+/// # Panics
+///
+/// Panics when `dst` is smaller than `dst_size`.
+pub fn blit_indexed(
+    dst: &mut [u8],
+    dst_size: (u32, u32),
+    sprite: &IndexedSprite<'_>,
+    position: (i32, i32),
+    remap: &PaletteRemap,
+) {
+    assert!(dst.len() >= dst_size.0 as usize * dst_size.1 as usize);
+
+    for_each_visible(dst_size, sprite, position, |dst_offset, index| {
+        dst[dst_offset] = remap.get(index);
+    });
+}
+
+/// Draw `sprite` into an RGBA frame of `dst_size` pixels with its top-left corner at `position`,
+/// replacing indices through `remap` and then looking them up in `palette`.
+///
+/// This draws indexed sprites straight into a [`Pixels`](crate::Pixels) frame in an 8-bit RGBA
+/// format. Pixels outside of `dst` are skipped, as are transparent pixels. Indices past the end
+/// of `palette` are drawn as transparent black.
+///
+/// # Panics
+///
+/// Panics when `dst` is smaller than `dst_size`.
+pub fn blit_indexed_rgba(
+    dst: &mut [u8],
+    dst_size: (u32, u32),
+    sprite: &IndexedSprite<'_>,
+    position: (i32, i32),
+    remap: &PaletteRemap,
+    palette: &[[u8; 4]],
+) {
+    assert!(dst.len() >= dst_size.0 as usize * dst_size.1 as usize * 4);
+
+    for_each_visible(dst_size, sprite, position, |dst_offset, index| {
+        let color = palette
+            .get(remap.get(index) as usize)
+            .copied()
+            .unwrap_or_default();
+        dst[dst_offset * 4..dst_offset * 4 + 4].copy_from_slice(&color);
+    });
+}
+
+/// Call `draw` with the destination pixel offset and source index of every visible sprite pixel.
+fn for_each_visible<F>(
+    dst_size: (u32, u32),
+    sprite: &IndexedSprite<'_>,
+    position: (i32, i32),
+    mut draw: F,
+) where
+    F: FnMut(usize, u8),
+{
+    let (dst_width, dst_height) = (i64::from(dst_size.0), i64::from(dst_size.1));
+    let (x, y) = (i64::from(position.0), i64::from(position.1));
+    let (width, height) = (i64::from(sprite.width), i64::from(sprite.height));
+
+    // Clip the sprite to the destination frame
+    let x0 = x.max(0);
+    let y0 = y.max(0);
+    let x1 = (x + width).min(dst_width);
+    let y1 = (y + height).min(dst_height);
+
+    for dst_y in y0..y1 {
+        let src_row = ((dst_y - y) * width) as usize;
+        let dst_row = (dst_y * dst_width) as usize;
+        for dst_x in x0..x1 {
+            let index = sprite.indices[src_row + (dst_x - x) as usize];
+            if sprite.transparent != Some(index) {
+                draw(dst_row + dst_x as usize, index);
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Helpers for ingesting frames produced by cameras, video decoders, and other external sources.
+//!
+//! External producers rarely hand out tightly packed RGBA rows. Camera drivers and decoders pad
+//! rows to an alignment (the "stride" or "pitch") and commonly emit YUV formats. The functions in
+//! this module copy and convert such frames directly into a pixel buffer in a single pass, so a
+//! 1080p30 feed does not need an intermediate allocation per frame.
+//!
+//! YUV conversions use the BT.601 limited-range coefficients used by the vast majority of webcams,
+//! and write opaque pixels in `RGBA` order. Chroma planes of odd-sized images are expected to be
+//! rounded up, matching the layouts produced by V4L2, PipeWire, and Media Foundation.
+
+use thiserror::Error;
+
+/// One plane of an image: the raw bytes and the distance in bytes between the starts of two rows.
+#[derive(Clone, Copy, Debug)]
+pub struct Plane<'a> {
+    /// The plane data, starting at the first byte of the first row.
+    pub data: &'a [u8],
+
+    /// The number of bytes from the start of one row to the start of the next.
+    pub stride: usize,
+}
+
+/// All the ways in which ingesting a frame can fail.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum IngestError {
+    /// A row stride is smaller than the row it describes
+    #[error("Stride {stride} is smaller than the row length {row_len}.")]
+    InvalidStride {
+        /// The stride given for the plane.
+        stride: usize,
+        /// The number of bytes in a row of the plane.
+        row_len: usize,
+    },
+    /// The source does not contain all rows of the image
+    #[error("Source is too small: expected at least {expected} bytes, got {actual}.")]
+    SourceTooSmall {
+        /// The minimum size of the source.
+        expected: usize,
+        /// The actual size of the source.
+        actual: usize,
+    },
+    /// The destination cannot hold the image
+    #[error("Destination is too small: expected at least {expected} bytes, got {actual}.")]
+    DestinationTooSmall {
+        /// The minimum size of the destination.
+        expected: usize,
+        /// The actual size of the destination.
+        actual: usize,
+    },
+}
+
+impl<'a> Plane<'a> {
+    /// Describe a plane.
+    pub fn new(data: &'a [u8], stride: usize) -> Self {
+        Self { data, stride }
+    }
+
+    /// Check that the plane holds `rows` rows of `row_len` bytes.
+    fn validate(&self, row_len: usize, rows: usize) -> Result<(), IngestError> {
+        validate(
+            self.data.len(),
+            self.stride,
+            row_len,
+            rows,
+            |expected, actual| IngestError::SourceTooSmall { expected, actual },
+        )
+    }
+
+    fn row(&self, y: usize) -> &'a [u8] {
+        &self.data[y * self.stride..]
+    }
+}
+
+/// Copy `rows` rows of `row_len` bytes between buffers with different strides.
+///
+/// This is the building block for accepting frames with padded rows. Bytes in the padding of
+/// `dst` are left unchanged.
+///
+/// ```
+/// use pixels::ingest::{copy_strided, Plane};
+///
+/// // Two rows of two RGBA pixels, padded to 12 bytes per row
+/// let src = [
+///     1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0,
+///     3, 3, 3, 3, 4, 4, 4, 4, 0, 0, 0, 0,
+/// ];
+/// let mut frame = [0; 16];
+/// copy_strided(&mut frame, 8, Plane::new(&src, 12), 8, 2)?;
+///
+/// assert_eq!(frame, [1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4]);
+/// # Ok::<(), pixels::ingest::IngestError>(())
+/// ```
+///
+/// # Errors
+///
+/// - [`IngestError::InvalidStride`] when either stride is smaller than `row_len`.
+/// - [`IngestError::SourceTooSmall`] or [`IngestError::DestinationTooSmall`] when a buffer does
+///   not hold all rows.
+pub fn copy_strided(
+    dst: &mut [u8],
+    dst_stride: usize,
+    src: Plane<'_>,
+    row_len: usize,
+    rows: usize,
+) -> Result<(), IngestError> {
+    src.validate(row_len, rows)?;
+    validate_dst(dst, dst_stride, row_len, rows)?;
+
+    if src.stride == row_len && dst_stride == row_len {
+        let len = row_len * rows;
+        dst[..len].copy_from_slice(&src.data[..len]);
+    } else {
+        for y in 0..rows {
+            dst[y * dst_stride..][..row_len].copy_from_slice(&src.row(y)[..row_len]);
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a packed `YUYV` (aka `YUY2`) image to tightly packed `RGBA`.
+///
+/// Each 4-byte group `Y0 U Y1 V` holds two horizontally adjacent pixels.
+///
+/// ```
+/// use pixels::ingest::{yuyv_to_rgba, Plane};
+///
+/// // Two pixels: limited-range black and white
+/// let src = [16, 128, 235, 128];
+/// let mut frame = [0; 8];
+/// yuyv_to_rgba(Plane::new(&src, 4), 2, 1, &mut frame)?;
+///
+/// assert_eq!(frame, [0, 0, 0, 255, 255, 255, 255, 255]);
+/// # Ok::<(), pixels::ingest::IngestError>(())
+/// ```
+///
+/// # Errors
+///
+/// See [`copy_strided`].
+pub fn yuyv_to_rgba(
+    src: Plane<'_>,
+    width: usize,
+    height: usize,
+    dst: &mut [u8],
+) -> Result<(), IngestError> {
+    src.validate(width.div_ceil(2) * 4, height)?;
+    validate_dst(dst, width * 4, width * 4, height)?;
+    if width == 0 {
+        return Ok(());
+    }
+
+    for (y, dst_row) in dst.chunks_exact_mut(width * 4).take(height).enumerate() {
+        let src_row = src.row(y);
+        for (x, pixel) in dst_row.chunks_exact_mut(4).enumerate() {
+            let group = &src_row[x / 2 * 4..][..4];
+            let luma = if x % 2 == 0 { group[0] } else { group[2] };
+
+            pixel.copy_from_slice(&yuv_to_rgba(luma, group[1], group[3]));
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a semi-planar `NV12` image to tightly packed `RGBA`.
+///
+/// `y` is the full-resolution luma plane, and `uv` is the interleaved half-resolution chroma
+/// plane.
+///
+/// ```
+/// use pixels::ingest::{nv12_to_rgba, Plane};
+///
+/// let y = [235, 235, 235, 235];
+/// let uv = [128, 128];
+/// let mut frame = [0; 16];
+/// nv12_to_rgba(Plane::new(&y, 2), Plane::new(&uv, 2), 2, 2, &mut frame)?;
+///
+/// assert!(frame.iter().all(|&c| c == 255));
+/// # Ok::<(), pixels::ingest::IngestError>(())
+/// ```
+///
+/// # Errors
+///
+/// See [`copy_strided`].
+pub fn nv12_to_rgba(
+    y: Plane<'_>,
+    uv: Plane<'_>,
+    width: usize,
+    height: usize,
+    dst: &mut [u8],
+) -> Result<(), IngestError> {
+    let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+    y.validate(width, height)?;
+    uv.validate(chroma_width * 2, chroma_height)?;
+    validate_dst(dst, width * 4, width * 4, height)?;
+    if width == 0 {
+        return Ok(());
+    }
+
+    for (row, dst_row) in dst.chunks_exact_mut(width * 4).take(height).enumerate() {
+        let luma = y.row(row);
+        let chroma = uv.row(row / 2);
+        for (x, pixel) in dst_row.chunks_exact_mut(4).enumerate() {
+            let c = x / 2 * 2;
+
+            pixel.copy_from_slice(&yuv_to_rgba(luma[x], chroma[c], chroma[c + 1]));
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert a planar `I420` (aka `YUV420p`) image to tightly packed `RGBA`.
+///
+/// `y` is the full-resolution luma plane, and `u` and `v` are the half-resolution chroma planes.
+///
+/// # Errors
+///
+/// See [`copy_strided`].
+pub fn i420_to_rgba(
+    y: Plane<'_>,
+    u: Plane<'_>,
+    v: Plane<'_>,
+    width: usize,
+    height: usize,
+    dst: &mut [u8],
+) -> Result<(), IngestError> {
+    let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+    y.validate(width, height)?;
+    u.validate(chroma_width, chroma_height)?;
+    v.validate(chroma_width, chroma_height)?;
+    validate_dst(dst, width * 4, width * 4, height)?;
+    if width == 0 {
+        return Ok(());
+    }
+
+    for (row, dst_row) in dst.chunks_exact_mut(width * 4).take(height).enumerate() {
+        let luma = y.row(row);
+        let (cb, cr) = (u.row(row / 2), v.row(row / 2));
+        for (x, pixel) in dst_row.chunks_exact_mut(4).enumerate() {
+            pixel.copy_from_slice(&yuv_to_rgba(luma[x], cb[x / 2], cr[x / 2]));
+        }
+    }
+
+    Ok(())
+}
+
+/// Convert one BT.601 limited-range sample to `RGBA` using 16.16 fixed point arithmetic.
+#[inline]
+fn yuv_to_rgba(y: u8, u: u8, v: u8) -> [u8; 4] {
+    let c = (y as i32 - 16) * 76_284; // 1.164
+    let d = u as i32 - 128;
+    let e = v as i32 - 128;
+
+    let r = (c + 104_595 * e + 0x8000) >> 16; // 1.596
+    let g = (c - 25_625 * d - 53_281 * e + 0x8000) >> 16; // 0.391, 0.813
+    let b = (c + 132_252 * d + 0x8000) >> 16; // 2.018
+
+    [
+        r.clamp(0, 255) as u8,
+        g.clamp(0, 255) as u8,
+        b.clamp(0, 255) as u8,
+        0xff,
+    ]
+}
+
+fn validate_dst(dst: &[u8], stride: usize, row_len: usize, rows: usize) -> Result<(), IngestError> {
+    validate(dst.len(), stride, row_len, rows, |expected, actual| {
+        IngestError::DestinationTooSmall { expected, actual }
+    })
+}
+
+fn validate(
+    len: usize,
+    stride: usize,
+    row_len: usize,
+    rows: usize,
+    too_small: impl FnOnce(usize, usize) -> IngestError,
+) -> Result<(), IngestError> {
+    if stride < row_len {
+        return Err(IngestError::InvalidStride { stride, row_len });
+    }
+
+    // The last row does not need to include padding
+    let expected = rows.saturating_sub(1) * stride + row_len;
+    if rows > 0 && len < expected {
+        return Err(too_small(expected, len));
+    }
+
+    Ok(())
+}
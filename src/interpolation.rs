@@ -0,0 +1,96 @@
+//! Smoothing between frames for content which updates slower than the display.
+
+use web_time::{Duration, Instant};
+
+/// How renders between two new frames of the pixel buffer are filled in.
+///
+/// A simulation stepping at 30 frames per second on a 144 Hz display presents each frame about
+/// five times in a row, which reads as judder during slow pans and fades. With frame interpolation,
+/// the scaling pass draws the previous frame over the newest one, so the output changes on every
+/// refresh instead of in steps. Frames count as new when the pixel buffer is written, e.g. with
+/// [`Pixels::frame_mut`](crate::Pixels::frame_mut), and renders without a new frame skip the
+/// upload.
+///
+/// Both modes show each frame late by up to one update interval, and neither estimates motion:
+/// moving objects appear doubled while they change position.
+///
+/// See [`PixelsBuilder::frame_interpolation`](crate::PixelsBuilder::frame_interpolation).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FrameInterpolation {
+    /// Show an even mix of the last two frames on every render.
+    ///
+    /// This also hides flicker from content which alternates between two frames, like sprites
+    /// drawn on every other frame for transparency.
+    Blend,
+    /// Fade from the previous frame to the newest one over the time between the last two frames.
+    ///
+    /// The newest frame is fully visible when the next one is due, so content at a steady rate
+    /// changes smoothly from one frame to the next.
+    Crossfade,
+}
+
+/// Tracks uploads of the pixel buffer to weigh the previous frame against the newest one.
+#[derive(Debug)]
+pub(crate) struct FrameInterpolator {
+    mode: FrameInterpolation,
+    // Uploads since the texture ring was created, saturating at 2
+    uploads: u8,
+    last_upload: Option<Instant>,
+    interval: Option<Duration>,
+}
+
+impl FrameInterpolator {
+    pub(crate) fn new(mode: FrameInterpolation) -> Self {
+        Self {
+            mode,
+            uploads: 0,
+            last_upload: None,
+            interval: None,
+        }
+    }
+
+    pub(crate) fn mode(&self) -> FrameInterpolation {
+        self.mode
+    }
+
+    /// Forget the uploaded frames, when the previous texture no longer holds the previous frame.
+    pub(crate) fn reset(&mut self) {
+        self.uploads = 0;
+        self.last_upload = None;
+        self.interval = None;
+    }
+
+    /// Record a new frame uploaded at `now`.
+    pub(crate) fn uploaded(&mut self, now: Instant) {
+        self.uploads = (self.uploads + 1).min(2);
+        self.interval = self
+            .last_upload
+            .map(|last| now.saturating_duration_since(last));
+        self.last_upload = Some(now);
+    }
+
+    /// Check whether the output still changes at `now` without a new frame.
+    pub(crate) fn is_fading(&self, now: Instant) -> bool {
+        self.mode == FrameInterpolation::Crossfade && self.opacity(now) > 0.0
+    }
+
+    /// Get the opacity of the previous frame drawn over the newest one at `now`.
+    pub(crate) fn opacity(&self, now: Instant) -> f64 {
+        if self.uploads < 2 {
+            return 0.0;
+        }
+
+        match self.mode {
+            FrameInterpolation::Blend => 0.5,
+            FrameInterpolation::Crossfade => match (self.last_upload, self.interval) {
+                (Some(last), Some(interval)) if !interval.is_zero() => {
+                    let elapsed = now.saturating_duration_since(last);
+                    1.0 - (elapsed.as_secs_f64() / interval.as_secs_f64()).min(1.0)
+                }
+                _ => 0.0,
+            },
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Input-to-photon latency measurements.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use web_time::{Duration, Instant};
+
+/// The number of recent present intervals the refresh interval is estimated from.
+const INTERVALS: usize = 32;
+
+/// The side of the square probe patch, in physical pixels of the surface.
+pub(crate) const PATCH_SIZE: u32 = 32;
+
+/// The latency between an input event and the first frame drawn after it, measured by
+/// [`Pixels::set_latency_probe`](crate::Pixels::set_latency_probe).
+///
+/// Times are on the same clock as [`Pixels::last_present_time`](crate::Pixels::last_present_time),
+/// and start at the time passed to [`Pixels::mark_input`](crate::Pixels::mark_input).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct InputLatency {
+    /// The index of the frame which answered the input, like
+    /// [`FrameInfo::index`](crate::FrameInfo::index).
+    pub index: u64,
+
+    /// The time until presenting the frame returned on the CPU.
+    pub presented: Duration,
+
+    /// The time until the GPU finished rendering the frame.
+    pub completed: Duration,
+
+    /// The estimated time until the display showed the frame.
+    ///
+    /// The display shows the frame from the next refresh after it was both presented and
+    /// completed, which is on average half a refresh interval later. The refresh interval comes
+    /// from [`Pixels::set_display_refresh_rate`](crate::Pixels::set_display_refresh_rate) when it
+    /// is known. Otherwise it is estimated as the shortest interval between recent presents,
+    /// which only matches the display with vsync, i.e. the `Fifo` and `AutoVsync` present modes.
+    pub estimated: Duration,
+}
+
+/// Tracks inputs and the frames which answer them, while the latency probe is enabled.
+#[derive(Debug, Default)]
+pub(crate) struct LatencyProbe {
+    enabled: bool,
+    // The earliest input which no frame has answered yet
+    input: Option<Instant>,
+    previous_present: Option<Instant>,
+    intervals: VecDeque<Duration>,
+    // The refresh interval of the display, when it is known
+    refresh: Option<Duration>,
+    // Set by the submitted work done callback of the queue
+    finished: Arc<Mutex<Option<InputLatency>>>,
+    latest: Option<InputLatency>,
+}
+
+impl LatencyProbe {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enable or disable the probe, forgetting inputs and present intervals when disabled.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.input = None;
+            self.previous_present = None;
+            self.intervals.clear();
+        }
+    }
+
+    /// Set the refresh interval of the display, or estimate it from present intervals with
+    /// `None`. Present intervals measured on another display are forgotten.
+    pub(crate) fn set_refresh(&mut self, refresh: Option<Duration>) {
+        self.refresh = refresh;
+        self.previous_present = None;
+        self.intervals.clear();
+    }
+
+    /// Record an input event at `time`, which the next frame answers.
+    pub(crate) fn mark_input(&mut self, time: Instant) {
+        if self.enabled {
+            self.input = Some(self.input.map_or(time, |input| input.min(time)));
+        }
+    }
+
+    /// Take the input which the frame being rendered answers.
+    pub(crate) fn take_input(&mut self) -> Option<Instant> {
+        self.input.take()
+    }
+
+    /// Record that frame `index` was presented, and measure the latency of `input` once the GPU
+    /// has finished the frame.
+    pub(crate) fn presented(
+        &mut self,
+        queue: &wgpu::Queue,
+        index: u64,
+        input: Option<Instant>,
+        presented: Instant,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        if let Some(previous) = self.previous_present.replace(presented) {
+            if self.intervals.len() == INTERVALS {
+                self.intervals.pop_front();
+            }
+            self.intervals.push_back(presented.duration_since(previous));
+        }
+        let Some(input) = input else {
+            return;
+        };
+
+        let refresh = self
+            .refresh
+            .or_else(|| self.intervals.iter().min().copied())
+            .unwrap_or_default();
+        let finished = Arc::clone(&self.finished);
+        queue.on_submitted_work_done(move || {
+            let completed = Instant::now();
+            let shown = presented.max(completed) + refresh / 2;
+
+            *finished.lock().unwrap() = Some(InputLatency {
+                index,
+                presented: presented.saturating_duration_since(input),
+                completed: completed.saturating_duration_since(input),
+                estimated: shown.saturating_duration_since(input),
+            });
+        });
+    }
+
+    /// Take the measurement which finished since the last call.
+    pub(crate) fn take_finished(&mut self) -> Option<InputLatency> {
+        let finished = self.finished.lock().unwrap().take();
+        if finished.is_some() {
+            self.latest = finished;
+        }
+
+        finished
+    }
+
+    /// Get the most recent measurement taken with [`LatencyProbe::take_finished`].
+    pub(crate) fn latest(&self) -> Option<InputLatency> {
+        self.latest
+    }
+}
@@ -0,0 +1,211 @@
+//! Pixel buffers composited over the main one on the GPU.
+
+use crate::renderers::LayerCompositor;
+use bytemuck::{Pod, Zeroable};
+
+/// A pixel buffer drawn over the main [`Pixels`](crate::Pixels) frame, with its own scroll offset
+/// and opacity.
+///
+/// Layers have the size and texture format of the pixel buffer. They are blended over it in
+/// order on the GPU, with the alpha channel of each pixel multiplied by the
+/// [opacity](Layer::set_opacity) of the layer, so backgrounds, sprites, and UI can be drawn into
+/// separate buffers without compositing them on the CPU. Create them with
+/// [`PixelsBuilder::layers`](crate::PixelsBuilder::layers), and draw into them with
+/// [`Pixels::layer_mut`](crate::Pixels::layer_mut).
+///
+/// A layer is only uploaded on renders after [`Layer::frame_mut`] was called, so static layers
+/// cost one draw call per frame.
+#[derive(Debug)]
+pub struct Layer {
+    pixels: Vec<u8>,
+    texture: wgpu::Texture,
+    texture_extent: wgpu::Extent3d,
+    bytes_per_pixel: usize,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    offset: (i32, i32),
+    opacity: f32,
+    // Whether the frame or the uniforms changed since they were last written to the GPU
+    frame_changed: bool,
+    locals_changed: bool,
+}
+
+/// The uniform buffer layout shared with `layers.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct LayerLocals {
+    offset: [i32; 2],
+    opacity: f32,
+    _padding: f32,
+}
+
+impl Layer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        compositor: &LayerCompositor,
+        width: u32,
+        height: u32,
+        texture_format: wgpu::TextureFormat,
+    ) -> Self {
+        let texture_extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("pixels_layer_texture"),
+            size: texture_extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: texture_format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_layer_uniform_buffer"),
+            size: std::mem::size_of::<LayerLocals>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = compositor.create_bind_group(device, &texture, &uniform_buffer);
+        let bytes_per_pixel = texture_format.block_copy_size(None).unwrap_or(0) as usize;
+        let pixels = vec![0; width as usize * height as usize * bytes_per_pixel];
+
+        Self {
+            pixels,
+            texture,
+            texture_extent,
+            bytes_per_pixel,
+            uniform_buffer,
+            bind_group,
+            offset: (0, 0),
+            opacity: 1.0,
+            frame_changed: false,
+            locals_changed: true,
+        }
+    }
+
+    /// Get an immutable byte slice for the layer.
+    pub fn frame(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Get a mutable byte slice for the layer.
+    ///
+    /// Like [`Pixels::frame_mut`](crate::Pixels::frame_mut), the buffer is _not_ cleared for you.
+    /// Layers start out transparent, with every byte zero.
+    pub fn frame_mut(&mut self) -> &mut [u8] {
+        self.frame_changed = true;
+        &mut self.pixels
+    }
+
+    /// Get the scroll offset of the layer, see [`Layer::set_offset`].
+    pub fn offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    /// Scroll the layer so that its top-left corner is drawn at pixel `(x, y)` of the pixel
+    /// buffer.
+    ///
+    /// Layers wrap around at the edges of the pixel buffer, so a scrolled layer tiles; this
+    /// makes endless parallax backgrounds a matter of changing the offset every frame. The
+    /// default offset is `(0, 0)`.
+    pub fn set_offset(&mut self, x: i32, y: i32) {
+        if self.offset != (x, y) {
+            self.offset = (x, y);
+            self.locals_changed = true;
+        }
+    }
+
+    /// Get the opacity of the layer, see [`Layer::set_opacity`].
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
+    /// Set the opacity of the layer, from 0 (hidden) to 1 (opaque), clamped to that range.
+    ///
+    /// The alpha channel of every pixel in the layer is multiplied by the opacity. Hidden layers
+    /// are not drawn at all. The default opacity is 1.
+    pub fn set_opacity(&mut self, opacity: f32) {
+        let opacity = if opacity.is_nan() {
+            0.0
+        } else {
+            opacity.clamp(0.0, 1.0)
+        };
+        if self.opacity != opacity {
+            self.opacity = opacity;
+            self.locals_changed = true;
+        }
+    }
+
+    /// Whether the layer is drawn, i.e. its opacity is above 0.
+    pub(crate) fn is_visible(&self) -> bool {
+        self.opacity > 0.0
+    }
+
+    pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Write the frame and uniforms to the GPU, when they changed.
+    pub(crate) fn upload(&mut self, queue: &wgpu::Queue) {
+        if self.frame_changed {
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &self.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                &self.pixels,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.texture_extent.width * self.bytes_per_pixel as u32),
+                    rows_per_image: Some(self.texture_extent.height),
+                },
+                self.texture_extent,
+            );
+            self.frame_changed = false;
+        }
+
+        if self.locals_changed {
+            // Keep the offset small, the shader wraps it anyway
+            let (width, height) = (self.texture_extent.width, self.texture_extent.height);
+            let locals = LayerLocals {
+                offset: [
+                    self.offset.0.rem_euclid(width as i32),
+                    self.offset.1.rem_euclid(height as i32),
+                ],
+                opacity: self.opacity,
+                _padding: 0.0,
+            };
+            queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+            self.locals_changed = false;
+        }
+    }
+}
+
+/// Check that layers can be drawn over a pixel buffer texture of `texture_format`.
+pub(crate) fn check_layer_format(
+    device: &wgpu::Device,
+    texture_format: wgpu::TextureFormat,
+) -> Result<(), crate::TextureError> {
+    let features = texture_format.guaranteed_format_features(device.features());
+    if !features
+        .allowed_usages
+        .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+    {
+        return Err(crate::TextureError::TextureUsage(
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        ));
+    }
+    if !features
+        .flags
+        .contains(wgpu::TextureFormatFeatureFlags::BLENDABLE)
+    {
+        return Err(crate::TextureError::TextureFormat(texture_format));
+    }
+
+    Ok(())
+}
@@ -28,26 +28,241 @@
 //!
 //! Note that `WGPU_ADAPTER_NAME` and `WGPU_POWER_PREF` are mutually exclusive and that
 //! `WGPU_ADAPTER_NAME` takes precedence.
+//!
+//! `WGPU_BACKEND` takes precedence over [`PixelsBuilder::backends`]. Applications can change how
+//! the environment is consulted with [`PixelsBuilder::backend_override`].
+//!
+//! # Async APIs
+//!
+//! [`PixelsBuilder::build_async`], [`Pixels::new_async`], and [`Pixels::read_region_async`] do
+//! not depend on any particular executor. They run the same under `pollster`, `tokio`,
+//! `async-std`, and `wasm-bindgen-futures`:
+//!
+//! * Creating a pixel buffer only awaits adapter and device requests, which `wgpu` completes
+//!   without help from the executor.
+//! * Readbacks wait on [`wgpu::BufferSlice::map_async`], whose callback only runs while the device
+//!   is polled. The futures returned by `pixels` poll the device themselves and ask to be polled
+//!   again right away, so they never wait on a wakeup that would not come. Awaiting one spins
+//!   until the GPU has finished the copy, so prefer to await it off the render thread.
+//! * On Web targets, the browser polls the device between tasks; there, the futures only
+//!   complete after control returns to the event loop, and blocking would deadlock.
+//!
+//! Native applications without an async runtime can use the blocking variants instead, which wait
+//! on the device directly: [`PixelsBuilder::build`], [`Pixels::new`], and
+//! [`Pixels::blocking_read_region`]. They are not available on Web targets.
+//!
+//! # Crate features
+//!
+//! * `gpu` (default): The GPU-backed [`Pixels`] buffer and its renderers, built on `wgpu`.
+//!   Without it, the drawing utilities ([`Canvas`], [`blit_indexed`], [`TextGrid`],
+//!   [`quantize_to_palette`]), color conversions, and frame management ([`SharedFrame`],
+//!   [`ingest`], [`shm`]) compile on their own, so server-side tools and tests can share drawing
+//!   code with the application, and triage tools can [`provenance::decode`] screenshots.
+//! * `winit`: Helpers for windows created with [`winit`](https://crates.io/crates/winit), such as
+//!   the [`overlay`] window configuration, and [`run`], which runs a whole application with a
+//!   fixed-timestep update from a few callbacks.
+//! * `effects`: Built-in post-processing effects for [`Pixels::add_effect`]: `CrtEffect`,
+//!   `ScanlineEffect`, and `GammaEffect`.
+//! * `canvas`: [`Pixels::canvas_mut`], which wraps the pixel buffer in a
+//!   [`tiny_skia::PixmapMut`](https://docs.rs/tiny-skia/0.11/tiny_skia/struct.PixmapMut.html) for
+//!   drawing antialiased paths, shapes, and images directly into it. `tiny_skia` is re-exported.
+//! * `png`, `y4m`: The [`recorder::PngSequence`] and [`recorder::Y4mWriter`] sinks for
+//!   [`Pixels::start_recording`], which write recorded frames as numbered PNG files and as
+//!   uncompressed Y4M video. With `winit` as well, a [`screenshot::ScreenshotHotkey`] saves
+//!   timestamped PNG screenshots when a key is pressed.
+//! * `serde`: [`Serialize`](https://docs.rs/serde/latest/serde/trait.Serialize.html)
+//!   implementations for [`CapabilityReport`] and [`PipelineDescription`], and `Deserialize` as
+//!   well for saving and loading the video settings in [`PixelsConfig`] and effect chains as
+//!   [`EffectPreset`]s.
 
 #![deny(clippy::all)]
 #![forbid(unsafe_code)]
 
+#[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+pub use crate::app::run;
+#[cfg(feature = "gpu")]
 pub use crate::builder::{check_texture_size, PixelsBuilder};
-pub use crate::renderers::ScalingRenderer;
+pub use crate::canvas::{Canvas, CanvasView};
+pub use crate::color::{linear_to_srgb, srgb_to_linear, ColorSpace};
+#[cfg(feature = "gpu")]
+pub use crate::config::PixelsConfig;
+#[cfg(feature = "gpu")]
+pub use crate::describe::{CapabilityReport, PassDescription, PipelineDescription};
+#[cfg(feature = "effects")]
+pub use crate::effects::{CrtEffect, GammaEffect, ScanlineEffect};
+#[cfg(feature = "gpu")]
+pub use crate::effects::{
+    EffectConstructor, EffectDegradation, EffectError, EffectId, EffectPreset, EffectRegistry,
+    RenderEffect,
+};
+#[cfg(feature = "gpu")]
+pub use crate::flash::FlashLimiter;
+pub use crate::flash::LuminanceHistogram;
+pub use crate::frame::Frame;
+pub use crate::gamma::GammaRamp;
+#[cfg(feature = "gpu")]
+pub use crate::gpu::SharedGpu;
+#[cfg(feature = "gpu")]
+pub use crate::icon::{IconError, IconImage};
+pub use crate::indexed::{blit_indexed, blit_indexed_rgba, IndexedSprite, PaletteRemap};
+#[cfg(feature = "gpu")]
+pub use crate::interpolation::FrameInterpolation;
+#[cfg(feature = "gpu")]
+pub use crate::latency::InputLatency;
+#[cfg(feature = "gpu")]
+pub use crate::layer::Layer;
+pub use crate::mesh::{DistortionMesh, MeshVertex};
+#[cfg(feature = "gpu")]
+pub use crate::plugin::{DefaultPasses, PixelsRenderer};
+#[cfg(feature = "gpu")]
+pub use crate::profiling::{FrameStats, ScopeTiming};
+pub use crate::quantize::{quantize_to_palette, Dither};
+#[cfg(feature = "gpu")]
+pub use crate::readback::{CaptureSource, CapturedFrame, PickedPixel, ReadbackError};
+#[cfg(feature = "gpu")]
+pub use crate::renderers::{
+    AmbientBezel, Background, BackgroundFit, FilterMode, GridRenderer, PackedFormat, PixelGrid,
+    Rotation, ScalingMode, ScalingRenderer, Selection, SelectionRenderer, SelectionStyle,
+    TextureBorder, Transition, Watermark, WatermarkCorner,
+};
+pub use crate::shared::{FrameSnapshot, SharedFrame};
+#[cfg(feature = "gpu")]
+pub use crate::split::{SplitLayout, SplitRenderer};
+#[cfg(feature = "gpu")]
+pub use crate::sprite::Sprite;
+#[cfg(feature = "gpu")]
+pub use crate::target::{BlitDestination, DrawTarget};
+#[cfg(feature = "gpu")]
+pub use crate::text::FontAtlas;
+pub use crate::text::{BitmapFont, TextCell, TextGrid};
+#[cfg(feature = "gpu")]
+pub use crate::tonemap::Tonemapping;
+#[cfg(feature = "gpu")]
+pub use crate::transform::Transform;
+#[cfg(feature = "gpu")]
+pub use crate::tween::Easing;
+#[cfg(feature = "gpu")]
+pub use crate::upload::UploadStrategy;
+#[cfg(feature = "gpu")]
 pub use raw_window_handle;
+#[cfg(feature = "gpu")]
+use std::collections::VecDeque;
+#[cfg(feature = "gpu")]
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(feature = "gpu")]
+use std::sync::{mpsc, Arc};
+#[cfg(feature = "gpu")]
 use thiserror::Error;
+#[cfg(feature = "canvas")]
+pub use tiny_skia;
+#[cfg(feature = "gpu")]
+use web_time::{Duration, Instant};
+#[cfg(feature = "gpu")]
 pub use wgpu;
 
+#[cfg(all(feature = "winit", not(target_arch = "wasm32")))]
+pub mod app;
+#[cfg(all(feature = "gpu", not(target_arch = "wasm32")))]
+pub mod bench;
+#[cfg(feature = "gpu")]
 mod builder;
+mod canvas;
+mod color;
+#[cfg(feature = "gpu")]
+mod config;
+#[cfg(feature = "gpu")]
+mod describe;
+#[cfg(feature = "gpu")]
+mod effects;
+#[cfg(feature = "gpu")]
+mod feel;
+mod flash;
+mod frame;
+mod gamma;
+#[cfg(feature = "gpu")]
+mod gpu;
+#[cfg(feature = "gpu")]
+mod icon;
+mod indexed;
+pub mod ingest;
+#[cfg(feature = "gpu")]
+mod interpolation;
+#[cfg(feature = "gpu")]
+mod latency;
+#[cfg(feature = "gpu")]
+mod layer;
+mod mesh;
+#[cfg(feature = "gpu")]
+pub mod overlay;
+#[cfg(feature = "gpu")]
+mod plugin;
+#[cfg(feature = "gpu")]
+mod profiling;
+pub mod provenance;
+mod quantize;
+#[cfg(feature = "gpu")]
+mod readback;
+#[cfg(feature = "gpu")]
+pub mod recorder;
+#[cfg(feature = "gpu")]
 mod renderers;
+#[cfg(all(feature = "png", feature = "winit", not(target_arch = "wasm32")))]
+pub mod screenshot;
+mod shared;
+pub mod shm;
+#[cfg(feature = "gpu")]
+mod split;
+#[cfg(feature = "gpu")]
+mod sprite;
+#[cfg(feature = "gpu")]
+mod target;
+mod text;
+#[cfg(feature = "gpu")]
+mod tonemap;
+#[cfg(feature = "gpu")]
+mod transform;
+#[cfg(feature = "gpu")]
+mod tween;
+#[cfg(feature = "gpu")]
+mod upload;
+pub mod window;
+#[cfg(feature = "gpu")]
+pub mod workarounds;
 
+#[cfg(feature = "gpu")]
 /// A logical texture for a window surface.
 #[derive(Debug)]
 pub struct SurfaceTexture<W: wgpu::WindowHandle> {
-    window: W,
+    // `None` for offscreen pixel buffers
+    window: Option<W>,
     size: SurfaceSize,
 }
 
+#[cfg(feature = "gpu")]
+/// The window type of [offscreen surface textures](SurfaceTexture::offscreen), which have no
+/// window. It has no values.
+#[derive(Clone, Copy, Debug)]
+pub enum NoWindow {}
+
+#[cfg(feature = "gpu")]
+impl raw_window_handle::HasWindowHandle for NoWindow {
+    fn window_handle(
+        &self,
+    ) -> Result<raw_window_handle::WindowHandle<'_>, raw_window_handle::HandleError> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl raw_window_handle::HasDisplayHandle for NoWindow {
+    fn display_handle(
+        &self,
+    ) -> Result<raw_window_handle::DisplayHandle<'_>, raw_window_handle::HandleError> {
+        match *self {}
+    }
+}
+
+#[cfg(feature = "gpu")]
 /// A logical texture size for a window surface.
 #[derive(Debug)]
 struct SurfaceSize {
@@ -55,6 +270,18 @@ struct SurfaceSize {
     height: u32,
 }
 
+#[cfg(feature = "gpu")]
+impl SurfaceSize {
+    /// Multiply both dimensions by a supersampling factor.
+    fn scaled(&self, factor: u32) -> Self {
+        Self {
+            width: self.width.saturating_mul(factor),
+            height: self.height.saturating_mul(factor),
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
 /// Provides the internal state for custom shaders.
 ///
 /// A reference to this struct is given to the `render_function` closure when using
@@ -62,15 +289,21 @@ struct SurfaceSize {
 #[derive(Debug)]
 pub struct PixelsContext<'win> {
     /// The `Device` allows creating GPU resources.
-    pub device: wgpu::Device,
+    ///
+    /// It is shared with other pixel buffers on the same [`SharedGpu`].
+    pub device: Arc<wgpu::Device>,
 
     /// The `Queue` provides access to the GPU command queue.
-    pub queue: wgpu::Queue,
+    pub queue: Arc<wgpu::Queue>,
 
-    surface: wgpu::Surface<'win>,
+    // `None` for offscreen pixel buffers, which render to `Pixels::offscreen_texture` instead
+    surface: Option<wgpu::Surface<'win>>,
 
     /// This is the texture that your raw data is copied to by [`Pixels::render`] or
     /// [`Pixels::render_with`].
+    ///
+    /// Uploads rotate through a ring of textures (see [`PixelsBuilder::texture_ring_size`]), so
+    /// this is the texture holding the frame being rendered.
     pub texture: wgpu::Texture,
 
     /// Provides access to the texture size.
@@ -85,8 +318,313 @@ pub struct PixelsContext<'win> {
 
     /// A default renderer to scale the input texture to the screen size.
     pub scaling_renderer: ScalingRenderer,
+
+    /// A renderer for the optional [`PixelGrid`] overlay.
+    pub grid_renderer: GridRenderer,
+
+    /// A renderer for [`Selection`] rectangles.
+    pub selection_renderer: SelectionRenderer,
+
+    /// A uniform buffer of [`FRAME_METADATA_SIZE`] bytes with the metadata set by
+    /// [`Pixels::set_frame_metadata`], for custom shaders and [effects](Pixels::add_effect).
+    pub frame_metadata: wgpu::Buffer,
+
+    profiler: profiling::Profiler,
+}
+
+#[cfg(feature = "gpu")]
+/// The size in bytes of the frame metadata buffer, see [`Pixels::set_frame_metadata`].
+pub const FRAME_METADATA_SIZE: usize = 256;
+
+#[cfg(feature = "gpu")]
+impl PixelsContext<'_> {
+    /// Get the layout entry for binding [`PixelsContext::frame_metadata`] as a uniform buffer at
+    /// `binding`, visible to vertex and fragment shaders.
+    pub fn frame_metadata_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
+        wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: wgpu::BufferSize::new(FRAME_METADATA_SIZE as u64),
+            },
+            count: None,
+        }
+    }
+
+    /// Record the commands of `f` in a scope named `name`, so they can be told apart from the
+    /// passes of Pixels in GPU captures and [frame statistics](Pixels::frame_stats).
+    ///
+    /// The scope is a debug group of `encoder`, which graphics debuggers like RenderDoc show
+    /// around its passes. With [frame statistics](PixelsBuilder::enable_frame_stats) enabled on a
+    /// device which supports timestamp queries, the GPU time of the scope is reported as a
+    /// [`ScopeTiming`] as well. Scopes nest. The render function of [`Pixels::render_with`] and
+    /// each [effect](RenderEffect::name) are recorded in a scope of their own.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # fn draw_hud(_: &mut pixels::wgpu::CommandEncoder, _: &pixels::wgpu::TextureView) {}
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// pixels.render_with(|encoder, render_target, context| {
+    ///     context.scaling_renderer.render(encoder, render_target);
+    ///     context.scope(encoder, "hud", |encoder| draw_hud(encoder, render_target));
+    ///
+    ///     Ok(())
+    /// })?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn scope<R>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        name: &str,
+        f: impl FnOnce(&mut wgpu::CommandEncoder) -> R,
+    ) -> R {
+        encoder.push_debug_group(name);
+        let scope = self.profiler.begin(encoder, name);
+        let result = f(encoder);
+        self.profiler.end(encoder, scope);
+        encoder.pop_debug_group();
+
+        result
+    }
+}
+
+#[cfg(feature = "gpu")]
+/// Information about the frame being rendered, passed to [`Pixels::render_with_info`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct FrameInfo {
+    /// The number of frames rendered before this one.
+    pub index: u64,
+
+    /// The time since the first frame was rendered.
+    pub elapsed: Duration,
+
+    /// The time since the previous frame was rendered, or zero for the first frame.
+    pub delta: Duration,
+
+    /// The clipping rectangle of the scaled pixel buffer, see [`ScalingRenderer::clip_rect`].
+    pub clip_rect: (u32, u32, u32, u32),
+
+    /// The visible part of the pixel buffer in pixels, see [`Pixels::view`].
+    pub view: (u32, u32, u32, u32),
+
+    /// The rotation of the scaled pixel buffer, see [`Pixels::rotation`].
+    pub rotation: Rotation,
+}
+
+#[cfg(feature = "gpu")]
+/// The timing of a rendered frame, sent by [`Pixels::present_events`] once the GPU finished it.
+///
+/// Times are on the same clock as [`Pixels::last_present_time`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PresentEvent {
+    /// The index of the frame, the same as [`FrameInfo::index`] while it was rendered.
+    pub index: u64,
+
+    /// When the frame was submitted to the GPU.
+    pub submitted: Instant,
+
+    /// When presenting the frame returned on the CPU, see [`Pixels::last_present_time`].
+    pub presented: Instant,
+
+    /// When the GPU finished rendering the frame. The display shows it from the next refresh
+    /// after both this and `presented`.
+    pub completed: Instant,
+}
+
+#[cfg(feature = "gpu")]
+/// The rectangle of the surface covered by the scaled pixel buffer, and the margins around it.
+///
+/// Returned by [`Pixels::safe_area`] in physical pixels, or by [`Pixels::logical_safe_area`] in
+/// logical window coordinates. Use it to place native UI or overlays relative to the visible
+/// pixel area, e.g. inside the letterbox borders.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct SafeArea {
+    /// The left edge of the scaled pixel buffer.
+    pub x: f64,
+
+    /// The top edge of the scaled pixel buffer.
+    pub y: f64,
+
+    /// The visible width of the scaled pixel buffer.
+    pub width: f64,
+
+    /// The visible height of the scaled pixel buffer.
+    pub height: f64,
+
+    /// The width of the border to the left of the pixel buffer.
+    pub left: f64,
+
+    /// The height of the border above the pixel buffer.
+    pub top: f64,
+
+    /// The width of the border to the right of the pixel buffer.
+    pub right: f64,
+
+    /// The height of the border below the pixel buffer.
+    pub bottom: f64,
+}
+
+#[cfg(feature = "gpu")]
+impl SafeArea {
+    fn new(clip_rect: (u32, u32, u32, u32), surface_size: (u32, u32), factor: f64) -> Self {
+        let (x, y, width, height) = clip_rect;
+        let (x, y) = (f64::from(x) / factor, f64::from(y) / factor);
+        let (width, height) = (f64::from(width) / factor, f64::from(height) / factor);
+        let (surface_width, surface_height) =
+            (f64::from(surface_size.0), f64::from(surface_size.1));
+
+        Self {
+            x,
+            y,
+            width,
+            height,
+            left: x,
+            top: y,
+            right: surface_width - x - width,
+            bottom: surface_height - y - height,
+        }
+    }
+
+    /// Divide every dimension by a window scale factor, converting physical pixels to logical
+    /// coordinates.
+    pub fn to_logical(&self, scale_factor: f64) -> Self {
+        Self {
+            x: self.x / scale_factor,
+            y: self.y / scale_factor,
+            width: self.width / scale_factor,
+            height: self.height / scale_factor,
+            left: self.left / scale_factor,
+            top: self.top / scale_factor,
+            right: self.right / scale_factor,
+            bottom: self.bottom / scale_factor,
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+/// The order in which the rows of a sliced upload reach the texture.
+///
+/// See [`PixelsBuilder::upload_slices`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum UploadOrder {
+    /// Each frame uploads one contiguous band of rows, from top to bottom. A single tear line
+    /// sweeps down the image between the new rows and the old ones.
+    #[default]
+    Sequential,
+    /// Each frame uploads every `slices`-th row, like an interlaced display. There is no tear
+    /// line; motion shows as combing instead. This issues one copy per row, which costs more CPU
+    /// time than [`UploadOrder::Sequential`].
+    Interleaved,
+}
+
+#[cfg(feature = "gpu")]
+/// When [`Pixels::schedule_redraw`] asks the window for another frame.
+///
+/// See [`Pixels::set_redraw_policy`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum RedrawPolicy {
+    /// Redraw on every pass through the event loop, which is paced by vsync. Best for games and
+    /// other animated content.
+    #[default]
+    Continuous,
+    /// Redraw only after the frame or the output changes: when the pixel buffer is borrowed
+    /// mutably or written to, the surface or buffer is resized, a readback is requested, an
+    /// effect is playing, or [`Pixels::request_redraw`] is called. Best for tools and other
+    /// mostly static content.
+    OnDirty,
+    /// Never redraw on its own; the application calls
+    /// [`winit::window::Window::request_redraw`] when it wants a frame.
+    Manual,
+}
+
+#[cfg(feature = "gpu")]
+/// What [`Pixels::resize_surface`] does with a zero width or height, which windowing systems
+/// report for minimized windows, e.g. on Windows.
+///
+/// See [`PixelsBuilder::zero_size_policy`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ZeroSizePolicy {
+    /// Return [`TextureError::ZeroSizedSurface`] and keep the surface as it was.
+    #[default]
+    Error,
+    /// Resize the surface to at least one physical pixel in each dimension.
+    Clamp,
+    /// Keep the surface as it was and skip rendering until it is resized to a nonzero size. The
+    /// render methods return `Ok(())` without drawing in the meantime; see
+    /// [`Pixels::is_paused`].
+    Pause,
+}
+
+#[cfg(feature = "gpu")]
+/// Where [`Pixels::resize_buffer_preserve`] places the previous contents in the resized pixel
+/// buffer.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ResizeAnchor {
+    /// Keep the top-left corner in place; the buffer grows or shrinks at the right and bottom.
+    #[default]
+    TopLeft,
+    /// Keep the center in place; the buffer grows or shrinks evenly on all sides, with the odd
+    /// pixel at the right and bottom.
+    Center,
+}
+
+#[cfg(feature = "gpu")]
+impl ResizeAnchor {
+    /// Get the offset of the preserved span in the old and new dimension, and its length.
+    fn span(self, old: u32, new: u32) -> (u32, u32, u32) {
+        match self {
+            Self::TopLeft => (0, 0, old.min(new)),
+            Self::Center if new >= old => (0, (new - old) / 2, old),
+            Self::Center => ((old - new) / 2, 0, new),
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+/// Where a window position lands, from [`Pixels::window_pos_to_pixel_clamped`].
+///
+/// Every variant holds the closest pixel of the buffer, so drags which leave the scaled pixel
+/// buffer keep following the cursor along its edge.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum PixelHit {
+    /// On the scaled pixel buffer, at this pixel.
+    Buffer(usize, usize),
+    /// In the border around the scaled pixel buffer, closest to this pixel.
+    Letterbox(usize, usize),
+    /// Outside of the surface, which windowing systems report while a drag continues past the
+    /// window edge. Closest to this pixel.
+    OutsideSurface(usize, usize),
+}
+
+#[cfg(feature = "gpu")]
+impl PixelHit {
+    /// Get the pixel position, clamped to the pixel buffer.
+    pub fn position(self) -> (usize, usize) {
+        match self {
+            Self::Buffer(x, y) | Self::Letterbox(x, y) | Self::OutsideSurface(x, y) => (x, y),
+        }
+    }
+
+    /// Check whether the position is on the scaled pixel buffer.
+    pub fn is_buffer(self) -> bool {
+        matches!(self, Self::Buffer(..))
+    }
 }
 
+#[cfg(feature = "gpu")]
 /// Represents a 2D pixel buffer with an explicit image resolution.
 ///
 /// See [`PixelsBuilder`] for building a customized pixel buffer.
@@ -97,18 +635,163 @@ pub struct Pixels<'win> {
     present_mode: wgpu::PresentMode,
     render_texture_format: wgpu::TextureFormat,
     surface_texture_format: wgpu::TextureFormat,
+    // The format the surface is configured with, viewed as `surface_texture_format` when they
+    // differ in sRGB encoding only
+    surface_storage_format: wgpu::TextureFormat,
     blend_state: wgpu::BlendState,
     alpha_mode: wgpu::CompositeAlphaMode,
-    adapter: wgpu::Adapter,
+    // Shared with other pixel buffers created from `Pixels::shared_gpu`
+    instance: Arc<wgpu::Instance>,
+    adapter: Arc<wgpu::Adapter>,
+    // Set when the device is lost, shared with the `SharedGpu` handles of the device
+    device_lost: Arc<AtomicBool>,
+
+    // Pixel buffer, copied on write while snapshots are alive
+    pixels: Arc<Vec<u8>>,
+
+    // Pixel buffers blended over the pixel buffer texture after each upload, in order
+    layers: Vec<Layer>,
+    layer_compositor: Option<renderers::LayerCompositor>,
+
+    // Optional photosensitivity safeguard
+    flash_limiter: Option<FlashLimiter>,
+
+    // Source textures waiting for upcoming frames, rotated with `context.texture` on each upload
+    spare_textures: VecDeque<wgpu::Texture>,
+    texture_ring_size: usize,
+
+    // Uploads split across frames: the number of slices and their order, the next slice, and
+    // whether the whole frame must be uploaded first (e.g. into a new texture)
+    upload_slices: u32,
+    upload_order: UploadOrder,
+    upload_slice: u32,
+    // Staging buffers for `UploadStrategy::StagingRing`
+    staging_ring: Option<upload::StagingRing>,
+    full_upload: bool,
+
+    // Blends the last two uploaded frames, and whether the pixel buffer changed since the last
+    // upload; renders without a new frame skip the upload while interpolating
+    frame_interpolator: Option<interpolation::FrameInterpolator>,
+    frame_changed: bool,
+
+    // Damaged regions (x, y, width, height) waiting for upload, when only those are uploaded
+    dirty_rects: Option<Vec<(u32, u32, u32, u32)>>,
+
+    // The pixel buffer texture format, saved while an imported texture is displayed instead
+    imported_texture: Option<wgpu::TextureFormat>,
+
+    // Driver workarounds applied for the adapter
+    workarounds: Vec<workarounds::Workaround>,
+
+    // Options for the scaling pipeline, including whether the minimal downlevel pipeline is used
+    scaling_options: renderers::ScalingOptions,
+
+    // Replaces the full-screen triangle of the scaling pass, kept to recreate the renderer
+    distortion_mesh: Option<DistortionMesh>,
+
+    // Renders at a multiple of the surface size and downsamples, when enabled
+    supersampler: Option<renderers::Supersampler>,
+
+    // Averages the last frames after downsampling, when enabled
+    frame_averager: Option<renderers::FrameAverager>,
+
+    // Copies the composited frame to the surface with a provenance mark, when one is set
+    provenance_renderer: Option<renderers::ProvenanceRenderer>,
 
-    // Pixel buffer
-    pixels: Vec<u8>,
+    // Number of frames to crossfade after resizing the pixel buffer, and the fade in progress
+    resize_transition: u32,
+    crossfade: Option<renderers::Crossfade>,
 
-    // The inverse of the scaling matrix used by the renderer
-    // Used to convert physical coordinates back to pixel coordinates (for the mouse)
+    // Whether window sizes are snapped to integer multiples of the pixel buffer
+    window_aspect_locked: bool,
+
+    // When to ask for redraws, and whether anything changed since the last render
+    redraw_policy: RedrawPolicy,
+    zero_size_policy: ZeroSizePolicy,
+    // Set while a zero-sized surface pauses rendering
+    surface_paused: bool,
+    redraw_pending: bool,
+
+    // Uncovers the current frame from under a snapshot of an earlier one
+    transition: Option<renderers::TransitionRenderer>,
+
+    // Screen shake and flashes, with the overlay renderer created on the first flash
+    shake: Option<feel::ScreenShake>,
+    screen_flash: Option<feel::ScreenFlash>,
+    color_overlay_renderer: Option<renderers::ColorOverlayRenderer>,
+
+    // Stamps an image at a corner of the surface
+    watermark_renderer: Option<renderers::WatermarkRenderer>,
+
+    // Fills the letterbox bars with a blurred copy of the frame
+    bezel_renderer: Option<renderers::BezelRenderer>,
+
+    // Fills the letterbox bars with an image
+    background_renderer: Option<renderers::BackgroundRenderer>,
+
+    // Copies of external textures into the next frame, before the blits, at their destination,
+    // and the copy textures of the previous frame for reuse
+    pending_copies: Vec<(wgpu::Texture, (u32, u32))>,
+    spare_copy_textures: Vec<wgpu::Texture>,
+    // Draws draw targets on the GPU, created on first use, and blits into the next frame
+    target_blitter: Option<renderers::TargetBlitter>,
+    pending_blits: Vec<renderers::PreparedBlit>,
+    // Draws text grids on the GPU, created on first use, into the next frame after the blits
+    text_grid_renderer: Option<renderers::TextGridRenderer>,
+    pending_text_grids: Vec<renderers::PreparedTextGrid>,
+    // Draws sprites on the GPU, created on first use, into the next frame after the text grids
+    sprite_renderer: Option<renderers::SpriteRenderer>,
+    pending_sprites: Vec<renderers::PreparedSprites>,
+    // The areas covered by the pending copies, blits, text grids, and sprites, and by those drawn
+    // by the last render, which the next upload restores from the frame
+    pending_drawn_rects: Vec<(u32, u32, u32, u32)>,
+    drawn_rects: Vec<(u32, u32, u32, u32)>,
+
+    // Reads back single pixels of the surface for eyedroppers; `COPY_SRC` is added on first use
+    output_picker: readback::OutputPicker,
+    frame_capture: readback::FrameCapture,
+    // Copies every rendered frame to the sink of `Pixels::start_recording`
+    recorder: Option<recorder::Recorder>,
+    surface_usage: wgpu::TextureUsages,
+    // The render output of offscreen pixel buffers, in place of surface textures
+    offscreen_texture: Option<wgpu::Texture>,
+    // Resizes reconfigure the surface on the next render, once for any number of resizes
+    surface_resize_pending: bool,
+    // The visible rectangle of the pixel buffer, clamped to it by `Pixels::view`
+    view_offset: (u32, u32),
+    view_size: Option<(u32, u32)>,
+
+    // The render target size last passed to `PixelsRenderer::resize`
+    renderer_size: Option<(u32, u32)>,
+    // Drawn by `Pixels::render` in place of `DefaultPasses`, unless in safe mode
+    registered_renderer: Option<plugin::RegisteredRenderer>,
+    safe_mode: bool,
+
+    // Post-processing passes between the render function and the surface
+    effects: effects::EffectChain,
+
+    // Frame metadata waiting to be written to `context.frame_metadata`
+    pending_metadata: Option<Vec<u8>>,
+
+    // Bookkeeping for `FrameInfo`
+    frame_index: u64,
+    first_frame_time: Option<Instant>,
+    previous_frame_time: Option<Instant>,
+
+    // When the most recent frame was handed to the compositor, and where its timing is sent
+    last_present_time: Option<Instant>,
+    present_events: Option<mpsc::Sender<PresentEvent>>,
+    // The refresh rate of the display showing the surface in millihertz, when it is known
+    display_refresh_rate: Option<u32>,
+    latency_probe: latency::LatencyProbe,
+    // Draws the probe patch; separate from flashes, which write their own color each frame
+    probe_renderer: Option<renderers::ColorOverlayRenderer>,
+
+    // Transforms physical window coordinates to pixel coordinates (for the mouse)
     scaling_matrix_inverse: ultraviolet::Mat4,
 }
 
+#[cfg(feature = "gpu")]
 /// All the ways in which creating a pixel buffer can fail.
 #[derive(Error, Debug)]
 #[non_exhaustive]
@@ -119,22 +802,43 @@ pub enum Error {
     /// Equivalent to [`wgpu::RequestDeviceError`]
     #[error("No wgpu::Device found.")]
     DeviceNotFound(#[from] wgpu::RequestDeviceError),
+    /// The [`wgpu::Adapter`] does not support the requested [`wgpu::Limits`]
+    #[error("The GPU does not support the requested limits: {}", .0.join(", "))]
+    UnsupportedLimits(Vec<&'static str>),
+    /// The surface does not support the requested [`wgpu::CompositeAlphaMode`]
+    #[error(
+        "The surface does not support alpha mode {requested:?}, supported modes: {supported:?}"
+    )]
+    UnsupportedAlphaMode {
+        /// The alpha mode requested with [`PixelsBuilder::alpha_mode`].
+        requested: wgpu::CompositeAlphaMode,
+        /// The alpha modes supported by the surface.
+        supported: Vec<wgpu::CompositeAlphaMode>,
+    },
     /// Equivalent to [`wgpu::SurfaceError`]
     #[error("The GPU failed to acquire a surface frame.")]
     Surface(#[from] wgpu::SurfaceError),
+    /// The GPU device was lost, e.g. after a driver reset; see [`Pixels::recreate_device`]
+    #[error("The GPU device was lost.")]
+    DeviceLost,
     /// Equivalent to [`wgpu::CreateSurfaceError`]
     #[error("Unable to create a surface.")]
     CreateSurface(#[from] wgpu::CreateSurfaceError),
     /// Equivalent to [`TextureError`]
     #[error("Texture creation failed: {0}")]
     InvalidTexture(#[from] TextureError),
+    /// Equivalent to [`ReadbackError`]
+    #[error("Reading back from the GPU failed: {0}")]
+    Readback(#[from] ReadbackError),
     /// User-defined error from custom render function
     #[error("User-defined error.")]
     UserDefined(#[from] DynError),
 }
 
+#[cfg(feature = "gpu")]
 type DynError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
+#[cfg(feature = "gpu")]
 /// All the ways in which creating a texture can fail.
 #[derive(Error, Debug)]
 #[non_exhaustive]
@@ -145,8 +849,18 @@ pub enum TextureError {
     /// Unable to create a backing texture; Height is either 0 or greater than GPU limits
     #[error("Texture height is invalid: {0}")]
     TextureHeight(u32),
+    /// An imported texture is missing required usages
+    #[error("Texture usage is missing: {0:?}")]
+    TextureUsage(wgpu::TextureUsages),
+    /// An imported texture cannot be sampled with filtering as a 2D texture
+    #[error("Texture format is unsupported: {0:?}")]
+    TextureFormat(wgpu::TextureFormat),
+    /// The surface was resized to a zero width or height, see [`ZeroSizePolicy`]
+    #[error("Surface size is zero: {0}x{1}")]
+    ZeroSizedSurface(u32, u32),
 }
 
+#[cfg(feature = "gpu")]
 impl<W: wgpu::WindowHandle> SurfaceTexture<W> {
     /// Create a logical texture for a window surface.
     ///
@@ -161,7 +875,8 @@ impl<W: wgpu::WindowHandle> SurfaceTexture<W> {
     /// use winit::window::Window;
     ///
     /// let event_loop = EventLoop::new().unwrap();
-    /// let window = Window::new(&event_loop).unwrap();
+    /// # #[allow(deprecated)]
+    /// let window = event_loop.create_window(Window::default_attributes()).unwrap();
     /// let size = window.inner_size();
     ///
     /// let surface_texture = SurfaceTexture::new(size.width, size.height, &window);
@@ -177,10 +892,79 @@ impl<W: wgpu::WindowHandle> SurfaceTexture<W> {
 
         let size = SurfaceSize { width, height };
 
-        Self { window, size }
+        Self {
+            window: Some(window),
+            size,
+        }
+    }
+}
+
+#[cfg(feature = "gpu")]
+impl SurfaceTexture<NoWindow> {
+    /// Create a logical texture without a window, for offscreen rendering.
+    ///
+    /// Pixel buffers built with it render to an internal texture of `width` by `height` pixels
+    /// instead of presenting to a window. See [`Pixels::new_offscreen`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0.
+    pub fn offscreen(width: u32, height: u32) -> Self {
+        assert!(width > 0);
+        assert!(height > 0);
+
+        let size = SurfaceSize { width, height };
+
+        Self { window: None, size }
     }
 }
 
+/// Render one frame of `width` by `height` pixels offscreen and read it back, without a window or
+/// an event loop.
+///
+/// `draw` gets an [offscreen](Pixels::new_offscreen) pixel buffer of the same size as its
+/// output. It fills the frame, and can use anything else of the pixel buffer as well, such as
+/// sprites, text grids, and effects, before the frame is rendered once. This suits scripts,
+/// asset pipelines, and tests which only need the output of some drawing code. Call
+/// [`CapturedFrame::rgba8`] on the result for the pixels. Unavailable on Web targets.
+///
+/// ```no_run
+/// let output = pixels::render_once(64, 64, |pixels| {
+///     for (i, pixel) in pixels.frame_mut().chunks_exact_mut(4).enumerate() {
+///         let (x, y) = (i % 64, i / 64);
+///         pixel.copy_from_slice(&[(x * 4) as u8, (y * 4) as u8, 0x80, 0xff]);
+///     }
+///     Ok(())
+/// })?;
+///
+/// let rgba = output.rgba8().unwrap();
+/// assert_eq!(&rgba[..4], &[0, 0, 0x80, 0xff]);
+/// # Ok::<(), pixels::Error>(())
+/// ```
+///
+/// # Errors
+///
+/// Returns an error when the pixel buffer cannot be created, `draw` fails, or the frame cannot be
+/// rendered or read back.
+///
+/// # Panics
+///
+/// Panics when `width` or `height` are 0.
+#[cfg(feature = "gpu")]
+#[cfg(not(target_arch = "wasm32"))]
+pub fn render_once(
+    width: u32,
+    height: u32,
+    draw: impl FnOnce(&mut Pixels<'static>) -> Result<(), Error>,
+) -> Result<CapturedFrame, Error> {
+    let mut pixels = Pixels::new_offscreen(width, height)?;
+    draw(&mut pixels)?;
+    pixels.render()?;
+
+    Ok(pixels.blocking_read_output()?)
+}
+
+#[cfg(feature = "gpu")]
 impl<'win> Pixels<'win> {
     /// Create a pixel buffer instance with default options.
     ///
@@ -270,6 +1054,54 @@ impl<'win> Pixels<'win> {
             .await
     }
 
+    /// Create a pixel buffer instance which renders to an internal texture of the same size,
+    /// instead of a window surface.
+    ///
+    /// Offscreen pixel buffers need no window or event loop, which makes them suitable for
+    /// testing rendering code in CI, generating thumbnails on servers, and running wgpu
+    /// validation. Rendering works as usual, but nothing is presented; read the result back with
+    /// [`Pixels::blocking_read_output`] or [`Pixels::capture_frame`]. For a different output
+    /// size or other options, pass [`SurfaceTexture::offscreen`] to [`PixelsBuilder::new`].
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    ///
+    /// let mut pixels = Pixels::new_offscreen(320, 240)?;
+    /// pixels.frame_mut().fill(0xff);
+    /// pixels.render()?;
+    ///
+    /// let output = pixels.blocking_read_output()?;
+    /// assert_eq!(output.rgba8().unwrap(), pixels.frame());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a [`wgpu::Adapter`] cannot be found.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new_offscreen(width: u32, height: u32) -> Result<Self, Error> {
+        PixelsBuilder::new(width, height, SurfaceTexture::offscreen(width, height)).build()
+    }
+
+    /// Check whether this pixel buffer renders to an internal texture instead of a window
+    /// surface, see [`Pixels::new_offscreen`].
+    pub fn is_offscreen(&self) -> bool {
+        self.context.surface.is_none()
+    }
+
+    /// Get the internal texture which an [offscreen](Pixels::new_offscreen) pixel buffer renders
+    /// to, or `None` for pixel buffers with a window surface.
+    ///
+    /// The texture holds the most recent frame. It is replaced when the surface is reconfigured,
+    /// and by the first render after the surface is resized.
+    pub fn offscreen_texture(&self) -> Option<&wgpu::Texture> {
+        self.offscreen_texture.as_ref()
+    }
+
     /// Change the clear color.
     ///
     /// Allows customization of the background color and the border drawn for non-integer scale
@@ -287,10 +1119,48 @@ impl<'win> Pixels<'win> {
     /// pixels.clear_color(Color::RED);
     /// # Ok::<(), pixels::Error>(())
     /// ```
+    ///
+    /// The color components are passed to the GPU unchanged, so the resulting shade depends on
+    /// whether the render texture format is sRGB. Use [`Pixels::clear_color_srgb`] or
+    /// [`Pixels::clear_color_linear`] for the same shade on every platform.
     pub fn clear_color(&mut self, color: wgpu::Color) {
         self.context.scaling_renderer.clear_color = color;
     }
 
+    /// Change the clear color, with components in the sRGB color space.
+    ///
+    /// See [`PixelsBuilder::clear_color_srgb`].
+    ///
+    /// ```no_run
+    /// use pixels::wgpu::Color;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Set clear color to 50% gray, as it would appear in an image editor.
+    /// pixels.clear_color_srgb(Color {
+    ///     r: 0.5,
+    ///     g: 0.5,
+    ///     b: 0.5,
+    ///     a: 1.0,
+    /// });
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn clear_color_srgb(&mut self, color: wgpu::Color) {
+        self.context.scaling_renderer.clear_color =
+            color::clear_color_for_target(color, ColorSpace::Srgb, self.render_texture_format);
+    }
+
+    /// Change the clear color, with components in linear light.
+    ///
+    /// See [`PixelsBuilder::clear_color_linear`].
+    pub fn clear_color_linear(&mut self, color: wgpu::Color) {
+        self.context.scaling_renderer.clear_color =
+            color::clear_color_for_target(color, ColorSpace::Linear, self.render_texture_format);
+    }
+
     /// Returns a reference of the `wgpu` adapter used by the crate.
     ///
     /// The adapter can be used to retrieve runtime information about the host system
@@ -309,7 +1179,14 @@ impl<'win> Pixels<'win> {
         &self.adapter
     }
 
-    /// Resize the pixel buffer and zero its contents.
+    /// Get the graphics API that was chosen for this pixel buffer.
+    ///
+    /// See [`PixelsBuilder::backends`] for selecting backends.
+    pub fn backend(&self) -> wgpu::Backend {
+        self.adapter.get_info().backend
+    }
+
+    /// Resize the pixel buffer and zero its contents, and those of its [layers](Layer).
     ///
     /// This does not resize the surface upon which the pixel buffer texture is rendered. Use
     /// [`Pixels::resize_surface`] to change the size of the surface texture.
@@ -321,395 +1198,4843 @@ impl<'win> Pixels<'win> {
     /// Call this method to change the virtual screen resolution. E.g. when you want your pixel
     /// buffer to be resized from `640x480` to `800x600`.
     ///
+    /// With a [resize transition](Pixels::set_resize_transition), the last frame drawn before the
+    /// resize fades out over the new pixel buffer.
+    ///
     /// # Errors
     ///
     /// - [`TextureError::TextureWidth`] when `width` is 0 or greater than GPU texture limits.
     /// - [`TextureError::TextureHeight`] when `height` is 0 or greater than GPU texture limits.
+    /// - [`TextureError::TextureUsage`] or [`TextureError::TextureFormat`] when the pixel buffer
+    ///   has [layers](Layer) and its texture format cannot be drawn to and blended.
     pub fn resize_buffer(&mut self, width: u32, height: u32) -> Result<(), TextureError> {
         // Recreate the backing texture
-        let (scaling_matrix_inverse, texture_extent, texture, scaling_renderer, pixels_buffer_size) =
-            builder::create_backing_texture(
-                &self.context.device,
-                // Backing texture values
-                width,
-                height,
-                self.context.texture_format,
-                // Render texture values
-                &self.surface_size,
-                self.render_texture_format,
-                self.context.scaling_renderer.clear_color,
-                self.blend_state,
-            )?;
+        let render_size = self.render_size();
+        let texture_format = self.imported_texture.unwrap_or(self.context.texture_format);
+        if !self.layers.is_empty() {
+            layer::check_layer_format(&self.context.device, texture_format)?;
+        }
+        let (
+            scaling_matrix_inverse,
+            texture_extent,
+            mut textures,
+            scaling_renderer,
+            pixels_buffer_size,
+        ) = builder::create_backing_texture(
+            &self.context.device,
+            // Backing texture values
+            width,
+            height,
+            texture_format,
+            // Render texture values
+            &render_size,
+            self.render_texture_format,
+            self.context.scaling_renderer.clear_color,
+            self.blend_state,
+            &self.scaling_options,
+            self.texture_ring_size,
+        )?;
 
         self.scaling_matrix_inverse = scaling_matrix_inverse;
         self.context.texture_extent = texture_extent;
-        self.context.texture = texture;
-        self.context.scaling_renderer = scaling_renderer;
+        self.context.texture = textures.pop_front().unwrap();
+        self.spare_textures = textures;
+        self.pending_copies.clear();
+        self.pending_blits.clear();
+        self.pending_text_grids.clear();
+        self.pending_sprites.clear();
+        self.pending_drawn_rects.clear();
+        self.drawn_rects.clear();
+        self.full_upload = true;
+        self.redraw_pending = true;
+        self.context.texture_format = texture_format;
+        self.context.texture_format_size = builder::texture_format_size(texture_format);
+        let previous = std::mem::replace(&mut self.context.scaling_renderer, scaling_renderer);
+        self.crossfade = (self.resize_transition > 0).then(|| {
+            renderers::Crossfade::new(&self.context.device, previous, self.resize_transition)
+        });
+        self.context
+            .scaling_renderer
+            .set_distortion_mesh(&self.context.device, self.distortion_mesh.as_ref());
+        self.update_view();
+        self.imported_texture = None;
+
+        // Recreate the layers at the new size, keeping their offset and opacity
+        if let Some(compositor) = self
+            .layer_compositor
+            .as_mut()
+            .filter(|compositor| compositor.texture_format() != texture_format)
+        {
+            *compositor = renderers::LayerCompositor::new(&self.context.device, texture_format);
+        }
+        if let Some(compositor) = &self.layer_compositor {
+            for layer in &mut self.layers {
+                let device = &self.context.device;
+                let mut resized = Layer::new(device, compositor, width, height, texture_format);
+                let (x, y) = layer.offset();
+                resized.set_offset(x, y);
+                resized.set_opacity(layer.opacity());
+                *layer = resized;
+            }
+        }
+
+        if let Some(flash_limiter) = self.flash_limiter.as_mut() {
+            flash_limiter.reset();
+        }
+
+        // The previous texture in the new ring holds no frame yet
+        if let Some(interpolator) = self.frame_interpolator.as_mut() {
+            interpolator.reset();
+            self.context
+                .scaling_renderer
+                .set_frame_interpolation(&self.context.device, true);
+        }
 
         // Resize the pixel buffer
-        self.pixels
-            .resize_with(pixels_buffer_size, Default::default);
+        Arc::make_mut(&mut self.pixels).resize_with(pixels_buffer_size, Default::default);
+        self.frame_changed = true;
 
         Ok(())
     }
 
-    /// Resize the surface upon which the pixel buffer texture is rendered.
+    /// Resize the pixel buffer like [`Pixels::resize_buffer`], keeping its previous contents.
     ///
-    /// This does not resize the pixel buffer. Use [`Pixels::resize_buffer`] to change the size of
-    /// the pixel buffer.
+    /// The old frame is copied into the new buffer at the position given by `anchor`. Parts that
+    /// no longer fit are cropped, and newly exposed pixels are zeroed. This is what paint
+    /// programs and terminals need when the canvas grows, without redrawing everything.
     ///
-    /// The pixel buffer texture will be fit onto the surface texture as best as possible by scaling
-    /// to the nearest integer, e.g. 2x, 3x, 4x, etc. A border will be added around the pixel buffer
-    /// texture for non-integer scaling ratios.
+    /// Block compressed formats are moved in whole blocks, so a centered frame may be off by up to
+    /// one block. Nothing is preserved while an [imported texture](Pixels::import_texture) is
+    /// displayed, since the pixel buffer is not shown then.
     ///
-    /// Call this method in response to a resize event from your window manager. The size expected
-    /// is in physical pixel units. Does nothing when `width` or `height` are 0.
+    /// ```no_run
+    /// use pixels::{Pixels, ResizeAnchor};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// pixels.frame_mut().fill(0xff);
+    ///
+    /// // Grow the canvas; the white frame stays in the middle
+    /// pixels.resize_buffer_preserve(640, 480, ResizeAnchor::Center)?;
+    /// assert_eq!(pixels.frame()[0], 0);
+    /// assert_eq!(pixels.frame()[(240 * 640 + 320) * 4], 0xff);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
     ///
     /// # Errors
     ///
-    /// - [`TextureError::TextureWidth`] when `width` is 0 or greater than GPU texture limits.
-    /// - [`TextureError::TextureHeight`] when `height` is 0 or greater than GPU texture limits.
-    pub fn resize_surface(&mut self, width: u32, height: u32) -> Result<(), TextureError> {
-        check_texture_size(&self.context.device, width, height)?;
+    /// The same as [`Pixels::resize_buffer`]. The pixel buffer is unchanged on error.
+    pub fn resize_buffer_preserve(
+        &mut self,
+        width: u32,
+        height: u32,
+        anchor: ResizeAnchor,
+    ) -> Result<(), TextureError> {
+        // Hold on to the old frame; the resize copies the buffer on write
+        let previous = Arc::clone(&self.pixels);
+        let previous_size = (
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        );
+        let preserve = self.imported_texture.is_none();
+        self.resize_buffer(width, height)?;
+        if !preserve {
+            return Ok(());
+        }
+
+        // Copy whole blocks, which are single pixels for uncompressed formats
+        let format = self.context.texture_format;
+        let (block_width, block_height) = format.block_dimensions();
+        let block_size = format.block_copy_size(None).unwrap_or_default() as usize;
+        let old_columns = previous_size.0 / block_width;
+        let new_columns = width / block_width;
+        let (src_x, dst_x, columns) = anchor.span(old_columns, new_columns);
+        let (src_y, dst_y, rows) =
+            anchor.span(previous_size.1 / block_height, height / block_height);
+        let len = columns as usize * block_size;
+
+        let frame = Arc::make_mut(&mut self.pixels);
+        frame.fill(0);
+        for row in 0..rows as usize {
+            let src = (src_y as usize + row) * old_columns as usize + src_x as usize;
+            let dst = (dst_y as usize + row) * new_columns as usize + dst_x as usize;
+            let (src, dst) = (src * block_size, dst * block_size);
+            frame[dst..dst + len].copy_from_slice(&previous[src..src + len]);
+        }
+
+        Ok(())
+    }
+
+    /// Display a GPU texture created outside of `pixels` instead of the pixel buffer.
+    ///
+    /// The texture replaces the internal source texture, and [`Pixels::render`] stops uploading the
+    /// pixel buffer until [`Pixels::resize_buffer`] is called to return to CPU-driven frames. The
+    /// texture is scaled with the same rules as the pixel buffer, using its own size and format.
+    ///
+    /// This is intended for zero-copy interop with video and camera pipelines: a dmabuf or
+    /// external-memory image can be imported on Vulkan with `wgpu::Device::create_texture_from_hal`
+    /// and the device returned by [`Pixels::device`], then handed to this method. The import
+    /// itself is `unsafe` and must be done by the application, because `pixels` forbids unsafe
+    /// code.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Any texture created on the same device will do, such as a video decoder output.
+    /// let texture = pixels.device().create_texture(&wgpu::TextureDescriptor {
+    ///     label: Some("video_frame"),
+    ///     size: wgpu::Extent3d {
+    ///         width: 1920,
+    ///         height: 1080,
+    ///         depth_or_array_layers: 1,
+    ///     },
+    ///     mip_level_count: 1,
+    ///     sample_count: 1,
+    ///     dimension: wgpu::TextureDimension::D2,
+    ///     format: wgpu::TextureFormat::Bgra8Unorm,
+    ///     usage: wgpu::TextureUsages::TEXTURE_BINDING,
+    ///     view_formats: &[],
+    /// });
+    /// pixels.import_texture(texture)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureUsage`] when the texture was not created with
+    ///   [`wgpu::TextureUsages::TEXTURE_BINDING`].
+    /// - [`TextureError::TextureFormat`] when the texture is not a 2D texture with a filterable
+    ///   float format and a single sample.
+    /// - [`TextureError::TextureWidth`] or [`TextureError::TextureHeight`] when a dimension is
+    ///   greater than GPU texture limits.
+    pub fn import_texture(&mut self, texture: wgpu::Texture) -> Result<(), TextureError> {
+        let usage = wgpu::TextureUsages::TEXTURE_BINDING;
+        if !texture.usage().contains(usage) {
+            return Err(TextureError::TextureUsage(usage));
+        }
+
+        let format = texture.format();
+        let sample_type = format.sample_type(None, Some(self.context.device.features()));
+        if texture.dimension() != wgpu::TextureDimension::D2
+            || texture.sample_count() != 1
+            || sample_type != Some(wgpu::TextureSampleType::Float { filterable: true })
+        {
+            return Err(TextureError::TextureFormat(format));
+        }
+
+        let texture_extent = wgpu::Extent3d {
+            depth_or_array_layers: 1,
+            ..texture.size()
+        };
+        check_texture_size(
+            &self.context.device,
+            texture_extent.width,
+            texture_extent.height,
+        )?;
+
+        let render_size = self.render_size();
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let scaling_renderer = ScalingRenderer::new(
+            &self.context.device,
+            vec![texture_view],
+            &texture_extent,
+            &render_size,
+            self.render_texture_format,
+            self.context.scaling_renderer.clear_color,
+            self.blend_state,
+            // Imported textures hold colors, not palette indices or packed values
+            &renderers::ScalingOptions {
+                palette: None,
+                packed: None,
+                ..self.scaling_options.clone()
+            },
+            false,
+        );
+
+        self.imported_texture
+            .get_or_insert(self.context.texture_format);
+        self.context.texture = texture;
+        self.spare_textures.clear();
+        self.redraw_pending = true;
+        self.context.texture_extent = texture_extent;
+        self.context.texture_format = format;
+        self.context.texture_format_size = builder::texture_format_size(format);
+        self.context.scaling_renderer = scaling_renderer;
+        self.context
+            .scaling_renderer
+            .set_distortion_mesh(&self.context.device, self.distortion_mesh.as_ref());
+        self.update_view();
+
+        Ok(())
+    }
+
+    /// Resize the surface upon which the pixel buffer texture is rendered.
+    ///
+    /// This does not resize the pixel buffer. Use [`Pixels::resize_buffer`] to change the size of
+    /// the pixel buffer.
+    ///
+    /// The pixel buffer texture will be fit onto the surface texture as best as possible by scaling
+    /// to the nearest integer, e.g. 2x, 3x, 4x, etc. A border will be added around the pixel buffer
+    /// texture for non-integer scaling ratios.
+    ///
+    /// Call this method in response to a resize event from your window manager. The size expected
+    /// is in physical pixel units. A zero `width` or `height`, as reported for minimized windows,
+    /// is handled according to the [`ZeroSizePolicy`] chosen with
+    /// [`PixelsBuilder::zero_size_policy`].
+    ///
+    /// The surface itself is reconfigured by the next render, with the latest size. Windows which
+    /// are resized by dragging send many resize events for each frame, and reconfiguring for every
+    /// one of them makes some compositors flicker.
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::ZeroSizedSurface`] when `width` or `height` is 0, with
+    ///   [`ZeroSizePolicy::Error`].
+    /// - [`TextureError::TextureWidth`] when `width` is greater than GPU texture limits.
+    /// - [`TextureError::TextureHeight`] when `height` is greater than GPU texture limits.
+    ///
+    /// With [supersampling](PixelsBuilder::supersampling), the limits apply to the supersampled
+    /// size.
+    pub fn resize_surface(&mut self, width: u32, height: u32) -> Result<(), TextureError> {
+        if width == 0 || height == 0 {
+            match self.zero_size_policy {
+                ZeroSizePolicy::Error => {
+                    return Err(TextureError::ZeroSizedSurface(width, height));
+                }
+                ZeroSizePolicy::Clamp => return self.resize_surface(width.max(1), height.max(1)),
+                ZeroSizePolicy::Pause => {
+                    self.surface_paused = true;
+                    return Ok(());
+                }
+            }
+        }
+        check_texture_size(&self.context.device, width, height)?;
+        let render_size = SurfaceSize { width, height }.scaled(self.supersampling());
+        check_texture_size(&self.context.device, render_size.width, render_size.height)?;
+        self.surface_paused = false;
 
         // Update SurfaceTexture dimensions
+        self.redraw_pending = true;
         self.surface_size.width = width;
         self.surface_size.height = height;
 
-        // Update ScalingMatrix for mouse transformation
-        self.scaling_matrix_inverse = renderers::ScalingMatrix::new(
-            (
-                self.context.texture_extent.width as f32,
-                self.context.texture_extent.height as f32,
-            ),
-            (width as f32, height as f32),
-        )
-        .transform
-        .inversed();
-
-        // Reconfigure the surface
-        self.reconfigure_surface();
+        // Reconfigure the surface on the next render
+        self.surface_resize_pending = true;
 
         // Update state for all render passes
+        if let Some(supersampler) = self.supersampler.as_mut() {
+            supersampler.resize(&self.context.device, width, height);
+        }
+        if let Some(frame_averager) = self.frame_averager.as_mut() {
+            frame_averager.resize(&self.context.device, width, height);
+        }
+        if let Some(provenance_renderer) = self.provenance_renderer.as_mut() {
+            provenance_renderer.resize(&self.context.device, width, height);
+        }
+        let (width, height) = (render_size.width, render_size.height);
         self.context
             .scaling_renderer
             .resize(&self.context.queue, width, height);
+        if let Some(crossfade) = self.crossfade.as_mut() {
+            crossfade.resize(&self.context.queue, width, height);
+        }
+        if let Some(transition) = self.transition.as_mut() {
+            transition.resize(width, height);
+        }
+        if let Some(watermark_renderer) = &self.watermark_renderer {
+            watermark_renderer.resize(&self.context.queue, width, height);
+        }
+        // Updates the mouse transformation, the pixel grid, and selections as well
+        self.update_view();
 
         Ok(())
     }
 
-    /// Enable or disable Vsync.
-    ///
-    /// Vsync is enabled by default. It cannot be disabled on Web targets.
+    /// Keep the window at integer multiples of the pixel buffer size, so that resizing the
+    /// window never letterboxes the image.
+    ///
+    /// This takes effect through [`Pixels::constrain_window_size`], which should be called on
+    /// every [`winit::event::WindowEvent::Resized`] event. Disabled by default.
+    pub fn lock_window_aspect(&mut self, locked: bool) {
+        self.window_aspect_locked = locked;
+    }
+
+    /// Check whether window sizes are kept at integer multiples of the pixel buffer size.
+    pub fn window_aspect_locked(&self) -> bool {
+        self.window_aspect_locked
+    }
+
+    /// Apply the [window aspect lock](Pixels::lock_window_aspect) to `window`, returning the
+    /// physical size to pass to [`Pixels::resize_surface`].
+    ///
+    /// While the lock is enabled, resize increments are set to the size of the
+    /// [view](Pixels::view), the whole pixel buffer by default, so that platforms which support
+    /// them resize in whole steps. Elsewhere, the lock is emulated: a window which is not at an
+    /// integer multiple is asked to snap to the [nearest one](crate::window::nearest_integer_size),
+    /// and the snapped size is returned when it was applied immediately. Otherwise the current size
+    /// is returned, and the snapped size arrives with another resize event. While the lock is
+    /// disabled, resize increments are cleared and the current size is returned.
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    /// use winit::event::WindowEvent;
+    /// use winit::window::Window;
+    ///
+    /// fn window_event(pixels: &mut Pixels<'_>, window: &Window, event: WindowEvent) {
+    ///     if let WindowEvent::Resized(_) = event {
+    ///         let size = pixels.constrain_window_size(window);
+    ///         pixels.resize_surface(size.width, size.height).unwrap();
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "winit")]
+    pub fn constrain_window_size(
+        &self,
+        window: &winit::window::Window,
+    ) -> winit::dpi::PhysicalSize<u32> {
+        let size = window.inner_size();
+        if !self.window_aspect_locked {
+            window.set_resize_increments(None::<winit::dpi::PhysicalSize<u32>>);
+            return size;
+        }
+
+        let (_, _, view_width, view_height) = self.view();
+        window.set_resize_increments(Some(winit::dpi::PhysicalSize::new(view_width, view_height)));
+
+        let (width, height) =
+            crate::window::nearest_integer_size(view_width, view_height, (size.width, size.height));
+        if (width, height) == (size.width, size.height) {
+            return size;
+        }
+
+        window
+            .request_inner_size(winit::dpi::PhysicalSize::new(width, height))
+            .unwrap_or(size)
+    }
+
+    /// Choose when [`Pixels::schedule_redraw`] asks the window for another frame.
+    ///
+    /// The default is [`RedrawPolicy::Continuous`]. [`RedrawPolicy::OnDirty`] stops rendering
+    /// static content, so GUI-style applications leave the GPU idle between changes.
+    pub fn set_redraw_policy(&mut self, policy: RedrawPolicy) {
+        self.redraw_policy = policy;
+    }
+
+    /// Get the policy for asking the window for another frame.
+    pub fn redraw_policy(&self) -> RedrawPolicy {
+        self.redraw_policy
+    }
+
+    /// Check whether rendering is paused because the surface was resized to a zero width or
+    /// height, with [`ZeroSizePolicy::Pause`].
+    ///
+    /// Rendering resumes with the next [`Pixels::resize_surface`] to a nonzero size.
+    pub fn is_paused(&self) -> bool {
+        self.surface_paused
+    }
+
+    /// Mark the output as changed, so that the next [`Pixels::schedule_redraw`] asks for a frame
+    /// under [`RedrawPolicy::OnDirty`].
+    ///
+    /// Changes to the pixel buffer and the surface are tracked already; call this after changing
+    /// other settings which affect the output, like the clear color or the sampler, and after
+    /// drawing into an [imported texture](Pixels::import_texture).
+    pub fn request_redraw(&mut self) {
+        self.redraw_pending = true;
+    }
+
+    /// Check whether the next pass through the event loop should draw a frame, according to the
+    /// [redraw policy](Pixels::set_redraw_policy). Never while [paused](Pixels::is_paused).
+    pub fn needs_redraw(&self) -> bool {
+        if self.surface_paused {
+            return false;
+        }
+
+        match self.redraw_policy {
+            RedrawPolicy::Continuous => true,
+            RedrawPolicy::OnDirty => {
+                self.redraw_pending
+                    || self.crossfade.is_some()
+                    || self.transition.is_some()
+                    || self.shake.is_some()
+                    || self.screen_flash.is_some()
+                    || self.effects.is_tweening()
+                    || self
+                        .frame_averager
+                        .as_ref()
+                        .is_some_and(renderers::FrameAverager::is_settling)
+                    || self
+                        .frame_interpolator
+                        .as_ref()
+                        .is_some_and(|interpolator| interpolator.is_fading(Instant::now()))
+            }
+            RedrawPolicy::Manual => false,
+        }
+    }
+
+    /// Ask `window` for a frame when [`Pixels::needs_redraw`] says so.
+    ///
+    /// Call this from [`winit::application::ApplicationHandler::about_to_wait`], and render on
+    /// [`winit::event::WindowEvent::RedrawRequested`]. With the default
+    /// [`winit::event_loop::ControlFlow::Wait`], the event loop then sleeps until the next input
+    /// event whenever nothing needs to be drawn.
+    ///
+    /// ```no_run
+    /// use pixels::{Pixels, RedrawPolicy};
+    /// use winit::window::Window;
+    ///
+    /// struct App<'win> {
+    ///     window: &'win Window,
+    ///     pixels: Pixels<'win>,
+    /// }
+    ///
+    /// impl App<'_> {
+    ///     fn resumed(&mut self) {
+    ///         self.pixels.set_redraw_policy(RedrawPolicy::OnDirty);
+    ///     }
+    ///
+    ///     // Called from `ApplicationHandler::about_to_wait`
+    ///     fn about_to_wait(&self) {
+    ///         self.pixels.schedule_redraw(self.window);
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "winit")]
+    pub fn schedule_redraw(&self, window: &winit::window::Window) {
+        if self.needs_redraw() {
+            window.request_redraw();
+        }
+    }
+
+    /// Enable or disable Vsync.
+    ///
+    /// Vsync is enabled by default. It cannot be disabled on Web targets.
+    ///
+    /// The `wgpu` present mode will be set to `AutoVsync` when Vsync is enabled, or `AutoNoVsync`
+    /// when Vsync is disabled. To set the present mode to `Mailbox` or another value, use the
+    /// [`Pixels::set_present_mode`] method.
+    pub fn enable_vsync(&mut self, enable_vsync: bool) {
+        self.present_mode = if enable_vsync {
+            wgpu::PresentMode::AutoVsync
+        } else {
+            wgpu::PresentMode::AutoNoVsync
+        };
+        self.reconfigure_surface();
+    }
+
+    /// Get the `wgpu` present mode.
+    ///
+    /// Returns the present mode currently in use by the surface, which can be changed through
+    /// [`Pixels::enable_vsync`] or [`Pixels::set_present_mode`]. This is
+    /// [`wgpu::PresentMode::Fifo`] when the [`Workaround::FifoPresentMode`] workaround is applied,
+    /// and an automatic mode when the surface does not support the requested one.
+    ///
+    /// [`Workaround::FifoPresentMode`]: workarounds::Workaround::FifoPresentMode
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_present_mode()
+    }
+
+    /// Set the `wgpu` present mode.
+    ///
+    /// This differs from [`Pixels::enable_vsync`] by allowing the present mode to be set to
+    /// any value. The surface is reconfigured immediately, so benchmarks and low-latency
+    /// applications can switch between [`wgpu::PresentMode::Mailbox`] and vsync at runtime.
+    ///
+    /// A mode the surface does not support falls back to [`wgpu::PresentMode::AutoVsync`] for
+    /// the FIFO modes, or [`wgpu::PresentMode::AutoNoVsync`] for the others. The supported modes
+    /// are listed by [`Pixels::capability_report`].
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        self.present_mode = present_mode;
+        self.reconfigure_surface();
+    }
+
+    /// Tell the pixel buffer the refresh rate of the display which shows the surface, in
+    /// millihertz, or `None` when it is unknown.
+    ///
+    /// When the rate changes, e.g. after the window was dragged to a monitor with another refresh
+    /// rate, the surface is reconfigured with the current [present mode](Pixels::present_mode),
+    /// so that vsync paces presents to the new display instead of the one the swapchain was
+    /// created on, and the [latency probe](Pixels::set_latency_probe) forgets the present
+    /// intervals measured on the previous display. The rate also replaces the estimate of the
+    /// refresh interval in [`InputLatency::estimated`]. With `winit`, call
+    /// [`Pixels::track_monitor`] instead, which reads the rate of the window's monitor.
+    pub fn set_display_refresh_rate(&mut self, millihertz: Option<u32>) {
+        let millihertz = millihertz.filter(|&millihertz| millihertz > 0);
+        if millihertz == self.display_refresh_rate {
+            return;
+        }
+
+        self.display_refresh_rate = millihertz;
+        self.latency_probe
+            .set_refresh(millihertz.map(|millihertz| Duration::from_secs(1000) / millihertz));
+        if self.context.surface.is_some() {
+            self.reconfigure_surface();
+        }
+        self.redraw_pending = true;
+    }
+
+    /// Get the refresh rate of the display in millihertz, as set with
+    /// [`Pixels::set_display_refresh_rate`] or [`Pixels::track_monitor`].
+    pub fn display_refresh_rate(&self) -> Option<u32> {
+        self.display_refresh_rate
+    }
+
+    /// Follow `window` to the monitor it is on, with [`Pixels::set_display_refresh_rate`].
+    /// Returns `true` when the refresh rate changed.
+    ///
+    /// Call this when the window is created, and on [`winit::event::WindowEvent::Moved`] and
+    /// [`winit::event::WindowEvent::ScaleFactorChanged`], which are sent when the window moves
+    /// from one monitor to another. Platforms which do not report the refresh rate of monitors,
+    /// such as the Web, reset it to `None`.
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    /// use winit::event::WindowEvent;
+    /// use winit::window::Window;
+    ///
+    /// fn handle_event(pixels: &mut Pixels<'_>, window: &Window, event: &WindowEvent) {
+    ///     if let WindowEvent::Moved(_) | WindowEvent::ScaleFactorChanged { .. } = event {
+    ///         pixels.track_monitor(window);
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "winit")]
+    pub fn track_monitor(&mut self, window: &winit::window::Window) -> bool {
+        let millihertz = window
+            .current_monitor()
+            .and_then(|monitor| monitor.refresh_rate_millihertz());
+        let previous = self.display_refresh_rate;
+        self.set_display_refresh_rate(millihertz);
+
+        self.display_refresh_rate != previous
+    }
+
+    /// Enable, replace, or disable the [`FlashLimiter`].
+    ///
+    /// The flash limiter is disabled by default. See [`PixelsBuilder::flash_limiter`]. It has no
+    /// effect with the [minimal pipeline](Pixels::minimal_pipeline).
+    pub fn set_flash_limiter(&mut self, flash_limiter: Option<FlashLimiter>) {
+        if flash_limiter.is_none() {
+            self.context
+                .scaling_renderer
+                .set_attenuation(&self.context.queue, flash::Attenuation::NONE);
+        }
+        self.flash_limiter = flash_limiter;
+    }
+
+    /// Get the [`FlashLimiter`], if it is enabled.
+    pub fn flash_limiter(&self) -> Option<&FlashLimiter> {
+        self.flash_limiter.as_ref()
+    }
+
+    /// Enable, replace, or disable [frame interpolation](FrameInterpolation).
+    ///
+    /// Interpolation is disabled by default. See [`PixelsBuilder::frame_interpolation`]. The first
+    /// new frame after changing the mode is shown without interpolation.
+    pub fn set_frame_interpolation(&mut self, mode: Option<FrameInterpolation>) {
+        self.frame_interpolator = mode.map(interpolation::FrameInterpolator::new);
+        self.context
+            .scaling_renderer
+            .set_frame_interpolation(&self.context.device, mode.is_some());
+        self.redraw_pending = true;
+    }
+
+    /// Get the [`FrameInterpolation`] mode, if interpolation is enabled.
+    pub fn frame_interpolation(&self) -> Option<FrameInterpolation> {
+        self.frame_interpolator
+            .as_ref()
+            .map(interpolation::FrameInterpolator::mode)
+    }
+
+    /// Enable, replace, or disable the [`PixelGrid`] overlay.
+    ///
+    /// The grid is disabled by default. See [`PixelsBuilder::pixel_grid`].
+    pub fn set_pixel_grid(&mut self, pixel_grid: Option<PixelGrid>) {
+        self.context
+            .grid_renderer
+            .set_grid(&self.context.queue, pixel_grid);
+    }
+
+    /// Get the [`PixelGrid`], if it is enabled.
+    pub fn pixel_grid(&self) -> Option<PixelGrid> {
+        self.context.grid_renderer.grid()
+    }
+
+    /// Warp the scaled pixel buffer with a [`DistortionMesh`], or restore the default with `None`.
+    ///
+    /// The mesh replaces the vertex stage of the [`ScalingRenderer`]; the pixel buffer texture,
+    /// filtering, and scaling transform are unchanged. It is kept when the buffer is resized.
+    /// [`Pixels::window_pos_to_pixel`] does not account for the warp.
+    ///
+    /// ```no_run
+    /// use pixels::DistortionMesh;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Simulate a curved screen
+    /// pixels.set_distortion_mesh(Some(DistortionMesh::grid(32, 32, |[x, y]| {
+    ///     let k = 1.0 - 0.08 * (x * x + y * y);
+    ///     [x * k, y * k]
+    /// })));
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn set_distortion_mesh(&mut self, mesh: Option<DistortionMesh>) {
+        self.context
+            .scaling_renderer
+            .set_distortion_mesh(&self.context.device, mesh.as_ref());
+        self.distortion_mesh = mesh;
+    }
+
+    /// Get the [`DistortionMesh`], if one is set.
+    pub fn distortion_mesh(&self) -> Option<&DistortionMesh> {
+        self.distortion_mesh.as_ref()
+    }
+
+    /// Crossfade from the previous frame to the new one over `frames` frames when
+    /// [`Pixels::resize_buffer`] is called.
+    ///
+    /// A value of 0 disables the transition, and cancels a transition in progress. The fade is
+    /// drawn after the [`Pixels::render_with`] closure, over any custom render passes.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(640, 480, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Switch resolutions with a quarter-second fade at 60 fps
+    /// pixels.set_resize_transition(15);
+    /// pixels.resize_buffer(640, 480)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn set_resize_transition(&mut self, frames: u32) {
+        self.resize_transition = frames;
+        if frames == 0 {
+            self.crossfade = None;
+        }
+    }
+
+    /// Split the upload of the pixel buffer into `slices` parts spread across consecutive frames.
+    ///
+    /// See [`PixelsBuilder::upload_slices`]. The next render uploads the whole frame, and the
+    /// following ones start with the first slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `slices` is 0.
+    pub fn set_upload_slices(&mut self, slices: u32, order: UploadOrder) {
+        assert!(slices > 0);
+        self.upload_slices = slices;
+        self.upload_order = order;
+        self.full_upload = true;
+    }
+
+    /// Get the number of slices that uploads are split into, and their order.
+    pub fn upload_slices(&self) -> (u32, UploadOrder) {
+        (self.upload_slices, self.upload_order)
+    }
+
+    /// Change how the pixel buffer is copied to its texture.
+    ///
+    /// See [`PixelsBuilder::upload_strategy`]. Switching away from
+    /// [`UploadStrategy::StagingRing`] frees the staging buffers.
+    pub fn set_upload_strategy(&mut self, strategy: UploadStrategy) {
+        if strategy != self.upload_strategy() {
+            self.staging_ring = match strategy {
+                UploadStrategy::WriteTexture => None,
+                UploadStrategy::StagingRing => Some(Default::default()),
+            };
+        }
+    }
+
+    /// Get how the pixel buffer is copied to its texture.
+    pub fn upload_strategy(&self) -> UploadStrategy {
+        if self.staging_ring.is_some() {
+            UploadStrategy::StagingRing
+        } else {
+            UploadStrategy::WriteTexture
+        }
+    }
+
+    /// Upload only the regions of the pixel buffer marked with [`Pixels::mark_dirty`], instead of
+    /// the whole frame on every render.
+    ///
+    /// Applications which redraw small parts of a large buffer, like terminal emulators and
+    /// editors, save most of the copy bandwidth this way. A render without marked regions uploads
+    /// nothing, so every change made through [`Pixels::frame_mut`] must be marked. The next render
+    /// uploads the whole frame, as does the first render after the buffer is resized.
+    ///
+    /// The APIs which replace the frame mark the area they write themselves:
+    /// [`Pixels::swap_frame`], [`Pixels::render_shared`], [`Pixels::copy_strided`],
+    /// [`Pixels::read_shared_frame`], and [`Pixels::draw_canvas`] mark the whole buffer, and
+    /// [`Pixels::blit_target`] marks the area covered by the target.
+    ///
+    /// Damage is written in place to one texture, so a
+    /// [texture ring](PixelsBuilder::texture_ring_size) is not rotated while tracking is enabled,
+    /// and tracking takes precedence over [sliced uploads](Pixels::set_upload_slices).
+    /// Block-compressed frames are always uploaded whole. GPU copies, blits, text grids, and
+    /// sprites drawn into the texture last for one render; the areas they covered are uploaded
+    /// again on the next one without being marked.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(640, 480, &window);
+    /// let mut pixels = Pixels::new(640, 480, surface_texture)?;
+    /// pixels.set_dirty_tracking(true);
+    /// pixels.render()?;
+    ///
+    /// // Draw a cursor cell, then upload only that cell
+    /// let frame = pixels.frame_mut();
+    /// for row in 0..16 {
+    ///     let start = (row * 640 + 8) * 4;
+    ///     frame[start..start + 8 * 4].fill(0xff);
+    /// }
+    /// pixels.mark_dirty((8, 0, 8, 16));
+    /// pixels.render()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn set_dirty_tracking(&mut self, enabled: bool) {
+        self.dirty_rects = enabled.then(Vec::new);
+        self.full_upload = true;
+    }
+
+    /// Check whether only the regions marked with [`Pixels::mark_dirty`] are uploaded.
+    pub fn dirty_tracking(&self) -> bool {
+        self.dirty_rects.is_some()
+    }
+
+    /// Mark the region `rect` (x, y, width, height) of the pixel buffer as changed, so that the
+    /// next render uploads it.
+    ///
+    /// See [`Pixels::set_dirty_tracking`]. The region is clipped to the buffer, and overlapping
+    /// or adjacent regions are merged into their bounding box. This does nothing while dirty
+    /// tracking is disabled, because the whole frame is uploaded anyway.
+    pub fn mark_dirty(&mut self, rect: (u32, u32, u32, u32)) {
+        let extent = self.context.texture_extent;
+        let Some(rects) = self.dirty_rects.as_mut() else {
+            return;
+        };
+
+        let (x, y, width, height) = rect;
+        let x1 = x.saturating_add(width).min(extent.width);
+        let y1 = y.saturating_add(height).min(extent.height);
+        if x >= x1 || y >= y1 {
+            return;
+        }
+
+        // Grow the region over every region it touches, until none is left
+        let mut merged = (x, y, x1, y1);
+        while let Some(index) = rects.iter().position(|&(rx, ry, rw, rh)| {
+            rx <= merged.2 && merged.0 <= rx + rw && ry <= merged.3 && merged.1 <= ry + rh
+        }) {
+            let (rx, ry, rw, rh) = rects.swap_remove(index);
+            merged = (
+                merged.0.min(rx),
+                merged.1.min(ry),
+                merged.2.max(rx + rw),
+                merged.3.max(ry + rh),
+            );
+        }
+        let (x0, y0, x1, y1) = merged;
+        rects.push((x0, y0, x1 - x0, y1 - y0));
+    }
+
+    /// Get the number of frames of the resize transition.
+    pub fn resize_transition(&self) -> u32 {
+        self.resize_transition
+    }
+
+    /// Play a [`Transition`] from the frame shown now to the frames drawn next, over `frames`
+    /// frames.
+    ///
+    /// The pixel buffer texture is copied on the GPU, so call this before changing the frame for
+    /// the new scene. The snapshot is drawn after the [`Pixels::render_with`] closure, over any
+    /// custom render passes, and is scaled like the pixel buffer was when it was taken. A new
+    /// transition replaces one in progress, and a value of 0 cancels it.
+    ///
+    /// ```no_run
+    /// # use pixels::{Pixels, Transition};
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Enter the next level with a half-second iris at 60 fps
+    /// pixels.start_transition(Transition::Iris, 30)?;
+    /// pixels.frame_mut().fill(0);
+    /// pixels.render()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TextureError::TextureUsage`] when an
+    /// [imported texture](Pixels::import_texture) is shown which was not created with
+    /// [`wgpu::TextureUsages::COPY_SRC`].
+    pub fn start_transition(
+        &mut self,
+        transition: Transition,
+        frames: u32,
+    ) -> Result<(), TextureError> {
+        if frames == 0 {
+            self.transition = None;
+            return Ok(());
+        }
+
+        let texture = &self.context.texture;
+        if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(TextureError::TextureUsage(wgpu::TextureUsages::COPY_SRC));
+        }
+
+        let snapshot = self
+            .context
+            .device
+            .create_texture(&wgpu::TextureDescriptor {
+                label: Some("pixels_transition_texture"),
+                size: texture.size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: texture.format(),
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+        let mut encoder =
+            self.context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("pixels_transition_command_encoder"),
+                });
+        encoder.copy_texture_to_texture(
+            texture.as_image_copy(),
+            snapshot.as_image_copy(),
+            texture.size(),
+        );
+        self.context.queue.submit(Some(encoder.finish()));
+
+        self.transition = Some(renderers::TransitionRenderer::new(
+            &self.context.device,
+            &snapshot,
+            &self.render_size(),
+            self.render_texture_format,
+            self.scaling_options.fit(),
+            transition,
+            frames,
+        ));
+
+        Ok(())
+    }
+
+    /// Get the [`Transition`] in progress, if any.
+    pub fn transition(&self) -> Option<Transition> {
+        self.transition
+            .as_ref()
+            .map(renderers::TransitionRenderer::transition)
+    }
+
+    /// Shake the scaled pixel buffer by up to `intensity` physical pixels, decaying to rest over
+    /// `duration`.
+    ///
+    /// Each frame moves the buffer by a random whole number of pixels within its border, showing
+    /// the clear color at the edges. A new shake replaces one in progress. The pixel grid,
+    /// selections, and [`Pixels::window_pos_to_pixel`] do not follow the offset.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // The player was hit
+    /// pixels.shake(6.0, std::time::Duration::from_millis(300));
+    /// pixels.flash(pixels::wgpu::Color::RED, std::time::Duration::from_millis(150));
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn shake(&mut self, intensity: f32, duration: Duration) {
+        let seed = self.frame_index as u32 ^ 0x9e37_79b9;
+        self.shake = Some(feel::ScreenShake::new(intensity, duration, seed));
+    }
+
+    /// Blend an sRGB color over the whole surface which fades out over `duration`.
+    ///
+    /// The alpha component sets the starting opacity. The flash is drawn after the
+    /// [`Pixels::render_with`] closure, and replaces any flash or fade in progress.
+    pub fn flash(&mut self, color: wgpu::Color, duration: Duration) {
+        self.start_screen_flash(feel::ScreenFlash::flash(color, duration));
+    }
+
+    /// Fade the whole surface to an sRGB color over `duration`, and hold it until
+    /// [`Pixels::clear_flash`] is called.
+    ///
+    /// The alpha component sets the final opacity. Fade to black before a scene change, then
+    /// clear the fade or [`flash`](Pixels::flash) black to fade back in.
+    pub fn fade(&mut self, color: wgpu::Color, duration: Duration) {
+        self.start_screen_flash(feel::ScreenFlash::fade(color, duration));
+    }
+
+    /// Remove a flash or fade immediately.
+    pub fn clear_flash(&mut self) {
+        self.screen_flash = None;
+    }
+
+    fn start_screen_flash(&mut self, screen_flash: feel::ScreenFlash) {
+        if self.color_overlay_renderer.is_none() {
+            self.color_overlay_renderer = Some(renderers::ColorOverlayRenderer::new(
+                &self.context.device,
+                self.render_texture_format,
+            ));
+        }
+        self.screen_flash = Some(screen_flash);
+    }
+
+    /// Stamp a [`Watermark`] at a corner of the surface, or remove it with `None`.
+    ///
+    /// The watermark is drawn after the [`Pixels::render_with`] closure, over any custom render
+    /// passes, and does not change the pixel buffer.
+    pub fn set_watermark(&mut self, watermark: Option<Watermark>) {
+        self.watermark_renderer = watermark.map(|watermark| {
+            renderers::WatermarkRenderer::new(
+                &self.context.device,
+                &self.context.queue,
+                watermark,
+                &self.render_size(),
+                self.render_texture_format,
+                self.supersampling(),
+            )
+        });
+    }
+
+    /// Fill the letterbox bars with an [`AmbientBezel`], or restore the clear color with `None`.
+    ///
+    /// See [`PixelsBuilder::ambient_bezel`].
+    pub fn set_ambient_bezel(&mut self, bezel: Option<AmbientBezel>) {
+        self.bezel_renderer = bezel.map(|bezel| {
+            renderers::BezelRenderer::new(&self.context.device, self.render_texture_format, bezel)
+        });
+    }
+
+    /// Fill the letterbox bars with a [`Background`] image, or restore the clear color with
+    /// `None`.
+    ///
+    /// See [`PixelsBuilder::background`].
+    pub fn set_background(&mut self, background: Option<Background>) {
+        self.background_renderer = background.map(|background| {
+            renderers::BackgroundRenderer::new(
+                &self.context.device,
+                &self.context.queue,
+                background,
+                self.render_texture_format,
+            )
+        });
+    }
+
+    /// Get the [`Background`], if one is set.
+    pub fn background(&self) -> Option<&Background> {
+        self.background_renderer
+            .as_ref()
+            .map(renderers::BackgroundRenderer::background)
+    }
+
+    /// Get the [`AmbientBezel`], if one is set.
+    pub fn ambient_bezel(&self) -> Option<AmbientBezel> {
+        self.bezel_renderer
+            .as_ref()
+            .map(renderers::BezelRenderer::bezel)
+    }
+
+    /// Show the average of the last `frames` rendered frames, which hides flicker from content
+    /// that alternates faster than the eye should see, such as interlaced CRT output or
+    /// multiplexed LED displays.
+    ///
+    /// Each frame is averaged with the frames before it in linear light, after downsampling and
+    /// before the provenance mark. The average covers the whole composited frame, including the
+    /// letterbox and overlays. It costs one more full-screen pass and a texture array with
+    /// `frames` layers the size of the surface. A change takes `frames` frames to settle, which
+    /// [`Pixels::needs_redraw`] asks for with [`RedrawPolicy::OnDirty`]. Values of 0 and 1
+    /// disable averaging, and values above 16 are clamped. Averaging restarts when the surface is
+    /// resized.
+    ///
+    /// Only the presented frames are averaged: recordings of [`CaptureSource::Presented`] see the
+    /// average, while [`CaptureSource::Frame`] does not. To average recorded frames instead, wrap
+    /// the sink in an [`AveragingSink`](recorder::AveragingSink).
+    pub fn set_frame_averaging(&mut self, frames: u32) {
+        self.frame_averager = (frames > 1).then(|| {
+            renderers::FrameAverager::new(
+                &self.context.device,
+                &self.surface_size,
+                self.render_texture_format,
+                frames,
+            )
+        });
+        self.redraw_pending = true;
+    }
+
+    /// Mark every presented frame with the build and frame index, or remove the mark with `None`.
+    ///
+    /// The mark is set on the whole composited frame, after the watermark and any custom render
+    /// passes. It costs one more full-screen pass and a texture the size of the surface. See the
+    /// [`provenance`] module.
+    pub fn set_provenance_mark(&mut self, mark: Option<provenance::ProvenanceMark>) {
+        self.provenance_renderer = mark.map(|mark| {
+            renderers::ProvenanceRenderer::new(
+                &self.context.device,
+                &self.surface_size,
+                self.render_texture_format,
+                self.surface_texture_format,
+                mark,
+            )
+        });
+    }
+
+    /// Get the [`ProvenanceMark`](provenance::ProvenanceMark), if one is set.
+    pub fn provenance_mark(&self) -> Option<provenance::ProvenanceMark> {
+        self.provenance_renderer
+            .as_ref()
+            .map(renderers::ProvenanceRenderer::mark)
+    }
+
+    /// Get the [`Watermark`], if one is set.
+    pub fn watermark(&self) -> Option<&Watermark> {
+        self.watermark_renderer
+            .as_ref()
+            .map(renderers::WatermarkRenderer::watermark)
+    }
+
+    /// Replace the [`Selection`] rectangles drawn over the pixel buffer.
+    ///
+    /// Pass an empty slice to remove all selections.
+    pub fn set_selections(&mut self, selections: &[Selection]) {
+        self.context.selection_renderer.set_selections(
+            &self.context.device,
+            &self.context.queue,
+            selections,
+        );
+    }
+
+    /// Get the [`Selection`] rectangles drawn over the pixel buffer.
+    pub fn selections(&self) -> &[Selection] {
+        self.context.selection_renderer.selections()
+    }
+
+    /// Set the offset of the [`SelectionStyle::MarchingAnts`] dash pattern in physical pixels.
+    ///
+    /// Increment the phase once per frame, or derive it from elapsed time, to animate the ants.
+    pub fn set_selection_phase(&mut self, phase: u32) {
+        self.context
+            .selection_renderer
+            .set_phase(&self.context.queue, phase);
+    }
+
+    /// Draw this pixel buffer to the configured [`SurfaceTexture`].
+    ///
+    /// This draws [`DefaultPasses`], or the renderer registered with [`Pixels::set_renderer`]
+    /// outside of [safe mode](Pixels::set_safe_mode).
+    ///
+    /// When the surface cannot give a texture, e.g. because it is outdated after a resize, it is
+    /// reconfigured and asked again. If it is still outdated or times out, the frame is skipped
+    /// and the redraw stays pending.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when [`wgpu::Surface::get_current_texture`] fails again after the
+    /// surface is reconfigured, [`Error::DeviceLost`] when the GPU device was lost and must be
+    /// [recreated](Pixels::recreate_device), or when [`PixelsRenderer::resize`] fails for the
+    /// registered renderer.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Clear the pixel buffer
+    /// let frame = pixels.frame_mut();
+    /// for pixel in frame.chunks_exact_mut(4) {
+    ///     pixel[0] = 0x00; // R
+    ///     pixel[1] = 0x00; // G
+    ///     pixel[2] = 0x00; // B
+    ///     pixel[3] = 0xff; // A
+    /// }
+    ///
+    /// // Draw it to the `SurfaceTexture`
+    /// pixels.render()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn render(&mut self) -> Result<(), Error> {
+        let registered = if self.safe_mode {
+            None
+        } else {
+            self.registered_renderer.take()
+        };
+        let Some(mut registered) = registered else {
+            return self.render_with(|encoder, render_target, context| {
+                DefaultPasses.render(encoder, render_target, context);
+
+                Ok(())
+            });
+        };
+
+        let result = self.render_registered(&mut registered);
+        self.registered_renderer = Some(registered);
+
+        result
+    }
+
+    fn render_registered(
+        &mut self,
+        registered: &mut plugin::RegisteredRenderer,
+    ) -> Result<(), Error> {
+        let render_size = self.render_size();
+        let size = (render_size.width, render_size.height);
+        if registered.size != Some(size) {
+            registered.renderer.resize(&self.context, size.0, size.1)?;
+            registered.size = Some(size);
+        }
+
+        let renderer = &mut registered.renderer;
+        self.render_with_info(|info, encoder, render_target, context| {
+            renderer.prepare(info, context);
+            renderer.render(encoder, render_target, context);
+
+            Ok(())
+        })
+    }
+
+    /// Register a [`PixelsRenderer`] which [`Pixels::render`] draws in place of
+    /// [`DefaultPasses`], returning the one registered before.
+    ///
+    /// The renderer is resized before its first frame and whenever the render target changes
+    /// size, like with [`Pixels::render_with_renderers`]. Pass `None` to go back to the default
+    /// passes. Render effects and overlays are drawn after the renderer as usual.
+    ///
+    /// [Safe mode](Pixels::set_safe_mode) switches back to the default passes without dropping
+    /// the renderer, e.g. when a shader a user has written misbehaves.
+    ///
+    /// ```no_run
+    /// # use pixels::{DefaultPasses, Pixels};
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # let user_shader = DefaultPasses;
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// pixels.set_renderer(Some(Box::new(user_shader)));
+    ///
+    /// // In the event loop
+    /// if let Err(err) = pixels.render() {
+    ///     eprintln!("Custom renderer failed, falling back to the default passes: {err}");
+    ///     pixels.set_safe_mode(true);
+    ///     pixels.render()?;
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn set_renderer(
+        &mut self,
+        renderer: Option<Box<dyn PixelsRenderer>>,
+    ) -> Option<Box<dyn PixelsRenderer>> {
+        let registered = renderer.map(|renderer| plugin::RegisteredRenderer {
+            renderer,
+            size: None,
+        });
+        self.redraw_pending = true;
+
+        std::mem::replace(&mut self.registered_renderer, registered)
+            .map(|registered| registered.renderer)
+    }
+
+    /// Check if a renderer is registered with [`Pixels::set_renderer`], whether or not it is
+    /// drawn in [safe mode](Pixels::set_safe_mode).
+    pub fn has_renderer(&self) -> bool {
+        self.registered_renderer.is_some()
+    }
+
+    /// Draw [`DefaultPasses`] with [`Pixels::render`] even when a renderer is
+    /// [registered](Pixels::set_renderer).
+    ///
+    /// This toggles between the built-in scaling renderer and the custom renderer on the next
+    /// frame, without rebuilding the pixel buffer or dropping the custom renderer. The custom
+    /// renderer is not prepared, resized, or drawn while in safe mode; leaving it resizes the
+    /// renderer first if the render target changed size in the meantime.
+    pub fn set_safe_mode(&mut self, safe_mode: bool) {
+        self.safe_mode = safe_mode;
+        self.redraw_pending = true;
+    }
+
+    /// Check if [safe mode](Pixels::set_safe_mode) is enabled.
+    pub fn safe_mode(&self) -> bool {
+        self.safe_mode
+    }
+
+    /// Draw this pixel buffer with a list of [`PixelsRenderer`]s, in order.
+    ///
+    /// The renderers replace the passes drawn by [`Pixels::render`]; put [`DefaultPasses`] first
+    /// to keep them. Every renderer is prepared before the first one draws. Renderers are resized
+    /// before the first frame and whenever the render target changes size, so pass the same
+    /// renderers on every frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when [`wgpu::Surface::get_current_texture`] fails, or when
+    /// [`PixelsRenderer::resize`] fails.
+    pub fn render_with_renderers(
+        &mut self,
+        renderers: &mut [&mut dyn PixelsRenderer],
+    ) -> Result<(), Error> {
+        let render_size = self.render_size();
+        let size = (render_size.width, render_size.height);
+        if self.renderer_size != Some(size) {
+            for renderer in renderers.iter_mut() {
+                renderer.resize(&self.context, size.0, size.1)?;
+            }
+            self.renderer_size = Some(size);
+        }
+
+        self.render_with_info(|info, encoder, render_target, context| {
+            for renderer in renderers.iter_mut() {
+                renderer.prepare(info, context);
+            }
+            for renderer in renderers.iter() {
+                renderer.render(encoder, render_target, context);
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Add a post-processing [`RenderEffect`] to the end of the effect chain.
+    ///
+    /// Effects run in the order they were added, on every render: the passes of [`Pixels::render`]
+    /// or of the render function draw to an intermediate texture, every effect reads the output
+    /// of the one before it, and the last draws to the surface. Pixels allocates the
+    /// intermediate textures at the render target size and recreates them on resize. The
+    /// ambient bezel, transitions, screen flashes, and the watermark are drawn after the effects.
+    ///
+    /// With the `effects` feature, CRT curvature, scanline, and gamma effects are built in:
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "effects")]
+    /// # {
+    /// use pixels::{CrtEffect, Pixels, ScanlineEffect};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// pixels.add_effect(Box::new(ScanlineEffect::new(&pixels)?));
+    /// pixels.add_effect(Box::new(CrtEffect::new(&pixels)?));
+    /// pixels.render()?;
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// The returned id [toggles](Pixels::set_effect_enabled), [moves](Pixels::move_effect), and
+    /// [removes](Pixels::remove_effect) the effect later. It can also be
+    /// [found](Pixels::find_effect) by its [`RenderEffect::name`].
+    pub fn add_effect(&mut self, effect: Box<dyn RenderEffect>) -> EffectId {
+        let name = effect.name().to_string();
+
+        self.push_effect(name, effect)
+    }
+
+    /// Add an effect to the end of the effect chain under `name`, for [`EffectRegistry`].
+    pub(crate) fn push_effect(&mut self, name: String, effect: Box<dyn RenderEffect>) -> EffectId {
+        self.redraw_pending = true;
+        self.effects.push(name, effect)
+    }
+
+    /// Attach metadata to the frames rendered from now on, such as a camera position, a palette
+    /// index, or game state flags.
+    ///
+    /// The bytes are written to the [`PixelsContext::frame_metadata`] uniform buffer with the next
+    /// frame upload, so custom shaders and effects see the state that matches the pixels they
+    /// draw, without managing a buffer of their own. Bind it with
+    /// [`PixelsContext::frame_metadata_layout_entry`]. Bytes past the end of `metadata` are zero.
+    /// Mind the WGSL layout rules, e.g. a `vec3<f32>` is aligned to 16 bytes.
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    ///
+    /// // Matches `struct Metadata { camera: vec2<f32>, time: f32, flags: u32 }` in WGSL
+    /// #[repr(C)]
+    /// #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+    /// struct Metadata {
+    ///     camera: [f32; 2],
+    ///     time: f32,
+    ///     flags: u32,
+    /// }
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let metadata = Metadata {
+    ///     camera: [12.0, 34.0],
+    ///     time: 1.5,
+    ///     flags: 0b01,
+    /// };
+    /// pixels.set_frame_metadata(bytemuck::bytes_of(&metadata));
+    /// pixels.render()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `metadata` is longer than [`FRAME_METADATA_SIZE`].
+    pub fn set_frame_metadata(&mut self, metadata: &[u8]) {
+        assert!(metadata.len() <= FRAME_METADATA_SIZE);
+
+        let mut bytes = vec![0; FRAME_METADATA_SIZE];
+        bytes[..metadata.len()].copy_from_slice(metadata);
+        self.pending_metadata = Some(bytes);
+        self.redraw_pending = true;
+    }
+
+    /// Remove every effect from the effect chain, returning them in order.
+    pub fn clear_effects(&mut self) -> Vec<Box<dyn RenderEffect>> {
+        self.redraw_pending = true;
+        self.effects.clear()
+    }
+
+    /// Remove an effect from the effect chain and return it, or `None` when it is not in the
+    /// chain.
+    pub fn remove_effect(&mut self, id: EffectId) -> Option<Box<dyn RenderEffect>> {
+        self.redraw_pending = true;
+        self.effects.remove(id)
+    }
+
+    /// Get the ids of the effects in the effect chain, in the order they run.
+    pub fn effect_ids(&self) -> impl Iterator<Item = EffectId> + '_ {
+        self.effects.ids()
+    }
+
+    /// Find the first effect in the effect chain with the name `name`.
+    ///
+    /// Effects created by an [`EffectRegistry`] have the name of their [`EffectPreset`], e.g.
+    /// `"scanlines"`, and other effects the [`RenderEffect::name`] they were added with. The
+    /// built-in effects have the names of their built-in presets, so a `ScanlineEffect` is
+    /// `"scanlines"` either way.
+    pub fn find_effect(&self, name: &str) -> Option<EffectId> {
+        self.effects.find(name)
+    }
+
+    /// Get the name of an effect, see [`Pixels::find_effect`], or `None` when it is not in the
+    /// effect chain.
+    pub fn effect_name(&self, id: EffectId) -> Option<&str> {
+        self.effects.name(id)
+    }
+
+    /// Get an effect in the effect chain mutably, e.g. to change its parameters, or `None` when
+    /// it is not in the chain.
+    pub fn effect_mut(&mut self, id: EffectId) -> Option<&mut dyn RenderEffect> {
+        self.redraw_pending = true;
+        self.effects.get_mut(id)
+    }
+
+    /// Check whether an effect runs, or `None` when it is not in the effect chain.
+    pub fn is_effect_enabled(&self, id: EffectId) -> Option<bool> {
+        self.effects.is_enabled(id)
+    }
+
+    /// Enable or disable an effect in the effect chain, e.g. from a settings menu. Returns `false`
+    /// when the effect is not in the chain.
+    ///
+    /// Disabled effects keep their place and their resources, but are skipped as if they were
+    /// removed. Effects are enabled when they are added.
+    ///
+    /// Only the effects after this one may read another intermediate texture afterward, and only
+    /// those are [resized](RenderEffect::resize); no pipelines are created, so toggling is cheap
+    /// enough to do at any time.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "effects")]
+    /// # {
+    /// use pixels::{Pixels, ScanlineEffect};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let scanlines = pixels.add_effect(Box::new(ScanlineEffect::new(&pixels)?));
+    ///
+    /// // The player switched scanlines off
+    /// pixels.set_effect_enabled(scanlines, false);
+    /// assert_eq!(pixels.is_effect_enabled(scanlines), Some(false));
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn set_effect_enabled(&mut self, id: EffectId, enabled: bool) -> bool {
+        self.redraw_pending = true;
+        self.effects.set_enabled(id, enabled)
+    }
+
+    /// Move an effect to position `index` of the effect chain, or to the end when `index` is
+    /// past it. Returns `false` when the effect is not in the chain.
+    ///
+    /// Like [`Pixels::set_effect_enabled`], this only resizes the effects which read another
+    /// intermediate texture afterward.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "effects")]
+    /// # {
+    /// use pixels::{CrtEffect, Pixels, ScanlineEffect};
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let crt = pixels.add_effect(Box::new(CrtEffect::new(&pixels)?));
+    /// let scanlines = pixels.add_effect(Box::new(ScanlineEffect::new(&pixels)?));
+    ///
+    /// // Draw the scanlines before the curvature, so that they bend with the screen
+    /// pixels.move_effect(scanlines, 0);
+    /// assert!(pixels.effect_ids().eq([scanlines, crt]));
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn move_effect(&mut self, id: EffectId, index: usize) -> bool {
+        self.redraw_pending = true;
+        self.effects.move_to(id, index)
+    }
+
+    /// Animate the [parameter](RenderEffect::param) `param` of an effect from its current value
+    /// to `to` over `duration`, for fades, flashes, and warm-up animations.
+    ///
+    /// The parameter is [set](RenderEffect::set_param) by every render until the tween ends with
+    /// exactly `to`, so the application does not update it every frame. Renders are requested
+    /// while tweens are in progress, also with [`RedrawPolicy::OnDirty`]. A new tween of the same
+    /// parameter replaces one in progress, starting from the value it reached. A tween stops
+    /// early when the effect rejects a value, and when the effect is removed.
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "effects")]
+    /// # {
+    /// use pixels::{CrtEffect, Easing, Pixels};
+    /// use std::time::Duration;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // The picture of a cold CRT brightens from black edges
+    /// let crt = CrtEffect::new(&pixels)?.with_vignette(1.0);
+    /// let crt = pixels.add_effect(Box::new(crt));
+    /// pixels.tween_effect_param(crt, "vignette", 0.5, Duration::from_secs(2), Easing::EaseOut)?;
+    /// # }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EffectError::UnknownEffect`] when the effect is not in the effect chain, or
+    /// [`EffectError::UnknownParameter`] when it has no parameter `param`.
+    pub fn tween_effect_param(
+        &mut self,
+        id: EffectId,
+        param: &str,
+        to: f32,
+        duration: Duration,
+        easing: Easing,
+    ) -> Result<(), EffectError> {
+        self.redraw_pending = true;
+        self.effects.tween(id, param, to, duration, easing)
+    }
+
+    /// Copy the newest frame published to a [`SharedFrame`] into the pixel buffer, then draw it
+    /// to the configured [`SurfaceTexture`].
+    ///
+    /// When no frame was published since the last call, the pixel buffer is drawn unchanged. This
+    /// never waits for the producer thread.
+    ///
+    /// ```no_run
+    /// # use pixels::{Pixels, SharedFrame};
+    /// # use std::sync::Arc;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let shared = Arc::new(SharedFrame::new(pixels.frame().len()));
+    ///
+    /// let producer = Arc::clone(&shared);
+    /// std::thread::spawn(move || loop {
+    ///     producer.write(|frame| frame.fill(0x80));
+    /// });
+    ///
+    /// // In the event loop
+    /// pixels.render_shared(&shared)?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when [`wgpu::Surface::get_current_texture`] fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a new frame is published and the length of `shared` differs from the pixel
+    /// buffer.
+    pub fn render_shared(&mut self, shared: &SharedFrame) -> Result<(), Error> {
+        if shared.read(Arc::make_mut(&mut self.pixels).as_mut_slice()) {
+            self.frame_changed = true;
+
+            let extent = self.context.texture_extent;
+            self.mark_dirty((0, 0, extent.width, extent.height));
+        }
+        self.render()
+    }
+
+    /// Draw this pixel buffer to the configured [`SurfaceTexture`] using a custom user-provided
+    /// render function.
+    ///
+    /// Provides access to a [`wgpu::CommandEncoder`], a [`wgpu::TextureView`] from the surface
+    /// which you can use to render to the screen, and a [`PixelsContext`] with all of the internal
+    /// `wgpu` context.
+    ///
+    /// With [supersampling](PixelsBuilder::supersampling), the render target is an intermediate
+    /// texture [`Pixels::supersampling`] times the size of the surface, which is downsampled to
+    /// the surface after the render function returns.
+    /// With [effects](Pixels::add_effect), the render target is the input texture of the first
+    /// effect, at the same size.
+    ///
+    /// The render function must return a `Result`. This allows fallible render functions to be
+    /// handled gracefully. The boxed `Error` will be made available in the [`Error::UserDefined`]
+    /// variant returned by `render_with()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when either [`wgpu::Surface::get_current_texture`] or the provided render
+    /// function fails, or [`Error::DeviceLost`], like [`Pixels::render`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Clear the pixel buffer
+    /// let frame = pixels.frame_mut();
+    /// for pixel in frame.chunks_exact_mut(4) {
+    ///     pixel[0] = 0x00; // R
+    ///     pixel[1] = 0x00; // G
+    ///     pixel[2] = 0x00; // B
+    ///     pixel[3] = 0xff; // A
+    /// }
+    ///
+    /// // Draw it to the `SurfaceTexture`
+    /// pixels.render_with(|encoder, render_target, context| {
+    ///     context.scaling_renderer.render(encoder, render_target);
+    ///     // etc...
+    ///     Ok(())
+    /// })?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn render_with<F>(&mut self, render_function: F) -> Result<(), Error>
+    where
+        F: FnOnce(
+            &mut wgpu::CommandEncoder,
+            &wgpu::TextureView,
+            &PixelsContext,
+        ) -> Result<(), DynError>,
+    {
+        self.render_with_info(|_, encoder, render_target, context| {
+            render_function(encoder, render_target, context)
+        })
+    }
+
+    /// Draw this pixel buffer like [`Pixels::render_with`], passing [`FrameInfo`] to the render
+    /// function.
+    ///
+    /// The frame index, elapsed time, and clipping rectangle let custom passes and effects animate
+    /// without keeping their own bookkeeping.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when either [`wgpu::Surface::get_current_texture`] or the provided render
+    /// function fails, or [`Error::DeviceLost`], like [`Pixels::render`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// pixels.render_with_info(|info, encoder, render_target, context| {
+    ///     context.scaling_renderer.render(encoder, render_target);
+    ///
+    ///     // Pulse an effect once per second
+    ///     let pulse = (info.elapsed.as_secs_f32() * std::f32::consts::TAU).sin();
+    ///     // etc...
+    ///     Ok(())
+    /// })?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn render_with_info<F>(&mut self, render_function: F) -> Result<(), Error>
+    where
+        F: FnOnce(
+            &FrameInfo,
+            &mut wgpu::CommandEncoder,
+            &wgpu::TextureView,
+            &PixelsContext,
+        ) -> Result<(), DynError>,
+    {
+        if self.surface_paused {
+            return Ok(());
+        }
+        if self.is_device_lost() {
+            return Err(Error::DeviceLost);
+        }
+
+        if self.surface_resize_pending {
+            self.reconfigure_surface();
+        }
+        let frame = match &self.context.surface {
+            Some(surface) => match surface.get_current_texture().or_else(|_| {
+                // Reconfigure the surface and retry immediately on any error.
+                // See https://github.com/parasyte/pixels/issues/121
+                // See https://github.com/parasyte/pixels/issues/346
+                self.reconfigure_surface();
+                self.context
+                    .surface
+                    .as_ref()
+                    .expect("the surface exists")
+                    .get_current_texture()
+            }) {
+                Ok(frame) => Some(frame),
+                // Surfaces can stay outdated for a few frames while the window is resized, e.g.
+                // on Wayland; skip the frame and keep the redraw pending
+                Err(wgpu::SurfaceError::Timeout | wgpu::SurfaceError::Outdated) => return Ok(()),
+                Err(err) => return Err(err.into()),
+            },
+            None => None,
+        };
+        let changed = self.redraw_pending;
+        let mut encoder =
+            self.context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("pixels_command_encoder"),
+                });
+        self.output_picker.poll(&self.context.device);
+        if let Some(recorder) = &mut self.recorder {
+            recorder.collect(&self.context.device, false);
+        }
+        if let Some(input_latency) = self.latency_probe.take_finished() {
+            self.context.profiler.report_input_latency(input_latency);
+        }
+        self.context
+            .profiler
+            .start_frame(&self.context.device, &mut encoder);
+
+        // Update the pixel buffer texture view
+        let upload_start = Instant::now();
+        if self.imported_texture.is_none() {
+            self.upload_frame(&mut encoder);
+        }
+        if let Some(metadata) = self.pending_metadata.take() {
+            self.context
+                .queue
+                .write_buffer(&self.context.frame_metadata, 0, &metadata);
+        }
+        // Copy textures which were not reused since the previous render are dropped
+        self.spare_copy_textures.clear();
+        for (texture, (x, y)) in self.pending_copies.drain(..) {
+            encoder.copy_texture_to_texture(
+                texture.as_image_copy(),
+                wgpu::ImageCopyTexture {
+                    origin: wgpu::Origin3d { x, y, z: 0 },
+                    ..self.context.texture.as_image_copy()
+                },
+                texture.size(),
+            );
+            self.spare_copy_textures.push(texture);
+        }
+        if let Some(blitter) = self.target_blitter.as_ref() {
+            if !self.pending_blits.is_empty() {
+                blitter.render(&mut encoder, &self.context.texture, &self.pending_blits);
+                self.pending_blits.clear();
+            }
+        }
+        if let Some(renderer) = self.text_grid_renderer.as_ref() {
+            if !self.pending_text_grids.is_empty() {
+                renderer.render(
+                    &mut encoder,
+                    &self.context.texture,
+                    &self.pending_text_grids,
+                );
+                self.pending_text_grids.clear();
+            }
+        }
+        if let Some(renderer) = self.sprite_renderer.as_ref() {
+            if !self.pending_sprites.is_empty() {
+                renderer.render(&mut encoder, &self.context.texture, &self.pending_sprites);
+                self.pending_sprites.clear();
+            }
+        }
+        self.drawn_rects.append(&mut self.pending_drawn_rects);
+        self.composite_layers(&mut encoder);
+        let upload = upload_start.elapsed();
+
+        let output_texture = match &frame {
+            Some(frame) => &frame.texture,
+            None => self
+                .offscreen_texture
+                .as_ref()
+                .expect("offscreen pixel buffers have an output texture"),
+        };
+        let view = output_texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(self.surface_texture_format),
+            ..Default::default()
+        });
+
+        let now = Instant::now();
+        let info = FrameInfo {
+            index: self.frame_index,
+            elapsed: self
+                .first_frame_time
+                .map_or(Duration::ZERO, |first| now - first),
+            delta: self
+                .previous_frame_time
+                .map_or(Duration::ZERO, |previous| now - previous),
+            clip_rect: self.context.scaling_renderer.clip_rect(),
+            view: self.view(),
+            rotation: self.scaling_options.rotation,
+        };
+
+        // Apply screen shake before the scaling pass is drawn
+        let shake_offset = match self.shake.as_mut().and_then(|shake| shake.offset(now)) {
+            Some((x, y)) => {
+                let factor = self.supersampling() as f32;
+                (x * factor, y * factor)
+            }
+            None => {
+                self.shake = None;
+                (0.0, 0.0)
+            }
+        };
+        self.context
+            .scaling_renderer
+            .set_offset(&self.context.queue, shake_offset);
+
+        // Call the user's render function and the effects, then fade out the frame from before a
+        // resize.
+        let averaged_view = self
+            .provenance_renderer
+            .as_ref()
+            .map_or(&view, |provenance| provenance.texture_view());
+        let output_view = self
+            .frame_averager
+            .as_ref()
+            .map_or(averaged_view, |averager| averager.texture_view());
+        let render_target = self
+            .supersampler
+            .as_ref()
+            .map_or(output_view, |supersampler| supersampler.texture_view());
+        let render_size = self.render_size();
+        self.effects.update_tweens(now);
+        self.effects.prepare(
+            &info,
+            &self.context,
+            (render_size.width, render_size.height),
+            self.render_texture_format,
+        )?;
+        // The redraw stays pending when the effects cannot be prepared, so the next render retries
+        self.redraw_pending = false;
+        let effect_input = self.effects.input().unwrap_or(render_target);
+        self.context
+            .scope(&mut encoder, "render_function", |encoder| {
+                (render_function)(&info, encoder, effect_input, &self.context)
+            })?;
+        self.effects
+            .render(&mut encoder, render_target, &self.context);
+        // The passes below borrow most of `self`, so the scope is opened by hand
+        encoder.push_debug_group("pixels_compositing");
+        let compositing = self
+            .context
+            .profiler
+            .begin(&mut encoder, "pixels_compositing");
+        if let Some(background_renderer) = &self.background_renderer {
+            let render_size = self.render_size();
+            background_renderer.render(
+                &self.context.queue,
+                &mut encoder,
+                render_target,
+                (render_size.width, render_size.height),
+                self.context.scaling_renderer.clip_rect(),
+                self.supersampling(),
+            );
+        }
+        if let Some(bezel_renderer) = self
+            .bezel_renderer
+            .as_ref()
+            .filter(|_| !self.scaling_options.minimal)
+        {
+            let render_size = self.render_size();
+            bezel_renderer.render(
+                &self.context.device,
+                &self.context.queue,
+                &mut encoder,
+                render_target,
+                (render_size.width, render_size.height),
+                &self.context.texture,
+                self.context.scaling_renderer.clip_rect(),
+            );
+        }
+        if let Some(crossfade) = self.crossfade.as_mut() {
+            if !crossfade.render(&mut encoder, render_target) {
+                self.crossfade = None;
+            }
+        }
+        if let Some(transition) = self.transition.as_mut() {
+            if !transition.render(&self.context.queue, &mut encoder, render_target) {
+                self.transition = None;
+            }
+        }
+        match self
+            .screen_flash
+            .as_ref()
+            .and_then(|flash| flash.color(now))
+        {
+            Some(color) => {
+                if let Some(color_overlay_renderer) = &self.color_overlay_renderer {
+                    color_overlay_renderer.render(
+                        &self.context.queue,
+                        &mut encoder,
+                        render_target,
+                        color,
+                    );
+                }
+            }
+            None => self.screen_flash = None,
+        }
+        if let Some(watermark_renderer) = &self.watermark_renderer {
+            watermark_renderer.render(&mut encoder, render_target);
+        }
+        let input = self.latency_probe.take_input();
+        if let Some(probe_renderer) = &self.probe_renderer {
+            // White answers an input, and black shows that the frame does not
+            let color = if input.is_some() {
+                wgpu::Color::WHITE
+            } else {
+                wgpu::Color::BLACK
+            };
+            let size = latency::PATCH_SIZE * self.supersampling();
+            let rect = (
+                0,
+                0,
+                size.min(render_size.width),
+                size.min(render_size.height),
+            );
+            probe_renderer.render_rect(
+                &self.context.queue,
+                &mut encoder,
+                render_target,
+                color,
+                Some(rect),
+            );
+        }
+        if let Some(supersampler) = &self.supersampler {
+            supersampler.render(&mut encoder, output_view);
+        }
+        if let Some(frame_averager) = self.frame_averager.as_mut() {
+            frame_averager.render(&self.context.queue, &mut encoder, averaged_view, changed);
+        }
+        if let Some(provenance_renderer) = &self.provenance_renderer {
+            provenance_renderer.render(&self.context.queue, &mut encoder, &view, self.frame_index);
+        }
+        self.context.profiler.end(&mut encoder, compositing);
+        encoder.pop_debug_group();
+        self.output_picker
+            .copy(&self.context.device, &mut encoder, output_texture);
+        self.frame_capture.copy(
+            &self.context.device,
+            &mut encoder,
+            output_texture,
+            &self.context.texture,
+        );
+        if let Some(recorder) = &mut self.recorder {
+            recorder.copy(
+                &self.context.device,
+                &mut encoder,
+                self.frame_index,
+                output_texture,
+                &self.context.texture,
+            );
+        }
+        self.context
+            .profiler
+            .finish(&mut encoder, self.frame_index, upload);
+
+        let submitted = Instant::now();
+        self.submit_upload(encoder);
+        self.context.profiler.submitted(submitted.elapsed());
+        self.output_picker.map();
+        self.frame_capture.map();
+        if let Some(recorder) = &mut self.recorder {
+            recorder.map();
+        }
+        if let Some(frame) = frame {
+            frame.present();
+        }
+        let presented = Instant::now();
+        self.last_present_time = Some(presented);
+        self.latency_probe
+            .presented(&self.context.queue, self.frame_index, input, presented);
+        if let Some(sender) = self.present_events.clone() {
+            let index = self.frame_index;
+            self.context.queue.on_submitted_work_done(move || {
+                // The receiver may be gone; the sender is replaced on the next subscription
+                let _ = sender.send(PresentEvent {
+                    index,
+                    submitted,
+                    presented,
+                    completed: Instant::now(),
+                });
+            });
+        }
+
+        self.frame_index += 1;
+        self.first_frame_time.get_or_insert(now);
+        self.previous_frame_time = Some(now);
+
+        Ok(())
+    }
+
+    /// Copy the pixel buffer to the next source texture in the ring.
+    fn upload_frame(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        // Layers are blended over the texture, so none of the previous frame may remain under them
+        if !self.layers.is_empty() {
+            self.full_upload = true;
+        }
+
+        let height = self.context.texture_extent.height;
+        let uncompressed = self.context.texture_format.block_dimensions() == (1, 1);
+        let slices = if uncompressed {
+            self.upload_slices.min(height)
+        } else {
+            1
+        };
+
+        // While interpolating, renders without a new frame keep the textures as they are, so that
+        // the ring holds the previous frame
+        let interpolating =
+            self.frame_interpolator.is_some() && self.dirty_rects.is_none() && slices <= 1;
+        let unchanged = interpolating && !self.frame_changed && !self.full_upload;
+        let mut whole_upload = true;
+
+        if unchanged {
+            whole_upload = false;
+            // Nothing to upload
+        } else if let Some(rects) = self.dirty_rects.as_mut().map(std::mem::take) {
+            // Damage accumulates in one texture, so the ring is not rotated
+            if self.full_upload || !uncompressed {
+                self.write_rows(encoder, 0, height);
+                self.full_upload = false;
+            } else {
+                for rect in rects {
+                    self.write_rect(encoder, rect);
+                }
+                whole_upload = false;
+            }
+        } else if slices <= 1 || self.full_upload {
+            // Sliced uploads accumulate in one texture, so only whole uploads rotate the ring
+            if slices <= 1 {
+                if let Some(texture) = self.spare_textures.pop_front() {
+                    let previous = std::mem::replace(&mut self.context.texture, texture);
+                    self.spare_textures.push_back(previous);
+                    self.context.scaling_renderer.rotate_source();
+                }
+            }
+            self.write_rows(encoder, 0, height);
+            self.full_upload = false;
+            self.upload_slice = 0;
+        } else {
+            let slice = self.upload_slice % slices;
+            whole_upload = false;
+            match self.upload_order {
+                UploadOrder::Sequential => {
+                    let start = slice * height / slices;
+                    let end = (slice + 1) * height / slices;
+                    self.write_rows(encoder, start, end - start);
+                }
+                UploadOrder::Interleaved => {
+                    for y in (slice..height).step_by(slices as usize) {
+                        self.write_rows(encoder, y, 1);
+                    }
+                }
+            }
+            self.upload_slice = (slice + 1) % slices;
+        }
+        self.frame_changed = false;
+
+        // Copies, blits, text grids, and sprites of the last render were drawn into the texture;
+        // restore the frame under them
+        let drawn_rects = std::mem::take(&mut self.drawn_rects);
+        if !whole_upload {
+            for rect in drawn_rects {
+                self.write_rect(encoder, rect);
+            }
+        }
+
+        if let Some(interpolator) = self.frame_interpolator.as_mut() {
+            let now = Instant::now();
+            if !interpolating {
+                interpolator.reset();
+            } else if !unchanged {
+                interpolator.uploaded(now);
+            }
+            self.context
+                .scaling_renderer
+                .set_previous_frame_opacity(interpolator.opacity(now));
+        }
+
+        // Measure the frame and attenuate large luminance swings
+        if let Some(flash_limiter) = self
+            .flash_limiter
+            .as_mut()
+            .filter(|_| !self.scaling_options.minimal)
+        {
+            let attenuation = flash_limiter
+                .update(&self.pixels, self.context.texture_format)
+                .unwrap_or(flash::Attenuation::NONE);
+            self.context
+                .scaling_renderer
+                .set_attenuation(&self.context.queue, attenuation);
+        }
+    }
+
+    /// Upload the layers which changed, then blend the visible ones over the pixel buffer texture.
+    fn composite_layers(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let Some(compositor) = self.layer_compositor.as_ref() else {
+            return;
+        };
+        if self.imported_texture.is_some() {
+            return;
+        }
+
+        for layer in &mut self.layers {
+            layer.upload(&self.context.queue);
+        }
+        let visible = self.layers.iter().filter(|layer| layer.is_visible());
+        compositor.render(
+            encoder,
+            &self.context.texture,
+            visible.map(Layer::bind_group),
+        );
+    }
+
+    /// Submit `encoder` with the copies of [`Pixels::upload_frame`], remapping the staging buffer.
+    fn submit_upload(&mut self, encoder: wgpu::CommandEncoder) {
+        if let Some(staging_ring) = self.staging_ring.as_mut() {
+            staging_ring.unmap();
+        }
+        self.context.queue.submit(Some(encoder.finish()));
+        if let Some(staging_ring) = self.staging_ring.as_mut() {
+            staging_ring.map();
+        }
+    }
+
+    /// Copy `rows` rows of the pixel buffer starting at row `y` to the texture.
+    fn write_rows(&mut self, encoder: &mut wgpu::CommandEncoder, y: u32, rows: u32) {
+        self.write_rect(encoder, (0, y, self.context.texture_extent.width, rows));
+    }
+
+    /// Copy the region `rect` (x, y, width, height) of the pixel buffer to the texture.
+    fn write_rect(&mut self, encoder: &mut wgpu::CommandEncoder, rect: (u32, u32, u32, u32)) {
+        if let Some(staging_ring) = self.staging_ring.as_mut() {
+            let texture = &self.context.texture;
+            if texture.format().block_dimensions() == (1, 1)
+                && staging_ring.write(&self.context.device, encoder, texture, &self.pixels, rect)
+            {
+                return;
+            }
+        }
+
+        let (x, y, width, rows) = rect;
+        let bytes_per_row =
+            (self.context.texture_extent.width as f32 * self.context.texture_format_size) as u32;
+        let start = y as usize * bytes_per_row as usize;
+        let end = start + rows as usize * bytes_per_row as usize;
+
+        self.context.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.context.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &self.pixels[start..end],
+            wgpu::ImageDataLayout {
+                offset: (x as f32 * self.context.texture_format_size) as u64,
+                bytes_per_row: Some(bytes_per_row),
+                rows_per_image: Some(rows),
+            },
+            wgpu::Extent3d {
+                width,
+                height: rows,
+                ..self.context.texture_extent
+            },
+        );
+    }
+
+    /// The present mode the surface is configured with, after workarounds and fallbacks.
+    fn surface_present_mode(&self) -> wgpu::PresentMode {
+        use wgpu::PresentMode;
+
+        if self
+            .workarounds
+            .contains(&workarounds::Workaround::FifoPresentMode)
+        {
+            return PresentMode::Fifo;
+        }
+
+        // The automatic modes always resolve to a supported mode; explicit ones may not exist
+        let supported = match self.present_mode {
+            PresentMode::AutoVsync | PresentMode::AutoNoVsync => true,
+            mode => self.surface_capabilities().present_modes.contains(&mode),
+        };
+        match self.present_mode {
+            mode if supported => mode,
+            PresentMode::Fifo | PresentMode::FifoRelaxed => PresentMode::AutoVsync,
+            _ => PresentMode::AutoNoVsync,
+        }
+    }
+
+    /// The capabilities of the surface, or those of the internal texture of offscreen pixel
+    /// buffers.
+    fn surface_capabilities(&self) -> wgpu::SurfaceCapabilities {
+        match &self.context.surface {
+            Some(surface) => surface.get_capabilities(&self.adapter),
+            None => builder::offscreen_capabilities(),
+        }
+    }
+
+    /// Reconfigure the surface.
+    ///
+    /// Call this when the surface or presentation mode needs to be changed. Offscreen pixel
+    /// buffers recreate their output texture instead.
+    pub(crate) fn reconfigure_surface(&mut self) {
+        self.surface_resize_pending = false;
+        let view_formats = if self.surface_storage_format == self.surface_texture_format {
+            vec![]
+        } else {
+            vec![self.surface_texture_format]
+        };
+
+        let Some(surface) = &self.context.surface else {
+            self.offscreen_texture = Some(self.context.device.create_texture(
+                &wgpu::TextureDescriptor {
+                    label: Some("pixels_offscreen_texture"),
+                    size: wgpu::Extent3d {
+                        width: self.surface_size.width,
+                        height: self.surface_size.height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.surface_storage_format,
+                    usage: self.surface_usage | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &view_formats,
+                },
+            ));
+            return;
+        };
+
+        surface.configure(
+            &self.context.device,
+            &wgpu::SurfaceConfiguration {
+                usage: self.surface_usage,
+                format: self.surface_storage_format,
+                width: self.surface_size.width,
+                height: self.surface_size.height,
+                present_mode: self.surface_present_mode(),
+                desired_maximum_frame_latency: 2,
+                alpha_mode: self.alpha_mode,
+                view_formats,
+            },
+        );
+    }
+
+    /// Get a mutable byte slice for the pixel buffer. The buffer is _not_ cleared for you; it will
+    /// retain the previous frame's contents until you clear it yourself.
+    pub fn frame_mut(&mut self) -> &mut [u8] {
+        self.redraw_pending = true;
+        self.frame_changed = true;
+        Arc::make_mut(&mut self.pixels).as_mut_slice()
+    }
+
+    /// Get the pixel buffer as a [`Frame`], with bounds-checked helpers for drawing pixels,
+    /// filling, and blitting.
+    ///
+    /// This borrows the buffer like [`Pixels::frame_mut`], which keeps returning the raw byte
+    /// slice rather than a [`Frame`], so that existing code which indexes and slices the frame
+    /// does not break.
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let mut frame = pixels.frame_mut_view();
+    /// frame.fill(&[0x00, 0x00, 0x00, 0xff]);
+    ///
+    /// // Out-of-bounds pixels are skipped instead of panicking
+    /// for x in -10..330 {
+    ///     frame.set_pixel(x, 120, &[0xff, 0xff, 0xff, 0xff]);
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics with block compressed texture formats, which have no individual pixels.
+    pub fn frame_mut_view(&mut self) -> Frame<'_> {
+        assert_eq!(
+            self.context.texture_format.block_dimensions(),
+            (1, 1),
+            "block compressed formats have no individual pixels",
+        );
+
+        let width = self.context.texture_extent.width;
+        let height = self.context.texture_extent.height;
+        let bytes_per_pixel = self.context.texture_format_size as usize;
+
+        Frame::new(self.frame_mut(), width, height, bytes_per_pixel)
+    }
+
+    /// Get the pixel buffer as a [`tiny_skia::PixmapMut`], for drawing antialiased paths,
+    /// shapes, and images directly into it.
+    ///
+    /// This borrows the buffer like [`Pixels::frame_mut`], so nothing is copied before the
+    /// upload. `tiny-skia` stores premultiplied alpha: opaque pixels are the same either way, but
+    /// translucent pixels it draws are premultiplied, which matters when the buffer is
+    /// [blended](PixelsBuilder::blend_state) over other content.
+    ///
+    /// ```no_run
+    /// use pixels::tiny_skia::{Color, FillRule, Paint, PathBuilder, Transform};
+    /// use pixels::Pixels;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let mut canvas = pixels.canvas_mut();
+    /// canvas.fill(Color::BLACK);
+    ///
+    /// let circle = PathBuilder::from_circle(160.0, 120.0, 50.0).unwrap();
+    /// let mut paint = Paint::default();
+    /// paint.set_color_rgba8(0xff, 0x80, 0x00, 0xff);
+    /// canvas.fill_path(&circle, &paint, FillRule::Winding, Transform::identity(), None);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when the texture format is not `Rgba8UnormSrgb` or `Rgba8Unorm`, the only layouts
+    /// `tiny-skia` draws.
+    #[cfg(feature = "canvas")]
+    pub fn canvas_mut(&mut self) -> tiny_skia::PixmapMut<'_> {
+        assert!(
+            matches!(
+                self.context.texture_format,
+                wgpu::TextureFormat::Rgba8UnormSrgb | wgpu::TextureFormat::Rgba8Unorm
+            ),
+            "the canvas needs an RGBA8 texture format",
+        );
+
+        let width = self.context.texture_extent.width;
+        let height = self.context.texture_extent.height;
+
+        tiny_skia::PixmapMut::from_bytes(self.frame_mut(), width, height)
+            .expect("the pixel buffer matches the texture size")
+    }
+
+    /// Get the number of [layers](Layer) over the pixel buffer, see [`PixelsBuilder::layers`].
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Get the layer `index` over the pixel buffer. Layer 0 is drawn first, right over the pixel
+    /// buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `index` is not less than [`Pixels::layer_count`].
+    pub fn layer(&self, index: usize) -> &Layer {
+        &self.layers[index]
+    }
+
+    /// Get the layer `index` over the pixel buffer, to draw into it or change its scroll offset
+    /// and opacity.
+    ///
+    /// ```no_run
+    /// use pixels::PixelsBuilder;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// // A far background in the pixel buffer, with hills and a HUD in layers over it
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .layers(2)
+    ///     .build()?;
+    /// # let camera_x = 0;
+    ///
+    /// // Scroll the hills at half the speed of the camera, and leave the HUD in place
+    /// pixels.layer_mut(0).set_offset(-camera_x / 2, 0);
+    /// pixels.layer_mut(1).frame_mut()[..4].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+    /// pixels.layer_mut(1).set_opacity(0.8);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `index` is not less than [`Pixels::layer_count`].
+    pub fn layer_mut(&mut self, index: usize) -> &mut Layer {
+        self.redraw_pending = true;
+        &mut self.layers[index]
+    }
+
+    /// Exchange the pixel buffer with `frame`, without copying either.
+    ///
+    /// This is double or triple buffering for game logic running on another thread: the thread
+    /// draws into a buffer it owns and sends it to the render thread, which swaps it in and sends
+    /// the previous front buffer back to be drawn again. Neither thread waits for the other to
+    /// draw or upload, and `Pixels` does not need a lock. Create the back buffers with the same
+    /// length as [`Pixels::frame`]. After the swap, `frame` holds the previous frame. It is
+    /// copied only when a [`FrameSnapshot`] of it is alive.
+    ///
+    /// For a producer which should never wait for a free buffer, see [`SharedFrame`].
+    ///
+    /// ```no_run
+    /// use std::sync::mpsc;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # fn draw_world(frame: &mut [u8]) {}
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let (drawn_sender, drawn) = mpsc::sync_channel::<Vec<u8>>(1);
+    /// let (free_sender, free) = mpsc::sync_channel::<Vec<u8>>(2);
+    ///
+    /// // Two back buffers make triple buffering with the pixel buffer
+    /// for _ in 0..2 {
+    ///     free_sender.send(vec![0; pixels.frame().len()]).unwrap();
+    /// }
+    ///
+    /// // Game logic thread
+    /// std::thread::spawn(move || {
+    ///     for mut frame in free {
+    ///         draw_world(&mut frame);
+    ///         if drawn_sender.send(frame).is_err() {
+    ///             break;
+    ///         }
+    ///     }
+    /// });
+    ///
+    /// // Render thread, on every redraw
+    /// if let Ok(mut frame) = drawn.try_recv() {
+    ///     pixels.swap_frame(&mut frame);
+    ///     free_sender.send(frame).unwrap();
+    /// }
+    /// pixels.render()?;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `frame` is not as long as the pixel buffer.
+    pub fn swap_frame(&mut self, frame: &mut Vec<u8>) {
+        assert_eq!(frame.len(), self.pixels.len());
+
+        let front = std::mem::replace(&mut self.pixels, Arc::new(std::mem::take(frame)));
+        *frame = Arc::try_unwrap(front).unwrap_or_else(|front| front.as_ref().clone());
+        self.redraw_pending = true;
+        self.frame_changed = true;
+
+        let extent = self.context.texture_extent;
+        self.mark_dirty((0, 0, extent.width, extent.height));
+    }
+
+    /// Copy a frame with padded rows into the pixel buffer.
+    ///
+    /// `stride` is the number of bytes between the starts of two rows in `src`, which is commonly
+    /// larger than the row length for camera and video frames. The pixel format of `src` must match
+    /// the texture format. See the [`ingest`] module for YUV conversions.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Rows aligned to 256 bytes
+    /// let camera_frame = vec![0; 1536 * 240];
+    /// pixels.copy_strided(&camera_frame, 1536)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`ingest::IngestError::InvalidStride`] when `stride` is smaller than a row of the pixel
+    ///   buffer.
+    /// - [`ingest::IngestError::SourceTooSmall`] when `src` does not hold all rows.
+    pub fn copy_strided(&mut self, src: &[u8], stride: usize) -> Result<(), ingest::IngestError> {
+        self.redraw_pending = true;
+        self.frame_changed = true;
+        let row_len =
+            (self.context.texture_extent.width as f32 * self.context.texture_format_size) as usize;
+        let rows = self.context.texture_extent.height as usize;
+
+        ingest::copy_strided(
+            Arc::make_mut(&mut self.pixels).as_mut_slice(),
+            row_len,
+            ingest::Plane::new(src, stride),
+            row_len,
+            rows,
+        )?;
+
+        let extent = self.context.texture_extent;
+        self.mark_dirty((0, 0, extent.width, extent.height));
+
+        Ok(())
+    }
+
+    /// Copy the latest frame from a shared-memory producer into the pixel buffer.
+    ///
+    /// Returns the [`shm::SharedFrameStatus`] of the read. The pixel buffer should only be
+    /// presented when the status is not [`shm::SharedFrameStatus::Contended`]. See the [`shm`]
+    /// module for a description of the protocol.
+    ///
+    /// ```no_run
+    /// use pixels::shm::{SharedFrameReader, SharedFrameStatus};
+    /// # use std::sync::atomic::AtomicU32;
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # let region: &[AtomicU32] = &[];
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // `region` is a view of memory mapped from the producer process
+    /// let mut reader = SharedFrameReader::new(region)?;
+    /// if pixels.read_shared_frame(&mut reader)? != SharedFrameStatus::Contended {
+    ///     pixels.render()?;
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`shm::SharedFrameError::SizeMismatch`] when the shared frame does not have the same
+    /// size as the pixel buffer.
+    pub fn read_shared_frame(
+        &mut self,
+        reader: &mut shm::SharedFrameReader<'_>,
+    ) -> Result<shm::SharedFrameStatus, shm::SharedFrameError> {
+        let status = reader.read(Arc::make_mut(&mut self.pixels).as_mut_slice())?;
+        if status == shm::SharedFrameStatus::Updated {
+            self.redraw_pending = true;
+            self.frame_changed = true;
+
+            let extent = self.context.texture_extent;
+            self.mark_dirty((0, 0, extent.width, extent.height));
+        }
+
+        Ok(status)
+    }
+
+    /// Get an immutable byte slice for the pixel buffer.
+    ///
+    /// This may be useful for operations that must sample the buffer, such as blending pixel
+    /// colours directly into it.
+    pub fn frame(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Take an immutable [`FrameSnapshot`] of the pixel buffer, without copying it.
+    ///
+    /// The snapshot stays valid while the frame is changed and rendered, so other threads can
+    /// read the displayed frame concurrently. Take it after [`Pixels::render`] to get the frame
+    /// that was just presented.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let (sender, receiver) = std::sync::mpsc::channel::<pixels::FrameSnapshot>();
+    /// std::thread::spawn(move || {
+    ///     for snapshot in receiver {
+    ///         let _bright = snapshot.iter().filter(|&&byte| byte > 0x80).count();
+    ///     }
+    /// });
+    ///
+    /// // In the event loop
+    /// pixels.render()?;
+    /// sender.send(pixels.frame_snapshot()).unwrap();
+    /// pixels.frame_mut().fill(0);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn frame_snapshot(&self) -> FrameSnapshot {
+        FrameSnapshot::new(
+            Arc::clone(&self.pixels),
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        )
+    }
+
+    /// Copy the part of a [`Canvas`] selected by `view` into the pixel buffer.
+    ///
+    /// Only the visible pixels are copied, so the canvas can be larger than the GPU texture
+    /// limits. Use [`CanvasView::frame_to_canvas`] with [`Pixels::window_pos_to_pixel`] to find
+    /// the canvas pixel under the cursor.
+    ///
+    /// ```no_run
+    /// use pixels::{Canvas, CanvasView};
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let mut canvas = Canvas::new(256, 4);
+    /// let mut view = CanvasView::default();
+    ///
+    /// // Pan and zoom in on the canvas with the mouse, then draw it
+    /// view.pan(-12.0, 4.0);
+    /// view.zoom_at(2.0, (160.0, 120.0));
+    /// pixels.draw_canvas(&canvas, &view);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when the bytes per pixel of `canvas` do not match the texture format.
+    pub fn draw_canvas(&mut self, canvas: &Canvas, view: &CanvasView) {
+        assert_eq!(
+            canvas.bytes_per_pixel() as f32,
+            self.context.texture_format_size,
+        );
+
+        self.redraw_pending = true;
+        self.frame_changed = true;
+        canvas.render(
+            view,
+            Arc::make_mut(&mut self.pixels).as_mut_slice(),
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        );
+
+        let extent = self.context.texture_extent;
+        self.mark_dirty((0, 0, extent.width, extent.height));
+    }
+
+    /// Cut a region of the pixel buffer for a window icon or cursor, scaled by the whole number
+    /// `scale`.
+    ///
+    /// `rect` is the `(x, y, width, height)` of the region in pixels. The pixel buffer must use an
+    /// 8-bit RGBA or BGRA texture format. See [`IconImage`].
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // The 16x16 sprite in the top-left corner, as a 32x32 icon
+    /// let icon = pixels.icon_image((0, 0, 16, 16), 2)?;
+    /// assert_eq!(icon.rgba.len(), 32 * 32 * 4);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// With the `winit` feature, convert it with [`IconImage::to_window_icon`] or
+    /// [`IconImage::to_cursor`].
+    ///
+    /// # Errors
+    ///
+    /// - [`IconError::TextureFormat`] when the pixel buffer is not 8-bit RGBA or BGRA.
+    /// - [`IconError::OutOfBounds`] when `rect` is empty or extends past the pixel buffer.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `scale` is 0.
+    pub fn icon_image(
+        &self,
+        rect: (u32, u32, u32, u32),
+        scale: u32,
+    ) -> Result<IconImage, IconError> {
+        let extent = self.context.texture_extent;
+        let texture_format = self.imported_texture.unwrap_or(self.context.texture_format);
+
+        IconImage::from_frame(
+            &self.pixels,
+            (extent.width, extent.height),
+            texture_format,
+            rect,
+            scale,
+        )
+    }
+
+    /// Read a region of the pixel buffer texture back from the GPU.
+    ///
+    /// `rect` is the `(x, y, width, height)` of the region in pixels. The returned bytes are
+    /// tightly packed rows in the texture format; the row padding required by the GPU is removed.
+    /// This is useful when the texture is populated on the GPU, e.g. by a compute shader or with
+    /// [`Pixels::import_texture`], and [`Pixels::frame`] does not hold its contents.
+    ///
+    /// The texture holds the frame from the most recent render. The future polls the device
+    /// itself, so it completes with any executor; see [the crate docs](crate#async-apis). Use
+    /// [`Pixels::blocking_read_region`] outside of async code on native targets.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// pixels.render()?;
+    ///
+    /// let region = pollster::block_on(pixels.read_region_async((16, 16, 32, 8)))?;
+    /// assert_eq!(region.len(), 32 * 8 * 4);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadbackError::TextureUsage`] when an imported texture was not created with
+    ///   [`wgpu::TextureUsages::COPY_SRC`].
+    /// - [`ReadbackError::TextureFormat`] when the texture format is block compressed.
+    /// - [`ReadbackError::OutOfBounds`] when `rect` is empty or extends past the texture.
+    /// - [`ReadbackError::Map`] when the region cannot be mapped for reading.
+    pub fn read_region_async(
+        &self,
+        rect: (u32, u32, u32, u32),
+    ) -> impl std::future::Future<Output = Result<Vec<u8>, ReadbackError>> + '_ {
+        readback::read_texture_region(
+            &self.context.device,
+            &self.context.queue,
+            &self.context.texture,
+            rect,
+        )
+    }
+
+    /// Read a region of the pixel buffer texture back from the GPU, blocking the current thread
+    /// until it arrives.
+    ///
+    /// This is [`Pixels::read_region_async`] for synchronous code. It waits on the device
+    /// directly instead of going through an executor, so it is safe to call from any thread,
+    /// including from within a `pollster::block_on` or an async runtime, but it stalls the calling
+    /// thread until the GPU has finished all submitted work. Unavailable on Web targets, where
+    /// the main thread cannot block.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// pixels.render()?;
+    ///
+    /// let region = pixels.blocking_read_region((0, 0, 320, 240))?;
+    /// assert_eq!(region, pixels.frame());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// The same as [`Pixels::read_region_async`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn blocking_read_region(
+        &self,
+        rect: (u32, u32, u32, u32),
+    ) -> Result<Vec<u8>, ReadbackError> {
+        readback::read_texture_region_blocking(
+            &self.context.device,
+            &self.context.queue,
+            &self.context.texture,
+            rect,
+        )
+    }
+
+    /// Read the most recent frame of an [offscreen](Pixels::new_offscreen) pixel buffer back from
+    /// the GPU, blocking the current thread until it arrives.
+    ///
+    /// The frame is the whole output texture after scaling and all render passes and effects,
+    /// like [`CaptureSource::Presented`] with [`Pixels::capture_frame`], but it is available as
+    /// soon as [`Pixels::render`] returns. Like [`Pixels::blocking_read_region`], it stalls the
+    /// calling thread until the GPU has finished all submitted work. Unavailable on Web targets.
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadbackError::NotOffscreen`] when the pixel buffer has a window surface.
+    /// - [`ReadbackError::Map`] when the frame cannot be mapped for reading.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn blocking_read_output(&self) -> Result<CapturedFrame, ReadbackError> {
+        let texture = self
+            .offscreen_texture
+            .as_ref()
+            .ok_or(ReadbackError::NotOffscreen)?;
+
+        readback::read_output_blocking(&self.context.device, &self.context.queue, texture)
+    }
+
+    /// Read back the final color of the surface pixel at a physical window position, after all
+    /// render passes and effects.
+    ///
+    /// The pixel is copied on the next render and read without blocking; the result is available
+    /// from [`Pixels::picked_output_pixel`] one or two frames later. An eyedropper tool calls this
+    /// with the cursor position on every frame. The first call enables copying from the surface,
+    /// which some platforms render to slightly less efficiently.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // A cursor position in physical units
+    /// pixels.pick_output_pixel(100.0, 50.0)?;
+    /// pixels.render()?;
+    ///
+    /// if let Some(rgba) = pixels.picked_output_pixel().and_then(|pixel| pixel.rgba8()) {
+    ///     println!("Color under the cursor: {rgba:?}");
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadbackError::TextureUsage`] when the surface cannot be copied from on this platform.
+    /// - [`ReadbackError::OutOfBounds`] when the position is outside of the surface.
+    pub fn pick_output_pixel(&mut self, window_x: f32, window_y: f32) -> Result<(), ReadbackError> {
+        let (x, y) = (window_x.floor(), window_y.floor());
+        let (width, height) = (self.surface_size.width, self.surface_size.height);
+        if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+            return Err(ReadbackError::OutOfBounds((
+                x.max(0.0) as u32,
+                y.max(0.0) as u32,
+                1,
+                1,
+            )));
+        }
+
+        self.enable_surface_copy()?;
+        self.output_picker.request((x as u32, y as u32));
+        self.redraw_pending = true;
+
+        Ok(())
+    }
+
+    /// Get the most recent pixel read back for [`Pixels::pick_output_pixel`].
+    pub fn picked_output_pixel(&self) -> Option<&PickedPixel> {
+        self.output_picker.picked()
+    }
+
+    /// Capture the next rendered frame, for screenshots, recordings, and visual regression tests.
+    ///
+    /// With [`CaptureSource::Presented`], the whole surface is copied exactly as it is presented,
+    /// after scaling and all render passes and effects, including custom ones. With
+    /// [`CaptureSource::Frame`], the pixel buffer texture is copied as uploaded, before scaling;
+    /// see also [`Pixels::read_region_async`].
+    ///
+    /// The frame is copied on the next render and mapped asynchronously with
+    /// [`wgpu::BufferSlice::map_async`]; collect it with [`Pixels::captured_frame`] once the GPU
+    /// has finished. Capturing the presented frame enables copying from the surface on first use,
+    /// like [`Pixels::pick_output_pixel`].
+    ///
+    /// The [`CapturedFrame::color_space`] tells how the values are encoded, and
+    /// [`CapturedFrame::rgba8`] converts any supported format to sRGB for image encoders, so gamma
+    /// is never applied twice.
+    ///
+    /// ```no_run
+    /// # use pixels::{CaptureSource, Pixels};
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// pixels.capture_frame(CaptureSource::Presented)?;
+    /// pixels.render()?;
+    ///
+    /// // Usually a frame or two later
+    /// if let Some(frame) = pixels.captured_frame() {
+    ///     let rgba = frame.rgba8().expect("a supported surface format");
+    ///     assert_eq!(rgba.len(), frame.width as usize * frame.height as usize * 4);
+    /// }
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`ReadbackError::TextureUsage`] when the surface cannot be copied from on this platform, or
+    /// an imported texture was not created with [`wgpu::TextureUsages::COPY_SRC`].
+    pub fn capture_frame(&mut self, source: CaptureSource) -> Result<(), ReadbackError> {
+        match source {
+            CaptureSource::Presented => self.enable_surface_copy()?,
+            CaptureSource::Frame => {
+                let usage = wgpu::TextureUsages::COPY_SRC;
+                if !self.context.texture.usage().contains(usage) {
+                    return Err(ReadbackError::TextureUsage(usage));
+                }
+            }
+        }
+        self.frame_capture.request(source);
+        self.redraw_pending = true;
+
+        Ok(())
+    }
+
+    /// Take the frame captured for [`Pixels::capture_frame`], once it has been read back.
+    ///
+    /// This polls the device without blocking, and returns `None` while the copy is in flight.
+    pub fn captured_frame(&mut self) -> Option<CapturedFrame> {
+        self.frame_capture.take(&self.context.device)
+    }
+
+    /// Start recording every rendered frame of `source` to `sink`, e.g. for gameplay videos or
+    /// deterministic replays.
+    ///
+    /// Each render copies the frame like [`Pixels::capture_frame`], into a small ring of staging
+    /// buffers which are read back without blocking over the following renders. Frames are
+    /// written to the sink in order, on the render thread, by later calls to [`Pixels::render`].
+    /// When the GPU falls behind and every staging buffer is still in flight, the frame is
+    /// dropped, so recording never stalls rendering; see [`RecordingStats::dropped`].
+    ///
+    /// Any recording in progress is stopped first, and its result is discarded; call
+    /// [`Pixels::stop_recording`] before to check it. Recordings carry over
+    /// [`Pixels::recreate_device_async`], dropping the frames in flight.
+    ///
+    /// See the [`recorder`] module for an example.
+    ///
+    /// [`RecordingStats::dropped`]: recorder::RecordingStats::dropped
+    ///
+    /// # Errors
+    ///
+    /// - [`ReadbackError::TextureUsage`] when the surface cannot be copied from on this platform,
+    ///   or an imported texture was not created with [`wgpu::TextureUsages::COPY_SRC`].
+    /// - [`ReadbackError::TextureFormat`] when the texture format is block compressed.
+    pub fn start_recording(
+        &mut self,
+        source: CaptureSource,
+        sink: impl recorder::FrameSink + 'static,
+    ) -> Result<(), ReadbackError> {
+        let format = match source {
+            CaptureSource::Presented => {
+                self.enable_surface_copy()?;
+                self.surface_texture_format
+            }
+            CaptureSource::Frame => {
+                let usage = wgpu::TextureUsages::COPY_SRC;
+                if !self.context.texture.usage().contains(usage) {
+                    return Err(ReadbackError::TextureUsage(usage));
+                }
+                self.context.texture_format
+            }
+        };
+        if format.block_dimensions() != (1, 1) || format.block_copy_size(None).is_none() {
+            return Err(ReadbackError::TextureFormat(format));
+        }
+
+        let _ = self.stop_recording();
+        self.recorder = Some(recorder::Recorder::new(source, Box::new(sink)));
+
+        Ok(())
+    }
+
+    /// Stop the recording started with [`Pixels::start_recording`], waiting for the frames in
+    /// flight to be written, and finish the sink.
+    ///
+    /// Returns `None` when nothing is being recorded.
+    ///
+    /// # Errors
+    ///
+    /// The first error of [`FrameSink::write_frame`], which stopped writing frames, or the error
+    /// of [`FrameSink::finish`].
+    ///
+    /// [`FrameSink::write_frame`]: recorder::FrameSink::write_frame
+    /// [`FrameSink::finish`]: recorder::FrameSink::finish
+    pub fn stop_recording(&mut self) -> Option<std::io::Result<recorder::RecordingStats>> {
+        let recorder = self.recorder.take()?;
+
+        Some(recorder.finish(&self.context.device))
+    }
+
+    /// Check if a recording was [started](Pixels::start_recording).
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    /// Get the progress of the recording, or `None` when nothing is being recorded.
+    pub fn recording_stats(&self) -> Option<recorder::RecordingStats> {
+        self.recorder.as_ref().map(recorder::Recorder::stats)
+    }
+
+    /// Add [`wgpu::TextureUsages::COPY_SRC`] to the surface, if the platform supports it.
+    fn enable_surface_copy(&mut self) -> Result<(), ReadbackError> {
+        let usage = wgpu::TextureUsages::COPY_SRC;
+        if !self.surface_usage.contains(usage) {
+            let capabilities = self.surface_capabilities();
+            if !capabilities.usages.contains(usage) {
+                return Err(ReadbackError::TextureUsage(usage));
+            }
+            self.surface_usage |= usage;
+            self.reconfigure_surface();
+        }
+
+        Ok(())
+    }
+
+    /// Create an offscreen [`DrawTarget`] with the texture format of the pixel buffer.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Draw a cached UI panel once...
+    /// let mut panel = pixels.create_target(64, 32)?;
+    /// panel.frame_mut().fill(0xff);
+    ///
+    /// // ... and copy it into every frame
+    /// pixels.blit_target(&panel, 8, 8);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureWidth`] when `width` is 0 or greater than GPU texture limits.
+    /// - [`TextureError::TextureHeight`] when `height` is 0 or greater than GPU texture limits.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the texture format of the pixel buffer is block compressed.
+    pub fn create_target(&self, width: u32, height: u32) -> Result<DrawTarget, TextureError> {
+        check_texture_size(&self.context.device, width, height)?;
+
+        Ok(DrawTarget::new(
+            &self.context.device,
+            width,
+            height,
+            self.context.texture_format,
+        ))
+    }
+
+    /// Copy a [`DrawTarget`] into the pixel buffer with its top-left corner at pixel `(x, y)`.
+    ///
+    /// Pixels are replaced, not blended. Parts of the target outside of the pixel buffer are
+    /// skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the texture format of `target` does not match the pixel buffer.
+    pub fn blit_target(&mut self, target: &DrawTarget, x: i32, y: i32) {
+        assert_eq!(target.texture_format(), self.context.texture_format);
+
+        let size = (
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        );
+        self.redraw_pending = true;
+        self.frame_changed = true;
+        target.blit(Arc::make_mut(&mut self.pixels).as_mut_slice(), size, (x, y));
+
+        let rect = (x, y, target.width(), target.height());
+        if let Some(rect) = sprite::clip_to_buffer(rect, size) {
+            self.mark_dirty(rect);
+        }
+    }
+
+    /// Copy the contents of a [`DrawTarget`] to its [texture](DrawTarget::texture), for
+    /// compositing on the GPU in [`Pixels::render_with`].
+    pub fn upload_target(&self, target: &DrawTarget) {
+        target.upload(&self.context.queue);
+    }
+
+    /// Draw an uploaded [`DrawTarget`] into a rectangle of another draw target or of the pixel
+    /// buffer texture on the GPU, scaling it with the given filter.
+    ///
+    /// `rect` is the `(x, y, width, height)` of the destination in pixels; parts outside of the
+    /// destination are clipped. Pixels are replaced, not blended. The source must be copied to its
+    /// texture with [`Pixels::upload_target`] first.
+    ///
+    /// Blits into another target happen immediately and only change its texture, not its
+    /// [`DrawTarget::frame`]. Blits into the [`BlitDestination::Frame`] are drawn over the pixel
+    /// buffer texture after it is uploaded on the next render, and are discarded when the buffer
+    /// is resized. Like the frame, they are drawn once: the areas they covered are uploaded again
+    /// from the frame on the following render, also with
+    /// [dirty tracking](Pixels::set_dirty_tracking) and sliced uploads.
+    ///
+    /// ```no_run
+    /// use pixels::BlitDestination;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // Render a mirror at half resolution, then stretch it into the frame
+    /// let mirror = pixels.create_target(80, 60)?;
+    /// pixels.upload_target(&mirror);
+    /// pixels.blit_target_gpu(
+    ///     &mirror,
+    ///     BlitDestination::Frame,
+    ///     (40, 20, 160, 120),
+    ///     pixels::wgpu::FilterMode::Linear,
+    /// )?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureUsage`] when the texture format cannot be rendered to.
+    /// - [`TextureError::TextureFormat`] when the texture format cannot be sampled with filtering.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `src` and the destination are the same target, or their texture format does
+    /// not match the pixel buffer.
+    pub fn blit_target_gpu(
+        &mut self,
+        src: &DrawTarget,
+        dst: BlitDestination<'_>,
+        rect: (i32, i32, u32, u32),
+        filter: wgpu::FilterMode,
+    ) -> Result<(), TextureError> {
+        let texture_format = self.context.texture_format;
+        assert_eq!(src.texture_format(), texture_format);
+        self.check_blit_format(src.texture())?;
+        if rect.2 == 0 || rect.3 == 0 {
+            return Ok(());
+        }
+
+        let filter = match filter {
+            wgpu::FilterMode::Nearest => FilterMode::Nearest,
+            wgpu::FilterMode::Linear => FilterMode::Linear,
+        };
+        let blitter = self.take_target_blitter();
+        let device = &self.context.device;
+
+        match dst {
+            BlitDestination::Frame => {
+                let size = (
+                    self.context.texture_extent.width,
+                    self.context.texture_extent.height,
+                );
+                let blit = blitter.prepare(device, src.texture(), size, rect, filter);
+                self.pending_blits.push(blit);
+                self.pending_drawn_rects
+                    .extend(sprite::clip_to_buffer(rect, size));
+                self.redraw_pending = true;
+                self.frame_changed = true;
+            }
+            BlitDestination::Target(dst) => {
+                assert!(!std::ptr::eq(src, dst));
+                assert_eq!(dst.texture_format(), texture_format);
+
+                let size = (dst.width(), dst.height());
+                let blit = blitter.prepare(device, src.texture(), size, rect, filter);
+                self.submit_blit(&blitter, &blit, dst.texture());
+            }
+        }
+        self.target_blitter = Some(blitter);
+
+        Ok(())
+    }
+
+    /// Copy the `src_rect` (x, y, width, height) of a texture owned by the application into the
+    /// pixel buffer texture on the GPU, with its top-left corner at pixel `dst_pos`.
+    ///
+    /// This mixes GPU-produced content, such as the output of a video decoder or another render
+    /// target, into the frame without reading it back to the CPU. The region is copied at once to
+    /// a texture kept by `Pixels`, so `src` may be changed or dropped afterwards, and copied over
+    /// the pixel buffer texture after it is uploaded on the next render, before the GPU blits of
+    /// [`Pixels::blit_target_gpu`]. Like those blits, the copy is drawn once: call this before
+    /// every render which shows it. The area it covered is uploaded again from the frame on the
+    /// next render, so this works with [dirty tracking](Pixels::set_dirty_tracking) and sliced
+    /// uploads. The CPU [`Pixels::frame`] is not changed.
+    ///
+    /// Pixels are replaced, not blended. Parts of the region outside of `src` or the pixel buffer
+    /// are skipped. Copies are discarded when the buffer is resized.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// # let video_frame = pixels.create_target(160, 120)?;
+    /// # let video_frame = video_frame.texture();
+    ///
+    /// // Show the decoded video in the top-right quarter of the frame
+    /// pixels.copy_from_texture(video_frame, (0, 0, 160, 120), (160, 0))?;
+    /// pixels.render()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureUsage`] when `src` was not created with
+    ///   [`wgpu::TextureUsages::COPY_SRC`], or an [imported texture](Pixels::import_texture) is
+    ///   displayed which was not created with [`wgpu::TextureUsages::COPY_DST`].
+    /// - [`TextureError::TextureFormat`] when the format of `src` differs from the pixel buffer
+    ///   in more than sRGB encoding, or `src` is not a single-sampled 2D texture, or the pixel
+    ///   buffer is block compressed.
+    pub fn copy_from_texture(
+        &mut self,
+        src: &wgpu::Texture,
+        src_rect: (u32, u32, u32, u32),
+        dst_pos: (i32, i32),
+    ) -> Result<(), TextureError> {
+        let texture_format = self.context.texture_format;
+        if !src.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return Err(TextureError::TextureUsage(wgpu::TextureUsages::COPY_SRC));
+        }
+        let usage = wgpu::TextureUsages::COPY_DST;
+        if !self.context.texture.usage().contains(usage) {
+            return Err(TextureError::TextureUsage(usage));
+        }
+        if src.format().remove_srgb_suffix() != texture_format.remove_srgb_suffix()
+            || texture_format.block_dimensions() != (1, 1)
+            || src.dimension() != wgpu::TextureDimension::D2
+            || src.sample_count() != 1
+        {
+            return Err(TextureError::TextureFormat(src.format()));
+        }
+
+        // Clip the region to the source, then to the pixel buffer
+        let (x, y, width, height) = src_rect;
+        let src_x = i64::from(x).min(i64::from(src.width()));
+        let src_y = i64::from(y).min(i64::from(src.height()));
+        let src_width = i64::from(width).min(i64::from(src.width()) - src_x);
+        let src_height = i64::from(height).min(i64::from(src.height()) - src_y);
+        let (dst_x, dst_y) = (i64::from(dst_pos.0), i64::from(dst_pos.1));
+        let left = dst_x.max(0);
+        let top = dst_y.max(0);
+        let right = (dst_x + src_width).min(i64::from(self.context.texture_extent.width));
+        let bottom = (dst_y + src_height).min(i64::from(self.context.texture_extent.height));
+        if left >= right || top >= bottom {
+            return Ok(());
+        }
+        let size = wgpu::Extent3d {
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+            depth_or_array_layers: 1,
+        };
+
+        self.spare_copy_textures
+            .retain(|texture| texture.format() == texture_format);
+        let texture = match self
+            .spare_copy_textures
+            .iter()
+            .position(|texture| texture.size() == size)
+        {
+            Some(index) => self.spare_copy_textures.swap_remove(index),
+            None => self
+                .context
+                .device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("pixels_copy_texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: texture_format,
+                    usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+                    view_formats: &[],
+                }),
+        };
+        let mut encoder =
+            self.context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("pixels_copy_command_encoder"),
+                });
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                origin: wgpu::Origin3d {
+                    x: (src_x + left - dst_x) as u32,
+                    y: (src_y + top - dst_y) as u32,
+                    z: 0,
+                },
+                ..src.as_image_copy()
+            },
+            texture.as_image_copy(),
+            size,
+        );
+        self.context.queue.submit(Some(encoder.finish()));
+
+        self.pending_copies
+            .push((texture, (left as u32, top as u32)));
+        self.pending_drawn_rects
+            .push((left as u32, top as u32, size.width, size.height));
+        self.redraw_pending = true;
+        self.frame_changed = true;
+
+        Ok(())
+    }
+
+    /// Downsample the pixel buffer texture into the texture of a [`DrawTarget`] on the GPU, for
+    /// a minimap of the current frame.
+    ///
+    /// Every texel of the pixel buffer covered by a target pixel is averaged, so small details
+    /// do not flicker as the frame changes. The pixel buffer is not read back or rasterized again
+    /// on the CPU; composite the target's [texture](DrawTarget::texture) in a custom render pass
+    /// with [`Pixels::render_with`]. The result shows the frame from the most recent render.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(1024, 1024, surface_texture)?;
+    /// let minimap = pixels.create_target(64, 64)?;
+    ///
+    /// pixels.render()?;
+    /// pixels.render_minimap(&minimap)?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureUsage`] when the texture format cannot be rendered to.
+    /// - [`TextureError::TextureFormat`] when the texture format cannot be sampled with filtering.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the texture format of `target` does not match the pixel buffer.
+    pub fn render_minimap(&mut self, target: &DrawTarget) -> Result<(), TextureError> {
+        assert_eq!(target.texture_format(), self.context.texture_format);
+        self.check_blit_format(target.texture())?;
+
+        let blitter = self.take_target_blitter();
+        let size = (target.width(), target.height());
+        let blit = blitter.prepare(
+            &self.context.device,
+            &self.context.texture,
+            size,
+            (0, 0, size.0, size.1),
+            FilterMode::Area,
+        );
+        self.submit_blit(&blitter, &blit, target.texture());
+        self.target_blitter = Some(blitter);
+
+        Ok(())
+    }
+
+    /// Draw `sprites` from the texture of `atlas` into the pixel buffer texture on the GPU.
+    ///
+    /// Sprites are drawn on the next render at buffer resolution, after the pixel buffer is
+    /// uploaded, [GPU blits](Pixels::blit_target_gpu), and [text grids](Pixels::draw_text_grid),
+    /// so games can keep the background in the frame and draw hundreds of sprites over it
+    /// without blitting them on the CPU. Each call draws one batch with a single instanced draw;
+    /// batches and the sprites in them are drawn in order, alpha blended over what is below.
+    ///
+    /// Like the frame, sprites are drawn once: call this before every render which shows them.
+    /// The areas they covered are uploaded again from the frame on the next render, so this
+    /// works with [dirty tracking](Pixels::set_dirty_tracking) and sliced uploads.
+    ///
+    /// Fill the atlas with [`DrawTarget::frame_mut`] and upload it once with
+    /// [`Pixels::upload_target`].
+    ///
+    /// ```no_run
+    /// use pixels::Sprite;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let atlas = pixels.create_target(256, 256)?;
+    /// // Draw the sprite sheet into `atlas.frame_mut()`, then
+    /// pixels.upload_target(&atlas);
+    ///
+    /// // Every frame
+    /// let sprites: Vec<_> = (0..200)
+    ///     .map(|i| Sprite::new((i % 20 * 16, i / 20 * 16), (0, 0, 16, 16)))
+    ///     .collect();
+    /// pixels.draw_sprites(&atlas, &sprites)?;
+    /// pixels.render()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureUsage`] when the texture format cannot be rendered to.
+    /// - [`TextureError::TextureFormat`] when the texture format cannot be sampled with filtering.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the texture format of `atlas` does not match the pixel buffer.
+    pub fn draw_sprites(
+        &mut self,
+        atlas: &DrawTarget,
+        sprites: &[Sprite],
+    ) -> Result<(), TextureError> {
+        let texture_format = self.context.texture_format;
+        assert_eq!(atlas.texture_format(), texture_format);
+        self.check_blit_format(atlas.texture())?;
+        self.check_blit_format(&self.context.texture)?;
+
+        let size = (
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        );
+        let rects: Vec<_> = sprites
+            .iter()
+            .filter_map(|sprite| sprite.clip_rect(size))
+            .collect();
+        if rects.is_empty() {
+            return Ok(());
+        }
+
+        let renderer = match self.sprite_renderer.take() {
+            Some(renderer) if renderer.texture_format() == texture_format => renderer,
+            _ => renderers::SpriteRenderer::new(&self.context.device, texture_format),
+        };
+        let batch = renderer.prepare(&self.context.device, atlas.texture(), sprites, size);
+        self.pending_sprites.push(batch);
+        self.pending_drawn_rects.extend(rects);
+        self.sprite_renderer = Some(renderer);
+        self.redraw_pending = true;
+        self.frame_changed = true;
+
+        Ok(())
+    }
+
+    /// Upload `font` to a [`FontAtlas`] for [`Pixels::draw_text_grid`].
+    ///
+    /// # Errors
+    ///
+    /// [`TextureError::TextureWidth`] or [`TextureError::TextureHeight`] when the atlas, which
+    /// holds 16 glyphs in each row, is larger than GPU texture limits or the font is empty.
+    pub fn create_font_atlas(&self, font: &BitmapFont) -> Result<FontAtlas, TextureError> {
+        if font.glyph_count() == 0 {
+            return Err(TextureError::TextureHeight(0));
+        }
+        let (width, height) = FontAtlas::texture_size(font);
+        check_texture_size(&self.context.device, width, height)?;
+
+        Ok(FontAtlas::new(
+            &self.context.device,
+            &self.context.queue,
+            font,
+        ))
+    }
+
+    /// Draw `grid` into the pixel buffer texture on the GPU, with `atlas` and `palette`, with its
+    /// top-left corner at `position`.
+    ///
+    /// This is the GPU counterpart of [`TextGrid::draw_rgba`]: the cells are uploaded as they
+    /// are, and the glyphs are looked up in the atlas while drawing, so text-mode displays never
+    /// draw glyphs pixel by pixel on the CPU. The grid is drawn on the next render, after the
+    /// pixel buffer is uploaded and after [GPU blits](Pixels::blit_target_gpu), so it covers the
+    /// frame. Palette colors are sRGB, and are written exactly as they are given to 8-bit pixel
+    /// buffers. Indices past the end of `palette` are drawn as transparent black.
+    ///
+    /// Like the frame, the grid is drawn once: call this before every render which shows it. The
+    /// area it covered is uploaded again from the frame on the next render, so this works with
+    /// [dirty tracking](Pixels::set_dirty_tracking) and sliced uploads.
+    ///
+    /// ```no_run
+    /// use pixels::{BitmapFont, TextGrid};
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(640, 400, &window);
+    /// # let bitmap = vec![0; 16 * 256];
+    /// let mut pixels = Pixels::new(640, 400, surface_texture)?;
+    /// let font = BitmapFont::new(8, 16, bitmap);
+    /// let atlas = pixels.create_font_atlas(&font)?;
+    /// let palette = [[0x00, 0x00, 0xaa, 0xff], [0xff, 0xff, 0xff, 0xff]];
+    ///
+    /// let mut grid = TextGrid::new(80, 25);
+    /// grid.print(0, 0, "READY.", 1, 0);
+    ///
+    /// // Every frame
+    /// pixels.draw_text_grid(&grid, &atlas, &palette, (0, 0))?;
+    /// pixels.render()?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - [`TextureError::TextureUsage`] when the pixel buffer texture cannot be rendered to.
+    /// - [`TextureError::TextureFormat`] when the pixel buffer texture format is an integer
+    ///   format.
+    /// - [`TextureError::TextureWidth`] or [`TextureError::TextureHeight`] when the grid has more
+    ///   columns or rows than GPU texture limits.
+    pub fn draw_text_grid(
+        &mut self,
+        grid: &TextGrid,
+        atlas: &FontAtlas,
+        palette: &[[u8; 4]],
+        position: (i32, i32),
+    ) -> Result<(), TextureError> {
+        let texture_format = self.context.texture_format;
+        let usage = wgpu::TextureUsages::RENDER_ATTACHMENT;
+        if !self.context.texture.usage().contains(usage) {
+            return Err(TextureError::TextureUsage(usage));
+        }
+        if !matches!(
+            texture_format.sample_type(None, None),
+            Some(wgpu::TextureSampleType::Float { .. })
+        ) {
+            return Err(TextureError::TextureFormat(texture_format));
+        }
+        if grid.cells().is_empty() {
+            return Ok(());
+        }
+        check_texture_size(&self.context.device, grid.columns(), grid.rows())?;
+
+        let renderer = match self.text_grid_renderer.take() {
+            Some(renderer) if renderer.texture_format() == texture_format => renderer,
+            _ => renderers::TextGridRenderer::new(&self.context.device, texture_format),
+        };
+        let size = (
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        );
+        let prepared = renderer.prepare(
+            &self.context.device,
+            &self.context.queue,
+            grid,
+            atlas,
+            palette,
+            position,
+            size,
+        );
+        if let Some(prepared) = prepared {
+            self.pending_drawn_rects.push(prepared.scissor_rect());
+            self.pending_text_grids.push(prepared);
+            self.redraw_pending = true;
+            self.frame_changed = true;
+        }
+        self.text_grid_renderer = Some(renderer);
+
+        Ok(())
+    }
+
+    /// Check that GPU blits can draw into `texture` with the pixel buffer texture format.
+    fn check_blit_format(&self, texture: &wgpu::Texture) -> Result<(), TextureError> {
+        let texture_format = self.context.texture_format;
+        if !texture
+            .usage()
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+        {
+            return Err(TextureError::TextureUsage(
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            ));
+        }
+        if texture_format.sample_type(None, Some(self.context.device.features()))
+            != Some(wgpu::TextureSampleType::Float { filterable: true })
+        {
+            return Err(TextureError::TextureFormat(texture_format));
+        }
+
+        Ok(())
+    }
+
+    /// Take the target blitter, creating it for the current texture format if needed.
+    fn take_target_blitter(&mut self) -> renderers::TargetBlitter {
+        let texture_format = self.context.texture_format;
+
+        match self.target_blitter.take() {
+            Some(blitter) if blitter.texture_format() == texture_format => blitter,
+            _ => renderers::TargetBlitter::new(&self.context.device, texture_format),
+        }
+    }
+
+    /// Draw a blit into `dst` immediately.
+    fn submit_blit(
+        &self,
+        blitter: &renderers::TargetBlitter,
+        blit: &renderers::PreparedBlit,
+        dst: &wgpu::Texture,
+    ) {
+        let mut encoder =
+            self.context
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("pixels_target_blitter_command_encoder"),
+                });
+        blitter.render(&mut encoder, dst, [blit]);
+        self.context.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Get the [`SafeArea`] covered by the scaled pixel buffer, in physical pixels of the surface.
+    ///
+    /// The area is updated by [`Pixels::resize_buffer`] and [`Pixels::resize_surface`].
+    pub fn safe_area(&self) -> SafeArea {
+        SafeArea::new(
+            self.context.scaling_renderer.clip_rect(),
+            (self.surface_size.width, self.surface_size.height),
+            f64::from(self.supersampling()),
+        )
+    }
+
+    /// Get the [`SafeArea`] covered by the scaled pixel buffer, in logical window coordinates.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(640, 480, &window);
+    /// let pixels = Pixels::new(320, 200, surface_texture)?;
+    ///
+    /// // The window scale factor, e.g. from winit's `Window::scale_factor`
+    /// let scale_factor = 2.0;
+    ///
+    /// // Center a status bar in the border below the pixel buffer
+    /// let area = pixels.logical_safe_area(scale_factor);
+    /// let status_bar_y = area.y + area.height + area.bottom / 2.0;
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn logical_safe_area(&self, scale_factor: f64) -> SafeArea {
+        self.safe_area().to_logical(scale_factor)
+    }
+
+    /// Calculate the pixel location from a physical location on the window,
+    /// dealing with window resizing, scaling, and margins. Takes a physical
+    /// position (x, y) within the window, and returns a pixel position (x, y).
+    ///
+    /// The location must be given in physical units (for example, winit's `PhysicalLocation`)
+    ///
+    /// If the given physical position is outside of the drawing area, this
+    /// function returns an `Err` value with the pixel coordinates outside of
+    /// the screen, using isize instead of usize.
+    ///
+    /// ```no_run
+    /// use winit::dpi::PhysicalPosition;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // A cursor position in physical units
+    /// let cursor_position: (f32, f32) = PhysicalPosition::new(0.0, 0.0).into();
+    ///
+    /// // Convert it to a pixel location
+    /// let pixel_position: (usize, usize) = pixels.window_pos_to_pixel(cursor_position)
+    ///     // Clamp the output to within the screen
+    ///     .unwrap_or_else(|pos| pixels.clamp_pixel_pos(pos));
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn window_pos_to_pixel(
+        &self,
+        physical_position: (f32, f32),
+    ) -> Result<(usize, usize), (isize, isize)> {
+        self.transform().window_pos_to_pixel(physical_position)
+    }
+
+    /// Calculate the physical location on the window of a position in pixel coordinates.
+    ///
+    /// This is the inverse of [`Pixels::window_pos_to_pixel`], e.g. for placing native widgets
+    /// over pixels of the buffer. Pass `(x + 0.5, y + 0.5)` for the center of pixel `(x, y)`.
+    /// See [`Transform`] for more mappings.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(640, 480, &window);
+    /// let pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // The pixel buffer is scaled 2x, filling the surface
+    /// assert_eq!(pixels.pixel_to_window_pos((10.0, 20.0)), (20.0, 40.0));
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn pixel_to_window_pos(&self, pixel_position: (f32, f32)) -> (f32, f32) {
+        self.transform().pixel_to_window_pos(pixel_position)
+    }
+
+    /// Get the [`Transform`] between window and pixel buffer coordinates.
+    ///
+    /// The transform follows the current buffer size, view, surface size, scaling mode, and
+    /// supersampling factor; get a new one when any of them change.
+    pub fn transform(&self) -> Transform {
+        Transform::new(
+            self.scaling_matrix_inverse,
+            self.supersampling(),
+            self.view(),
+            self.context.scaling_renderer.clip_rect(),
+        )
+    }
+
+    /// Place the IME candidate window of `window` next to a rectangle of pixels, such as the text
+    /// caret of a text field drawn in the frame.
+    ///
+    /// `rect` is the `(x, y, width, height)` of the caret in pixels. It is converted to window
+    /// coordinates with the current [`Transform`] and passed to
+    /// [`Window::set_ime_cursor_area`](winit::window::Window::set_ime_cursor_area), so the
+    /// candidate window follows the letterbox border and scaling. Call it again when the caret
+    /// moves or the window is resized. IME input must be enabled with
+    /// [`Window::set_ime_allowed`](winit::window::Window::set_ime_allowed).
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    /// use winit::window::Window;
+    ///
+    /// fn draw_text_field(pixels: &mut Pixels<'_>, window: &Window, caret: (u32, u32)) {
+    ///     // Keep the candidate window next to the caret, which is 1x8 pixels
+    ///     let rect = (caret.0, caret.1, 1, 8);
+    ///     pixels.set_ime_cursor_area(window, rect);
+    /// }
+    /// ```
+    #[cfg(feature = "winit")]
+    pub fn set_ime_cursor_area(&self, window: &winit::window::Window, rect: (u32, u32, u32, u32)) {
+        let (x, y, width, height) = self.transform().pixel_rect_to_window_rect(rect);
+
+        window.set_ime_cursor_area(
+            winit::dpi::PhysicalPosition::new(x, y),
+            winit::dpi::PhysicalSize::new(width, height),
+        );
+    }
+
+    /// Clamp a pixel position to the pixel buffer texture size, or to the [view](Pixels::view)
+    /// when only part of the pixel buffer is visible.
+    ///
+    /// This can be used to clamp the `Err` value returned by [`Pixels::window_pos_to_pixel`]
+    /// to a position clamped within the drawing area.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// let pixel_pos = pixels.clamp_pixel_pos((-19, 20));
+    /// assert_eq!(pixel_pos, (0, 20));
+    ///
+    /// let pixel_pos = pixels.clamp_pixel_pos((11, 3000));
+    /// assert_eq!(pixel_pos, (11, 239));
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn clamp_pixel_pos(&self, pos: (isize, isize)) -> (usize, usize) {
+        let (x, y, width, height) = self.view();
+        let (x, y) = (x as isize, y as isize);
+
+        (
+            pos.0.clamp(x, x + width as isize - 1) as usize,
+            pos.1.clamp(y, y + height as isize - 1) as usize,
+        )
+    }
+
+    /// Calculate the pixel location from a physical location on the window like
+    /// [`Pixels::window_pos_to_pixel`], clamped to the pixel buffer.
+    ///
+    /// Instead of an error, positions in the border around the scaled pixel buffer and outside
+    /// of the surface are told apart with [`PixelHit`], both with the closest pixel. This keeps
+    /// drag interactions simple: a stroke that starts on the pixel buffer continues along its
+    /// edge when the cursor leaves it, while clicks in the border can be ignored.
+    ///
+    /// ```no_run
+    /// use pixels::PixelHit;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // On mouse button press, start dragging only on the pixel buffer
+    /// let dragging = pixels.window_pos_to_pixel_clamped((10.0, 10.0)).is_buffer();
+    ///
+    /// // On cursor movement, follow the cursor anywhere
+    /// if dragging {
+    ///     let (x, y) = pixels.window_pos_to_pixel_clamped((-50.0, 10.0)).position();
+    ///     assert_eq!(x, 0);
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn window_pos_to_pixel_clamped(&self, physical_position: (f32, f32)) -> PixelHit {
+        match self.window_pos_to_pixel(physical_position) {
+            Ok((x, y)) => PixelHit::Buffer(x, y),
+            Err(pos) => {
+                let (x, y) = self.clamp_pixel_pos(pos);
+                let (width, height) = (
+                    self.surface_size.width as f32,
+                    self.surface_size.height as f32,
+                );
+                let (window_x, window_y) = physical_position;
+                if (0.0..width).contains(&window_x) && (0.0..height).contains(&window_y) {
+                    PixelHit::Letterbox(x, y)
+                } else {
+                    PixelHit::OutsideSurface(x, y)
+                }
+            }
+        }
+    }
+
+    /// The supersampling factor, or 1 when supersampling is disabled.
+    ///
+    /// See [`PixelsBuilder::supersampling`].
+    pub fn supersampling(&self) -> u32 {
+        self.supersampler
+            .as_ref()
+            .map_or(1, renderers::Supersampler::factor)
+    }
+
+    /// The number of frames averaged on the display, or 1 when averaging is disabled.
+    ///
+    /// See [`Pixels::set_frame_averaging`].
+    pub fn frame_averaging(&self) -> u32 {
+        self.frame_averager
+            .as_ref()
+            .map_or(1, renderers::FrameAverager::frames)
+    }
+
+    /// The size of the render target passed to render functions.
+    fn render_size(&self) -> SurfaceSize {
+        self.surface_size.scaled(self.supersampling())
+    }
+
+    /// The time at which the most recent frame was presented, or `None` before the first frame.
+    ///
+    /// `wgpu` does not expose presentation feedback from the compositor, so this is the moment
+    /// presenting returned on the CPU. With Vsync enabled, presenting blocks until a swapchain
+    /// image is available on most platforms, making the difference between two present times a
+    /// close estimate of the display refresh interval. Emulators can compare it with their own
+    /// frame timing to tune pacing.
+    ///
+    /// On the Web this uses `performance.now()`; elsewhere it is a [`std::time::Instant`].
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// pixels.render()?;
+    /// let previous = pixels.last_present_time();
+    /// pixels.render()?;
+    ///
+    /// if let (Some(previous), Some(latest)) = (previous, pixels.last_present_time()) {
+    ///     println!("Present interval: {:?}", latest - previous);
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn last_present_time(&self) -> Option<Instant> {
+        self.last_present_time
+    }
+
+    /// Receive a [`PresentEvent`] for every frame rendered from now on, e.g. to schedule the
+    /// audio of an emulator against the frames the display shows rather than when they were
+    /// submitted.
+    ///
+    /// Each event is sent when the GPU has finished the frame, from within the next poll of the
+    /// device. Every render polls the device, so events arrive one frame late at most; poll
+    /// sooner with [`wgpu::Device::poll`] for tighter timing. The events measure what the CPU
+    /// can observe: `wgpu` does not report when the compositor scans a frame out.
+    ///
+    /// Calling this again replaces the previous receiver, which then disconnects.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # let mut audio_clock = std::collections::HashMap::new();
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// let present_events = pixels.present_events();
+    ///
+    /// pixels.render()?;
+    /// for event in present_events.try_iter() {
+    ///     // Line up the audio of each frame with the moment it became visible
+    ///     audio_clock.insert(event.index, event.completed.max(event.presented));
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn present_events(&mut self) -> mpsc::Receiver<PresentEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.present_events = Some(sender);
+
+        receiver
+    }
+
+    /// Get the statistics of a recently rendered frame, or `None` unless they were enabled with
+    /// [`PixelsBuilder::enable_frame_stats`].
+    ///
+    /// The statistics include the CPU time spent uploading the frame and submitting it, the GPU
+    /// time of its render passes, and the GPU time of each [scope](PixelsContext::scope). GPU
+    /// timings come from timestamp queries which are read back without blocking, so the
+    /// statistics describe a frame from one or more renders ago; check [`FrameStats::index`], or
+    /// get the statistics of every frame with [`Pixels::on_frame_stats`].
+    ///
+    /// ```no_run
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .enable_frame_stats(true)
+    ///     .build()?;
+    ///
+    /// pixels.render()?;
+    /// if let Some(stats) = pixels.frame_stats() {
+    ///     for scope in &stats.scopes {
+    ///         let indent = "  ".repeat(scope.depth);
+    ///         println!("{indent}{}: {:?}", scope.name, scope.duration);
+    ///     }
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn frame_stats(&self) -> Option<&FrameStats> {
+        self.context.profiler.stats()
+    }
+
+    /// Call `callback` with the [`FrameStats`] of every frame from now on, as soon as they are
+    /// complete, e.g. to adjust frame pacing when the GPU falls behind.
+    ///
+    /// Statistics are completed within a later render, in the order the frames were rendered; see
+    /// [`Pixels::frame_stats`]. The callback is only called when frame statistics were enabled
+    /// with [`PixelsBuilder::enable_frame_stats`]. Calling this again replaces the previous
+    /// callback.
+    ///
+    /// ```no_run
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .enable_frame_stats(true)
+    ///     .build()?;
+    ///
+    /// // Skip expensive effects while a frame takes longer than 60 Hz allows on the GPU
+    /// let gpu_bound = Arc::new(AtomicBool::new(false));
+    /// let flag = Arc::clone(&gpu_bound);
+    /// pixels.on_frame_stats(move |stats| {
+    ///     let slow = stats
+    ///         .gpu_render
+    ///         .is_some_and(|duration| duration > Duration::from_micros(16_667));
+    ///     flag.store(slow, Ordering::Relaxed);
+    /// });
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn on_frame_stats(&mut self, callback: impl FnMut(&FrameStats) + Send + 'static) {
+        self.context.profiler.set_callback(Box::new(callback));
+    }
+
+    /// Enable or disable the input latency probe, a diagnostic mode for tuning present modes in
+    /// emulators, rhythm games, and other latency-sensitive applications.
+    ///
+    /// While enabled, call [`Pixels::mark_input`] for every input event. The first frame rendered
+    /// after an input answers it: a white square patch is drawn in the top-left corner of the
+    /// surface, and the patch is black in other frames, so a camera or photodiode can measure
+    /// the latency of the display as well. The time from the input to the frame being presented
+    /// and completed is measured with the same timing as [`Pixels::present_events`], and an
+    /// [`InputLatency`] estimate of when the frame was shown is reported by
+    /// [`Pixels::input_latency`] and in [`FrameStats::input_latency`].
+    ///
+    /// ```no_run
+    /// use pixels::Pixels;
+    /// use web_time::Instant;
+    ///
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// pixels.set_latency_probe(true);
+    ///
+    /// // In the event loop, when a key is pressed
+    /// pixels.mark_input(Instant::now());
+    ///
+    /// // Every redraw
+    /// pixels.render()?;
+    /// if let Some(latency) = pixels.input_latency() {
+    ///     println!("Input to photon: about {:?}", latency.estimated);
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn set_latency_probe(&mut self, enabled: bool) {
+        self.latency_probe.set_enabled(enabled);
+        self.probe_renderer = enabled.then(|| {
+            renderers::ColorOverlayRenderer::new(&self.context.device, self.render_texture_format)
+        });
+        self.redraw_pending = true;
+    }
+
+    /// Check if the [input latency probe](Pixels::set_latency_probe) is enabled.
+    pub fn latency_probe(&self) -> bool {
+        self.latency_probe.is_enabled()
+    }
+
+    /// Record an input event which happened at `time`, for the
+    /// [input latency probe](Pixels::set_latency_probe). This does nothing while the probe is
+    /// disabled.
+    ///
+    /// Pass the time the event was received, e.g. [`Instant::now`] at the top of the event
+    /// handler, on the clock of [`Pixels::last_present_time`]. Several inputs before one frame
+    /// are measured from the earliest.
+    pub fn mark_input(&mut self, time: Instant) {
+        self.latency_probe.mark_input(time);
+        self.redraw_pending = true;
+    }
+
+    /// Get the most recent measurement of the [input latency probe](Pixels::set_latency_probe).
+    ///
+    /// Measurements finish once the GPU has finished the frame, and are picked up by the next
+    /// render.
+    pub fn input_latency(&self) -> Option<InputLatency> {
+        self.latency_probe.latest()
+    }
+
+    /// Get a handle to the GPU of this pixel buffer, to create more pixel buffers which share it.
+    ///
+    /// See [`SharedGpu`].
+    pub fn shared_gpu(&self) -> SharedGpu {
+        SharedGpu {
+            instance: Arc::clone(&self.instance),
+            adapter: Arc::clone(&self.adapter),
+            device: Arc::clone(&self.context.device),
+            queue: Arc::clone(&self.context.queue),
+            device_lost: Arc::clone(&self.device_lost),
+        }
+    }
+
+    /// Check whether the GPU device was lost, e.g. after a driver reset or a GPU hang.
+    ///
+    /// Rendering returns [`Error::DeviceLost`] from then on, until the pixel buffer gets a new
+    /// device with [`Pixels::recreate_device`]. Pixel buffers sharing the device with
+    /// [`Pixels::shared_gpu`] all see the loss. Loss is detected with the device lost callback of
+    /// [`wgpu::Device`], so do not replace it.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    /// Rebuild the GPU state of this pixel buffer on a new adapter and device, after the device
+    /// was lost.
+    ///
+    /// This blocks the current thread like [`PixelsBuilder::build`]; see
+    /// [`Pixels::recreate_device_async`] for the details and a non-blocking alternative.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// // In the event loop
+    /// match pixels.render() {
+    ///     Err(pixels::Error::DeviceLost) => pixels.recreate_device()?,
+    ///     result => result?,
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when no adapter or device can be found, like [`PixelsBuilder::build`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn recreate_device(&mut self) -> Result<(), Error> {
+        pollster::block_on(self.recreate_device_async())
+    }
+
+    /// Rebuild the GPU state of this pixel buffer on a new adapter and device, without blocking
+    /// the current thread.
+    ///
+    /// The new device is requested from the same instance, with the features and limits of the lost
+    /// one, and the surface is configured for it. The pixel buffer keeps its contents, size, and
+    /// settings, including the [`PixelsConfig`], palette, shader hook, gamma ramp, overlays,
+    /// [layers](Layer), distortion mesh, and selections. Everything which holds resources of the
+    /// lost device is dropped: [render effects](Pixels::add_effect), the
+    /// [registered renderer](Pixels::set_renderer), [imported textures](Pixels::import_texture),
+    /// pending [draw target](DrawTarget) blits, and transitions in progress. Pipelines and textures
+    /// the application created on the lost device must be recreated with the new
+    /// [`Pixels::device`], and pixel buffers which shared it need a new [`Pixels::shared_gpu`].
+    ///
+    /// If this fails after the new device was created, the surface is gone with it, and the
+    /// pixel buffer must be created again from the window.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when no adapter or device can be found, [`Error::UnsupportedLimits`] when
+    /// the new adapter cannot satisfy the limits of the lost device, or an error from building
+    /// the pixel buffer.
+    pub async fn recreate_device_async(&mut self) -> Result<(), Error> {
+        let gpu = builder::request_gpu(
+            Arc::clone(&self.instance),
+            self.context.surface.as_ref(),
+            self.context.device.features(),
+            self.context.device.limits(),
+        )
+        .await?;
+        let mut pixels = PixelsBuilder::rebuild(self, gpu).build_async().await?;
+
+        // Keep the frame and the state which does not live on the GPU
+        if pixels.pixels.len() == self.pixels.len() {
+            pixels.pixels = Arc::clone(&self.pixels);
+        }
+        for (layer, previous) in pixels.layers.iter_mut().zip(&self.layers) {
+            layer.frame_mut().copy_from_slice(previous.frame());
+            let (x, y) = previous.offset();
+            layer.set_offset(x, y);
+            layer.set_opacity(previous.opacity());
+        }
+        pixels.set_distortion_mesh(self.distortion_mesh.take());
+        pixels.set_selections(self.selections());
+        pixels.surface_usage = self.surface_usage;
+        pixels.window_aspect_locked = self.window_aspect_locked;
+        pixels.redraw_policy = self.redraw_policy;
+        pixels.safe_mode = self.safe_mode;
+        pixels.shake = self.shake.take();
+        pixels.screen_flash = self.screen_flash.take();
+        pixels.present_events = self.present_events.take();
+        pixels.recorder = self.recorder.take();
+        if let Some(recorder) = &mut pixels.recorder {
+            recorder.lose_device();
+        }
+        pixels.display_refresh_rate = self.display_refresh_rate;
+        pixels.latency_probe = std::mem::take(&mut self.latency_probe);
+        pixels.set_latency_probe(pixels.latency_probe.is_enabled());
+        pixels.context.profiler.inherit(&mut self.context.profiler);
+        pixels.view_offset = self.view_offset;
+        pixels.view_size = self.view_size;
+        pixels.update_view();
+        pixels.frame_index = self.frame_index;
+        pixels.first_frame_time = self.first_frame_time;
+        pixels.previous_frame_time = self.previous_frame_time;
+        pixels.last_present_time = self.last_present_time;
+        if pixels.surface_usage != wgpu::TextureUsages::RENDER_ATTACHMENT {
+            pixels.reconfigure_surface();
+        }
+        *self = pixels;
+
+        Ok(())
+    }
+
+    /// Provides access to the internal [`wgpu::Device`].
+    pub fn device(&self) -> &wgpu::Device {
+        &self.context.device
+    }
+
+    /// Provides access to the internal [`wgpu::Queue`].
+    pub fn queue(&self) -> &wgpu::Queue {
+        &self.context.queue
+    }
+
+    /// Provides access to the internal source [`wgpu::Texture`].
+    ///
+    /// This is the pre-scaled texture copied from the pixel buffer.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.context.texture
+    }
+
+    /// Provides access to the internal [`PixelsContext`].
+    pub fn context(&self) -> &PixelsContext<'_> {
+        &self.context
+    }
+
+    /// Get the surface texture format.
+    ///
+    /// This texture format may be chosen automatically by the surface. See
+    /// [`PixelsBuilder::surface_texture_format`] for more information. It is the format of the
+    /// views that render passes draw to; the surface itself may be configured with the variant
+    /// of this format with the other sRGB encoding, see [`PipelineDescription`].
+    pub fn surface_texture_format(&self) -> wgpu::TextureFormat {
+        self.surface_texture_format
+    }
+
+    /// Describe the active rendering configuration: formats, texture sizes, and the passes drawn
+    /// by [`Pixels::render`] in order.
+    ///
+    /// Include the description in bug reports, or show it in a debug overlay, to see exactly
+    /// which configuration was selected on each platform. Each enabled effect is listed as a pass
+    /// named after the effect, in the order the effects are drawn.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// eprintln!("{}", pixels.describe_pipeline());
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn describe_pipeline(&self) -> PipelineDescription {
+        let texture_size = (
+            self.context.texture_extent.width,
+            self.context.texture_extent.height,
+        );
+        let surface_size = (self.surface_size.width, self.surface_size.height);
+        let render_size = self.render_size();
+        let render_size = (render_size.width, render_size.height);
+        let render_format = self.render_texture_format;
+
+        let mut passes = Vec::new();
+        if !self.pending_blits.is_empty() {
+            let format = self.context.texture_format;
+            passes.push(PassDescription::new("target blits", texture_size, format));
+        }
+        if !self.pending_text_grids.is_empty() {
+            let format = self.context.texture_format;
+            passes.push(PassDescription::new("text grids", texture_size, format));
+        }
+        if !self.pending_sprites.is_empty() {
+            let format = self.context.texture_format;
+            passes.push(PassDescription::new("sprites", texture_size, format));
+        }
+        if self.imported_texture.is_none() && self.layers.iter().any(Layer::is_visible) {
+            let format = self.context.texture_format;
+            passes.push(PassDescription::new("layers", texture_size, format));
+        }
+        let scaling = if self.distortion_mesh.is_some() {
+            "scaling (distortion mesh)"
+        } else {
+            "scaling"
+        };
+        if self.registered_renderer.is_some() && !self.safe_mode {
+            passes.push(PassDescription::new(
+                "custom renderer",
+                render_size,
+                render_format,
+            ));
+        } else {
+            passes.push(PassDescription::new(scaling, render_size, render_format));
+            if self.context.grid_renderer.is_visible() {
+                passes.push(PassDescription::new(
+                    "pixel grid",
+                    render_size,
+                    render_format,
+                ));
+            }
+            if !self.selections().is_empty() {
+                passes.push(PassDescription::new(
+                    "selections",
+                    render_size,
+                    render_format,
+                ));
+            }
+        }
+        for name in self.effects.enabled_names() {
+            passes.push(PassDescription::new(
+                name.to_string(),
+                render_size,
+                render_format,
+            ));
+        }
+        if self.background_renderer.is_some() {
+            passes.push(PassDescription::new(
+                "background",
+                render_size,
+                render_format,
+            ));
+        }
+        if self.bezel_renderer.is_some() && !self.scaling_options.minimal {
+            passes.push(PassDescription::new(
+                "ambient bezel",
+                render_size,
+                render_format,
+            ));
+        }
+        if self.crossfade.is_some() {
+            passes.push(PassDescription::new(
+                "resize crossfade",
+                render_size,
+                render_format,
+            ));
+        }
+        if self.transition.is_some() {
+            passes.push(PassDescription::new(
+                "transition",
+                render_size,
+                render_format,
+            ));
+        }
+        if self.screen_flash.is_some() {
+            passes.push(PassDescription::new(
+                "screen flash",
+                render_size,
+                render_format,
+            ));
+        }
+        if self.watermark_renderer.is_some() {
+            passes.push(PassDescription::new(
+                "watermark",
+                render_size,
+                render_format,
+            ));
+        }
+        if self.supersampler.is_some() {
+            passes.push(PassDescription::new(
+                "downsample",
+                surface_size,
+                self.surface_texture_format,
+            ));
+        }
+        if self.frame_averager.is_some() {
+            passes.push(PassDescription::new(
+                "averaging",
+                surface_size,
+                self.surface_texture_format,
+            ));
+        }
+        if self.provenance_renderer.is_some() {
+            passes.push(PassDescription::new(
+                "provenance",
+                surface_size,
+                self.surface_texture_format,
+            ));
+        }
+
+        PipelineDescription {
+            adapter_name: self.adapter.get_info().name,
+            backend: self.backend(),
+            workarounds: self.workarounds.clone(),
+            texture_size,
+            texture_format: self.context.texture_format,
+            texture_ring_size: self.spare_textures.len() + 1,
+            upload_slices: self.upload_slices,
+            upload_strategy: self.upload_strategy(),
+            imported_texture: self.imported_texture.is_some(),
+            surface_size,
+            surface_texture_format: self.surface_texture_format,
+            surface_storage_format: self.surface_storage_format,
+            render_texture_format: render_format,
+            present_mode: self.surface_present_mode(),
+            alpha_mode: self.alpha_mode,
+            filter: self.scaling_options.filter,
+            scaling_mode: self.scaling_options.mode,
+            rotation: self.scaling_options.rotation,
+            flip: self.scaling_options.flip,
+            texture_border: self.scaling_options.border,
+            custom_sampler: self.scaling_options.sampler.is_some(),
+            palette: self.scaling_options.palette.is_some(),
+            packed_format: self.scaling_options.packed,
+            minimal_pipeline: self.scaling_options.minimal,
+            supersampling: self.supersampling(),
+            frame_averaging: self.frame_averaging(),
+            frame_interpolation: self.frame_interpolation(),
+            passes,
+        }
+    }
+
+    /// Summarize what the adapter and surface support: the adapter and driver, features, limits,
+    /// surface formats, present modes, and the configuration chosen from them.
+    ///
+    /// Ask users to attach the report to bug reports. With the `serde` feature, it can be
+    /// serialized, e.g. to JSON.
+    ///
+    /// ```no_run
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// let pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// eprintln!("{}", pixels.capability_report());
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    pub fn capability_report(&self) -> CapabilityReport {
+        let capabilities = self.surface_capabilities();
+
+        CapabilityReport {
+            adapter: self.adapter.get_info(),
+            features: self.adapter.features(),
+            downlevel: self.adapter.get_downlevel_capabilities(),
+            limits: self.context.device.limits(),
+            surface_formats: capabilities.formats,
+            present_modes: capabilities.present_modes,
+            alpha_modes: capabilities.alpha_modes,
+            configuration: self.describe_pipeline(),
+        }
+    }
+
+    /// Get the driver workarounds applied for the adapter.
+    ///
+    /// See the [`workarounds`] module and [`PixelsBuilder::workaround_override`].
+    pub fn workarounds(&self) -> &[workarounds::Workaround] {
+        &self.workarounds
+    }
+
+    /// Check whether the minimal scaling pipeline is in use.
+    ///
+    /// The minimal pipeline trades features for compatibility with downlevel targets: it draws
+    /// without vertex buffers and skips optional passes like the [`FlashLimiter`]. It is selected
+    /// by [`PixelsBuilder::downlevel_profile`], or automatically when the adapter only supports
+    /// GLES2-class shaders or needs the [`MinimalPipeline`] workaround.
+    ///
+    /// [`MinimalPipeline`]: workarounds::Workaround::MinimalPipeline
+    pub fn minimal_pipeline(&self) -> bool {
+        self.scaling_options.minimal
+    }
+
+    /// Change how the scaled pixel buffer fills the surface.
+    ///
+    /// See [`PixelsBuilder::scaling_mode`]. The scaling pass, the pixel grid, selections, and
+    /// [`Pixels::window_pos_to_pixel`] follow the new mode from the next frame.
+    pub fn set_scaling_mode(&mut self, mode: ScalingMode) {
+        self.scaling_options.mode = mode;
+        self.update_fit();
+    }
+
+    /// Get how the scaled pixel buffer fills the surface.
+    pub fn scaling_mode(&self) -> ScalingMode {
+        self.scaling_options.mode
+    }
+
+    /// Rotate the scaled pixel buffer clockwise on the surface.
     ///
-    /// The `wgpu` present mode will be set to `AutoVsync` when Vsync is enabled, or `AutoNoVsync`
-    /// when Vsync is disabled. To set the present mode to `Mailbox` or another value, use the
-    /// [`Pixels::set_present_mode`] method.
-    pub fn enable_vsync(&mut self, enable_vsync: bool) {
-        self.present_mode = if enable_vsync {
-            wgpu::PresentMode::AutoVsync
-        } else {
-            wgpu::PresentMode::AutoNoVsync
-        };
-        self.reconfigure_surface();
+    /// See [`PixelsBuilder::rotation`]. Like [`Pixels::set_scaling_mode`], the change applies to
+    /// the scaling pass, the pixel grid, selections, and [`Pixels::window_pos_to_pixel`] from the
+    /// next frame.
+    pub fn set_rotation(&mut self, rotation: Rotation) {
+        self.scaling_options.rotation = rotation;
+        self.update_fit();
     }
 
-    /// Get the `wgpu` present mode.
-    ///
-    /// Returns the present mode currently in use by the surface, which can be changed through
-    /// [`Pixels::enable_vsync`] or [`Pixels::set_present_mode`].
-    pub fn present_mode(&self) -> wgpu::PresentMode {
-        self.present_mode
+    /// Get the rotation of the scaled pixel buffer.
+    pub fn rotation(&self) -> Rotation {
+        self.scaling_options.rotation
     }
 
-    /// Set the `wgpu` present mode.
+    /// Mirror the scaled pixel buffer on the surface, `horizontal`ly and `vertical`ly.
     ///
-    /// This differs from [`Pixels::enable_vsync`] by allowing the present mode to be set to
-    /// any value.
-    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
-        self.present_mode = present_mode;
-        self.reconfigure_surface();
+    /// See [`PixelsBuilder::flip`]. The change applies from the next frame, like
+    /// [`Pixels::set_rotation`].
+    pub fn set_flip(&mut self, horizontal: bool, vertical: bool) {
+        self.scaling_options.flip = (horizontal, vertical);
+        self.update_fit();
     }
 
-    /// Draw this pixel buffer to the configured [`SurfaceTexture`].
-    ///
-    /// # Errors
+    /// Get whether the scaled pixel buffer is mirrored horizontally and vertically.
+    pub fn flip(&self) -> (bool, bool) {
+        self.scaling_options.flip
+    }
+
+    /// Fit the pixel buffer to the surface again, after the scaling mode or the orientation
+    /// changed.
+    fn update_fit(&mut self) {
+        self.redraw_pending = true;
+
+        let fit = self.scaling_options.fit();
+        let render_size = self.render_size();
+        let (width, height) = (render_size.width, render_size.height);
+        let queue = &self.context.queue;
+        self.context.scaling_renderer.set_fit(fit);
+        self.context.scaling_renderer.resize(queue, width, height);
+        if let Some(transition) = self.transition.as_mut() {
+            transition.set_fit(fit);
+            transition.resize(width, height);
+        }
+        self.context.grid_renderer.set_fit(fit);
+        self.context.selection_renderer.set_fit(fit);
+        self.update_view();
+    }
+
+    /// Scroll the view to `(x, y)` of the pixel buffer.
     ///
-    /// Returns an error when [`wgpu::Surface::get_current_texture`] fails.
+    /// The view is the rectangle of the pixel buffer which is scaled to the surface, see
+    /// [`Pixels::set_view_size`]. Tile-based games can draw a whole map into a pixel buffer larger
+    /// than the screen once and scroll over it by moving the view, which copies nothing. With
+    /// [dirty tracking](Pixels::set_dirty_tracking), renders which only scroll upload nothing
+    /// either.
     ///
-    /// # Example
+    /// The offset is clamped to keep the view within the pixel buffer. It is `(0, 0)` by default.
     ///
     /// ```no_run
     /// # use pixels::Pixels;
     /// # let window = pixels_mocks::Window;
     /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
-    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
-    ///
-    /// // Clear the pixel buffer
-    /// let frame = pixels.frame_mut();
-    /// for pixel in frame.chunks_exact_mut(4) {
-    ///     pixel[0] = 0x00; // R
-    ///     pixel[1] = 0x00; // G
-    ///     pixel[2] = 0x00; // B
-    ///     pixel[3] = 0xff; // A
-    /// }
+    /// # let camera = (100, 40);
+    /// // The whole level, of which a screen of 320x240 pixels is visible
+    /// let mut pixels = Pixels::new(2048, 1024, surface_texture)?;
+    /// pixels.set_view_size(320, 240);
     ///
-    /// // Draw it to the `SurfaceTexture`
+    /// // Follow the camera
+    /// pixels.set_view_offset(camera.0, camera.1);
     /// pixels.render()?;
     /// # Ok::<(), pixels::Error>(())
     /// ```
-    pub fn render(&self) -> Result<(), Error> {
-        self.render_with(|encoder, render_target, context| {
-            context.scaling_renderer.render(encoder, render_target);
-
-            Ok(())
-        })
+    pub fn set_view_offset(&mut self, x: u32, y: u32) {
+        self.view_offset = (x, y);
+        self.redraw_pending = true;
+        self.update_view();
     }
 
-    /// Draw this pixel buffer to the configured [`SurfaceTexture`] using a custom user-provided
-    /// render function.
+    /// Scale only `width` by `height` pixels of the pixel buffer to the surface, starting at the
+    /// [view offset](Pixels::set_view_offset).
     ///
-    /// Provides access to a [`wgpu::CommandEncoder`], a [`wgpu::TextureView`] from the surface
-    /// which you can use to render to the screen, and a [`PixelsContext`] with all of the internal
-    /// `wgpu` context.
+    /// The view is scaled like the whole pixel buffer would be, so the [`ScalingMode`] fits the
+    /// view to the surface, and the pixel grid and selections cover the view, with coordinates
+    /// relative to it. [`Pixels::window_pos_to_pixel`] and the [`Transform`] still return
+    /// coordinates in the pixel buffer, including the offset. Shader hooks get texture
+    /// coordinates in the whole pixel buffer. With linear filtering, the pixels just
+    /// outside of the view are blended into its edges, as they would be after scrolling.
     ///
-    /// The render function must return a `Result`. This allows fallible render functions to be
-    /// handled gracefully. The boxed `Error` will be made available in the [`Error::UserDefined`]
-    /// variant returned by `render_with()`.
+    /// The size is clamped to the pixel buffer. The view covers the whole pixel buffer by default.
     ///
-    /// # Errors
+    /// # Panics
     ///
-    /// Returns an error when either [`wgpu::Surface::get_current_texture`] or the provided render
-    /// function fails.
+    /// Panics when `width` or `height` are 0.
+    pub fn set_view_size(&mut self, width: u32, height: u32) {
+        assert!(width > 0 && height > 0, "the view must not be empty");
+
+        self.view_size = Some((width, height));
+        self.redraw_pending = true;
+        self.update_view();
+    }
+
+    /// Get the `(x, y, width, height)` rectangle of the pixel buffer which is scaled to the
+    /// surface, see [`Pixels::set_view_offset`] and [`Pixels::set_view_size`].
+    pub fn view(&self) -> (u32, u32, u32, u32) {
+        let extent = self.context.texture_extent;
+        let (width, height) = self
+            .view_size
+            .map_or((extent.width, extent.height), |(width, height)| {
+                (width.min(extent.width), height.min(extent.height))
+            });
+        let x = self.view_offset.0.min(extent.width - width);
+        let y = self.view_offset.1.min(extent.height - height);
+
+        (x, y, width, height)
+    }
+
+    /// Scale the view to the render target in the scaling pass, the overlays, and the mouse
+    /// transformation, after the view, the pixel buffer, the surface, or the scaling mode changed.
+    fn update_view(&mut self) {
+        let view = self.view();
+        let (x, y, width, height) = view;
+        let render_size = self.render_size();
+        let (render_width, render_height) = (render_size.width, render_size.height);
+
+        self.scaling_matrix_inverse =
+            ultraviolet::Mat4::from_translation(ultraviolet::Vec3::new(x as f32, y as f32, 0.0))
+                * renderers::ScalingMatrix::new(
+                    (width, height),
+                    (render_width, render_height),
+                    self.scaling_options.fit(),
+                )
+                .window_to_texture();
+
+        let queue = &self.context.queue;
+        let view_extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        self.context.scaling_renderer.set_view(queue, view);
+        self.context
+            .grid_renderer
+            .resize(queue, &view_extent, render_width, render_height);
+        self.context
+            .selection_renderer
+            .resize(queue, &view_extent, render_width, render_height);
+    }
+
+    /// Apply saved video settings in one call.
     ///
-    /// # Example
+    /// See [`PixelsConfig`]. Only the settings which differ from [`Pixels::config`] are changed,
+    /// so applying the same config again costs nothing.
     ///
-    /// ```no_run
-    /// # use pixels::Pixels;
-    /// # let window = pixels_mocks::Window;
-    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
-    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+    /// A new filter or texture format recreates the pixel buffer texture like
+    /// [`Pixels::resize_buffer`] at the same size: the frame is resized to the new format without
+    /// converting it, so redraw it afterward. A new texture format also disables a palette or a
+    /// packed format. While an [imported texture](Pixels::import_texture) is displayed, both take
+    /// effect with the next [`Pixels::resize_buffer`].
     ///
-    /// // Clear the pixel buffer
-    /// let frame = pixels.frame_mut();
-    /// for pixel in frame.chunks_exact_mut(4) {
-    ///     pixel[0] = 0x00; // R
-    ///     pixel[1] = 0x00; // G
-    ///     pixel[2] = 0x00; // B
-    ///     pixel[3] = 0xff; // A
-    /// }
+    /// # Errors
     ///
-    /// // Draw it to the `SurfaceTexture`
-    /// pixels.render_with(|encoder, render_target, context| {
-    ///     context.scaling_renderer.render(encoder, render_target);
-    ///     // etc...
-    ///     Ok(())
-    /// })?;
-    /// # Ok::<(), pixels::Error>(())
-    /// ```
-    pub fn render_with<F>(&self, render_function: F) -> Result<(), Error>
-    where
-        F: FnOnce(
-            &mut wgpu::CommandEncoder,
-            &wgpu::TextureView,
-            &PixelsContext,
-        ) -> Result<(), DynError>,
-    {
-        let frame = self.context.surface.get_current_texture().or_else(|_| {
-            // Reconfigure the surface and retry immediately on any error.
-            // See https://github.com/parasyte/pixels/issues/121
-            // See https://github.com/parasyte/pixels/issues/346
-            self.reconfigure_surface();
-            self.context.surface.get_current_texture()
-        })?;
-        let mut encoder =
-            self.context
-                .device
-                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("pixels_command_encoder"),
-                });
-
-        // Update the pixel buffer texture view
-        let bytes_per_row =
-            (self.context.texture_extent.width as f32 * self.context.texture_format_size) as u32;
-        self.context.queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &self.context.texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
-                aspect: wgpu::TextureAspect::All,
-            },
-            &self.pixels,
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: Some(bytes_per_row),
-                rows_per_image: Some(self.context.texture_extent.height),
-            },
-            self.context.texture_extent,
-        );
+    /// Returns an error when the pixel buffer texture cannot be created with the new format. The
+    /// pixel buffer is unchanged on error.
+    pub fn apply_config(&mut self, config: &PixelsConfig) -> Result<(), TextureError> {
+        let texture_format = self.imported_texture.unwrap_or(self.context.texture_format);
+        let format_changed = config.texture_format != texture_format;
+        if format_changed || config.filter != self.scaling_options.filter {
+            let scaling_options = self.scaling_options.clone();
+            self.scaling_options.filter = config.filter;
+            if format_changed {
+                self.scaling_options.palette = None;
+                self.scaling_options.packed = None;
+            }
 
-        let view = frame
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+            if let Some(buffer_format) = self.imported_texture.as_mut() {
+                *buffer_format = config.texture_format;
+            } else {
+                self.context.texture_format = config.texture_format;
+                let texture_extent = self.context.texture_extent;
+                if let Err(err) = self.resize_buffer(texture_extent.width, texture_extent.height) {
+                    self.scaling_options = scaling_options;
+                    self.context.texture_format = texture_format;
+                    return Err(err);
+                }
+            }
+        }
 
-        // Call the user's render function.
-        (render_function)(&mut encoder, &view, &self.context)?;
+        let current = self.config();
+        if config.scaling_mode != current.scaling_mode {
+            self.set_scaling_mode(config.scaling_mode);
+        }
+        if config.present_mode != current.present_mode {
+            self.set_present_mode(config.present_mode);
+        }
+        if config.clear_color != current.clear_color {
+            self.clear_color_srgb(config.clear_color);
+        }
+        if config.pixel_grid != current.pixel_grid {
+            self.set_pixel_grid(config.pixel_grid);
+        }
+        if config.ambient_bezel != current.ambient_bezel {
+            self.set_ambient_bezel(config.ambient_bezel);
+        }
+        if config.frame_interpolation != current.frame_interpolation {
+            self.set_frame_interpolation(config.frame_interpolation);
+        }
+        self.redraw_pending = true;
 
-        self.context.queue.submit(Some(encoder.finish()));
-        frame.present();
         Ok(())
     }
 
-    /// Reconfigure the surface.
+    /// Get the video settings in use, for saving them with the `serde` feature.
     ///
-    /// Call this when the surface or presentation mode needs to be changed.
-    pub(crate) fn reconfigure_surface(&self) {
-        self.context.surface.configure(
-            &self.context.device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: self.surface_texture_format,
-                width: self.surface_size.width,
-                height: self.surface_size.height,
-                present_mode: self.present_mode,
-                desired_maximum_frame_latency: 2,
-                alpha_mode: self.alpha_mode,
-                view_formats: vec![],
-            },
-        );
+    /// The present mode is the requested one, which may differ from [`Pixels::present_mode`] when
+    /// the surface does not support it.
+    pub fn config(&self) -> PixelsConfig {
+        // Linear render targets take clear values in linear light
+        let clear_color = self.context.scaling_renderer.clear_color;
+        let clear_color = if color::is_linear_target(self.render_texture_format) {
+            wgpu::Color {
+                r: linear_to_srgb(clear_color.r),
+                g: linear_to_srgb(clear_color.g),
+                b: linear_to_srgb(clear_color.b),
+                a: clear_color.a,
+            }
+        } else {
+            clear_color
+        };
+
+        PixelsConfig {
+            scaling_mode: self.scaling_options.mode,
+            filter: self.scaling_options.filter,
+            present_mode: self.present_mode,
+            clear_color,
+            texture_format: self.imported_texture.unwrap_or(self.context.texture_format),
+            pixel_grid: self.pixel_grid(),
+            ambient_bezel: self.ambient_bezel(),
+            frame_interpolation: self.frame_interpolation(),
+        }
     }
 
-    /// Get a mutable byte slice for the pixel buffer. The buffer is _not_ cleared for you; it will
-    /// retain the previous frame's contents until you clear it yourself.
-    pub fn frame_mut(&mut self) -> &mut [u8] {
-        &mut self.pixels
+    /// Get the [`TextureBorder`] in use, which is [`TextureBorder::Clamp`] when the requested
+    /// border color is not supported by the device.
+    pub fn texture_border(&self) -> TextureBorder {
+        self.scaling_options.border
     }
 
-    /// Get an immutable byte slice for the pixel buffer.
+    /// Replace the sampler of the scaling pass, or restore the one derived from the filter and
+    /// texture border with `None`.
     ///
-    /// This may be useful for operations that must sample the buffer, such as blending pixel
-    /// colours directly into it.
-    pub fn frame(&self) -> &[u8] {
-        &self.pixels
+    /// See [`PixelsBuilder::sampler_descriptor`]. The descriptor is ignored when the device does
+    /// not support its address modes; check [`Pixels::sampler_descriptor`] for the one in use.
+    pub fn set_sampler_descriptor(&mut self, descriptor: Option<wgpu::SamplerDescriptor<'static>>) {
+        let features = self.context.device.features();
+        self.scaling_options.sampler = descriptor.filter(|descriptor| {
+            features.contains(renderers::sampler_required_features(descriptor))
+        });
+        self.context
+            .scaling_renderer
+            .set_sampler(&self.context.device, &self.scaling_options);
     }
 
-    /// Calculate the pixel location from a physical location on the window,
-    /// dealing with window resizing, scaling, and margins. Takes a physical
-    /// position (x, y) within the window, and returns a pixel position (x, y).
-    ///
-    /// The location must be given in physical units (for example, winit's `PhysicalLocation`)
+    /// Get the custom sampler descriptor of the scaling pass, if one is in use.
+    pub fn sampler_descriptor(&self) -> Option<&wgpu::SamplerDescriptor<'static>> {
+        self.scaling_options.sampler.as_ref()
+    }
+
+    /// Replace the palette of an indexed pixel buffer, e.g. for palette cycling.
     ///
-    /// If the given physical position is outside of the drawing area, this
-    /// function returns an `Err` value with the pixel coordinates outside of
-    /// the screen, using isize instead of usize.
+    /// Only the 4 KiB palette is uploaded; the frame is not touched. This does nothing unless the
+    /// pixel buffer was built with [`PixelsBuilder::palette`].
     ///
     /// ```no_run
-    /// use winit::dpi::PhysicalPosition;
-    ///
-    /// # use pixels::Pixels;
+    /// # use pixels::PixelsBuilder;
     /// # let window = pixels_mocks::Window;
     /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
-    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
-    ///
-    /// // A cursor position in physical units
-    /// let cursor_position: (f32, f32) = PhysicalPosition::new(0.0, 0.0).into();
+    /// # let mut palette = [[0; 4]; 256];
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .palette(&palette)
+    ///     .build()?;
     ///
-    /// // Convert it to a pixel location
-    /// let pixel_position: (usize, usize) = pixels.window_pos_to_pixel(cursor_position)
-    ///     // Clamp the output to within the screen
-    ///     .unwrap_or_else(|pos| pixels.clamp_pixel_pos(pos));
+    /// // Cycle the colors of a waterfall in indices 16 to 31
+    /// palette[16..32].rotate_left(1);
+    /// pixels.set_palette(&palette);
     /// # Ok::<(), pixels::Error>(())
     /// ```
-    pub fn window_pos_to_pixel(
-        &self,
-        physical_position: (f32, f32),
-    ) -> Result<(usize, usize), (isize, isize)> {
-        let physical_width = self.surface_size.width as f32;
-        let physical_height = self.surface_size.height as f32;
-
-        let pixels_width = self.context.texture_extent.width as f32;
-        let pixels_height = self.context.texture_extent.height as f32;
+    pub fn set_palette(&mut self, palette: &[[u8; 4]; 256]) {
+        if let Some(current) = self.scaling_options.palette.as_mut() {
+            **current = *palette;
+            self.redraw_pending = true;
+            self.context
+                .scaling_renderer
+                .set_palette(&self.context.queue, palette);
+        }
+    }
 
-        let pos = ultraviolet::Vec4::new(
-            (physical_position.0 / physical_width - 0.5) * pixels_width,
-            (physical_position.1 / physical_height - 0.5) * pixels_height,
-            0.0,
-            1.0,
-        );
+    /// Replace some colors of the palette of an indexed pixel buffer, starting at index `start`.
+    ///
+    /// This is [`Pixels::set_palette`] for palette editors and effects which change a few colors
+    /// at a time: only the changed entries are uploaded, 16 bytes each. Like `set_palette`, it
+    /// does nothing unless the pixel buffer was built with [`PixelsBuilder::palette`]. The whole
+    /// palette is available from [`Pixels::palette`].
+    ///
+    /// ```no_run
+    /// # use pixels::PixelsBuilder;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+    /// # let palette = [[0; 4]; 256];
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .palette(&palette)
+    ///     .build()?;
+    ///
+    /// // The user picked a new color for index 42 in a color picker
+    /// pixels.update_palette(42, &[[0xff, 0x80, 0x00, 0xff]]);
+    /// assert_eq!(pixels.palette().unwrap()[42], [0xff, 0x80, 0x00, 0xff]);
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when the colors extend past the end of the palette, i.e. `start + colors.len()` is
+    /// greater than 256.
+    pub fn update_palette(&mut self, start: usize, colors: &[[u8; 4]]) {
+        let end = start
+            .checked_add(colors.len())
+            .filter(|&end| end <= 256)
+            .expect("palette colors out of range");
 
-        let pos = self.scaling_matrix_inverse * pos;
-        let offset_width = pixels_width.min(physical_width) / 2.0;
-        let offset_height = pixels_height.min(physical_height) / 2.0;
+        if let Some(current) = self.scaling_options.palette.as_mut() {
+            current[start..end].copy_from_slice(colors);
+            self.redraw_pending = true;
+            self.context
+                .scaling_renderer
+                .update_palette(&self.context.queue, start, colors);
+        }
+    }
 
-        let pixel_x = (pos.x / pos.w + offset_width).floor() as isize;
-        let pixel_y = (pos.y / pos.w + offset_height).floor() as isize;
+    /// Replace the uniforms of the [shader hook](PixelsBuilder::shader_hook).
+    ///
+    /// The new values are uploaded for the next render, without rebuilding the pipeline, so they
+    /// can change every frame, e.g. with the time or a fade amount. This does nothing unless the
+    /// pixel buffer was built with a shader hook.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `uniforms` is larger than the uniforms the hook was built with.
+    pub fn set_uniform<T: bytemuck::Pod>(&mut self, uniforms: &T) {
+        let bytes = bytemuck::bytes_of(uniforms);
 
-        if pixel_x < 0
-            || pixel_x >= self.context.texture_extent.width as isize
-            || pixel_y < 0
-            || pixel_y >= self.context.texture_extent.height as isize
-        {
-            Err((pixel_x, pixel_y))
-        } else {
-            Ok((pixel_x as usize, pixel_y as usize))
+        if let Some(hook) = self.scaling_options.hook.as_mut() {
+            assert!(
+                bytes.len() <= hook.uniforms.len(),
+                "uniforms larger than the shader hook uniform buffer",
+            );
+            hook.uniforms[..bytes.len()].copy_from_slice(bytes);
+            self.redraw_pending = true;
+            self.context
+                .scaling_renderer
+                .set_hook_uniforms(&self.context.queue, &hook.uniforms);
         }
     }
 
-    /// Clamp a pixel position to the pixel buffer texture size.
+    /// Replace the [gamma ramp](PixelsBuilder::gamma_ramp) applied to the output.
     ///
-    /// This can be used to clamp the `Err` value returned by [`Pixels::window_pos_to_pixel`]
-    /// to a position clamped within the drawing area.
+    /// Only the 4 KiB ramp is uploaded, so a port can call this wherever the original called
+    /// `SetDeviceGammaRamp`, e.g. every frame of a fade. This does nothing unless the pixel buffer
+    /// was built with a gamma ramp.
     ///
     /// ```no_run
-    /// # use pixels::Pixels;
+    /// use pixels::{GammaRamp, PixelsBuilder};
+    ///
     /// # let window = pixels_mocks::Window;
     /// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
-    /// let mut pixels = Pixels::new(320, 240, surface_texture)?;
-    ///
-    /// let pixel_pos = pixels.clamp_pixel_pos((-19, 20));
-    /// assert_eq!(pixel_pos, (0, 20));
+    /// let mut pixels = PixelsBuilder::new(320, 240, surface_texture)
+    ///     .gamma_ramp(&GammaRamp::default())
+    ///     .build()?;
     ///
-    /// let pixel_pos = pixels.clamp_pixel_pos((11, 3000));
-    /// assert_eq!(pixel_pos, (11, 239));
+    /// // The player moved the brightness slider
+    /// pixels.set_gamma_ramp(&GammaRamp::from_gamma(1.4));
     /// # Ok::<(), pixels::Error>(())
     /// ```
-    pub fn clamp_pixel_pos(&self, pos: (isize, isize)) -> (usize, usize) {
-        (
-            pos.0
-                .clamp(0, self.context.texture_extent.width as isize - 1) as usize,
-            pos.1
-                .clamp(0, self.context.texture_extent.height as isize - 1) as usize,
-        )
+    pub fn set_gamma_ramp(&mut self, ramp: &GammaRamp) {
+        if let Some(current) = self.scaling_options.gamma_ramp.as_mut() {
+            **current = ramp.clone();
+            self.redraw_pending = true;
+            self.context
+                .scaling_renderer
+                .set_gamma_ramp(&self.context.queue, ramp);
+        }
     }
 
-    /// Provides access to the internal [`wgpu::Device`].
-    pub fn device(&self) -> &wgpu::Device {
-        &self.context.device
+    /// Get the gamma ramp applied to the output, see [`PixelsBuilder::gamma_ramp`].
+    pub fn gamma_ramp(&self) -> Option<&GammaRamp> {
+        self.scaling_options.gamma_ramp.as_deref()
     }
 
-    /// Provides access to the internal [`wgpu::Queue`].
-    pub fn queue(&self) -> &wgpu::Queue {
-        &self.context.queue
+    /// Replace the [`Tonemapping`] curve, see [`PixelsBuilder::tonemapping`].
+    ///
+    /// # Panics
+    ///
+    /// Panics when the white point of [`Tonemapping::Reinhard`] is not positive.
+    pub fn set_tonemapping(&mut self, tonemapping: Tonemapping) {
+        tonemapping.validate();
+        self.scaling_options.tonemapping = tonemapping;
+        self.update_tonemapping();
     }
 
-    /// Provides access to the internal source [`wgpu::Texture`].
+    /// Get the [`Tonemapping`] curve.
+    pub fn tonemapping(&self) -> Tonemapping {
+        self.scaling_options.tonemapping
+    }
+
+    /// Replace the exposure, see [`PixelsBuilder::exposure`].
     ///
-    /// This is the pre-scaled texture copied from the pixel buffer.
-    pub fn texture(&self) -> &wgpu::Texture {
-        &self.context.texture
+    /// # Panics
+    ///
+    /// Panics when `exposure` is negative or not finite.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        assert!(exposure.is_finite() && exposure >= 0.0);
+        self.scaling_options.exposure = exposure;
+        self.update_tonemapping();
     }
 
-    /// Provides access to the internal [`PixelsContext`].
-    pub fn context(&self) -> &PixelsContext {
-        &self.context
+    /// Get the exposure.
+    pub fn exposure(&self) -> f32 {
+        self.scaling_options.exposure
     }
 
-    /// Get the surface texture format.
+    fn update_tonemapping(&mut self) {
+        let options = &self.scaling_options;
+        self.context.scaling_renderer.set_tonemapping(
+            &self.context.queue,
+            options.tonemapping,
+            options.exposure,
+        );
+        self.redraw_pending = true;
+    }
+
+    /// Check if the surface is HDR, with colors brighter than SDR white, see
+    /// [`PixelsBuilder::hdr`].
+    pub fn is_hdr(&self) -> bool {
+        self.surface_texture_format == wgpu::TextureFormat::Rgba16Float
+    }
+
+    /// Get the layout of a packed pixel buffer, see [`PixelsBuilder::packed_format`].
+    pub fn packed_format(&self) -> Option<PackedFormat> {
+        self.scaling_options.packed
+    }
+
+    /// Get the palette of an indexed pixel buffer, see [`PixelsBuilder::palette`].
+    pub fn palette(&self) -> Option<&[[u8; 4]; 256]> {
+        self.scaling_options.palette.as_deref()
+    }
+
+    /// Get the composite alpha mode of the surface.
     ///
-    /// This texture format may be chosen automatically by the surface. See
-    /// [`PixelsBuilder::surface_texture_format`] for more information.
-    pub fn surface_texture_format(&self) -> wgpu::TextureFormat {
-        self.surface_texture_format
+    /// Anything other than [`wgpu::CompositeAlphaMode::Opaque`] lets the desktop show through
+    /// transparent pixels. See [`PixelsBuilder::alpha_mode`] and [`PixelsBuilder::overlay_mode`].
+    pub fn alpha_mode(&self) -> wgpu::CompositeAlphaMode {
+        self.alpha_mode
+    }
+
+    /// Get the composite alpha modes supported by the surface on this platform.
+    ///
+    /// A transparent window is only possible when this contains a mode other than
+    /// [`wgpu::CompositeAlphaMode::Opaque`].
+    pub fn supported_alpha_modes(&self) -> Vec<wgpu::CompositeAlphaMode> {
+        self.surface_capabilities().alpha_modes
     }
 
     /// Get the render texture format.
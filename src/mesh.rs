@@ -0,0 +1,79 @@
+//! Distortion meshes for warping the scaled pixel buffer.
+
+use bytemuck::{Pod, Zeroable};
+
+/// One vertex of a [`DistortionMesh`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct MeshVertex {
+    /// The position within the scaled pixel buffer, from `-1.0` (left, bottom) to `1.0` (right,
+    /// top). Positions outside this range are clipped.
+    pub position: [f32; 2],
+
+    /// The texture coordinate to sample, from `0.0` (left, top) to `1.0` (right, bottom).
+    pub tex_coord: [f32; 2],
+}
+
+/// A triangle mesh which replaces the full-screen triangle of the scaling pass.
+///
+/// Each vertex maps a position within the scaled pixel buffer to a texture coordinate, so the
+/// output can be warped for projection mapping, curved-screen simulation, or lens correction. The
+/// scaling transform, texture uploads, and filtering work as usual. Set the mesh with
+/// [`Pixels::set_distortion_mesh`](crate::Pixels::set_distortion_mesh).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DistortionMesh {
+    /// The vertices of the mesh.
+    pub vertices: Vec<MeshVertex>,
+
+    /// Three indices into `vertices` for each triangle.
+    pub indices: Vec<u32>,
+}
+
+impl DistortionMesh {
+    /// Create a regular grid of `columns` by `rows` cells, and warp each vertex.
+    ///
+    /// The `warp` function receives the undistorted position of a vertex and returns where it
+    /// should be drawn. Texture coordinates follow the undistorted grid.
+    ///
+    /// ```
+    /// use pixels::DistortionMesh;
+    ///
+    /// // Barrel distortion, as seen on a curved CRT screen
+    /// let mesh = DistortionMesh::grid(16, 16, |[x, y]| {
+    ///     let k = 1.0 - 0.1 * (x * x + y * y);
+    ///     [x * k, y * k]
+    /// });
+    ///
+    /// assert_eq!(mesh.vertices.len(), 17 * 17);
+    /// assert_eq!(mesh.indices.len(), 16 * 16 * 6);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `columns` or `rows` are 0.
+    pub fn grid(columns: u32, rows: u32, warp: impl Fn([f32; 2]) -> [f32; 2]) -> Self {
+        assert!(columns > 0);
+        assert!(rows > 0);
+
+        let vertices = (0..=rows)
+            .flat_map(|row| (0..=columns).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let tex_coord = [column as f32 / columns as f32, row as f32 / rows as f32];
+                let position = [tex_coord[0] * 2.0 - 1.0, 1.0 - tex_coord[1] * 2.0];
+
+                MeshVertex {
+                    position: warp(position),
+                    tex_coord,
+                }
+            })
+            .collect();
+
+        let stride = columns + 1;
+        let indices = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| row * stride + column))
+            .flat_map(|i| [i, i + 1, i + stride, i + stride, i + 1, i + stride + 1])
+            .collect();
+
+        Self { vertices, indices }
+    }
+}
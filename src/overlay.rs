@@ -0,0 +1,100 @@
+//! Desktop overlay mode for desktop pets, stream overlays, and other always-on-top widgets.
+//!
+//! An overlay is a borderless window that floats above other windows, shows the desktop through
+//! transparent pixels, and optionally lets mouse clicks pass through to the windows beneath it.
+//! Building one takes two halves:
+//!
+//! 1. The window must be created transparent, undecorated, and on top. With the `winit` feature
+//!    enabled, [`window_attributes`] configures this, and [`set_click_through`] toggles input
+//!    pass-through at runtime.
+//! 2. The surface must be composited with alpha. [`PixelsBuilder::overlay_mode`] selects a
+//!    transparent composite alpha mode and clears the border to transparent black. The pixel
+//!    buffer alpha channel then controls which parts of the window are see-through.
+//!
+//! Not every platform can composite a transparent surface. Check [`Pixels::alpha_mode`] after
+//! building: when it is [`wgpu::CompositeAlphaMode::Opaque`], the window will be opaque. To fail
+//! instead, request a mode from [`Pixels::supported_alpha_modes`] with
+//! [`PixelsBuilder::alpha_mode`].
+//!
+//! [`PixelsBuilder::overlay_mode`]: crate::PixelsBuilder::overlay_mode
+//! [`PixelsBuilder::alpha_mode`]: crate::PixelsBuilder::alpha_mode
+//! [`Pixels::alpha_mode`]: crate::Pixels::alpha_mode
+//! [`Pixels::supported_alpha_modes`]: crate::Pixels::supported_alpha_modes
+
+/// Composite alpha modes that show the desktop through transparent pixels, in order of
+/// preference.
+///
+/// The scaling renderer blends straight alpha onto a transparent clear color, which produces
+/// premultiplied output, so that mode is preferred.
+const TRANSPARENT_ALPHA_MODES: [wgpu::CompositeAlphaMode; 3] = [
+    wgpu::CompositeAlphaMode::PreMultiplied,
+    wgpu::CompositeAlphaMode::Inherit,
+    wgpu::CompositeAlphaMode::PostMultiplied,
+];
+
+/// Pick the best transparent alpha mode supported by the surface.
+pub(crate) fn transparent_alpha_mode(
+    supported: &[wgpu::CompositeAlphaMode],
+) -> Option<wgpu::CompositeAlphaMode> {
+    TRANSPARENT_ALPHA_MODES
+        .into_iter()
+        .find(|mode| supported.contains(mode))
+}
+
+/// Configure window attributes for an overlay window.
+///
+/// The window is made transparent, undecorated, and always on top of other windows.
+///
+/// ```no_run
+/// use pixels::{overlay, PixelsBuilder, SurfaceTexture};
+/// use winit::event_loop::ActiveEventLoop;
+/// use winit::window::Window;
+///
+/// fn create_overlay(event_loop: &ActiveEventLoop) -> Result<(), Box<dyn std::error::Error>> {
+///     let attributes = overlay::window_attributes(Window::default_attributes());
+///     let window = event_loop.create_window(attributes)?;
+///     overlay::set_click_through(&window, true)?;
+///
+///     let size = window.inner_size();
+///     let surface_texture = SurfaceTexture::new(size.width, size.height, &window);
+///     let pixels = PixelsBuilder::new(64, 64, surface_texture)
+///         .overlay_mode(true)
+///         .build()?;
+///     # Ok(())
+/// }
+/// ```
+#[cfg(feature = "winit")]
+pub fn window_attributes(
+    attributes: winit::window::WindowAttributes,
+) -> winit::window::WindowAttributes {
+    attributes
+        .with_transparent(true)
+        .with_decorations(false)
+        .with_window_level(winit::window::WindowLevel::AlwaysOnTop)
+}
+
+/// Let mouse input pass through the window to the windows beneath it.
+///
+/// Enable this for purely decorative overlays; disable it again while the user should be able to
+/// interact with the overlay (e.g. to drag it around).
+///
+/// # Errors
+///
+/// Returns an error when the platform does not support click-through windows.
+#[cfg(feature = "winit")]
+pub fn set_click_through(
+    window: &winit::window::Window,
+    click_through: bool,
+) -> Result<(), winit::error::ExternalError> {
+    window.set_cursor_hittest(!click_through)
+}
+
+/// Keep the window above all other windows, or return it to the normal window level.
+#[cfg(feature = "winit")]
+pub fn set_always_on_top(window: &winit::window::Window, always_on_top: bool) {
+    window.set_window_level(if always_on_top {
+        winit::window::WindowLevel::AlwaysOnTop
+    } else {
+        winit::window::WindowLevel::Normal
+    });
+}
@@ -0,0 +1,118 @@
+//! A common interface for render passes published by other crates.
+
+use crate::{FrameInfo, PixelsContext, TextureError};
+
+/// A render pass which plugs into
+/// [`Pixels::render_with_renderers`](crate::Pixels::render_with_renderers).
+///
+/// Effect crates implement this trait so that applications can combine their passes without
+/// glue code for each one: CRT filters, noise, color grading, and other post-processing. A
+/// renderer is created from a [`Pixels`](crate::Pixels) instance, usually taking the
+/// [render texture format](crate::Pixels::render_texture_format), and then draws to the render
+/// target on every frame. Passes which process the scaled pixel buffer, like the `NoiseRenderer`
+/// of the `custom-shader` example, draw it into their own texture with
+/// [`ScalingRenderer::render`](crate::ScalingRenderer::render) first.
+///
+/// ```no_run
+/// use pixels::wgpu;
+/// use pixels::{DefaultPasses, FrameInfo, Pixels, PixelsContext, PixelsRenderer};
+///
+/// /// Counts frames; a real effect would update uniforms and draw here.
+/// struct FrameCounter(u64);
+///
+/// impl PixelsRenderer for FrameCounter {
+///     fn prepare(&mut self, info: &FrameInfo, _context: &PixelsContext<'_>) {
+///         self.0 = info.index;
+///     }
+///
+///     fn render(
+///         &self,
+///         _encoder: &mut wgpu::CommandEncoder,
+///         _render_target: &wgpu::TextureView,
+///         _context: &PixelsContext<'_>,
+///     ) {
+///     }
+/// }
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let mut pixels = Pixels::new(320, 240, surface_texture)?;
+/// let mut counter = FrameCounter(0);
+/// pixels.render_with_renderers(&mut [&mut DefaultPasses, &mut counter])?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+pub trait PixelsRenderer {
+    /// Update per-frame state, such as uniforms, before any renderer draws.
+    ///
+    /// The default implementation does nothing.
+    fn prepare(&mut self, info: &FrameInfo, context: &PixelsContext<'_>) {
+        let _ = (info, context);
+    }
+
+    /// Resize the render target dependent resources to `width` by `height` physical pixels.
+    ///
+    /// This is called before the first frame, and whenever the render target changes size. The
+    /// default implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when a texture of the new size cannot be created.
+    fn resize(
+        &mut self,
+        context: &PixelsContext<'_>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError> {
+        let _ = (context, width, height);
+        Ok(())
+    }
+
+    /// Record the pass into `encoder`, drawing to `render_target`.
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        context: &PixelsContext<'_>,
+    );
+}
+
+/// The renderer registered with [`Pixels::set_renderer`](crate::Pixels::set_renderer), and the
+/// render target size it was last resized to.
+pub(crate) struct RegisteredRenderer {
+    pub(crate) renderer: Box<dyn PixelsRenderer>,
+    pub(crate) size: Option<(u32, u32)>,
+}
+
+impl std::fmt::Debug for RegisteredRenderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisteredRenderer")
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The passes drawn by [`Pixels::render`](crate::Pixels::render): the scaled pixel buffer, the
+/// pixel grid, and selections.
+///
+/// Put it first in the list passed to
+/// [`Pixels::render_with_renderers`](crate::Pixels::render_with_renderers) to draw other
+/// renderers over the pixel buffer.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultPasses;
+
+impl PixelsRenderer for DefaultPasses {
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        context: &PixelsContext<'_>,
+    ) {
+        context.scope(encoder, "pixels_scaling", |encoder| {
+            context.scaling_renderer.render(encoder, render_target);
+        });
+        context.scope(encoder, "pixels_overlays", |encoder| {
+            context.grid_renderer.render(encoder, render_target);
+            context.selection_renderer.render(encoder, render_target);
+        });
+    }
+}
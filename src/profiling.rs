@@ -0,0 +1,423 @@
+//! Named GPU timing scopes and per-frame statistics.
+
+use crate::latency::InputLatency;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use web_time::Duration;
+
+/// The most timestamps written in one frame: the start and end of the frame, and two for each
+/// timed scope. Scopes beyond it are still debug groups, but they are not timed.
+const MAX_TIMESTAMPS: u32 = 128;
+
+/// The number of frames whose timestamps can be read back at once. Frames which end while every
+/// readback buffer is in flight are not timed on the GPU.
+const READBACK_BUFFERS: usize = 3;
+
+/// The features a device needs for timed scopes.
+pub(crate) const TIMESTAMP_FEATURES: wgpu::Features =
+    wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+/// The GPU time taken by a named scope, see [`PixelsContext::scope`](crate::PixelsContext::scope).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ScopeTiming {
+    /// The name the scope was opened with.
+    pub name: String,
+
+    /// The number of scopes this one is nested in.
+    pub depth: usize,
+
+    /// The time between the GPU reaching the start and the end of the scope.
+    pub duration: Duration,
+}
+
+/// Statistics of a rendered frame, see [`Pixels::frame_stats`](crate::Pixels::frame_stats).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct FrameStats {
+    /// The index of the frame, like [`FrameInfo::index`](crate::FrameInfo::index).
+    pub index: u64,
+
+    /// The CPU time taken to upload the pixel buffer and the frame metadata, and to record the
+    /// pending blits.
+    pub upload: Duration,
+
+    /// The CPU time taken to finish the command encoder and submit it to the queue.
+    pub submit: Duration,
+
+    /// The GPU time taken by the render passes of the frame, from the first command of its
+    /// encoder to the last.
+    ///
+    /// This is `None` when the device does not support timestamp queries, or when the frame
+    /// ended while the timestamps of several earlier frames were still being read back.
+    pub gpu_render: Option<Duration>,
+
+    /// The timed scopes of the frame, in the order they were opened.
+    ///
+    /// This is empty when [`FrameStats::gpu_render`] is `None`.
+    pub scopes: Vec<ScopeTiming>,
+
+    /// The newest input latency measurement which finished since the statistics of the previous
+    /// frame, while the [latency probe](crate::Pixels::set_latency_probe) is enabled.
+    ///
+    /// The measurement is usually of an earlier frame, see [`InputLatency::index`].
+    pub input_latency: Option<InputLatency>,
+}
+
+/// A callback for the statistics of every frame, see
+/// [`Pixels::on_frame_stats`](crate::Pixels::on_frame_stats).
+type StatsCallback = Box<dyn FnMut(&FrameStats) + Send>;
+
+/// The result of mapping a readback buffer, set by the `map_async` callback.
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+/// Records the scopes of each frame, and reads their timestamps back.
+pub(crate) struct Profiler {
+    enabled: bool,
+    timestamps: Option<Timestamps>,
+    // Scopes are opened through a shared `PixelsContext`
+    frame: Mutex<FrameScopes>,
+    // Frames waiting for their timestamps, oldest first
+    in_flight: VecDeque<InFlightStats>,
+    // An untimed frame waiting for its submit time
+    pending: Option<FrameStats>,
+    stats: Option<FrameStats>,
+    // Added to the next completed statistics
+    input_latency: Option<InputLatency>,
+    // Only called through `&mut self`; the mutex keeps `PixelsContext` shareable between threads
+    callback: Option<Mutex<StatsCallback>>,
+}
+
+impl std::fmt::Debug for Profiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Profiler")
+            .field("enabled", &self.enabled)
+            .field("timestamps", &self.timestamps)
+            .field("in_flight", &self.in_flight.len())
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct Timestamps {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    free_buffers: Vec<wgpu::Buffer>,
+    // Nanoseconds per timestamp tick
+    period: f32,
+}
+
+#[derive(Debug, Default)]
+struct FrameScopes {
+    scopes: Vec<RecordedScope>,
+    depth: usize,
+    next_query: u32,
+    // Timed scopes which are still open
+    open: u32,
+}
+
+#[derive(Debug)]
+struct RecordedScope {
+    name: String,
+    depth: usize,
+    start: u32,
+    end: Option<u32>,
+}
+
+/// An open scope; `None` for scopes which are not timed.
+#[derive(Debug)]
+pub(crate) struct ScopeToken(Option<usize>);
+
+#[derive(Debug)]
+struct InFlightStats {
+    stats: FrameStats,
+    scopes: Vec<RecordedScope>,
+    // The buffer and the number of timestamps copied to it; `None` when no buffer was free
+    readback: Option<(wgpu::Buffer, u32)>,
+    mapped: Option<MapResult>,
+}
+
+impl Profiler {
+    /// Create a profiler, which times frames and scopes when `enabled` and the device supports
+    /// timestamp queries.
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue, enabled: bool) -> Self {
+        let timestamps = (enabled && device.features().contains(TIMESTAMP_FEATURES)).then(|| {
+            let size = u64::from(MAX_TIMESTAMPS) * wgpu::QUERY_SIZE as u64;
+
+            Timestamps {
+                query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+                    label: Some("pixels_timestamp_query_set"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: MAX_TIMESTAMPS,
+                }),
+                resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("pixels_timestamp_resolve_buffer"),
+                    size,
+                    usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                }),
+                free_buffers: (0..READBACK_BUFFERS)
+                    .map(|_| {
+                        device.create_buffer(&wgpu::BufferDescriptor {
+                            label: Some("pixels_timestamp_readback_buffer"),
+                            size,
+                            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                            mapped_at_creation: false,
+                        })
+                    })
+                    .collect(),
+                period: queue.get_timestamp_period(),
+            }
+        });
+
+        Self {
+            enabled,
+            timestamps,
+            frame: Mutex::default(),
+            in_flight: VecDeque::new(),
+            pending: None,
+            stats: None,
+            input_latency: None,
+            callback: None,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Get the statistics of the most recent frame which were completed.
+    pub(crate) fn stats(&self) -> Option<&FrameStats> {
+        self.stats.as_ref()
+    }
+
+    /// Call `callback` with the statistics of every frame completed from now on.
+    pub(crate) fn set_callback(&mut self, callback: StatsCallback) {
+        self.callback = Some(Mutex::new(callback));
+    }
+
+    /// Report `input_latency` with the statistics of the next frame which is completed.
+    pub(crate) fn report_input_latency(&mut self, input_latency: InputLatency) {
+        self.input_latency = Some(input_latency);
+    }
+
+    /// Take over the callback and the latest statistics of the profiler of a lost device.
+    pub(crate) fn inherit(&mut self, lost: &mut Profiler) {
+        self.callback = lost.callback.take();
+        self.stats = lost.stats.take();
+    }
+
+    /// Open a scope named `name`, writing its start timestamp when scopes are timed.
+    pub(crate) fn begin(&self, encoder: &mut wgpu::CommandEncoder, name: &str) -> ScopeToken {
+        let mut frame = self.frame.lock().unwrap();
+        let depth = frame.depth;
+        frame.depth += 1;
+
+        let Some(timestamps) = &self.timestamps else {
+            return ScopeToken(None);
+        };
+        // Keep timestamps for the ends of the open scopes, this scope, and the frame
+        if frame.next_query + frame.open + 2 >= MAX_TIMESTAMPS {
+            return ScopeToken(None);
+        }
+
+        let start = frame.next_query;
+        encoder.write_timestamp(&timestamps.query_set, start);
+        frame.next_query += 1;
+        frame.open += 1;
+        frame.scopes.push(RecordedScope {
+            name: name.to_string(),
+            depth,
+            start,
+            end: None,
+        });
+
+        ScopeToken(Some(frame.scopes.len() - 1))
+    }
+
+    /// Close the scope opened with `token`, writing its end timestamp when it is timed.
+    pub(crate) fn end(&self, encoder: &mut wgpu::CommandEncoder, token: ScopeToken) {
+        let mut frame = self.frame.lock().unwrap();
+        frame.depth = frame.depth.saturating_sub(1);
+
+        if let (Some(timestamps), Some(index)) = (&self.timestamps, token.0) {
+            let end = frame.next_query;
+            encoder.write_timestamp(&timestamps.query_set, end);
+            frame.next_query += 1;
+            frame.open -= 1;
+            frame.scopes[index].end = Some(end);
+        }
+    }
+
+    /// Start recording a frame into `encoder`, and complete the frames whose timestamps were
+    /// read back, without blocking.
+    pub(crate) fn start_frame(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+    ) {
+        // Drop the scopes of a frame which was not submitted
+        let frame = self.frame.get_mut().unwrap();
+        *frame = FrameScopes::default();
+
+        if let Some(timestamps) = &self.timestamps {
+            encoder.write_timestamp(&timestamps.query_set, 0);
+            frame.next_query = 1;
+        }
+
+        self.collect(device);
+    }
+
+    /// Record the end of the frame `index` into `encoder`, and the readback of its timestamps,
+    /// before it is submitted.
+    pub(crate) fn finish(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        index: u64,
+        upload: Duration,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        let frame = std::mem::take(self.frame.get_mut().unwrap());
+        let stats = FrameStats {
+            index,
+            upload,
+            ..FrameStats::default()
+        };
+        let Some(timestamps) = &mut self.timestamps else {
+            self.pending = Some(stats);
+            return;
+        };
+
+        let queries = frame.next_query + 1;
+        encoder.write_timestamp(&timestamps.query_set, frame.next_query);
+        let readback = timestamps.free_buffers.pop().map(|buffer| {
+            encoder.resolve_query_set(
+                &timestamps.query_set,
+                0..queries,
+                &timestamps.resolve_buffer,
+                0,
+            );
+            encoder.copy_buffer_to_buffer(
+                &timestamps.resolve_buffer,
+                0,
+                &buffer,
+                0,
+                u64::from(queries) * wgpu::QUERY_SIZE as u64,
+            );
+
+            (buffer, queries)
+        });
+
+        self.in_flight.push_back(InFlightStats {
+            stats,
+            scopes: frame.scopes,
+            readback,
+            mapped: None,
+        });
+    }
+
+    /// Record the `submit` time of the frame recorded by [`Profiler::finish`], and start mapping
+    /// its timestamps.
+    pub(crate) fn submitted(&mut self, submit: Duration) {
+        if let Some(mut stats) = self.pending.take() {
+            stats.submit = submit;
+            self.complete(stats);
+            return;
+        }
+
+        let Some(in_flight) = self.in_flight.back_mut() else {
+            return;
+        };
+        in_flight.stats.submit = submit;
+        let Some((buffer, queries)) = &in_flight.readback else {
+            return;
+        };
+
+        let mapped = Arc::new(Mutex::new(None));
+        let result = Arc::clone(&mapped);
+        let size = u64::from(*queries) * wgpu::QUERY_SIZE as u64;
+        buffer
+            .slice(..size)
+            .map_async(wgpu::MapMode::Read, move |mapped| {
+                *result.lock().unwrap() = Some(mapped);
+            });
+        in_flight.mapped = Some(mapped);
+    }
+
+    /// Complete the oldest frames whose timestamps were read back, in order.
+    fn collect(&mut self, device: &wgpu::Device) {
+        if self
+            .in_flight
+            .iter()
+            .any(|in_flight| in_flight.mapped.is_some())
+        {
+            device.poll(wgpu::Maintain::Poll);
+        }
+
+        while let Some(in_flight) = self.in_flight.front() {
+            let result = match &in_flight.mapped {
+                Some(mapped) => match mapped.lock().unwrap().take() {
+                    Some(result) => Some(result),
+                    None => break,
+                },
+                // Never mapped, so there is nothing to read back
+                None if in_flight.readback.is_some() => None,
+                None => Some(Ok(())),
+            };
+
+            let InFlightStats {
+                mut stats,
+                scopes,
+                readback,
+                ..
+            } = self.in_flight.pop_front().unwrap();
+            if let (Some((buffer, queries)), Some(timestamps)) = (readback, &mut self.timestamps) {
+                if let Some(Ok(())) = result {
+                    let size = u64::from(queries) * wgpu::QUERY_SIZE as u64;
+                    let ticks: Vec<u64> = buffer
+                        .slice(..size)
+                        .get_mapped_range()
+                        .chunks_exact(wgpu::QUERY_SIZE as usize)
+                        .map(|tick| u64::from_ne_bytes(tick.try_into().unwrap()))
+                        .collect();
+                    buffer.unmap();
+
+                    let period = f64::from(timestamps.period);
+                    let duration = |start: u32, end: u32| {
+                        let elapsed = ticks[end as usize].saturating_sub(ticks[start as usize]);
+                        Duration::from_nanos((elapsed as f64 * period) as u64)
+                    };
+                    stats.gpu_render = Some(duration(0, queries - 1));
+                    // Scopes left open when the frame ended are not timed
+                    stats.scopes = scopes
+                        .into_iter()
+                        .filter_map(|scope| {
+                            Some(ScopeTiming {
+                                duration: duration(scope.start, scope.end?),
+                                name: scope.name,
+                                depth: scope.depth,
+                            })
+                        })
+                        .collect();
+                }
+                timestamps.free_buffers.push(buffer);
+            }
+
+            if result.is_some() {
+                self.complete(stats);
+            }
+        }
+    }
+
+    fn complete(&mut self, mut stats: FrameStats) {
+        stats.input_latency = self.input_latency.take();
+        if let Some(callback) = &mut self.callback {
+            (callback.get_mut().unwrap())(&stats);
+        }
+        self.stats = Some(stats);
+    }
+}
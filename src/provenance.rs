@@ -0,0 +1,186 @@
+//! Invisible provenance marks, which trace screenshots to the build and frame that presented them.
+//!
+//! With a [`ProvenanceMark`] set on [`PixelsBuilder::provenance_mark`] or
+//! [`Pixels::set_provenance_mark`], every presented frame carries the build id and the frame index
+//! in the least significant bit of its blue channel. The 128-bit message is a tile of 16x8 pixels,
+//! repeated over the whole surface, so a change of one step in the blue value of some pixels is
+//! all that is visible. [`decode`] reads it back from a screenshot, including a cropped one, and a
+//! checksum tells a mark apart from the noise of an unmarked image:
+//!
+//! ```
+//! use pixels::provenance::{self, Provenance, ProvenanceMark};
+//!
+//! // A 32x16 screenshot, marked the way the GPU marks the presented frame
+//! let mut rgba = vec![96; 32 * 16 * 4];
+//! provenance::mark_rgba(&mut rgba, 32, ProvenanceMark::new(0x1234_abcd), 42);
+//!
+//! let found = provenance::decode(&rgba, 32, 16);
+//! assert_eq!(found, Some(Provenance { build_id: 0x1234_abcd, frame_index: 42 }));
+//! assert_eq!(provenance::decode(&vec![96; 32 * 16 * 4], 32, 16), None);
+//! ```
+//!
+//! The mark only survives lossless copies of the presented pixels, such as PNG screenshots of the
+//! window or [`Pixels::blocking_read_output`]. Lossy compression, scaling, color management, and
+//! 10-bit or float surface formats destroy it. It is meant for triage, not as a secure
+//! fingerprint: anyone who knows the layout can remove or forge it.
+//!
+//! [`PixelsBuilder::provenance_mark`]: crate::PixelsBuilder::provenance_mark
+//! [`Pixels::set_provenance_mark`]: crate::Pixels::set_provenance_mark
+//! [`Pixels::blocking_read_output`]: crate::Pixels::blocking_read_output
+
+/// The width of the tile holding one copy of the message, in pixels.
+pub(crate) const TILE_WIDTH: u32 = 16;
+
+/// The height of the tile holding one copy of the message, in pixels.
+pub(crate) const TILE_HEIGHT: u32 = 8;
+
+/// The first two bytes of every message.
+const MAGIC: [u8; 2] = *b"PX";
+
+/// The build to identify in the provenance mark of presented frames.
+///
+/// See the [module documentation](self).
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ProvenanceMark {
+    /// An application-defined build id, such as the first 8 hex digits of the commit hash.
+    pub build_id: u32,
+}
+
+impl ProvenanceMark {
+    /// Create a mark for the build `build_id`.
+    pub const fn new(build_id: u32) -> Self {
+        Self { build_id }
+    }
+}
+
+/// The build and frame read from a provenance mark by [`decode`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Provenance {
+    /// The [`ProvenanceMark::build_id`] of the build which presented the frame.
+    pub build_id: u32,
+
+    /// The index of the frame, as in [`FrameInfo::index`](crate::FrameInfo::index).
+    ///
+    /// Only the low 48 bits are marked, so the index wraps after 2<sup>48</sup> frames.
+    pub frame_index: u64,
+}
+
+/// The message for `frame_index` as four words, where bit `i` of the message is bit `i % 32` of
+/// word `i / 32`.
+pub(crate) fn message(mark: ProvenanceMark, frame_index: u64) -> [u32; 4] {
+    let mut bytes = [0; 16];
+    bytes[..2].copy_from_slice(&MAGIC);
+    bytes[2..6].copy_from_slice(&mark.build_id.to_le_bytes());
+    bytes[6..12].copy_from_slice(&frame_index.to_le_bytes()[..6]);
+    let checksum = checksum(&bytes[..12]);
+    bytes[12..].copy_from_slice(&checksum.to_le_bytes());
+
+    let mut words = [0; 4];
+    for (word, bytes) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    words
+}
+
+/// The 32-bit FNV-1a hash of `bytes`.
+fn checksum(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, &byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
+/// The index of the message bit carried by pixel `(x, y)`.
+const fn bit_index(x: u32, y: u32) -> u32 {
+    x % TILE_WIDTH + y % TILE_HEIGHT * TILE_WIDTH
+}
+
+/// Mark `rgba`, an 8-bit RGBA image `width` pixels wide, like the GPU marks frame `frame_index`.
+///
+/// This is the CPU counterpart of
+/// [`Pixels::set_provenance_mark`](crate::Pixels::set_provenance_mark), for images which are not
+/// presented by [`Pixels`](crate::Pixels), and for testing decoders.
+///
+/// # Panics
+///
+/// Panics when `rgba` does not hold whole rows of `width` pixels.
+pub fn mark_rgba(rgba: &mut [u8], width: u32, mark: ProvenanceMark, frame_index: u64) {
+    let row_len = width as usize * 4;
+    assert!(row_len > 0 && rgba.len() % row_len == 0);
+
+    let words = message(mark, frame_index);
+    for (y, row) in rgba.chunks_exact_mut(row_len).enumerate() {
+        for (x, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let i = bit_index(x as u32, y as u32);
+            let bit = (words[i as usize / 32] >> (i % 32)) as u8 & 1;
+            pixel[2] = (pixel[2] & !1) | bit;
+        }
+    }
+}
+
+/// Read the provenance mark of `rgba`, an 8-bit RGBA image of `width` by `height` pixels.
+///
+/// Every tile casts a vote for each bit of the message, so a mark is still found under small
+/// overlays such as a mouse cursor. Screenshots may be cropped at any offset. Returns `None` when
+/// the image is smaller than one tile, or no offset yields a message with a valid checksum.
+///
+/// # Panics
+///
+/// Panics when `rgba` does not hold `width * height` pixels.
+pub fn decode(rgba: &[u8], width: u32, height: u32) -> Option<Provenance> {
+    assert_eq!(rgba.len(), width as usize * height as usize * 4);
+    if width < TILE_WIDTH || height < TILE_HEIGHT {
+        return None;
+    }
+
+    // Count the set bits at each position in the tile, relative to the top-left of the image
+    let tile_len = (TILE_WIDTH * TILE_HEIGHT) as usize;
+    let mut ones = vec![0u32; tile_len];
+    let mut totals = vec![0u32; tile_len];
+    for (y, row) in rgba.chunks_exact(width as usize * 4).enumerate() {
+        for (x, pixel) in row.chunks_exact(4).enumerate() {
+            let i = bit_index(x as u32, y as u32) as usize;
+            ones[i] += u32::from(pixel[2] & 1);
+            totals[i] += 1;
+        }
+    }
+
+    // The tiles of a cropped image start at an unknown offset
+    for offset_y in 0..TILE_HEIGHT {
+        for offset_x in 0..TILE_WIDTH {
+            let mut bytes = [0u8; 16];
+            for y in 0..TILE_HEIGHT {
+                for x in 0..TILE_WIDTH {
+                    let i = bit_index(x + offset_x, y + offset_y) as usize;
+                    if ones[i] * 2 > totals[i] {
+                        let bit = bit_index(x, y);
+                        bytes[bit as usize / 8] |= 1 << (bit % 8);
+                    }
+                }
+            }
+
+            let stored = u32::from_le_bytes(bytes[12..].try_into().unwrap());
+            if bytes[..2] == MAGIC && checksum(&bytes[..12]) == stored {
+                let mut frame_index = [0; 8];
+                frame_index[..6].copy_from_slice(&bytes[6..12]);
+                return Some(Provenance {
+                    build_id: u32::from_le_bytes(bytes[2..6].try_into().unwrap()),
+                    frame_index: u64::from_le_bytes(frame_index),
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Read the provenance mark of a frame captured with
+/// [`Pixels::blocking_read_output`](crate::Pixels::blocking_read_output).
+///
+/// Returns `None` when the frame does not carry a mark, which is always the case for formats with
+/// more than 8 bits per channel. See [`decode`].
+#[cfg(feature = "gpu")]
+pub fn decode_frame(frame: &crate::CapturedFrame) -> Option<Provenance> {
+    decode(&frame.rgba8()?, frame.width, frame.height)
+}
@@ -0,0 +1,115 @@
+//! Color quantization of RGBA images to a fixed palette.
+
+/// How [`quantize_to_palette`] hides the banding of a small palette.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Dither {
+    /// Map each pixel to the nearest palette color.
+    #[default]
+    None,
+    /// Floyd-Steinberg error diffusion, which spreads the error of each pixel to its unvisited
+    /// neighbors. Best for photos and gradients.
+    FloydSteinberg,
+}
+
+/// Quantize an RGBA image to the nearest colors of `palette`, returning one palette index per
+/// pixel.
+///
+/// `rgba` holds 8-bit RGBA pixels in rows of `width` pixels, e.g. a PNG decoded by the `image`
+/// or `png` crates. Colors are compared by their squared distance in RGBA space, so fully
+/// transparent pixels map to a transparent palette entry when there is one. Apply the palette to
+/// the indices to get an RGBA frame back.
+///
+/// ```
+/// use pixels::{quantize_to_palette, Dither};
+///
+/// let palette = [[0x00, 0x00, 0x00, 0xff], [0xff, 0xff, 0xff, 0xff]];
+/// let image = [[0x20, 0x20, 0x20, 0xff], [0xe0, 0xd0, 0xf0, 0xff]].concat();
+///
+/// let indices = quantize_to_palette(&image, 2, &palette, Dither::None);
+/// assert_eq!(indices, [0, 1]);
+///
+/// // Draw the quantized image into an RGBA frame
+/// let frame: Vec<u8> = indices.iter().flat_map(|&i| palette[i as usize]).collect();
+/// assert_eq!(frame, [0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff]);
+/// ```
+///
+/// # Panics
+///
+/// Panics when `palette` is empty or has more than 256 colors, `width` is 0, or `rgba` does not
+/// hold whole rows of `width` pixels.
+pub fn quantize_to_palette(
+    rgba: &[u8],
+    width: u32,
+    palette: &[[u8; 4]],
+    dither: Dither,
+) -> Vec<u8> {
+    assert!(!palette.is_empty() && palette.len() <= 256);
+    assert!(width > 0);
+    let width = width as usize;
+    assert_eq!(rgba.len() % (width * 4), 0);
+
+    match dither {
+        Dither::None => rgba
+            .chunks_exact(4)
+            .map(|pixel| {
+                nearest(
+                    palette,
+                    [pixel[0], pixel[1], pixel[2], pixel[3]].map(i32::from),
+                )
+            })
+            .collect(),
+        Dither::FloydSteinberg => floyd_steinberg(rgba, width, palette),
+    }
+}
+
+fn floyd_steinberg(rgba: &[u8], width: usize, palette: &[[u8; 4]]) -> Vec<u8> {
+    let mut indices = Vec::with_capacity(rgba.len() / 4);
+
+    // Errors in sixteenths for the current and next rows, with a column of padding on each side
+    let mut current = vec![[0; 4]; width + 2];
+    let mut next = vec![[0; 4]; width + 2];
+
+    for row in rgba.chunks_exact(width * 4) {
+        for (x, pixel) in row.chunks_exact(4).enumerate() {
+            let error = current[x + 1];
+            let mut wanted = [0; 4];
+            for channel in 0..4 {
+                let value = i32::from(pixel[channel]) + error[channel] / 16;
+                wanted[channel] = value.clamp(0, 255);
+            }
+
+            let index = nearest(palette, wanted);
+            indices.push(index);
+
+            let chosen = palette[index as usize];
+            for channel in 0..4 {
+                let diff = wanted[channel] - i32::from(chosen[channel]);
+                current[x + 2][channel] += diff * 7;
+                next[x][channel] += diff * 3;
+                next[x + 1][channel] += diff * 5;
+                next[x + 2][channel] += diff;
+            }
+        }
+
+        std::mem::swap(&mut current, &mut next);
+        next.fill([0; 4]);
+    }
+
+    indices
+}
+
+/// Find the index of the palette color closest to `color`.
+fn nearest(palette: &[[u8; 4]], color: [i32; 4]) -> u8 {
+    let distance = |entry: &[u8; 4]| -> i32 {
+        (0..4)
+            .map(|channel| (color[channel] - i32::from(entry[channel])).pow(2))
+            .sum()
+    };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, entry)| distance(entry))
+        .map_or(0, |(index, _)| index as u8)
+}
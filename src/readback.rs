@@ -0,0 +1,676 @@
+//! Copying texture regions back from the GPU.
+
+use crate::color::{linear_to_srgb, ColorSpace};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
+use thiserror::Error;
+
+/// All the ways in which reading back from the GPU can fail.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum ReadbackError {
+    /// The texture was not created with the usages required for copying it to a buffer
+    #[error("Texture usage is missing: {0:?}")]
+    TextureUsage(wgpu::TextureUsages),
+    /// The texture format is block compressed, or cannot be copied as a whole
+    #[error("Texture format is unsupported: {0:?}")]
+    TextureFormat(wgpu::TextureFormat),
+    /// The region (x, y, width, height) is empty or extends past the texture
+    #[error("Region is out of bounds: {0:?}")]
+    OutOfBounds((u32, u32, u32, u32)),
+    /// Mapping the staging buffer failed, e.g. because the device was lost
+    #[error("Buffer mapping failed")]
+    Map(#[from] wgpu::BufferAsyncError),
+    /// The pixel buffer presents to a window surface instead of rendering offscreen
+    #[error("The pixel buffer does not render offscreen")]
+    NotOffscreen,
+}
+
+#[derive(Debug, Default)]
+struct MapState {
+    result: Option<Result<(), wgpu::BufferAsyncError>>,
+    waker: Option<Waker>,
+}
+
+/// Copy `rect` (x, y, width, height) of `texture` to the CPU, without row padding.
+///
+/// The future polls `device` itself, so it completes when driven by any executor.
+pub(crate) fn read_texture_region<'a>(
+    device: &'a wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    rect: (u32, u32, u32, u32),
+) -> impl Future<Output = Result<Vec<u8>, ReadbackError>> + 'a {
+    let staging = copy_to_staging(device, queue, texture, rect);
+
+    async move {
+        let (buffer, row_len, padded_row_len) = staging?;
+
+        let state = Arc::new(Mutex::new(MapState::default()));
+        let callback_state = Arc::clone(&state);
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let mut state = callback_state.lock().unwrap();
+                state.result = Some(result);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+
+        std::future::poll_fn(|cx| {
+            // Native callbacks only run while the device is polled; this is a no-op on the Web
+            device.poll(wgpu::Maintain::Poll);
+
+            let mut state = state.lock().unwrap();
+            match state.result.take() {
+                Some(result) => Poll::Ready(result),
+                None => {
+                    state.waker = Some(cx.waker().clone());
+                    #[cfg(not(target_arch = "wasm32"))]
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        })
+        .await?;
+
+        Ok(unpad_rows(&buffer, row_len, padded_row_len))
+    }
+}
+
+/// Copy `rect` (x, y, width, height) of `texture` to the CPU like [`read_texture_region`], blocking
+/// the current thread until the device has finished the copy.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_texture_region_blocking(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    rect: (u32, u32, u32, u32),
+) -> Result<Vec<u8>, ReadbackError> {
+    let (buffer, row_len, padded_row_len) = copy_to_staging(device, queue, texture, rect)?;
+
+    let (sender, receiver) = std::sync::mpsc::channel();
+    buffer
+        .slice(..)
+        .map_async(wgpu::MapMode::Read, move |result| {
+            // The receiver outlives the wait below
+            sender.send(result).ok();
+        });
+    device.poll(wgpu::Maintain::Wait);
+    receiver
+        .recv()
+        .expect("the map callback runs before `Maintain::Wait` returns")?;
+
+    Ok(unpad_rows(&buffer, row_len, padded_row_len))
+}
+
+/// Copy the rows out of a mapped staging buffer without their padding, and unmap it.
+pub(crate) fn unpad_rows(buffer: &wgpu::Buffer, row_len: usize, padded_row_len: usize) -> Vec<u8> {
+    let pixels = buffer
+        .slice(..)
+        .get_mapped_range()
+        .chunks(padded_row_len)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect();
+    buffer.unmap();
+
+    pixels
+}
+
+/// Record and submit the copy into a staging buffer. Returns the buffer and its unpadded and
+/// padded row lengths in bytes.
+fn copy_to_staging(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    rect: (u32, u32, u32, u32),
+) -> Result<(wgpu::Buffer, usize, usize), ReadbackError> {
+    if !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+        return Err(ReadbackError::TextureUsage(wgpu::TextureUsages::COPY_SRC));
+    }
+
+    let format = texture.format();
+    let bytes_per_pixel = match (format.block_dimensions(), format.block_copy_size(None)) {
+        ((1, 1), Some(size)) => size,
+        _ => return Err(ReadbackError::TextureFormat(format)),
+    };
+
+    let (x, y, width, height) = rect;
+    let size = texture.size();
+    let in_bounds = |offset: u32, len: u32, limit: u32| {
+        len > 0 && offset.checked_add(len).is_some_and(|end| end <= limit)
+    };
+    if !in_bounds(x, width, size.width) || !in_bounds(y, height, size.height) {
+        return Err(ReadbackError::OutOfBounds(rect));
+    }
+
+    let row_len = width * bytes_per_pixel;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_row_len = row_len.div_ceil(align) * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("pixels_readback_buffer"),
+        size: u64::from(padded_row_len) * u64::from(height),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("pixels_readback_command_encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d { x, y, z: 0 },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_row_len),
+                rows_per_image: Some(height),
+            },
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    Ok((buffer, row_len as usize, padded_row_len as usize))
+}
+
+/// A pixel of the surface read back for
+/// [`Pixels::pick_output_pixel`](crate::Pixels::pick_output_pixel).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct PickedPixel {
+    /// The position of the pixel on the surface, in physical pixels.
+    pub position: (u32, u32),
+
+    /// The surface texture format, which determines the layout of `bytes`.
+    pub format: wgpu::TextureFormat,
+
+    /// The bytes of the pixel in the surface texture format.
+    pub bytes: Vec<u8>,
+}
+
+impl PickedPixel {
+    /// Get the color as RGBA bytes, for 8-bit RGBA and BGRA surface formats.
+    ///
+    /// The bytes are encoded like the surface, e.g. sRGB for `Bgra8UnormSrgb`. Returns `None` for
+    /// other formats.
+    pub fn rgba8(&self) -> Option<[u8; 4]> {
+        match (self.format, self.bytes.as_slice()) {
+            (
+                wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb,
+                &[r, g, b, a],
+            ) => Some([r, g, b, a]),
+            (
+                wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb,
+                &[b, g, r, a],
+            ) => Some([r, g, b, a]),
+            _ => None,
+        }
+    }
+}
+
+/// Copies single pixels of the surface texture to the CPU over the following frames.
+#[derive(Debug, Default)]
+pub(crate) struct OutputPicker {
+    position: Option<(u32, u32)>,
+    in_flight: Option<InFlightPick>,
+    picked: Option<PickedPixel>,
+}
+
+#[derive(Debug)]
+struct InFlightPick {
+    buffer: wgpu::Buffer,
+    position: (u32, u32),
+    format: wgpu::TextureFormat,
+    bytes_per_pixel: usize,
+    mapped: Option<Arc<Mutex<MapState>>>,
+}
+
+impl OutputPicker {
+    /// Pick the pixel at `position` on upcoming frames.
+    pub(crate) fn request(&mut self, position: (u32, u32)) {
+        self.position = Some(position);
+    }
+
+    /// Get the most recently picked pixel.
+    pub(crate) fn picked(&self) -> Option<&PickedPixel> {
+        self.picked.as_ref()
+    }
+
+    /// Collect a finished readback, without blocking.
+    pub(crate) fn poll(&mut self, device: &wgpu::Device) {
+        let Some(mapped) = self
+            .in_flight
+            .as_ref()
+            .and_then(|pick| pick.mapped.as_ref())
+        else {
+            return;
+        };
+
+        device.poll(wgpu::Maintain::Poll);
+        let result = mapped.lock().unwrap().result.take();
+        let Some(result) = result else {
+            return;
+        };
+
+        let in_flight = self.in_flight.take().unwrap();
+        if result.is_ok() {
+            let bytes =
+                in_flight.buffer.slice(..).get_mapped_range()[..in_flight.bytes_per_pixel].to_vec();
+            in_flight.buffer.unmap();
+            self.picked = Some(PickedPixel {
+                position: in_flight.position,
+                format: in_flight.format,
+                bytes,
+            });
+        }
+    }
+
+    /// Record a copy of the requested pixel of `texture`, unless a readback is still in flight.
+    pub(crate) fn copy(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) {
+        let Some((x, y)) = self.position else {
+            return;
+        };
+        let format = texture.format();
+        let bytes_per_pixel = match (format.block_dimensions(), format.block_copy_size(None)) {
+            ((1, 1), Some(size)) => size,
+            _ => return,
+        };
+        let size = texture.size();
+        if self.in_flight.is_some()
+            || !texture.usage().contains(wgpu::TextureUsages::COPY_SRC)
+            || x >= size.width
+            || y >= size.height
+        {
+            return;
+        }
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_output_picker_buffer"),
+            size: u64::from(bytes_per_pixel),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: None,
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.in_flight = Some(InFlightPick {
+            buffer,
+            position: (x, y),
+            format,
+            bytes_per_pixel: bytes_per_pixel as usize,
+            mapped: None,
+        });
+    }
+
+    /// Start mapping a copy recorded by [`OutputPicker::copy`], after it has been submitted.
+    pub(crate) fn map(&mut self) {
+        let Some(in_flight) = self.in_flight.as_mut() else {
+            return;
+        };
+        if in_flight.mapped.is_some() {
+            return;
+        }
+
+        let mapped = Arc::new(Mutex::new(MapState::default()));
+        let callback_mapped = Arc::clone(&mapped);
+        in_flight
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                callback_mapped.lock().unwrap().result = Some(result);
+            });
+        in_flight.mapped = Some(mapped);
+    }
+}
+
+/// What [`Pixels::capture_frame`](crate::Pixels::capture_frame) reads back.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CaptureSource {
+    /// The surface exactly as presented, after scaling and all render passes and effects.
+    #[default]
+    Presented,
+    /// The pixel buffer texture as uploaded, before scaling.
+    Frame,
+}
+
+/// A frame read back for [`Pixels::capture_frame`](crate::Pixels::capture_frame).
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct CapturedFrame {
+    /// Where the frame was read from.
+    pub source: CaptureSource,
+
+    /// The width of the frame in pixels.
+    pub width: u32,
+
+    /// The height of the frame in pixels.
+    pub height: u32,
+
+    /// The texture format, which determines the layout of `bytes`.
+    pub format: wgpu::TextureFormat,
+
+    /// The color space of the values in `bytes`.
+    ///
+    /// Fixed-point formats hold gamma-encoded sRGB, whether or not the format has the `Srgb`
+    /// suffix: the GPU encodes on write to sRGB formats, and the compositor shows the values of
+    /// other formats as sRGB. Float formats hold linear light.
+    pub color_space: ColorSpace,
+
+    /// The pixels in the texture format, in tightly packed rows from top to bottom.
+    pub bytes: Vec<u8>,
+}
+
+impl CapturedFrame {
+    /// Create a frame of `width` by `height` pixels from 8-bit RGBA encoded in sRGB, e.g. to
+    /// test a [`FrameSink`](crate::recorder::FrameSink).
+    ///
+    /// # Panics
+    ///
+    /// Panics when `rgba` does not hold `width * height` pixels.
+    pub fn from_rgba8(source: CaptureSource, width: u32, height: u32, rgba: Vec<u8>) -> Self {
+        assert_eq!(rgba.len(), width as usize * height as usize * 4);
+
+        Self {
+            source,
+            width,
+            height,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            color_space: ColorSpace::Srgb,
+            bytes: rgba,
+        }
+    }
+
+    /// Get the pixels as 8-bit RGBA encoded in sRGB, ready for image encoders.
+    ///
+    /// Tag the encoded image as sRGB, e.g. with the PNG `sRGB` chunk, so that viewers do not
+    /// apply gamma a second time. 8-bit formats are copied as they are, 10-bit formats are
+    /// rounded, and float formats are encoded from linear light and clamped to `0.0..=1.0`.
+    /// Returns `None` for other formats.
+    pub fn rgba8(&self) -> Option<Vec<u8>> {
+        use wgpu::TextureFormat::*;
+
+        let bytes = &self.bytes;
+        let rgba = match self.format {
+            Rgba8Unorm | Rgba8UnormSrgb => bytes.clone(),
+            Bgra8Unorm | Bgra8UnormSrgb => bytes
+                .chunks_exact(4)
+                .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+                .collect(),
+            Rgb10a2Unorm => bytes
+                .chunks_exact(4)
+                .flat_map(|pixel| {
+                    let bits = u32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+                    let channel =
+                        |shift: u32| ((((bits >> shift) & 0x3ff) * 255 + 511) / 1023) as u8;
+                    [
+                        channel(0),
+                        channel(10),
+                        channel(20),
+                        ((bits >> 30) * 85) as u8,
+                    ]
+                })
+                .collect(),
+            Rgba16Float => bytes
+                .chunks_exact(8)
+                .flat_map(|pixel| {
+                    let channel =
+                        |i: usize| f16_to_f32(u16::from_le_bytes([pixel[i], pixel[i + 1]]));
+                    encode_linear([channel(0), channel(2), channel(4), channel(6)])
+                })
+                .collect(),
+            Rgba32Float => bytes
+                .chunks_exact(16)
+                .flat_map(|pixel| {
+                    let channel = |i: usize| {
+                        f32::from_le_bytes([pixel[i], pixel[i + 1], pixel[i + 2], pixel[i + 3]])
+                    };
+                    encode_linear([channel(0), channel(4), channel(8), channel(12)])
+                })
+                .collect(),
+            _ => return None,
+        };
+
+        Some(rgba)
+    }
+}
+
+/// Copy the whole `texture` which an offscreen pixel buffer renders to, blocking the current
+/// thread like [`read_texture_region_blocking`].
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn read_output_blocking(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<CapturedFrame, ReadbackError> {
+    let (width, height) = (texture.width(), texture.height());
+    let bytes = read_texture_region_blocking(device, queue, texture, (0, 0, width, height))?;
+
+    Ok(CapturedFrame {
+        source: CaptureSource::Presented,
+        width,
+        height,
+        format: texture.format(),
+        color_space: capture_color_space(texture.format()),
+        bytes,
+    })
+}
+
+/// The color space of captured values in `format`.
+pub(crate) fn capture_color_space(format: wgpu::TextureFormat) -> ColorSpace {
+    use wgpu::TextureFormat::*;
+
+    match format {
+        R16Float | Rg16Float | Rgba16Float | R32Float | Rg32Float | Rgba32Float | Rg11b10Float
+        | Rgb9e5Ufloat => ColorSpace::Linear,
+        _ => ColorSpace::Srgb,
+    }
+}
+
+/// Encode a linear-light RGBA color to 8-bit sRGB, keeping alpha linear.
+fn encode_linear([r, g, b, a]: [f32; 4]) -> [u8; 4] {
+    let to_u8 = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let encode = |c: f32| to_u8(linear_to_srgb(f64::from(c).clamp(0.0, 1.0)));
+
+    [encode(r), encode(g), encode(b), to_u8(f64::from(a))]
+}
+
+/// Convert the bits of an IEEE 754 half-precision float.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let mantissa = f32::from(bits & 0x3ff);
+
+    sign * match exponent {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f if mantissa == 0.0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// Copies whole frames of the surface or pixel buffer texture to the CPU over the following
+/// frames.
+#[derive(Debug, Default)]
+pub(crate) struct FrameCapture {
+    requested: Option<CaptureSource>,
+    in_flight: Option<InFlightCapture>,
+    captured: Option<CapturedFrame>,
+}
+
+#[derive(Debug)]
+struct InFlightCapture {
+    buffer: wgpu::Buffer,
+    source: CaptureSource,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+    row_len: usize,
+    padded_row_len: usize,
+    mapped: Option<Arc<Mutex<MapState>>>,
+}
+
+impl FrameCapture {
+    /// Capture `source` on the next render.
+    pub(crate) fn request(&mut self, source: CaptureSource) {
+        self.requested = Some(source);
+    }
+
+    /// Take the captured frame, collecting a finished readback without blocking.
+    pub(crate) fn take(&mut self, device: &wgpu::Device) -> Option<CapturedFrame> {
+        if let Some(mapped) = self
+            .in_flight
+            .as_ref()
+            .and_then(|capture| capture.mapped.as_ref())
+        {
+            device.poll(wgpu::Maintain::Poll);
+            let result = mapped.lock().unwrap().result.take();
+            if let Some(result) = result {
+                let in_flight = self.in_flight.take().unwrap();
+                if result.is_ok() {
+                    let (width, height) = in_flight.size;
+                    self.captured = Some(CapturedFrame {
+                        source: in_flight.source,
+                        width,
+                        height,
+                        format: in_flight.format,
+                        color_space: capture_color_space(in_flight.format),
+                        bytes: unpad_rows(
+                            &in_flight.buffer,
+                            in_flight.row_len,
+                            in_flight.padded_row_len,
+                        ),
+                    });
+                }
+            }
+        }
+
+        self.captured.take()
+    }
+
+    /// Record a copy of the `surface` or `frame` texture when a capture was requested, unless one
+    /// is still in flight.
+    pub(crate) fn copy(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        surface: &wgpu::Texture,
+        frame: &wgpu::Texture,
+    ) {
+        let Some(source) = self.requested else {
+            return;
+        };
+        let texture = match source {
+            CaptureSource::Presented => surface,
+            CaptureSource::Frame => frame,
+        };
+        let format = texture.format();
+        let bytes_per_pixel = match (format.block_dimensions(), format.block_copy_size(None)) {
+            ((1, 1), Some(size)) => size,
+            _ => return,
+        };
+        if self.in_flight.is_some() || !texture.usage().contains(wgpu::TextureUsages::COPY_SRC) {
+            return;
+        }
+        self.requested = None;
+
+        let size = texture.size();
+        let row_len = size.width * bytes_per_pixel;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_len = row_len.div_ceil(align) * align;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_frame_capture_buffer"),
+            size: u64::from(padded_row_len) * u64::from(size.height),
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_len),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            wgpu::Extent3d {
+                depth_or_array_layers: 1,
+                ..size
+            },
+        );
+
+        self.in_flight = Some(InFlightCapture {
+            buffer,
+            source,
+            size: (size.width, size.height),
+            format,
+            row_len: row_len as usize,
+            padded_row_len: padded_row_len as usize,
+            mapped: None,
+        });
+    }
+
+    /// Start mapping a copy recorded by [`FrameCapture::copy`], after it has been submitted.
+    pub(crate) fn map(&mut self) {
+        let Some(in_flight) = self.in_flight.as_mut() else {
+            return;
+        };
+        if in_flight.mapped.is_some() {
+            return;
+        }
+
+        let mapped = Arc::new(Mutex::new(MapState::default()));
+        let callback_mapped = Arc::clone(&mapped);
+        in_flight
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                callback_mapped.lock().unwrap().result = Some(result);
+            });
+        in_flight.mapped = Some(mapped);
+    }
+}
@@ -0,0 +1,599 @@
+//! Recording every rendered frame to a [`FrameSink`].
+//!
+//! Start a recording with [`Pixels::start_recording`](crate::Pixels::start_recording). Every
+//! frame rendered from then on is copied to a staging buffer, read back without blocking over
+//! the following frames, and written to the sink in order. Sinks for PNG sequences and Y4M video
+//! are built in with the `png` and `y4m` features; implement [`FrameSink`] to encode frames any
+//! other way, e.g. by piping them to an external encoder. [`AveragingSink`] wraps another sink
+//! to record the average of each run of frames.
+//!
+//! ```no_run
+//! # #[cfg(feature = "y4m")]
+//! # {
+//! use pixels::recorder::Y4mWriter;
+//! use pixels::{CaptureSource, Pixels};
+//!
+//! # let window = pixels_mocks::Window;
+//! # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+//! let mut pixels = Pixels::new(320, 240, surface_texture)?;
+//! let file = std::fs::File::create("gameplay.y4m")?;
+//! pixels.start_recording(CaptureSource::Presented, Y4mWriter::new(file, (60, 1)))?;
+//!
+//! for _ in 0..600 {
+//!     // Draw the frame
+//!     pixels.render()?;
+//! }
+//!
+//! let stats = pixels.stop_recording().expect("a recording was started")?;
+//! println!("Recorded {} frames, dropped {}", stats.frames, stats.dropped);
+//! # }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::readback::{self, CaptureSource, CapturedFrame};
+use std::collections::VecDeque;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// The number of frames which can be read back at once. Frames which end while every staging
+/// buffer is in flight are dropped.
+const RING_SIZE: usize = 3;
+
+/// The result of mapping a staging buffer, set by the `map_async` callback.
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+/// A destination for recorded frames, see
+/// [`Pixels::start_recording`](crate::Pixels::start_recording).
+///
+/// ```
+/// use pixels::recorder::FrameSink;
+/// use pixels::CapturedFrame;
+///
+/// /// Keeps the frames in memory, e.g. as test fixtures.
+/// #[derive(Default)]
+/// struct Frames(Vec<Vec<u8>>);
+///
+/// impl FrameSink for Frames {
+///     fn write_frame(&mut self, _index: u64, frame: &CapturedFrame) -> std::io::Result<()> {
+///         self.0.push(frame.bytes.clone());
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait FrameSink {
+    /// Write the frame `index`, like [`FrameInfo::index`](crate::FrameInfo::index).
+    ///
+    /// Frames are written in order, but indices skip the frames which were dropped.
+    ///
+    /// # Errors
+    ///
+    /// An error stops the recording; it is returned by
+    /// [`Pixels::stop_recording`](crate::Pixels::stop_recording).
+    fn write_frame(&mut self, index: u64, frame: &CapturedFrame) -> io::Result<()>;
+
+    /// Flush the frames written so far, when the recording stops.
+    ///
+    /// The default implementation does nothing.
+    ///
+    /// # Errors
+    ///
+    /// The error is returned by [`Pixels::stop_recording`](crate::Pixels::stop_recording).
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The progress of a recording, see [`Pixels::recording_stats`](crate::Pixels::recording_stats).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct RecordingStats {
+    /// The number of frames written to the sink.
+    pub frames: u64,
+
+    /// The number of frames which were not recorded, because the staging buffers of earlier
+    /// frames were still being read back or the device was lost.
+    pub dropped: u64,
+}
+
+/// Copies every rendered frame to a ring of staging buffers, and writes them to the sink.
+pub(crate) struct Recorder {
+    source: CaptureSource,
+    sink: Box<dyn FrameSink>,
+    in_flight: VecDeque<InFlightFrame>,
+    // Staging buffers which were read back, for frames of the same size
+    free_buffers: Vec<wgpu::Buffer>,
+    stats: RecordingStats,
+    error: Option<io::Error>,
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("source", &self.source)
+            .field("in_flight", &self.in_flight.len())
+            .field("stats", &self.stats)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+struct InFlightFrame {
+    buffer: wgpu::Buffer,
+    index: u64,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+    row_len: usize,
+    padded_row_len: usize,
+    mapped: Option<MapResult>,
+}
+
+impl Recorder {
+    pub(crate) fn new(source: CaptureSource, sink: Box<dyn FrameSink>) -> Self {
+        Self {
+            source,
+            sink,
+            in_flight: VecDeque::new(),
+            free_buffers: Vec::new(),
+            stats: RecordingStats::default(),
+            error: None,
+        }
+    }
+
+    pub(crate) fn stats(&self) -> RecordingStats {
+        self.stats
+    }
+
+    /// Record a copy of the `surface` or `frame` texture for the frame `index`.
+    pub(crate) fn copy(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        index: u64,
+        surface: &wgpu::Texture,
+        frame: &wgpu::Texture,
+    ) {
+        if self.error.is_some() {
+            return;
+        }
+        let texture = match self.source {
+            CaptureSource::Presented => surface,
+            CaptureSource::Frame => frame,
+        };
+        let format = texture.format();
+        let bytes_per_pixel = match (format.block_dimensions(), format.block_copy_size(None)) {
+            ((1, 1), Some(size)) => size,
+            _ => return,
+        };
+        if self.in_flight.len() >= RING_SIZE
+            || !texture.usage().contains(wgpu::TextureUsages::COPY_SRC)
+        {
+            self.stats.dropped += 1;
+            return;
+        }
+
+        let size = texture.size();
+        let row_len = size.width * bytes_per_pixel;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_len = row_len.div_ceil(align) * align;
+        let buffer_size = u64::from(padded_row_len) * u64::from(size.height);
+
+        // Buffers of other sizes are left from before a resize
+        self.free_buffers
+            .retain(|buffer| buffer.size() == buffer_size);
+        let buffer = self.free_buffers.pop().unwrap_or_else(|| {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("pixels_recorder_buffer"),
+                size: buffer_size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_len),
+                    rows_per_image: Some(size.height),
+                },
+            },
+            wgpu::Extent3d {
+                depth_or_array_layers: 1,
+                ..size
+            },
+        );
+
+        self.in_flight.push_back(InFlightFrame {
+            buffer,
+            index,
+            size: (size.width, size.height),
+            format,
+            row_len: row_len as usize,
+            padded_row_len: padded_row_len as usize,
+            mapped: None,
+        });
+    }
+
+    /// Start mapping the copies recorded by [`Recorder::copy`], after they have been submitted.
+    pub(crate) fn map(&mut self) {
+        for in_flight in self.in_flight.iter_mut() {
+            if in_flight.mapped.is_some() {
+                continue;
+            }
+
+            let mapped = Arc::new(Mutex::new(None));
+            let result = Arc::clone(&mapped);
+            in_flight
+                .buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |mapped| {
+                    *result.lock().unwrap() = Some(mapped);
+                });
+            in_flight.mapped = Some(mapped);
+        }
+    }
+
+    /// Write the oldest frames which were read back to the sink, in order. With `wait`, this
+    /// blocks until every frame in flight was read back.
+    pub(crate) fn collect(&mut self, device: &wgpu::Device, wait: bool) {
+        if self.in_flight.is_empty() {
+            return;
+        }
+        device.poll(if wait {
+            wgpu::Maintain::Wait
+        } else {
+            wgpu::Maintain::Poll
+        });
+
+        while let Some(in_flight) = self.in_flight.front() {
+            let Some(mapped) = &in_flight.mapped else {
+                break;
+            };
+            let Some(result) = mapped.lock().unwrap().take() else {
+                break;
+            };
+
+            let in_flight = self.in_flight.pop_front().unwrap();
+            if result.is_err() {
+                self.stats.dropped += 1;
+                continue;
+            }
+            let (width, height) = in_flight.size;
+            let frame = CapturedFrame {
+                source: self.source,
+                width,
+                height,
+                format: in_flight.format,
+                color_space: readback::capture_color_space(in_flight.format),
+                bytes: readback::unpad_rows(
+                    &in_flight.buffer,
+                    in_flight.row_len,
+                    in_flight.padded_row_len,
+                ),
+            };
+            self.free_buffers.push(in_flight.buffer);
+
+            if self.error.is_some() {
+                self.stats.dropped += 1;
+            } else if let Err(err) = self.sink.write_frame(in_flight.index, &frame) {
+                self.stats.dropped += 1;
+                self.error = Some(err);
+            } else {
+                self.stats.frames += 1;
+            }
+        }
+    }
+
+    /// Drop the frames in flight and the staging buffers, which belong to a lost device.
+    pub(crate) fn lose_device(&mut self) {
+        self.stats.dropped += self.in_flight.len() as u64;
+        self.in_flight.clear();
+        self.free_buffers.clear();
+    }
+
+    /// Write the remaining frames, and finish the sink.
+    pub(crate) fn finish(mut self, device: &wgpu::Device) -> io::Result<RecordingStats> {
+        self.collect(device, true);
+        // Frames which never finished, e.g. on the Web, where waiting does not block
+        self.lose_device();
+
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        self.sink.finish()?;
+
+        Ok(self.stats)
+    }
+}
+
+/// Writes every frame to a numbered PNG file in a directory, tagged as sRGB.
+///
+/// Files are named `frame_000000.png`, `frame_000001.png`, and so on, in the order the frames
+/// are written, so tools like `ffmpeg -i frame_%06d.png` can encode them. Frames are converted
+/// with [`CapturedFrame::rgba8`]; other formats fail with [`io::ErrorKind::InvalidData`].
+#[cfg(feature = "png")]
+#[derive(Debug)]
+pub struct PngSequence {
+    directory: std::path::PathBuf,
+    next: u64,
+}
+
+#[cfg(feature = "png")]
+impl PngSequence {
+    /// Create a sink which writes PNG files into `directory`, creating it if it does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error when the directory cannot be created.
+    pub fn new(directory: impl Into<std::path::PathBuf>) -> io::Result<Self> {
+        let directory = directory.into();
+        std::fs::create_dir_all(&directory)?;
+
+        Ok(Self { directory, next: 0 })
+    }
+}
+
+#[cfg(feature = "png")]
+impl FrameSink for PngSequence {
+    fn write_frame(&mut self, _index: u64, frame: &CapturedFrame) -> io::Result<()> {
+        let path = self.directory.join(format!("frame_{:06}.png", self.next));
+        write_png(&path, frame)?;
+        self.next += 1;
+
+        Ok(())
+    }
+}
+
+/// Write `frame` to a new PNG file at `path`, converted with [`CapturedFrame::rgba8`] and tagged
+/// as sRGB.
+#[cfg(feature = "png")]
+pub(crate) fn write_png(path: &std::path::Path, frame: &CapturedFrame) -> io::Result<()> {
+    let rgba = rgba8(frame)?;
+    let file = io::BufWriter::new(std::fs::File::create(path)?);
+
+    let mut encoder = png::Encoder::new(file, frame.width, frame.height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_srgb(png::SrgbRenderingIntent::Perceptual);
+    encoder.write_header()?.write_image_data(&rgba)?;
+
+    Ok(())
+}
+
+/// Writes the frames as an uncompressed YUV4MPEG2 (Y4M) video, which players and encoders like
+/// `ffmpeg` read directly.
+///
+/// Frames are converted with [`CapturedFrame::rgba8`] to full-resolution (4:4:4) BT.601 YCbCr
+/// in the limited range, and alpha is dropped. Every frame must have the size of the first;
+/// resizing the recorded texture fails with [`io::ErrorKind::InvalidInput`].
+#[cfg(feature = "y4m")]
+#[derive(Debug)]
+pub struct Y4mWriter<W: io::Write> {
+    writer: W,
+    frame_rate: (u32, u32),
+    size: Option<(u32, u32)>,
+    planes: Vec<u8>,
+}
+
+#[cfg(feature = "y4m")]
+impl<W: io::Write> Y4mWriter<W> {
+    /// Create a sink which writes a video at `frame_rate` frames per second, as a fraction
+    /// `(numerator, denominator)`, e.g. `(60, 1)` or `(60000, 1001)`.
+    ///
+    /// The stream header is written with the first frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics when either part of `frame_rate` is 0.
+    pub fn new(writer: W, frame_rate: (u32, u32)) -> Self {
+        assert!(frame_rate.0 > 0 && frame_rate.1 > 0);
+
+        Self {
+            writer,
+            frame_rate,
+            size: None,
+            planes: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "y4m")]
+impl<W: io::Write> FrameSink for Y4mWriter<W> {
+    fn write_frame(&mut self, _index: u64, frame: &CapturedFrame) -> io::Result<()> {
+        let size = (frame.width, frame.height);
+        match self.size {
+            None => {
+                let (numerator, denominator) = self.frame_rate;
+                writeln!(
+                    self.writer,
+                    "YUV4MPEG2 W{} H{} F{numerator}:{denominator} Ip A1:1 C444 XCOLORRANGE=LIMITED",
+                    size.0, size.1,
+                )?;
+                self.size = Some(size);
+            }
+            Some(first) if first != size => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("frame size {size:?} differs from the first frame {first:?}"),
+                ));
+            }
+            Some(_) => (),
+        }
+
+        // Planar Y, Cb, and Cr
+        let rgba = rgba8(frame)?;
+        let pixels = rgba.len() / 4;
+        self.planes.clear();
+        self.planes.resize(pixels * 3, 0);
+        for (i, pixel) in rgba.chunks_exact(4).enumerate() {
+            let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|c| f32::from(c) / 255.0);
+            let y = 16.0 + 65.481 * r + 128.553 * g + 24.966 * b;
+            let cb = 128.0 - 37.797 * r - 74.203 * g + 112.0 * b;
+            let cr = 128.0 + 112.0 * r - 93.786 * g - 18.214 * b;
+
+            self.planes[i] = y.round() as u8;
+            self.planes[pixels + i] = cb.round() as u8;
+            self.planes[pixels * 2 + i] = cr.round() as u8;
+        }
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&self.planes)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Writes the average of each run of frames to another sink, which turns content that flickers
+/// from frame to frame into a steady image, e.g. for GIFs of interlaced or multiplexed displays.
+///
+/// Every `frames` consecutive recorded frames are averaged in linear light and written as one
+/// 8-bit sRGB frame, so the recording has `1 / frames` of the frame rate; a 60 Hz flicker between
+/// two frames recorded with `AveragingSink::new(sink, 2)` becomes a steady 30 FPS video. This is
+/// independent of [`Pixels::set_frame_averaging`](crate::Pixels::set_frame_averaging), which
+/// averages what is shown on the display.
+///
+/// Frames are converted with [`CapturedFrame::rgba8`]; other formats fail with
+/// [`io::ErrorKind::InvalidData`]. The written frames have the index of the first frame of their
+/// run. A run is started over when the frame size changes, and the frames of a run which is not
+/// complete when the recording stops are dropped.
+///
+/// ```
+/// use pixels::recorder::{AveragingSink, FrameSink};
+/// use pixels::{CaptureSource, CapturedFrame};
+///
+/// #[derive(Default)]
+/// struct Frames(Vec<Vec<u8>>);
+///
+/// impl FrameSink for Frames {
+///     fn write_frame(&mut self, _index: u64, frame: &CapturedFrame) -> std::io::Result<()> {
+///         self.0.push(frame.bytes.clone());
+///         Ok(())
+///     }
+/// }
+///
+/// // A pixel which flickers between black and white
+/// let mut sink = AveragingSink::new(Frames::default(), 2);
+/// for (index, value) in [0, 255, 0, 255].into_iter().enumerate() {
+///     let pixel = vec![value, value, value, 255];
+///     let frame = CapturedFrame::from_rgba8(CaptureSource::Frame, 1, 1, pixel);
+///     sink.write_frame(index as u64, &frame)?;
+/// }
+///
+/// // Half of the light of white, encoded in sRGB
+/// assert_eq!(sink.get_ref().0, [[188, 188, 188, 255], [188, 188, 188, 255]]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct AveragingSink<S: FrameSink> {
+    sink: S,
+    frames: u32,
+    // The linear sums of the run so far, and the index, source, and size of its first frame
+    sums: Vec<f32>,
+    count: u32,
+    first: Option<(u64, CaptureSource, (u32, u32))>,
+}
+
+impl<S: FrameSink> AveragingSink<S> {
+    /// Create a sink which writes the average of every `frames` frames to `sink`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `frames` is 0.
+    pub fn new(sink: S, frames: u32) -> Self {
+        assert!(frames > 0);
+
+        Self {
+            sink,
+            frames,
+            sums: Vec::new(),
+            count: 0,
+            first: None,
+        }
+    }
+
+    /// Get a reference to the sink the averages are written to.
+    pub fn get_ref(&self) -> &S {
+        &self.sink
+    }
+
+    /// Get the sink the averages are written to.
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
+impl<S: FrameSink> FrameSink for AveragingSink<S> {
+    fn write_frame(&mut self, index: u64, frame: &CapturedFrame) -> io::Result<()> {
+        let rgba = rgba8(frame)?;
+        let size = (frame.width, frame.height);
+        if self
+            .first
+            .as_ref()
+            .is_some_and(|&(_, _, first)| first != size)
+        {
+            self.count = 0;
+        }
+        if self.count == 0 {
+            self.sums.clear();
+            self.sums.resize(rgba.len(), 0.0);
+            self.first = Some((index, frame.source, size));
+        }
+
+        // Color is averaged in linear light, and alpha as it is
+        for (i, (sum, &value)) in self.sums.iter_mut().zip(&rgba).enumerate() {
+            let value = f64::from(value) / 255.0;
+            *sum += if i % 4 == 3 {
+                value as f32
+            } else {
+                srgb_to_linear(value) as f32
+            };
+        }
+        self.count += 1;
+        if self.count < self.frames {
+            return Ok(());
+        }
+
+        self.count = 0;
+        let (index, source, (width, height)) = self.first.take().expect("a run was started");
+        let frames = f64::from(self.frames);
+        let bytes = self
+            .sums
+            .iter()
+            .enumerate()
+            .map(|(i, &sum)| {
+                let value = f64::from(sum) / frames;
+                let value = if i % 4 == 3 {
+                    value
+                } else {
+                    linear_to_srgb(value)
+                };
+                (value.clamp(0.0, 1.0) * 255.0).round() as u8
+            })
+            .collect();
+        let average = CapturedFrame::from_rgba8(source, width, height, bytes);
+
+        self.sink.write_frame(index, &average)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.sink.finish()
+    }
+}
+
+/// Convert `frame` to 8-bit sRGB, or fail for unsupported formats.
+fn rgba8(frame: &CapturedFrame) -> io::Result<Vec<u8>> {
+    frame.rgba8().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("cannot convert {:?} to RGBA", frame.format),
+        )
+    })
+}
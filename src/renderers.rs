@@ -1,94 +1,4121 @@
+use crate::color::srgb_to_linear;
+use crate::flash::Attenuation;
+use crate::gamma::GammaRamp;
+use crate::mesh::{DistortionMesh, MeshVertex};
+use crate::provenance::{self, ProvenanceMark};
+use crate::sprite::Sprite;
+use crate::text::{FontAtlas, TextGrid};
+use crate::tonemap::Tonemapping;
 use crate::SurfaceSize;
+use bytemuck::{Pod, Zeroable};
+use std::collections::VecDeque;
+use std::sync::Arc;
 use ultraviolet::Mat4;
 use wgpu::util::DeviceExt;
 
+/// The filter used to sample the pixel buffer when it is scaled to the surface.
+///
+/// See [`PixelsBuilder::filter`](crate::PixelsBuilder::filter).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FilterMode {
+    /// Nearest neighbor sampling, which keeps pixels crisp.
+    #[default]
+    Nearest,
+    /// Bilinear filtering, which smooths the image at the cost of sharpness.
+    Linear,
+    /// Nearest neighbor sampling with antialiased edges, also known as sharp bilinear.
+    ///
+    /// At non-integer scale factors, nearest neighbor sampling draws some buffer pixels one
+    /// physical pixel wider than others, which shimmers as the image moves. This mode keeps every
+    /// pixel sharp and blends only the physical pixel on each edge between two buffer pixels, so
+    /// all pixels look the same size without the blur of [`FilterMode::Linear`]. When the buffer
+    /// is shrunk, it is the same as bilinear filtering.
+    ///
+    /// The [minimal pipeline](crate::Pixels::minimal_pipeline) uses bilinear filtering instead.
+    SharpBilinear,
+    /// Area averaging (a box filter), for previews of buffers larger than the surface.
+    ///
+    /// When the surface is smaller than the pixel buffer, the buffer is shrunk to fit and each
+    /// physical pixel is the average of all buffer pixels it covers. This avoids both the dropped
+    /// rows and columns of nearest neighbor and the blur of bilinear filtering. Integer upscaling
+    /// is identical to [`FilterMode::Nearest`].
+    ///
+    /// The [minimal pipeline](crate::Pixels::minimal_pipeline) shrinks the buffer with nearest
+    /// neighbor sampling instead.
+    Area,
+}
+
+/// How the scaled pixel buffer fills the surface.
+///
+/// See [`PixelsBuilder::scaling_mode`](crate::PixelsBuilder::scaling_mode).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ScalingMode {
+    /// Scale by the largest whole multiple of the buffer size which fits, leaving a border
+    /// around the image. Every buffer pixel covers the same square of physical pixels, so pixel
+    /// art never shimmers. Buffers larger than the surface are cropped, or shrunk to fit with
+    /// [`FilterMode::Area`].
+    #[default]
+    IntegerOnly,
+    /// Scale by the largest fractional factor which fits, keeping the aspect ratio of the buffer
+    /// and leaving a border along one pair of edges.
+    Fit,
+    /// Scale to fill the whole surface, ignoring the aspect ratio of the buffer.
+    Stretch,
+}
+
+/// A clockwise rotation of the scaled pixel buffer on the surface, for rotated panels.
+///
+/// See [`PixelsBuilder::rotation`](crate::PixelsBuilder::rotation).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Rotation {
+    /// The pixel buffer is upright.
+    #[default]
+    D0,
+    /// The top of the pixel buffer faces the right edge of the surface.
+    D90,
+    /// The pixel buffer is upside down.
+    D180,
+    /// The top of the pixel buffer faces the left edge of the surface.
+    D270,
+}
+
+impl Rotation {
+    /// Whether the width and height of the pixel buffer are swapped on the surface.
+    pub fn is_sideways(self) -> bool {
+        matches!(self, Self::D90 | Self::D270)
+    }
+}
+
+/// A pixel layout which the scaling pass unpacks on the GPU, for frames produced by emulators
+/// and cameras.
+///
+/// See [`PixelsBuilder::packed_format`](crate::PixelsBuilder::packed_format).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum PackedFormat {
+    /// One byte of sRGB-encoded luminance per pixel, shown as gray. The frame is stored in an
+    /// `R8Unorm` texture and filtered as usual.
+    Luma8,
+    /// Two bytes per pixel in little-endian order, with 5 bits of red in the high bits, 6 bits of
+    /// green, and 5 bits of blue, all sRGB-encoded. The frame is stored in an `Rg8Unorm` texture
+    /// and unpacked without filtering.
+    Rgb565,
+}
+
+impl PackedFormat {
+    /// The texture format which holds frames in this layout.
+    pub fn texture_format(self) -> wgpu::TextureFormat {
+        match self {
+            Self::Luma8 => wgpu::TextureFormat::R8Unorm,
+            Self::Rgb565 => wgpu::TextureFormat::Rg8Unorm,
+        }
+    }
+}
+
+/// What the scaling pass samples outside of the pixel buffer texture.
+///
+/// This matters wherever texture coordinates stray past the edges, such as bilinear filtering of
+/// the outermost pixels and [distortion meshes](crate::DistortionMesh) which warp the image
+/// inward.
+///
+/// See [`PixelsBuilder::texture_border`](crate::PixelsBuilder::texture_border).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum TextureBorder {
+    /// Repeat the outermost row or column of pixels, as if the texture were padded with a
+    /// duplicated one-pixel border. Neither the opposite edge nor the clear color bleeds in.
+    #[default]
+    Clamp,
+    /// Sample a fixed border color.
+    ///
+    /// Requires [`wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER`], or
+    /// [`wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO`] for [`wgpu::SamplerBorderColor::Zero`].
+    /// The builder requests the feature when the adapter supports it, and falls back to
+    /// [`TextureBorder::Clamp`] otherwise. [`FilterMode::Area`] always clamps.
+    Color(wgpu::SamplerBorderColor),
+}
+
+impl TextureBorder {
+    /// The device feature required for this border, if any.
+    pub(crate) fn required_feature(&self) -> wgpu::Features {
+        match self {
+            Self::Clamp => wgpu::Features::empty(),
+            Self::Color(wgpu::SamplerBorderColor::Zero) => {
+                wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO
+            }
+            Self::Color(_) => wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Options for the scaling pipeline, chosen when the pixel buffer is built.
+#[derive(Clone, Debug)]
+pub(crate) struct ScalingOptions {
+    /// Generate the triangle without a vertex buffer and ignore optional passes such as flash
+    /// attenuation, for downlevel targets.
+    pub(crate) minimal: bool,
+    pub(crate) filter: FilterMode,
+    /// Filter non-sRGB textures in linear light; requires [`wgpu::DownlevelFlags::VIEW_FORMATS`].
+    pub(crate) linear_light: bool,
+    /// Sampled outside of the texture; the device must have the required feature.
+    pub(crate) border: TextureBorder,
+    /// Replaces the sampler derived from `filter` and `border`; the device must have the required
+    /// features.
+    pub(crate) sampler: Option<wgpu::SamplerDescriptor<'static>>,
+    /// Look up the colors of an `R8Unorm` index texture in this palette.
+    pub(crate) palette: Option<Box<[[u8; 4]; 256]>>,
+    /// Unpack texels in this layout instead of sampling colors.
+    pub(crate) packed: Option<PackedFormat>,
+    /// WGSL appended to the scaling shader, and the contents of its uniform buffer.
+    pub(crate) hook: Option<ShaderHook>,
+    /// Map every output color through this ramp.
+    pub(crate) gamma_ramp: Option<Box<GammaRamp>>,
+    /// Map output colors through this curve, after multiplying them by `exposure`.
+    pub(crate) tonemapping: Tonemapping,
+    pub(crate) exposure: f32,
+    pub(crate) mode: ScalingMode,
+    pub(crate) rotation: Rotation,
+    /// Mirror the rotated image horizontally and vertically on the surface.
+    pub(crate) flip: (bool, bool),
+}
+
+/// A [shader hook](crate::PixelsBuilder::shader_hook) in the scaling shader.
+#[derive(Clone, Debug)]
+pub(crate) struct ShaderHook {
+    pub(crate) source: Arc<str>,
+    /// Padded to a multiple of 16 bytes, like a WGSL uniform struct.
+    pub(crate) uniforms: Vec<u8>,
+}
+
+impl ShaderHook {
+    pub(crate) fn new(source: &str, uniforms: &[u8]) -> Self {
+        let mut padded = uniforms.to_vec();
+        padded.resize(uniforms.len().max(1).next_multiple_of(16), 0);
+
+        Self {
+            source: source.into(),
+            uniforms: padded,
+        }
+    }
+}
+
+/// Defines `user_color` in `scale.wgsl` for pixel buffers without a shader hook.
+const DEFAULT_SHADER_HOOK: &str = "
+fn user_color(color: vec4<f32>, tex_coord: vec2<f32>) -> vec4<f32> {
+    return color;
+}
+";
+
+/// Defines `gamma_ramp` in `scale.wgsl` for pixel buffers without a gamma ramp.
+const DEFAULT_GAMMA_RAMP: &str = "
+fn gamma_ramp(color: vec4<f32>) -> vec4<f32> {
+    return color;
+}
+";
+
+/// How a [`ScalingMatrix`] fits the texture to the surface.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Fit {
+    pub(crate) mode: ScalingMode,
+    /// Shrink textures larger than the surface in integer mode, instead of cropping them.
+    pub(crate) downscale: bool,
+    pub(crate) rotation: Rotation,
+    pub(crate) flip: (bool, bool),
+}
+
+impl Fit {
+    /// The orientation of the texture on the surface, mapping texture axes to surface axes with
+    /// `y` pointing down, as rows of a matrix with entries of -1, 0, or 1.
+    fn orientation(&self) -> [[i64; 2]; 2] {
+        let [mut x, mut y] = match self.rotation {
+            Rotation::D0 => [[1, 0], [0, 1]],
+            Rotation::D90 => [[0, -1], [1, 0]],
+            Rotation::D180 => [[-1, 0], [0, -1]],
+            Rotation::D270 => [[0, 1], [-1, 0]],
+        };
+        if self.flip.0 {
+            x = [-x[0], -x[1]];
+        }
+        if self.flip.1 {
+            y = [-y[0], -y[1]];
+        }
+
+        [x, y]
+    }
+}
+
+impl ScalingOptions {
+    /// How the scaling matrices fit the texture to the surface.
+    pub(crate) fn fit(&self) -> Fit {
+        Fit {
+            mode: self.mode,
+            downscale: self.filter == FilterMode::Area,
+            rotation: self.rotation,
+            flip: self.flip,
+        }
+    }
+
+    /// The view format which makes the sampler filter a texture of the given format in linear
+    /// light, if it needs one.
+    ///
+    /// sRGB textures are always decoded before filtering. Other 8-bit textures are viewed with
+    /// their sRGB variant when linear-light filtering is enabled.
+    pub(crate) fn srgb_view_format(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> Option<wgpu::TextureFormat> {
+        let srgb = format.add_srgb_suffix();
+        let filtered = self.filter != FilterMode::Nearest;
+        let enabled = filtered && self.linear_light && !self.minimal;
+
+        (enabled && srgb != format).then_some(srgb)
+    }
+
+    /// The descriptor of the sampler used by the scaling pass.
+    fn sampler_descriptor(&self) -> wgpu::SamplerDescriptor<'static> {
+        if let Some(descriptor) = &self.sampler {
+            return wgpu::SamplerDescriptor {
+                label: Some("pixels_scaling_renderer_sampler"),
+                compare: None,
+                ..descriptor.clone()
+            };
+        }
+
+        let filter = match self.filter {
+            FilterMode::Nearest | FilterMode::Area => wgpu::FilterMode::Nearest,
+            FilterMode::Linear | FilterMode::SharpBilinear => wgpu::FilterMode::Linear,
+        };
+        let (address_mode, border_color) = match self.border {
+            TextureBorder::Clamp => (wgpu::AddressMode::ClampToEdge, None),
+            TextureBorder::Color(color) => (wgpu::AddressMode::ClampToBorder, Some(color)),
+        };
+
+        wgpu::SamplerDescriptor {
+            label: Some("pixels_scaling_renderer_sampler"),
+            address_mode_u: address_mode,
+            address_mode_v: address_mode,
+            address_mode_w: address_mode,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            lod_min_clamp: 0.0,
+            lod_max_clamp: 1.0,
+            compare: None,
+            anisotropy_clamp: 1,
+            border_color,
+        }
+    }
+}
+
+/// The device features required by a sampler descriptor.
+pub(crate) fn sampler_required_features(
+    descriptor: &wgpu::SamplerDescriptor<'_>,
+) -> wgpu::Features {
+    let address_modes = [
+        descriptor.address_mode_u,
+        descriptor.address_mode_v,
+        descriptor.address_mode_w,
+    ];
+    if !address_modes.contains(&wgpu::AddressMode::ClampToBorder) {
+        return wgpu::Features::empty();
+    }
+
+    match descriptor.border_color {
+        Some(wgpu::SamplerBorderColor::Zero) => wgpu::Features::ADDRESS_MODE_CLAMP_TO_ZERO,
+        _ => wgpu::Features::ADDRESS_MODE_CLAMP_TO_BORDER,
+    }
+}
+
 /// The default renderer that scales your frame to the screen size.
 #[derive(Debug)]
-pub struct ScalingRenderer {
-    vertex_buffer: wgpu::Buffer,
-    uniform_buffer: wgpu::Buffer,
+pub struct ScalingRenderer {
+    vertex_buffer: Option<wgpu::Buffer>,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    // Bind groups for the other source textures in the ring, in upload order
+    spare_bind_groups: VecDeque<wgpu::BindGroup>,
+    // Every source texture in the ring, starting with the sampled one, to recreate bind groups
+    texture_views: VecDeque<wgpu::TextureView>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    palette_buffer: Option<wgpu::Buffer>,
+    hook_buffer: Option<wgpu::Buffer>,
+    gamma_ramp_buffer: Option<wgpu::Buffer>,
+    render_pipeline: wgpu::RenderPipeline,
+    pipeline: ScalingPipeline,
+    mesh: Option<MeshBuffers>,
+    previous_frame: Option<PreviousFrame>,
+    pub(crate) clear_color: wgpu::Color,
+    width: u32,
+    height: u32,
+    // The `(x, y, width, height)` of the texture which is scaled to the surface
+    view: (u32, u32, u32, u32),
+    fit: Fit,
+    clip_rect: (u32, u32, u32, u32),
+    // The scaling transform before the offset, and the surface size to convert the offset with
+    transform: [f32; 16],
+    surface_size: (u32, u32),
+    offset: (f32, f32),
+    locals: Locals,
+}
+
+/// Create one scaling bind group for each texture view, in order.
+///
+/// `buffers` are the uniform buffers from binding 2 on, as returned by
+/// [`ScalingRenderer::uniform_buffers`]; missing optional buffers are left unbound.
+fn create_scaling_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture_views: &VecDeque<wgpu::TextureView>,
+    sampler: &wgpu::Sampler,
+    buffers: &[(u32, Option<&wgpu::Buffer>)],
+) -> VecDeque<wgpu::BindGroup> {
+    texture_views
+        .iter()
+        .map(|texture_view| {
+            let mut entries = vec![
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ];
+            for &(binding, buffer) in buffers {
+                if let Some(buffer) = buffer {
+                    entries.push(wgpu::BindGroupEntry {
+                        binding,
+                        resource: buffer.as_entire_binding(),
+                    });
+                }
+            }
+
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("pixels_scaling_renderer_bind_group"),
+                layout,
+                entries: &entries,
+            })
+        })
+        .collect()
+}
+
+/// Decode a palette of sRGB colors to the linear colors of the `Palette` uniform in
+/// `scale.wgsl`, which match sampling an `Rgba8UnormSrgb` texture.
+fn palette_colors(palette: &[[u8; 4]]) -> Vec<[f32; 4]> {
+    palette
+        .iter()
+        .map(|&[r, g, b, a]| {
+            let decode = |c: u8| srgb_to_linear(f64::from(c) / 255.0) as f32;
+            [decode(r), decode(g), decode(b), f32::from(a) / 255.0]
+        })
+        .collect()
+}
+
+/// Everything needed to create pipeline variants after the renderer is created.
+#[derive(Debug)]
+struct ScalingPipeline {
+    module: wgpu::ShaderModule,
+    layout: wgpu::PipelineLayout,
+    minimal: bool,
+    fragment_entry: &'static str,
+    color_target: wgpu::ColorTargetState,
+}
+
+/// The GPU resources for a [`DistortionMesh`].
+#[derive(Debug)]
+struct MeshBuffers {
+    render_pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+}
+
+/// Pipelines which draw the previous source texture in the ring over the sampled one, for
+/// [frame interpolation](crate::FrameInterpolation).
+#[derive(Debug)]
+struct PreviousFrame {
+    render_pipeline: wgpu::RenderPipeline,
+    mesh_pipeline: Option<wgpu::RenderPipeline>,
+    opacity: f64,
+}
+
+/// The uniform buffer layout shared with `scale.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct Locals {
+    transform: [f32; 16],
+    attenuation: [f32; 4],
+    // The view as offset and size in texture coordinates
+    tex_rect: [f32; 4],
+    tonemap: [f32; 4],
+}
+
+impl ScalingRenderer {
+    /// Create the scaling renderer.
+    ///
+    /// One bind group is created for each of the `texture_views`, which are sampled in rotation
+    /// with [`ScalingRenderer::rotate_source`]. The first view is sampled initially.
+    ///
+    /// When `srgb_view` is true, the views decode the texture as sRGB so that filtering happens in
+    /// linear light (see [`ScalingOptions::srgb_view_format`]), and the output is encoded again.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        texture_views: Vec<wgpu::TextureView>,
+        texture_size: &wgpu::Extent3d,
+        surface_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        clear_color: wgpu::Color,
+        blend_state: wgpu::BlendState,
+        options: &ScalingOptions,
+        srgb_view: bool,
+    ) -> Self {
+        let minimal = options.minimal;
+        // The minimal pipeline skips the shader hook, like other optional passes
+        let hook = options.hook.as_ref().filter(|_| !minimal);
+        let shader = if minimal {
+            wgpu::include_wgsl!("../shaders/blit.wgsl")
+        } else {
+            let hook_source = hook.map_or(DEFAULT_SHADER_HOOK, |hook| &hook.source);
+            let gamma_ramp_source = if options.gamma_ramp.is_some() {
+                let linear_output = crate::color::is_linear_target(render_texture_format);
+                let gamma_ramp = include_str!("../shaders/gamma_ramp.wgsl");
+                format!("const LINEAR_OUTPUT: bool = {linear_output};\n{gamma_ramp}")
+            } else {
+                DEFAULT_GAMMA_RAMP.to_string()
+            };
+            let source = format!(
+                "{}\n{hook_source}\n{gamma_ramp_source}",
+                include_str!("../shaders/scale.wgsl"),
+            );
+            wgpu::ShaderModuleDescriptor {
+                label: Some("scale.wgsl"),
+                source: wgpu::ShaderSource::Wgsl(source.into()),
+            }
+        };
+        let module = device.create_shader_module(shader);
+
+        // Create a texture sampler with the configured filter
+        let sampler = device.create_sampler(&options.sampler_descriptor());
+
+        // Create vertex buffer; array-of-array of position and texture coordinates
+        let vertex_data: [[f32; 2]; 3] = [
+            // One full-screen triangle
+            // See: https://github.com/parasyte/pixels/issues/180
+            [-1.0, -1.0],
+            [3.0, -1.0],
+            [-1.0, 3.0],
+        ];
+        let vertex_data_slice = bytemuck::cast_slice(&vertex_data);
+        let vertex_buffer = (!minimal).then(|| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("pixels_scaling_renderer_vertex_buffer"),
+                contents: vertex_data_slice,
+                usage: wgpu::BufferUsages::VERTEX,
+            })
+        });
+
+        // Create uniform buffer
+        let fit = options.fit();
+        let matrix = ScalingMatrix::new(
+            (texture_size.width, texture_size.height),
+            (surface_size.width, surface_size.height),
+            fit,
+        );
+        let locals = Locals {
+            transform: *matrix.transform.as_array(),
+            attenuation: Attenuation::NONE.into(),
+            tex_rect: [0.0, 0.0, 1.0, 1.0],
+            tonemap: options.tonemapping.uniform(options.exposure),
+        };
+        let locals_bytes = bytemuck::bytes_of(&locals);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_scaling_renderer_matrix_uniform_buffer"),
+            contents: locals_bytes,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Create palette uniform buffer
+        let palette_buffer = options.palette.as_ref().map(|palette| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("pixels_scaling_renderer_palette_uniform_buffer"),
+                contents: bytemuck::cast_slice(&palette_colors(&palette[..])),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        });
+
+        // Create shader hook uniform buffer
+        let hook_buffer = hook.map(|hook| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("pixels_scaling_renderer_hook_uniform_buffer"),
+                contents: &hook.uniforms,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            })
+        });
+
+        // Create gamma ramp uniform buffer
+        let gamma_ramp_buffer = options
+            .gamma_ramp
+            .as_ref()
+            .filter(|_| !minimal)
+            .map(|ramp| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("pixels_scaling_renderer_gamma_ramp_uniform_buffer"),
+                    contents: bytemuck::cast_slice(&ramp.entries()),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+            });
+
+        // Create bind group
+        let buffers = [
+            (2, Some(&uniform_buffer)),
+            (3, palette_buffer.as_ref()),
+            (4, hook_buffer.as_ref()),
+            (5, gamma_ramp_buffer.as_ref()),
+        ];
+        let mut layout_entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(locals_bytes.len() as u64),
+                },
+                count: None,
+            },
+        ];
+        // The optional buffers after the locals
+        for &(binding, buffer) in &buffers[1..] {
+            if let Some(buffer) = buffer {
+                layout_entries.push(wgpu::BindGroupLayoutEntry {
+                    binding,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(buffer.size()),
+                    },
+                    count: None,
+                });
+            }
+        }
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_scaling_renderer_bind_group_layout"),
+            entries: &layout_entries,
+        });
+        let texture_views = VecDeque::from(texture_views);
+        let mut bind_groups = create_scaling_bind_groups(
+            device,
+            &bind_group_layout,
+            &texture_views,
+            &sampler,
+            &buffers,
+        );
+        let bind_group = bind_groups.pop_front().expect("at least one texture view");
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_scaling_renderer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let fragment_entry = match (minimal, options.filter, srgb_view) {
+            _ if palette_buffer.is_some() => "fs_main_palette",
+            _ if options.packed == Some(PackedFormat::Luma8) => "fs_main_luma",
+            _ if options.packed == Some(PackedFormat::Rgb565) => "fs_main_rgb565",
+            (true, _, _) => "fs_main",
+            (false, FilterMode::Area, false) => "fs_main_area",
+            (false, FilterMode::Area, true) => "fs_main_area_srgb",
+            (false, FilterMode::SharpBilinear, false) => "fs_main_sharp",
+            (false, FilterMode::SharpBilinear, true) => "fs_main_sharp_srgb",
+            (false, _, false) => "fs_main",
+            (false, _, true) => "fs_main_srgb",
+        };
+        let color_target = wgpu::ColorTargetState {
+            format: render_texture_format,
+            blend: Some(blend_state),
+            write_mask: wgpu::ColorWrites::ALL,
+        };
+        let pipeline = ScalingPipeline {
+            module,
+            layout: pipeline_layout,
+            minimal,
+            fragment_entry,
+            color_target,
+        };
+        let render_pipeline = pipeline.create_triangle(device);
+
+        // Create clipping rectangle
+        let clip_rect = matrix.clip_rect();
+
+        Self {
+            vertex_buffer,
+            uniform_buffer,
+            bind_group,
+            spare_bind_groups: bind_groups,
+            texture_views,
+            bind_group_layout,
+            palette_buffer,
+            hook_buffer,
+            gamma_ramp_buffer,
+            render_pipeline,
+            pipeline,
+            mesh: None,
+            previous_frame: None,
+            clear_color,
+            width: texture_size.width,
+            height: texture_size.height,
+            view: (0, 0, texture_size.width, texture_size.height),
+            fit,
+            clip_rect,
+            transform: locals.transform,
+            surface_size: (surface_size.width, surface_size.height),
+            offset: (0.0, 0.0),
+            locals,
+        }
+    }
+
+    /// Draw the pixel buffer to the render target.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, render_target: &wgpu::TextureView) {
+        self.draw(
+            encoder,
+            render_target,
+            wgpu::LoadOp::Clear(self.clear_color),
+            None,
+        );
+    }
+
+    /// Draw the pixel buffer over the existing contents of the render target with the given
+    /// opacity. Requires [`ScalingRenderer::use_constant_blend`].
+    pub(crate) fn render_faded(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        opacity: f64,
+    ) {
+        self.draw(encoder, render_target, wgpu::LoadOp::Load, Some(opacity));
+    }
+
+    fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        load: wgpu::LoadOp<wgpu::Color>,
+        blend_constant: Option<f64>,
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_scaling_renderer_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        if let Some(constant) = blend_constant {
+            rpass.set_blend_constant(blend_color(constant));
+        }
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_scissor_rect(
+            self.clip_rect.0,
+            self.clip_rect.1,
+            self.clip_rect.2,
+            self.clip_rect.3,
+        );
+        if let Some(mesh) = &self.mesh {
+            rpass.set_pipeline(&mesh.render_pipeline);
+            rpass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+            rpass.set_index_buffer(mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            rpass.draw_indexed(0..mesh.index_count, 0, 0..1);
+        } else {
+            rpass.set_pipeline(&self.render_pipeline);
+            if let Some(vertex_buffer) = &self.vertex_buffer {
+                rpass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            }
+            rpass.draw(0..3, 0..1);
+        }
+
+        // Draw the previous frame over the newest one, unless the whole output is faded
+        let previous = self
+            .previous_frame
+            .as_ref()
+            .filter(|previous| blend_constant.is_none() && previous.opacity > 0.0)
+            .zip(self.spare_bind_groups.back());
+        if let Some((previous, bind_group)) = previous {
+            rpass.set_blend_constant(blend_color(previous.opacity));
+            rpass.set_bind_group(0, bind_group, &[]);
+            match (&self.mesh, &previous.mesh_pipeline) {
+                (Some(mesh), Some(pipeline)) => {
+                    rpass.set_pipeline(pipeline);
+                    rpass.draw_indexed(0..mesh.index_count, 0, 0..1);
+                }
+                _ => {
+                    rpass.set_pipeline(&previous.render_pipeline);
+                    rpass.draw(0..3, 0..1);
+                }
+            }
+        }
+    }
+
+    /// Draw a [`DistortionMesh`] instead of the full-screen triangle, or restore the triangle
+    /// with `None`.
+    pub(crate) fn set_distortion_mesh(
+        &mut self,
+        device: &wgpu::Device,
+        mesh: Option<&DistortionMesh>,
+    ) {
+        self.mesh = mesh.filter(|mesh| !mesh.indices.is_empty()).map(|mesh| {
+            let render_pipeline = self.pipeline.create_mesh(device);
+
+            MeshBuffers {
+                render_pipeline,
+                vertex_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("pixels_scaling_renderer_mesh_vertex_buffer"),
+                    contents: bytemuck::cast_slice(&mesh.vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                index_buffer: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("pixels_scaling_renderer_mesh_index_buffer"),
+                    contents: bytemuck::cast_slice(&mesh.indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                }),
+                index_count: mesh.indices.len() as u32,
+            }
+        });
+        if self.previous_frame.is_some() {
+            self.set_frame_interpolation(device, true);
+        }
+    }
+
+    /// Create or drop the pipelines which draw the previous frame over the newest one.
+    pub(crate) fn set_frame_interpolation(&mut self, device: &wgpu::Device, enable: bool) {
+        if !enable {
+            self.previous_frame = None;
+            return;
+        }
+
+        let blend = self
+            .pipeline
+            .color_target
+            .blend
+            .replace(constant_blend_state());
+        let render_pipeline = self.pipeline.create_triangle(device);
+        let mesh_pipeline = self
+            .mesh
+            .as_ref()
+            .map(|_| self.pipeline.create_mesh(device));
+        self.pipeline.color_target.blend = blend;
+
+        let opacity = self
+            .previous_frame
+            .as_ref()
+            .map_or(0.0, |previous| previous.opacity);
+        self.previous_frame = Some(PreviousFrame {
+            render_pipeline,
+            mesh_pipeline,
+            opacity,
+        });
+    }
+
+    /// Set the opacity of the previous frame drawn over the newest one, with frame interpolation.
+    pub(crate) fn set_previous_frame_opacity(&mut self, opacity: f64) {
+        if let Some(previous) = self.previous_frame.as_mut() {
+            previous.opacity = opacity;
+        }
+    }
+
+    /// Blend the output over the render target by the constant passed to
+    /// [`ScalingRenderer::render_faded`], instead of the configured blend state.
+    pub(crate) fn use_constant_blend(&mut self, device: &wgpu::Device) {
+        self.pipeline.color_target.blend = Some(constant_blend_state());
+
+        self.render_pipeline = self.pipeline.create_triangle(device);
+        if let Some(mesh) = self.mesh.as_mut() {
+            mesh.render_pipeline = self.pipeline.create_mesh(device);
+        }
+    }
+
+    /// Get the clipping rectangle for the scaling renderer.
+    ///
+    /// This rectangle defines the inner bounds of the surface texture, without the border.
+    pub fn clip_rect(&self) -> (u32, u32, u32, u32) {
+        self.clip_rect
+    }
+
+    /// Change how the texture fits the surface, from the next resize.
+    pub(crate) fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+    }
+
+    pub(crate) fn resize(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
+        let (_, _, view_width, view_height) = self.view;
+        let matrix = ScalingMatrix::new((view_width, view_height), (width, height), self.fit);
+        self.transform = *matrix.transform.as_array();
+        self.surface_size = (width, height);
+        self.apply_offset();
+        self.write_locals(queue);
+
+        self.clip_rect = matrix.clip_rect();
+    }
+
+    /// Scale the `(x, y, width, height)` rectangle `view` of the texture to the surface, instead
+    /// of the whole texture.
+    pub(crate) fn set_view(&mut self, queue: &wgpu::Queue, view: (u32, u32, u32, u32)) {
+        if self.view == view {
+            return;
+        }
+
+        let (x, y, width, height) = view;
+        let (texture_width, texture_height) = (self.width as f32, self.height as f32);
+        self.view = view;
+        self.locals.tex_rect = [
+            x as f32 / texture_width,
+            y as f32 / texture_height,
+            width as f32 / texture_width,
+            height as f32 / texture_height,
+        ];
+        let (surface_width, surface_height) = self.surface_size;
+        self.resize(queue, surface_width, surface_height);
+    }
+
+    /// Move the scaled pixel buffer by `offset` physical pixels of the render target, within the
+    /// clipping rectangle. Used for screen shake.
+    pub(crate) fn set_offset(&mut self, queue: &wgpu::Queue, offset: (f32, f32)) {
+        if self.offset != offset {
+            self.offset = offset;
+            self.apply_offset();
+            self.write_locals(queue);
+        }
+    }
+
+    fn apply_offset(&mut self) {
+        let (width, height) = self.surface_size;
+        let mut transform = self.transform;
+        transform[12] += self.offset.0 * 2.0 / width as f32;
+        transform[13] -= self.offset.1 * 2.0 / height as f32;
+        self.locals.transform = transform;
+    }
+
+    /// Sample the next source texture in the ring.
+    pub(crate) fn rotate_source(&mut self) {
+        if let Some(bind_group) = self.spare_bind_groups.pop_front() {
+            let previous = std::mem::replace(&mut self.bind_group, bind_group);
+            self.spare_bind_groups.push_back(previous);
+            self.texture_views.rotate_left(1);
+        }
+    }
+
+    /// The uniform buffers of the bind groups, by binding.
+    fn uniform_buffers(&self) -> [(u32, Option<&wgpu::Buffer>); 4] {
+        [
+            (2, Some(&self.uniform_buffer)),
+            (3, self.palette_buffer.as_ref()),
+            (4, self.hook_buffer.as_ref()),
+            (5, self.gamma_ramp_buffer.as_ref()),
+        ]
+    }
+
+    /// Recreate the sampler from `options`, keeping the source textures and their rotation.
+    pub(crate) fn set_sampler(&mut self, device: &wgpu::Device, options: &ScalingOptions) {
+        let sampler = device.create_sampler(&options.sampler_descriptor());
+        let mut bind_groups = create_scaling_bind_groups(
+            device,
+            &self.bind_group_layout,
+            &self.texture_views,
+            &sampler,
+            &self.uniform_buffers(),
+        );
+
+        self.bind_group = bind_groups.pop_front().expect("at least one texture view");
+        self.spare_bind_groups = bind_groups;
+    }
+
+    /// Replace the colors of the palette, when the renderer was created with one.
+    pub(crate) fn set_palette(&self, queue: &wgpu::Queue, palette: &[[u8; 4]; 256]) {
+        self.update_palette(queue, 0, palette);
+    }
+
+    /// Replace the palette colors from index `start`, when the renderer was created with a
+    /// palette. Only the given colors are uploaded.
+    pub(crate) fn update_palette(&self, queue: &wgpu::Queue, start: usize, colors: &[[u8; 4]]) {
+        if let Some(palette_buffer) = self.palette_buffer.as_ref().filter(|_| !colors.is_empty()) {
+            let offset = (start * std::mem::size_of::<[f32; 4]>()) as wgpu::BufferAddress;
+            queue.write_buffer(
+                palette_buffer,
+                offset,
+                bytemuck::cast_slice(&palette_colors(colors)),
+            );
+        }
+    }
+
+    /// Replace the contents of the shader hook uniform buffer, when the renderer has one.
+    pub(crate) fn set_hook_uniforms(&self, queue: &wgpu::Queue, uniforms: &[u8]) {
+        if let Some(hook_buffer) = &self.hook_buffer {
+            queue.write_buffer(hook_buffer, 0, uniforms);
+        }
+    }
+
+    /// Replace the gamma ramp, when the renderer was created with one.
+    pub(crate) fn set_gamma_ramp(&self, queue: &wgpu::Queue, ramp: &GammaRamp) {
+        if let Some(gamma_ramp_buffer) = &self.gamma_ramp_buffer {
+            queue.write_buffer(gamma_ramp_buffer, 0, bytemuck::cast_slice(&ramp.entries()));
+        }
+    }
+
+    /// Blend the output toward a flat color, e.g. to limit flashes.
+    pub(crate) fn set_attenuation(&mut self, queue: &wgpu::Queue, attenuation: Attenuation) {
+        let attenuation = attenuation.into();
+        if self.locals.attenuation != attenuation {
+            self.locals.attenuation = attenuation;
+            self.write_locals(queue);
+        }
+    }
+
+    /// Replace the tonemapping curve and exposure.
+    pub(crate) fn set_tonemapping(
+        &mut self,
+        queue: &wgpu::Queue,
+        tonemapping: Tonemapping,
+        exposure: f32,
+    ) {
+        self.locals.tonemap = tonemapping.uniform(exposure);
+        self.write_locals(queue);
+    }
+
+    fn write_locals(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&self.locals));
+    }
+}
+
+/// Configuration for the pixel grid overlay.
+///
+/// The grid draws lines between the pixels of the scaled pixel buffer, which is essential for
+/// sprite and tile editors. It is only shown while each buffer pixel covers at least `min_scale`
+/// physical pixels, so the lines never drown out the image.
+///
+/// ```no_run
+/// use pixels::{PixelGrid, PixelsBuilder};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let pixels = PixelsBuilder::new(32, 32, surface_texture)
+///     .pixel_grid(PixelGrid {
+///         color: pixels::wgpu::Color { r: 1.0, g: 1.0, b: 1.0, a: 0.5 },
+///         ..PixelGrid::default()
+///     })
+///     .build()?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PixelGrid {
+    /// The sRGB line color. The alpha component sets the opacity of the lines.
+    pub color: wgpu::Color,
+
+    /// The smallest integer scale factor at which the grid is drawn.
+    pub min_scale: u32,
+
+    /// The width of the lines in physical pixels.
+    pub line_width: u32,
+}
+
+impl Default for PixelGrid {
+    /// Translucent black lines, one physical pixel wide, from 4x scale upward.
+    fn default() -> Self {
+        Self {
+            color: wgpu::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.25,
+            },
+            min_scale: 4,
+            line_width: 1,
+        }
+    }
+}
+
+/// A renderer that draws the [`PixelGrid`] over the scaled pixel buffer.
+///
+/// [`Pixels::render`](crate::Pixels::render) draws the grid after the [`ScalingRenderer`]. Custom
+/// render functions should do the same; the renderer does nothing while the grid is disabled or
+/// the scale factor is below [`PixelGrid::min_scale`].
+#[derive(Debug)]
+pub struct GridRenderer {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    render_texture_format: wgpu::TextureFormat,
+    grid: Option<PixelGrid>,
+    matrix: ScalingMatrix,
+    fit: Fit,
+}
+
+/// The uniform buffer layout shared with `grid.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct GridLocals {
+    color: [f32; 4],
+    origin: [f32; 2],
+    scale: [f32; 2],
+    line_width: f32,
+    _padding: [f32; 3],
+}
+
+impl GridRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        texture_size: &wgpu::Extent3d,
+        surface_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        fit: Fit,
+        grid: Option<PixelGrid>,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/grid.wgsl"));
+
+        // Create uniform buffer
+        let matrix = ScalingMatrix::new(
+            (texture_size.width, texture_size.height),
+            (surface_size.width, surface_size.height),
+            fit,
+        );
+        let locals = GridLocals::new(grid, &matrix, render_texture_format);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_grid_renderer_uniform_buffer"),
+            contents: bytemuck::bytes_of(&locals),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_grid_renderer_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<GridLocals>() as u64
+                    ),
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_grid_renderer_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_grid_renderer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_grid_renderer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+            render_texture_format,
+            grid,
+            matrix,
+            fit,
+        }
+    }
+
+    /// Draw the grid over the render target.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, render_target: &wgpu::TextureView) {
+        if !self.is_visible() {
+            return;
+        }
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_grid_renderer_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_scissor_rect(
+            self.matrix.clip_rect.0,
+            self.matrix.clip_rect.1,
+            self.matrix.clip_rect.2,
+            self.matrix.clip_rect.3,
+        );
+        rpass.draw(0..3, 0..1);
+    }
+
+    /// Get the grid configuration, if the grid is enabled.
+    pub fn grid(&self) -> Option<PixelGrid> {
+        self.grid
+    }
+
+    /// Whether the grid is enabled and the scale factor is large enough to show it.
+    pub fn is_visible(&self) -> bool {
+        self.grid.is_some_and(|grid| {
+            self.matrix.scale.0.min(self.matrix.scale.1) >= grid.min_scale.max(1) as f32
+        })
+    }
+
+    pub(crate) fn set_grid(&mut self, queue: &wgpu::Queue, grid: Option<PixelGrid>) {
+        self.grid = grid;
+        self.write_locals(queue);
+    }
+
+    /// Change how the texture fits the surface, from the next resize.
+    pub(crate) fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+    }
+
+    pub(crate) fn resize(
+        &mut self,
+        queue: &wgpu::Queue,
+        texture_size: &wgpu::Extent3d,
+        width: u32,
+        height: u32,
+    ) {
+        self.matrix = ScalingMatrix::new(
+            (texture_size.width, texture_size.height),
+            (width, height),
+            self.fit,
+        );
+        self.write_locals(queue);
+    }
+
+    fn write_locals(&self, queue: &wgpu::Queue) {
+        let locals = GridLocals::new(self.grid, &self.matrix, self.render_texture_format);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+    }
+}
+
+impl GridLocals {
+    fn new(
+        grid: Option<PixelGrid>,
+        matrix: &ScalingMatrix,
+        render_texture_format: wgpu::TextureFormat,
+    ) -> Self {
+        let grid = grid.unwrap_or_default();
+        let color = crate::color::clear_color_for_target(
+            grid.color,
+            crate::ColorSpace::Srgb,
+            render_texture_format,
+        );
+
+        Self {
+            color: [
+                color.r as f32,
+                color.g as f32,
+                color.b as f32,
+                color.a as f32,
+            ],
+            origin: [matrix.origin.0 as f32, matrix.origin.1 as f32],
+            scale: [matrix.scale.0, matrix.scale.1],
+            line_width: grid.line_width as f32,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+/// A rectangle drawn over the scaled pixel buffer by the [`SelectionRenderer`].
+///
+/// Selections are drawn on the GPU above the frame, so editor chrome never ends up in the pixel
+/// buffer (or in exported images).
+///
+/// ```no_run
+/// use pixels::{Selection, SelectionStyle};
+///
+/// # use pixels::Pixels;
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let mut pixels = Pixels::new(64, 64, surface_texture)?;
+///
+/// pixels.set_selections(&[
+///     Selection::new(8, 8, 16, 16, SelectionStyle::MarchingAnts),
+///     Selection::new(
+///         32,
+///         32,
+///         8,
+///         4,
+///         SelectionStyle::Highlight(pixels::wgpu::Color { r: 1.0, g: 1.0, b: 0.0, a: 0.3 }),
+///     ),
+/// ]);
+///
+/// // Advance the marching ants once per frame
+/// let mut phase = 0;
+/// phase += 1;
+/// pixels.set_selection_phase(phase);
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Selection {
+    /// The left edge, in pixel buffer coordinates.
+    pub x: u32,
+    /// The top edge, in pixel buffer coordinates.
+    pub y: u32,
+    /// The width, in pixel buffer pixels.
+    pub width: u32,
+    /// The height, in pixel buffer pixels.
+    pub height: u32,
+    /// How the rectangle is drawn.
+    pub style: SelectionStyle,
+}
+
+/// How a [`Selection`] is drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum SelectionStyle {
+    /// A dashed black and white outline, one physical pixel wide, which moves with the selection
+    /// phase.
+    MarchingAnts,
+    /// A filled rectangle in the given sRGB color. The alpha component sets the opacity.
+    Highlight(wgpu::Color),
+}
+
+impl Selection {
+    /// Create a selection rectangle.
+    pub fn new(x: u32, y: u32, width: u32, height: u32, style: SelectionStyle) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            style,
+        }
+    }
+}
+
+/// A renderer that draws [`Selection`] rectangles over the scaled pixel buffer.
+///
+/// [`Pixels::render`](crate::Pixels::render) draws the selections last. Custom render functions
+/// should do the same; the renderer does nothing while there are no selections.
+#[derive(Debug)]
+pub struct SelectionRenderer {
+    instance_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    render_texture_format: wgpu::TextureFormat,
+    selections: Vec<Selection>,
+    phase: u32,
+    matrix: ScalingMatrix,
+    fit: Fit,
+    surface_size: (u32, u32),
+}
+
+/// The uniform buffer layout shared with `selection.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct SelectionLocals {
+    origin: [f32; 2],
+    screen_size: [f32; 2],
+    x_axis: [f32; 2],
+    y_axis: [f32; 2],
+    phase: f32,
+    _padding: [f32; 3],
+}
+
+/// The per-instance vertex layout shared with `selection.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct SelectionInstance {
+    rect: [f32; 4],
+    color: [f32; 4],
+    style: u32,
+}
+
+impl SelectionRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        texture_size: &wgpu::Extent3d,
+        surface_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        fit: Fit,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/selection.wgsl"));
+
+        // Create instance buffer; it is grown as needed when selections are set
+        let instance_buffer = create_selection_buffer(device, 1);
+        let instance_buffer_layout = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SelectionInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4, 2 => Uint32],
+        };
+
+        // Create uniform buffer
+        let matrix = ScalingMatrix::new(
+            (texture_size.width, texture_size.height),
+            (surface_size.width, surface_size.height),
+            fit,
+        );
+        let surface_size = (surface_size.width, surface_size.height);
+        let locals = SelectionLocals::new(&matrix, surface_size, 0);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_selection_renderer_uniform_buffer"),
+            contents: bytemuck::bytes_of(&locals),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_selection_renderer_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(
+                        std::mem::size_of::<SelectionLocals>() as u64
+                    ),
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_selection_renderer_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_selection_renderer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_selection_renderer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[instance_buffer_layout],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            instance_buffer,
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+            render_texture_format,
+            selections: Vec::new(),
+            phase: 0,
+            matrix,
+            fit,
+            surface_size,
+        }
+    }
+
+    /// Draw the selections over the render target.
+    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, render_target: &wgpu::TextureView) {
+        if self.selections.is_empty() {
+            return;
+        }
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_selection_renderer_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_vertex_buffer(0, self.instance_buffer.slice(..));
+        let clip_rect = self.matrix.clip_rect();
+        rpass.set_scissor_rect(clip_rect.0, clip_rect.1, clip_rect.2, clip_rect.3);
+        rpass.draw(0..6, 0..self.selections.len() as u32);
+    }
+
+    /// Get the selections which are drawn.
+    pub fn selections(&self) -> &[Selection] {
+        &self.selections
+    }
+
+    pub(crate) fn set_selections(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        selections: &[Selection],
+    ) {
+        let instances: Vec<_> = selections
+            .iter()
+            .map(|selection| SelectionInstance::new(selection, self.render_texture_format))
+            .collect();
+
+        let size = std::mem::size_of_val(instances.as_slice()) as wgpu::BufferAddress;
+        if size > self.instance_buffer.size() {
+            self.instance_buffer = create_selection_buffer(device, instances.len());
+        }
+        if !instances.is_empty() {
+            queue.write_buffer(&self.instance_buffer, 0, bytemuck::cast_slice(&instances));
+        }
+
+        self.selections.clear();
+        self.selections.extend_from_slice(selections);
+    }
+
+    pub(crate) fn set_phase(&mut self, queue: &wgpu::Queue, phase: u32) {
+        self.phase = phase;
+        self.write_locals(queue);
+    }
+
+    /// Change how the texture fits the surface, from the next resize.
+    pub(crate) fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+    }
+
+    pub(crate) fn resize(
+        &mut self,
+        queue: &wgpu::Queue,
+        texture_size: &wgpu::Extent3d,
+        width: u32,
+        height: u32,
+    ) {
+        self.matrix = ScalingMatrix::new(
+            (texture_size.width, texture_size.height),
+            (width, height),
+            self.fit,
+        );
+        self.surface_size = (width, height);
+        self.write_locals(queue);
+    }
+
+    fn write_locals(&self, queue: &wgpu::Queue) {
+        let locals = SelectionLocals::new(&self.matrix, self.surface_size, self.phase);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+    }
+}
+
+impl SelectionLocals {
+    fn new(matrix: &ScalingMatrix, surface_size: (u32, u32), phase: u32) -> Self {
+        let texture_to_window = matrix.texture_to_window;
+
+        Self {
+            origin: [texture_to_window.cols[3].x, texture_to_window.cols[3].y],
+            screen_size: [surface_size.0 as f32, surface_size.1 as f32],
+            x_axis: [texture_to_window.cols[0].x, texture_to_window.cols[0].y],
+            y_axis: [texture_to_window.cols[1].x, texture_to_window.cols[1].y],
+            // The dash pattern repeats every 8 physical pixels
+            phase: (phase % 8) as f32,
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+impl SelectionInstance {
+    fn new(selection: &Selection, render_texture_format: wgpu::TextureFormat) -> Self {
+        let (color, style) = match selection.style {
+            SelectionStyle::MarchingAnts => (wgpu::Color::TRANSPARENT, 0),
+            SelectionStyle::Highlight(color) => (
+                crate::color::clear_color_for_target(
+                    color,
+                    crate::ColorSpace::Srgb,
+                    render_texture_format,
+                ),
+                1,
+            ),
+        };
+
+        Self {
+            rect: [
+                selection.x as f32,
+                selection.y as f32,
+                selection.width as f32,
+                selection.height as f32,
+            ],
+            color: [
+                color.r as f32,
+                color.g as f32,
+                color.b as f32,
+                color.a as f32,
+            ],
+            style,
+        }
+    }
+}
+
+fn create_selection_buffer(device: &wgpu::Device, len: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("pixels_selection_renderer_instance_buffer"),
+        size: (len.max(1) * std::mem::size_of::<SelectionInstance>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// The corner of the surface where a [`Watermark`] is stamped.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum WatermarkCorner {
+    /// The top-left corner.
+    TopLeft,
+    /// The top-right corner.
+    TopRight,
+    /// The bottom-left corner.
+    BottomLeft,
+    /// The bottom-right corner.
+    #[default]
+    BottomRight,
+}
+
+/// A small image stamped at a corner of the surface, over the pixel buffer and all render
+/// passes.
+///
+/// The watermark is drawn on the GPU and never touches the pixel buffer, so it suits demo
+/// builds, recording overlays, and branding. Each image pixel covers one physical pixel.
+///
+/// ```no_run
+/// use pixels::{PixelsBuilder, Watermark, WatermarkCorner};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// let logo = vec![0xff; 16 * 16 * 4];
+/// let pixels = PixelsBuilder::new(320, 240, surface_texture)
+///     .watermark(Watermark {
+///         corner: WatermarkCorner::TopRight,
+///         opacity: 0.5,
+///         ..Watermark::new(16, 16, logo)
+///     })
+///     .build()?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Watermark {
+    /// The width of the image in pixels.
+    pub width: u32,
+
+    /// The height of the image in pixels.
+    pub height: u32,
+
+    /// The image as sRGB RGBA bytes, with straight (not premultiplied) alpha.
+    pub image: Vec<u8>,
+
+    /// The corner of the surface to stamp the image at.
+    pub corner: WatermarkCorner,
+
+    /// The distance from the edges of the surface in physical pixels.
+    pub margin: u32,
+
+    /// The opacity of the image, from `0.0` (invisible) to `1.0`.
+    pub opacity: f32,
+}
+
+impl Watermark {
+    /// Create an opaque watermark at the bottom-right corner, with an 8 pixel margin.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0, or `image` does not hold `width * height` RGBA
+    /// pixels.
+    pub fn new(width: u32, height: u32, image: Vec<u8>) -> Self {
+        assert!(width > 0);
+        assert!(height > 0);
+        assert_eq!(image.len(), width as usize * height as usize * 4);
+
+        Self {
+            width,
+            height,
+            image,
+            corner: WatermarkCorner::default(),
+            margin: 8,
+            opacity: 1.0,
+        }
+    }
+}
+
+/// Draws a [`Watermark`] over the render target.
+#[derive(Debug)]
+pub(crate) struct WatermarkRenderer {
+    watermark: Watermark,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    factor: u32,
+}
+
+/// The uniform buffer layout shared with `watermark.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct WatermarkLocals {
+    rect: [f32; 4],
+    opacity: f32,
+    _padding: [f32; 3],
+}
+
+impl WatermarkLocals {
+    /// Place the watermark in a render target of `size` pixels, which is `factor` times the
+    /// surface size.
+    fn new(watermark: &Watermark, size: (u32, u32), factor: u32) -> Self {
+        let (target_width, target_height) = (size.0 as f32, size.1 as f32);
+        let width = (watermark.width * factor) as f32;
+        let height = (watermark.height * factor) as f32;
+        let margin = (watermark.margin * factor) as f32;
+
+        let x = match watermark.corner {
+            WatermarkCorner::TopLeft | WatermarkCorner::BottomLeft => margin,
+            WatermarkCorner::TopRight | WatermarkCorner::BottomRight => {
+                target_width - margin - width
+            }
+        };
+        let y = match watermark.corner {
+            WatermarkCorner::TopLeft | WatermarkCorner::TopRight => margin,
+            WatermarkCorner::BottomLeft | WatermarkCorner::BottomRight => {
+                target_height - margin - height
+            }
+        };
+
+        Self {
+            rect: [
+                x / target_width * 2.0 - 1.0,
+                1.0 - y / target_height * 2.0,
+                (x + width) / target_width * 2.0 - 1.0,
+                1.0 - (y + height) / target_height * 2.0,
+            ],
+            opacity: watermark.opacity.clamp(0.0, 1.0),
+            _padding: [0.0; 3],
+        }
+    }
+}
+
+impl WatermarkRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        watermark: Watermark,
+        render_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        factor: u32,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/watermark.wgsl"));
+
+        // Upload the image, decoding sRGB only when the render target takes linear light
+        let texture_format = if crate::color::is_linear_target(render_texture_format) {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("pixels_watermark_texture"),
+                size: wgpu::Extent3d {
+                    width: watermark.width,
+                    height: watermark.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: texture_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &watermark.image,
+        );
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pixels_watermark_sampler"),
+            ..Default::default()
+        });
+
+        // Create uniform buffer
+        let size = (render_size.width, render_size.height);
+        let locals = WatermarkLocals::new(&watermark, size, factor);
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_watermark_uniform_buffer"),
+            contents: bytemuck::bytes_of(&locals),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_watermark_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<WatermarkLocals>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_watermark_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_watermark_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_watermark_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            watermark,
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+            factor,
+        }
+    }
+
+    pub(crate) fn watermark(&self) -> &Watermark {
+        &self.watermark
+    }
+
+    /// Stamp the watermark over the existing contents of the render target.
+    pub(crate) fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_watermark_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..4, 0..1);
+    }
+
+    pub(crate) fn resize(&self, queue: &wgpu::Queue, width: u32, height: u32) {
+        let locals = WatermarkLocals::new(&self.watermark, (width, height), self.factor);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+    }
+}
+
+/// Fills the letterbox bars with a dimmed, blurred copy of the frame, like the "ambient" look of
+/// video players.
+///
+/// See [`PixelsBuilder::ambient_bezel`](crate::PixelsBuilder::ambient_bezel).
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AmbientBezel {
+    /// The brightness of the bars relative to the frame, from 0 (black) to 1.
+    pub brightness: f32,
+
+    /// The strength of the blur, as a fraction of the pixel buffer size. 0 stretches the frame
+    /// without blurring it.
+    pub blur: f32,
+}
+
+impl Default for AmbientBezel {
+    /// A subtle glow, a third as bright as the frame.
+    fn default() -> Self {
+        Self {
+            brightness: 0.35,
+            blur: 0.02,
+        }
+    }
+}
+
+impl AmbientBezel {
+    /// Create an ambient bezel with the given brightness and blur.
+    pub fn new(brightness: f32, blur: f32) -> Self {
+        Self { brightness, blur }
+    }
+}
+
+/// Draws an [`AmbientBezel`] outside of the scaled pixel buffer.
+#[derive(Debug)]
+pub(crate) struct BezelRenderer {
+    bezel: AmbientBezel,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+}
+
+/// The uniform buffer layout shared with `bezel.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct BezelLocals {
+    uv_transform: [f32; 4],
+    clip_rect: [f32; 4],
+    brightness: f32,
+    radius: f32,
+    _padding: [f32; 2],
+}
+
+impl BezelLocals {
+    /// Stretch a texture of `texture_size` to cover a render target of `render_size`, keeping its
+    /// aspect ratio.
+    fn new(
+        bezel: &AmbientBezel,
+        texture_size: (u32, u32),
+        render_size: (u32, u32),
+        clip_rect: (u32, u32, u32, u32),
+    ) -> Self {
+        let (texture_width, texture_height) = (texture_size.0 as f32, texture_size.1 as f32);
+        let (render_width, render_height) = (render_size.0 as f32, render_size.1 as f32);
+        let scale = (render_width / texture_width).max(render_height / texture_height);
+        let (width, height) = (texture_width * scale, texture_height * scale);
+        let (x, y, clip_width, clip_height) = clip_rect;
+
+        Self {
+            uv_transform: [
+                1.0 / width,
+                1.0 / height,
+                (width - render_width) / 2.0 / width,
+                (height - render_height) / 2.0 / height,
+            ],
+            clip_rect: [
+                x as f32,
+                y as f32,
+                (x + clip_width) as f32,
+                (y + clip_height) as f32,
+            ],
+            brightness: bezel.brightness.clamp(0.0, 1.0),
+            radius: bezel.blur.max(0.0) / 2.0,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+impl BezelRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        render_texture_format: wgpu::TextureFormat,
+        bezel: AmbientBezel,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/bezel.wgsl"));
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pixels_bezel_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        // Create uniform buffer
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_bezel_uniform_buffer"),
+            size: std::mem::size_of::<BezelLocals>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create bind group layout; the bind group is created for each frame, because the source
+        // texture rotates
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_bezel_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<BezelLocals>() as u64
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_bezel_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_bezel_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            bezel,
+            uniform_buffer,
+            sampler,
+            bind_group_layout,
+            render_pipeline,
+        }
+    }
+
+    pub(crate) fn bezel(&self) -> AmbientBezel {
+        self.bezel
+    }
+
+    /// Fill the render target outside of `clip_rect` with the blurred `texture`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn render(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        render_size: (u32, u32),
+        texture: &wgpu::Texture,
+        clip_rect: (u32, u32, u32, u32),
+    ) {
+        let texture_size = (texture.width(), texture.height());
+        let locals = BezelLocals::new(&self.bezel, texture_size, render_size, clip_rect);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+
+        // Create bind group
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_bezel_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: self.uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_bezel_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+/// How a [`Background`] image is scaled to the surface.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum BackgroundFit {
+    /// Scale the image to cover the whole surface, keeping its aspect ratio and cropping the
+    /// edges which do not fit.
+    #[default]
+    Cover,
+    /// Scale the image to fit inside the surface, keeping its aspect ratio. The clear color
+    /// shows around it.
+    Contain,
+    /// Stretch the image to the size of the surface.
+    Stretch,
+    /// Repeat the image at its own size in physical pixels, from the top-left corner of the
+    /// surface, without filtering. See [`Background::checkerboard`].
+    Tile,
+}
+
+/// An image filling the letterbox bars around the scaled pixel buffer, such as a menu backdrop or
+/// bezel art.
+///
+/// The image is scaled to the whole surface with its [`BackgroundFit`] and bilinear filtering,
+/// and the pixel buffer is drawn over it, so only the bars show it. Transparent parts of the
+/// image show the clear color, which also fills solid bars without a background; both can be
+/// changed at runtime with [`Pixels::set_background`](crate::Pixels::set_background) and
+/// [`Pixels::clear_color_srgb`](crate::Pixels::clear_color_srgb).
+///
+/// ```no_run
+/// use pixels::{Background, BackgroundFit, PixelsBuilder};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(1920, 1080, &window);
+/// let art = vec![0x40; 640 * 360 * 4];
+/// let pixels = PixelsBuilder::new(320, 240, surface_texture)
+///     .background(Background {
+///         fit: BackgroundFit::Stretch,
+///         ..Background::new(640, 360, art)
+///     })
+///     .build()?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Background {
+    /// The width of the image in pixels.
+    pub width: u32,
+
+    /// The height of the image in pixels.
+    pub height: u32,
+
+    /// The image as sRGB RGBA bytes, with straight (not premultiplied) alpha.
+    pub image: Vec<u8>,
+
+    /// How the image is scaled to the surface.
+    pub fit: BackgroundFit,
+}
+
+impl Background {
+    /// Create a background which covers the surface.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `width` or `height` are 0, or `image` does not hold `width * height` RGBA
+    /// pixels.
+    pub fn new(width: u32, height: u32, image: Vec<u8>) -> Self {
+        assert!(width > 0);
+        assert!(height > 0);
+        assert_eq!(image.len(), width as usize * height as usize * 4);
+
+        Self {
+            width,
+            height,
+            image,
+            fit: BackgroundFit::default(),
+        }
+    }
+
+    /// Create a checkerboard of `cell_size` physical pixels in two sRGB RGBA colors, tiled over
+    /// the surface, e.g. to tell the bars apart from black parts of the image.
+    ///
+    /// ```
+    /// use pixels::{Background, BackgroundFit};
+    ///
+    /// let checkerboard = Background::checkerboard(8, [0x30, 0x30, 0x30, 0xff], [0x50; 4]);
+    /// assert_eq!((checkerboard.width, checkerboard.fit), (16, BackgroundFit::Tile));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics when `cell_size` is 0.
+    pub fn checkerboard(cell_size: u32, light: [u8; 4], dark: [u8; 4]) -> Self {
+        assert!(cell_size > 0);
+
+        let size = cell_size * 2;
+        let image = (0..size * size)
+            .flat_map(|i| {
+                let (x, y) = (i % size / cell_size, i / size / cell_size);
+                if x == y {
+                    light
+                } else {
+                    dark
+                }
+            })
+            .collect();
+
+        Self {
+            fit: BackgroundFit::Tile,
+            ..Self::new(size, size, image)
+        }
+    }
+}
+
+/// Draws a [`Background`] outside of the scaled pixel buffer.
+#[derive(Debug)]
+pub(crate) struct BackgroundRenderer {
+    background: Background,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+}
+
+/// The uniform buffer layout shared with `background.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct BackgroundLocals {
+    uv_transform: [f32; 4],
+    clip_rect: [f32; 4],
+    tile: [f32; 4],
+}
+
+impl BackgroundLocals {
+    /// Scale the image of `background` to a render target of `render_size`, centered, or tile it
+    /// with each image pixel covering `factor` render target pixels.
+    fn new(
+        background: &Background,
+        render_size: (u32, u32),
+        clip_rect: (u32, u32, u32, u32),
+        factor: u32,
+    ) -> Self {
+        let (image_width, image_height) = (background.width as f32, background.height as f32);
+        let (render_width, render_height) = (render_size.0 as f32, render_size.1 as f32);
+        let (width, height) = match background.fit {
+            BackgroundFit::Cover | BackgroundFit::Contain => {
+                let (scale_x, scale_y) = (render_width / image_width, render_height / image_height);
+                let scale = if background.fit == BackgroundFit::Cover {
+                    scale_x.max(scale_y)
+                } else {
+                    scale_x.min(scale_y)
+                };
+                (image_width * scale, image_height * scale)
+            }
+            BackgroundFit::Stretch | BackgroundFit::Tile => (render_width, render_height),
+        };
+        let (x, y, clip_width, clip_height) = clip_rect;
+
+        Self {
+            uv_transform: [
+                1.0 / width,
+                1.0 / height,
+                (width - render_width) / 2.0 / width,
+                (height - render_height) / 2.0 / height,
+            ],
+            clip_rect: [
+                x as f32,
+                y as f32,
+                (x + clip_width) as f32,
+                (y + clip_height) as f32,
+            ],
+            tile: [
+                factor as f32,
+                factor as f32,
+                f32::from(background.fit == BackgroundFit::Tile),
+                0.0,
+            ],
+        }
+    }
+}
+
+impl BackgroundRenderer {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        background: Background,
+        render_texture_format: wgpu::TextureFormat,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/background.wgsl"));
+
+        // Upload the image, decoding sRGB only when the render target takes linear light
+        let texture_format = if crate::color::is_linear_target(render_texture_format) {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        let texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("pixels_background_texture"),
+                size: wgpu::Extent3d {
+                    width: background.width,
+                    height: background.height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: texture_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            &background.image,
+        );
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pixels_background_sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        // Create uniform buffer; it is written before each frame, when the clip rect is known
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_background_uniform_buffer"),
+            size: std::mem::size_of::<BackgroundLocals>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_background_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<BackgroundLocals>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_background_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_background_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_background_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            background,
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+        }
+    }
+
+    pub(crate) fn background(&self) -> &Background {
+        &self.background
+    }
+
+    /// Fill the render target outside of `clip_rect` with the background image.
+    pub(crate) fn render(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        render_size: (u32, u32),
+        clip_rect: (u32, u32, u32, u32),
+        factor: u32,
+    ) {
+        let locals = BackgroundLocals::new(&self.background, render_size, clip_rect, factor);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_background_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+/// Blends a flat color over the render target, for screen flashes, fades, and the latency probe.
+#[derive(Debug)]
+pub(crate) struct ColorOverlayRenderer {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    render_texture_format: wgpu::TextureFormat,
+}
+
+impl ColorOverlayRenderer {
+    pub(crate) fn new(device: &wgpu::Device, render_texture_format: wgpu::TextureFormat) -> Self {
+        let module =
+            device.create_shader_module(wgpu::include_wgsl!("../shaders/color_overlay.wgsl"));
+
+        // Create uniform buffer
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_color_overlay_uniform_buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_color_overlay_bind_group_layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: wgpu::BufferSize::new(16),
+                },
+                count: None,
+            }],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_color_overlay_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_color_overlay_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_color_overlay_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+            render_texture_format,
+        }
+    }
+
+    /// Blend the sRGB `color` over the render target with its alpha component.
+    pub(crate) fn render(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        color: wgpu::Color,
+    ) {
+        self.render_rect(queue, encoder, render_target, color, None);
+    }
+
+    /// Blend the sRGB `color` over the `(x, y, width, height)` rectangle of the render target, or
+    /// the whole render target with `None`.
+    pub(crate) fn render_rect(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        color: wgpu::Color,
+        rect: Option<(u32, u32, u32, u32)>,
+    ) {
+        let color = crate::color::clear_color_for_target(
+            color,
+            crate::ColorSpace::Srgb,
+            self.render_texture_format,
+        );
+        let color = [color.r, color.g, color.b, color.a].map(|c| c as f32);
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&color));
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_color_overlay_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        if let Some((x, y, width, height)) = rect {
+            rpass.set_scissor_rect(x, y, width, height);
+        }
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+/// Blend by the render pass blend constant, `source * constant + target * (1 - constant)`.
+fn constant_blend_state() -> wgpu::BlendState {
+    let component = wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Constant,
+        dst_factor: wgpu::BlendFactor::OneMinusConstant,
+        operation: wgpu::BlendOperation::Add,
+    };
+
+    wgpu::BlendState {
+        color: component,
+        alpha: component,
+    }
+}
+
+/// The blend constant color with every channel set to `constant`.
+fn blend_color(constant: f64) -> wgpu::Color {
+    wgpu::Color {
+        r: constant,
+        g: constant,
+        b: constant,
+        a: constant,
+    }
+}
+
+/// Fades out the previous frame over the new one after the pixel buffer is resized.
+#[derive(Debug)]
+pub(crate) struct Crossfade {
+    renderer: ScalingRenderer,
+    frame: u32,
+    frames: u32,
+}
+
+impl Crossfade {
+    /// Fade out the frame drawn by `renderer` over `frames` frames.
+    pub(crate) fn new(device: &wgpu::Device, mut renderer: ScalingRenderer, frames: u32) -> Self {
+        renderer.use_constant_blend(device);
+
+        Self {
+            renderer,
+            frame: 0,
+            frames,
+        }
+    }
+
+    /// Draw the next step of the transition. Returns `false` once the transition has finished.
+    pub(crate) fn render(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+    ) -> bool {
+        self.frame += 1;
+        let opacity = 1.0 - f64::from(self.frame) / f64::from(self.frames + 1);
+        self.renderer.render_faded(encoder, render_target, opacity);
+
+        self.frame < self.frames
+    }
+
+    pub(crate) fn resize(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
+        self.renderer.resize(queue, width, height);
+    }
+}
+
+/// The effects for [`Pixels::start_transition`](crate::Pixels::start_transition), which uncover
+/// the current frame from under a snapshot of the previous one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Transition {
+    /// Fade the snapshot out.
+    #[default]
+    Crossfade,
+    /// Reveal the current frame from left to right, one pixel column at a time.
+    Wipe,
+    /// Reveal the current frame one pixel at a time, in a random order.
+    Dissolve,
+    /// Reveal the current frame in a circle growing from the center.
+    Iris,
+}
+
+impl Transition {
+    /// The effect selector in `transition.wgsl`.
+    fn kind(self) -> u32 {
+        match self {
+            Self::Crossfade => 0,
+            Self::Wipe => 1,
+            Self::Dissolve => 2,
+            Self::Iris => 3,
+        }
+    }
+}
+
+/// Draws a snapshot of the pixel buffer over the render target, uncovered by a [`Transition`].
+#[derive(Debug)]
+pub(crate) struct TransitionRenderer {
+    transition: Transition,
+    texture_size: (u32, u32),
+    fit: Fit,
+    locals: TransitionLocals,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    clip_rect: (u32, u32, u32, u32),
+    frame: u32,
+    frames: u32,
+}
+
+/// The uniform buffer layout shared with `transition.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct TransitionLocals {
+    transform: [f32; 16],
+    progress: f32,
+    kind: u32,
+    _padding: [u32; 2],
+}
+
+impl TransitionRenderer {
+    /// Play `transition` over `frames` frames, from a snapshot which is scaled like the pixel
+    /// buffer.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        snapshot: &wgpu::Texture,
+        render_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        fit: Fit,
+        transition: Transition,
+        frames: u32,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/transition.wgsl"));
+        let texture_view = snapshot.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pixels_transition_sampler"),
+            ..Default::default()
+        });
+
+        // Create uniform buffer
+        let texture_size = (snapshot.width(), snapshot.height());
+        let matrix = ScalingMatrix::new(texture_size, (render_size.width, render_size.height), fit);
+        let locals = TransitionLocals {
+            transform: *matrix.transform.as_array(),
+            progress: 0.0,
+            kind: transition.kind(),
+            _padding: [0; 2],
+        };
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_transition_uniform_buffer"),
+            contents: bytemuck::bytes_of(&locals),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_transition_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<TransitionLocals>() as u64,
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_transition_bind_group"),
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_transition_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_transition_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            transition,
+            texture_size,
+            fit,
+            locals,
+            uniform_buffer,
+            bind_group,
+            render_pipeline,
+            clip_rect: matrix.clip_rect(),
+            frame: 0,
+            frames,
+        }
+    }
+
+    pub(crate) fn transition(&self) -> Transition {
+        self.transition
+    }
+
+    /// Draw the next step of the transition. Returns `false` once the transition has finished.
+    pub(crate) fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+    ) -> bool {
+        self.frame += 1;
+        self.locals.progress = self.frame as f32 / (self.frames + 1) as f32;
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&self.locals));
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_transition_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.set_scissor_rect(
+            self.clip_rect.0,
+            self.clip_rect.1,
+            self.clip_rect.2,
+            self.clip_rect.3,
+        );
+        rpass.draw(0..3, 0..1);
+
+        self.frame < self.frames
+    }
+
+    /// Change how the texture fits the surface, from the next resize.
+    pub(crate) fn set_fit(&mut self, fit: Fit) {
+        self.fit = fit;
+    }
+
+    pub(crate) fn resize(&mut self, width: u32, height: u32) {
+        let matrix = ScalingMatrix::new(self.texture_size, (width, height), self.fit);
+        self.locals.transform = *matrix.transform.as_array();
+        self.clip_rect = matrix.clip_rect();
+    }
+}
+
+/// Copies draw targets into rectangles of other textures on the GPU.
+#[derive(Debug)]
+pub(crate) struct TargetBlitter {
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    area_pipeline: wgpu::RenderPipeline,
+    nearest_sampler: wgpu::Sampler,
+    linear_sampler: wgpu::Sampler,
+    texture_format: wgpu::TextureFormat,
+}
+
+/// A blit with its source and destination rectangle bound, waiting for a render pass.
+#[derive(Debug)]
+pub(crate) struct PreparedBlit {
+    bind_group: wgpu::BindGroup,
+    area: bool,
+}
+
+impl TargetBlitter {
+    pub(crate) fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        let module =
+            device.create_shader_module(wgpu::include_wgsl!("../shaders/target_blit.wgsl"));
+
+        // Create texture samplers
+        let create_sampler = |filter| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("pixels_target_blitter_sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: filter,
+                min_filter: filter,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                ..Default::default()
+            })
+        };
+        let nearest_sampler = create_sampler(wgpu::FilterMode::Nearest);
+        let linear_sampler = create_sampler(wgpu::FilterMode::Linear);
+
+        // Create bind group layout
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_target_blitter_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(16),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_target_blitter_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let create_pipeline = |fragment_entry| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("pixels_target_blitter_pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                primitive: wgpu::PrimitiveState {
+                    topology: wgpu::PrimitiveTopology::TriangleStrip,
+                    ..Default::default()
+                },
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: fragment_entry,
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: texture_format,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: Default::default(),
+                }),
+                multiview: None,
+                cache: None,
+            })
+        };
+        let render_pipeline = create_pipeline("fs_main");
+        let area_pipeline = create_pipeline("fs_area");
+
+        Self {
+            bind_group_layout,
+            render_pipeline,
+            area_pipeline,
+            nearest_sampler,
+            linear_sampler,
+            texture_format,
+        }
+    }
+
+    pub(crate) fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture_format
+    }
+
+    /// Bind `src` for drawing into `rect` (x, y, width, height) of a destination with the given
+    /// size in pixels.
+    pub(crate) fn prepare(
+        &self,
+        device: &wgpu::Device,
+        src: &wgpu::Texture,
+        dst_size: (u32, u32),
+        rect: (i32, i32, u32, u32),
+        filter: FilterMode,
+    ) -> PreparedBlit {
+        let (x, y, width, height) = rect;
+        let (dst_width, dst_height) = (dst_size.0 as f32, dst_size.1 as f32);
+        let left = x as f32 / dst_width * 2.0 - 1.0;
+        let top = 1.0 - y as f32 / dst_height * 2.0;
+        let right = (x as f32 + width as f32) / dst_width * 2.0 - 1.0;
+        let bottom = 1.0 - (y as f32 + height as f32) / dst_height * 2.0;
+
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_target_blitter_uniform_buffer"),
+            contents: bytemuck::bytes_of(&[left, top, right, bottom]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let texture_view = src.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = match filter {
+            FilterMode::Linear | FilterMode::SharpBilinear => &self.linear_sampler,
+            FilterMode::Nearest | FilterMode::Area => &self.nearest_sampler,
+        };
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_target_blitter_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        PreparedBlit {
+            bind_group,
+            area: filter == FilterMode::Area,
+        }
+    }
+
+    /// Draw prepared blits over the existing contents of `dst`.
+    pub(crate) fn render<'a>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        dst: &wgpu::Texture,
+        blits: impl IntoIterator<Item = &'a PreparedBlit>,
+    ) {
+        let view = dst.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_target_blitter_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        for blit in blits {
+            rpass.set_pipeline(if blit.area {
+                &self.area_pipeline
+            } else {
+                &self.render_pipeline
+            });
+            rpass.set_bind_group(0, &blit.bind_group, &[]);
+            rpass.draw(0..4, 0..1);
+        }
+    }
+}
+
+/// Draws text grids into the pixel buffer texture on the GPU.
+#[derive(Debug)]
+pub(crate) struct TextGridRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    texture_format: wgpu::TextureFormat,
+}
+
+/// A text grid with its cells and palette uploaded, waiting for a render pass.
+#[derive(Debug)]
+pub(crate) struct PreparedTextGrid {
+    bind_group: wgpu::BindGroup,
+    // The (x, y, width, height) of the destination covered by the grid
+    scissor_rect: (u32, u32, u32, u32),
+}
+
+impl PreparedTextGrid {
+    /// The `(x, y, width, height)` of the destination covered by the grid.
+    pub(crate) fn scissor_rect(&self) -> (u32, u32, u32, u32) {
+        self.scissor_rect
+    }
+}
+
+impl TextGridRenderer {
+    pub(crate) fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/text_grid.wgsl"));
+
+        // Create bind group layout
+        let texture_entry = |binding, sample_type| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type,
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            count: None,
+        };
+        let unfiltered = wgpu::TextureSampleType::Float { filterable: false };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_text_grid_renderer_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(32),
+                    },
+                    count: None,
+                },
+                texture_entry(1, wgpu::TextureSampleType::Uint),
+                texture_entry(2, unfiltered),
+                texture_entry(3, unfiltered),
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_text_grid_renderer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_text_grid_renderer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            bind_group_layout,
+            render_pipeline,
+            texture_format,
+        }
+    }
+
+    pub(crate) fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture_format
+    }
+
+    /// Upload the cells of `grid` and `palette` for drawing with `atlas` at `position` of a
+    /// destination with the given size in pixels. Returns `None` when the grid is not visible.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn prepare(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        grid: &TextGrid,
+        atlas: &FontAtlas,
+        palette: &[[u8; 4]],
+        position: (i32, i32),
+        dst_size: (u32, u32),
+    ) -> Option<PreparedTextGrid> {
+        let (glyph_width, glyph_height) = (atlas.glyph_width(), atlas.glyph_height());
+        let (x, y) = (i64::from(position.0), i64::from(position.1));
+        let width = i64::from(grid.columns()) * i64::from(glyph_width);
+        let height = i64::from(grid.rows()) * i64::from(glyph_height);
+
+        // Clip the grid to the destination
+        let x0 = x.max(0);
+        let y0 = y.max(0);
+        let x1 = (x + width).min(i64::from(dst_size.0));
+        let y1 = (y + height).min(i64::from(dst_size.1));
+        if x0 >= x1 || y0 >= y1 {
+            return None;
+        }
+        let scissor_rect = (x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32);
+
+        let locals: [u32; 8] = [
+            position.0 as u32,
+            position.1 as u32,
+            glyph_width,
+            glyph_height,
+            atlas.glyph_count(),
+            FontAtlas::COLUMNS,
+            0,
+            0,
+        ];
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_text_grid_renderer_uniform_buffer"),
+            contents: bytemuck::cast_slice(&locals),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let cells: Vec<[u8; 4]> = grid
+            .cells()
+            .iter()
+            .map(|cell| {
+                let [lo, hi] = cell.glyph.to_le_bytes();
+                [lo, hi, cell.fg, cell.bg]
+            })
+            .collect();
+        let cells_texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("pixels_text_grid_cells_texture"),
+                size: wgpu::Extent3d {
+                    width: grid.columns(),
+                    height: grid.rows(),
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Uint,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            bytemuck::cast_slice(&cells),
+        );
+
+        // The palette is stored in the color space of the destination, so 8-bit colors are
+        // written exactly as they are given, like the CPU path
+        let mut colors = [[0; 4]; 256];
+        for (color, entry) in colors.iter_mut().zip(palette) {
+            *color = *entry;
+        }
+        let palette_format = if crate::color::is_linear_target(self.texture_format) {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        };
+        let palette_texture = device.create_texture_with_data(
+            queue,
+            &wgpu::TextureDescriptor {
+                label: Some("pixels_text_grid_palette_texture"),
+                size: wgpu::Extent3d {
+                    width: 256,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: palette_format,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            },
+            wgpu::util::TextureDataOrder::LayerMajor,
+            bytemuck::cast_slice(&colors),
+        );
+
+        let cells_view = cells_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let atlas_view = atlas
+            .texture()
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        let palette_view = palette_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_text_grid_renderer_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&cells_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&atlas_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(&palette_view),
+                },
+            ],
+        });
+
+        Some(PreparedTextGrid {
+            bind_group,
+            scissor_rect,
+        })
+    }
+
+    /// Draw prepared text grids over the existing contents of `dst`.
+    pub(crate) fn render<'a>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        dst: &wgpu::Texture,
+        grids: impl IntoIterator<Item = &'a PreparedTextGrid>,
+    ) {
+        let view = dst.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_text_grid_renderer_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        for grid in grids {
+            let (x, y, width, height) = grid.scissor_rect;
+            rpass.set_scissor_rect(x, y, width, height);
+            rpass.set_bind_group(0, &grid.bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Draws batches of sprites from draw target atlases into the pixel buffer texture on the GPU.
+#[derive(Debug)]
+pub(crate) struct SpriteRenderer {
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    texture_format: wgpu::TextureFormat,
+}
+
+/// The per-instance vertex layout shared with `sprite.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct SpriteInstance {
+    dst: [f32; 4],
+    src: [f32; 4],
+}
+
+/// A batch of sprites with its atlas and instances bound, waiting for a render pass.
+#[derive(Debug)]
+pub(crate) struct PreparedSprites {
+    bind_group: wgpu::BindGroup,
+    instance_buffer: wgpu::Buffer,
+    instances: u32,
+}
+
+impl SpriteRenderer {
+    pub(crate) fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/sprite.wgsl"));
+
+        // Create texture sampler
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pixels_sprite_renderer_sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Nearest,
+            min_filter: wgpu::FilterMode::Nearest,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        // Create bind group layout
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_sprite_renderer_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(16),
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_sprite_renderer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let instance_buffer_layout = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<SpriteInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x4],
+        };
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_sprite_renderer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[instance_buffer_layout],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            bind_group_layout,
+            render_pipeline,
+            sampler,
+            texture_format,
+        }
+    }
+
+    pub(crate) fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture_format
+    }
+
+    /// Bind `atlas` and upload the instances of `sprites` for drawing into a destination with
+    /// the given size in pixels.
+    pub(crate) fn prepare(
+        &self,
+        device: &wgpu::Device,
+        atlas: &wgpu::Texture,
+        sprites: &[Sprite],
+        dst_size: (u32, u32),
+    ) -> PreparedSprites {
+        let instances: Vec<SpriteInstance> = sprites
+            .iter()
+            .map(|sprite| {
+                let (x, y) = (sprite.position.0 as f32, sprite.position.1 as f32);
+                let (src_x, src_y, width, height) = sprite.source;
+                let (width, height) = (width as f32, height as f32);
+                let (mut left, mut right) = (src_x as f32, src_x as f32 + width);
+                let (mut top, mut bottom) = (src_y as f32, src_y as f32 + height);
+                if sprite.flip_x {
+                    std::mem::swap(&mut left, &mut right);
+                }
+                if sprite.flip_y {
+                    std::mem::swap(&mut top, &mut bottom);
+                }
+
+                SpriteInstance {
+                    dst: [x, y, width, height],
+                    src: [left, top, right, bottom],
+                }
+            })
+            .collect();
+        let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_sprite_renderer_instance_buffer"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let locals = [
+            dst_size.0 as f32,
+            dst_size.1 as f32,
+            atlas.width() as f32,
+            atlas.height() as f32,
+        ];
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_sprite_renderer_uniform_buffer"),
+            contents: bytemuck::bytes_of(&locals),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let texture_view = atlas.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_sprite_renderer_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        PreparedSprites {
+            bind_group,
+            instance_buffer,
+            instances: instances.len() as u32,
+        }
+    }
+
+    /// Draw prepared batches of sprites over the existing contents of `dst`, in order.
+    pub(crate) fn render<'a>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        dst: &wgpu::Texture,
+        batches: impl IntoIterator<Item = &'a PreparedSprites>,
+    ) {
+        let view = dst.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_sprite_renderer_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        for batch in batches {
+            rpass.set_bind_group(0, &batch.bind_group, &[]);
+            rpass.set_vertex_buffer(0, batch.instance_buffer.slice(..));
+            rpass.draw(0..4, 0..batch.instances);
+        }
+    }
+}
+
+/// Draws [layers](crate::Layer) over the pixel buffer texture on the GPU.
+#[derive(Debug)]
+pub(crate) struct LayerCompositor {
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    texture_format: wgpu::TextureFormat,
+}
+
+impl LayerCompositor {
+    pub(crate) fn new(device: &wgpu::Device, texture_format: wgpu::TextureFormat) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/layers.wgsl"));
+
+        // Create bind group layout
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_layer_compositor_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(16),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_layer_compositor_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_layer_compositor_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: texture_format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            bind_group_layout,
+            render_pipeline,
+            texture_format,
+        }
+    }
+
+    pub(crate) fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture_format
+    }
+
+    /// Bind the texture and uniform buffer of a layer.
+    pub(crate) fn create_bind_group(
+        &self,
+        device: &wgpu::Device,
+        texture: &wgpu::Texture,
+        uniform_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let texture_view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("pixels_layer_compositor_bind_group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&texture_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Blend the layers with these bind groups over `dst`, in order.
+    pub(crate) fn render<'a>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        dst: &wgpu::Texture,
+        layers: impl IntoIterator<Item = &'a wgpu::BindGroup>,
+    ) {
+        let view = dst.create_view(&wgpu::TextureViewDescriptor::default());
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_layer_compositor_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        for bind_group in layers {
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+    }
+}
+/// Renders into an intermediate texture larger than the surface, then downsamples it.
+#[derive(Debug)]
+pub(crate) struct Supersampler {
+    factor: u32,
+    texture_view: wgpu::TextureView,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+    render_texture_format: wgpu::TextureFormat,
+    size: (u32, u32),
+}
+
+impl Supersampler {
+    /// Create a supersampler for a surface of the given size.
+    ///
+    /// The intermediate texture is `factor` times the size of the surface in each dimension.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        surface_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        factor: u32,
+    ) -> Self {
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/downsample.wgsl"));
+
+        // Create uniform buffer
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("pixels_supersampler_uniform_buffer"),
+            contents: bytemuck::bytes_of(&[factor, 0, 0, 0]),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_supersampler_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(16),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let size = (surface_size.width, surface_size.height);
+        let texture_view = create_supersampled_view(device, size, factor, render_texture_format);
+        let bind_group = create_intermediate_bind_group(
+            device,
+            "pixels_supersampler_bind_group",
+            &bind_group_layout,
+            &texture_view,
+            &uniform_buffer,
+        );
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_supersampler_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_supersampler_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_texture_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            factor,
+            texture_view,
+            bind_group_layout,
+            bind_group,
+            uniform_buffer,
+            render_pipeline,
+            render_texture_format,
+            size,
+        }
+    }
+
+    /// The supersampling factor.
+    pub(crate) fn factor(&self) -> u32 {
+        self.factor
+    }
+
+    /// The intermediate render target.
+    pub(crate) fn texture_view(&self) -> &wgpu::TextureView {
+        &self.texture_view
+    }
+
+    /// Downsample the intermediate texture to the render target.
+    pub(crate) fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_supersampler_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+
+    /// Match the intermediate texture to a new surface size.
+    ///
+    /// The texture is only reallocated when the size actually changes; the previous texture is
+    /// released once the GPU has finished with it.
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if self.size == (width, height) {
+            return;
+        }
+
+        self.size = (width, height);
+        self.texture_view =
+            create_supersampled_view(device, self.size, self.factor, self.render_texture_format);
+        self.bind_group = create_intermediate_bind_group(
+            device,
+            "pixels_supersampler_bind_group",
+            &self.bind_group_layout,
+            &self.texture_view,
+            &self.uniform_buffer,
+        );
+    }
+}
+
+fn create_supersampled_view(
+    device: &wgpu::Device,
+    size: (u32, u32),
+    factor: u32,
+    format: wgpu::TextureFormat,
+) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_supersampler_texture"),
+        size: wgpu::Extent3d {
+            width: size.0 * factor,
+            height: size.1 * factor,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// Bind the intermediate texture and uniforms of the [`Supersampler`], the [`FrameAverager`], or
+/// the [`ProvenanceRenderer`].
+fn create_intermediate_bind_group(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    texture_view: &wgpu::TextureView,
+    uniform_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(label),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
+
+/// Renders into an intermediate texture the size of the surface, then copies it to the surface
+/// with a provenance mark.
+#[derive(Debug)]
+pub(crate) struct ProvenanceRenderer {
+    mark: ProvenanceMark,
+    texture_view: wgpu::TextureView,
+    bind_group_layout: wgpu::BindGroupLayout,
     bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
-    pub(crate) clear_color: wgpu::Color,
-    width: f32,
-    height: f32,
-    clip_rect: (u32, u32, u32, u32),
+    render_texture_format: wgpu::TextureFormat,
+    srgb: bool,
+    size: (u32, u32),
 }
 
-impl ScalingRenderer {
+impl ProvenanceRenderer {
+    /// Create a renderer for a surface of the given size.
+    ///
+    /// The intermediate texture has the `render_texture_format`, and the mark is drawn to a
+    /// surface view with the `surface_texture_format`.
     pub(crate) fn new(
         device: &wgpu::Device,
-        texture_view: &wgpu::TextureView,
-        texture_size: &wgpu::Extent3d,
         surface_size: &SurfaceSize,
         render_texture_format: wgpu::TextureFormat,
-        clear_color: wgpu::Color,
-        blend_state: wgpu::BlendState,
+        surface_texture_format: wgpu::TextureFormat,
+        mark: ProvenanceMark,
     ) -> Self {
-        let shader = wgpu::include_wgsl!("../shaders/scale.wgsl");
-        let module = device.create_shader_module(shader);
-
-        // Create a texture sampler with nearest neighbor
-        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("pixels_scaling_renderer_sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: 1.0,
-            compare: None,
-            anisotropy_clamp: 1,
-            border_color: None,
-        });
-
-        // Create vertex buffer; array-of-array of position and texture coordinates
-        let vertex_data: [[f32; 2]; 3] = [
-            // One full-screen triangle
-            // See: https://github.com/parasyte/pixels/issues/180
-            [-1.0, -1.0],
-            [3.0, -1.0],
-            [-1.0, 3.0],
-        ];
-        let vertex_data_slice = bytemuck::cast_slice(&vertex_data);
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("pixels_scaling_renderer_vertex_buffer"),
-            contents: vertex_data_slice,
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-        let vertex_buffer_layout = wgpu::VertexBufferLayout {
-            array_stride: (vertex_data_slice.len() / vertex_data.len()) as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                format: wgpu::VertexFormat::Float32x2,
-                offset: 0,
-                shader_location: 0,
-            }],
-        };
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/provenance.wgsl"));
 
         // Create uniform buffer
-        let matrix = ScalingMatrix::new(
-            (texture_size.width as f32, texture_size.height as f32),
-            (surface_size.width as f32, surface_size.height as f32),
-        );
-        let transform_bytes = matrix.as_bytes();
-        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("pixels_scaling_renderer_matrix_uniform_buffer"),
-            contents: transform_bytes,
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_provenance_uniform_buffer"),
+            size: 32,
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
 
         // Create bind group
         let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            label: Some("pixels_scaling_renderer_bind_group_layout"),
+            label: Some("pixels_provenance_bind_group_layout"),
             entries: &[
                 wgpu::BindGroupLayoutEntry {
                     binding: 0,
                     visibility: wgpu::ShaderStages::FRAGMENT,
                     ty: wgpu::BindingType::Texture {
-                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
                         multisampled: false,
                         view_dimension: wgpu::TextureViewDimension::D2,
                     },
@@ -97,54 +4124,256 @@ impl ScalingRenderer {
                 wgpu::BindGroupLayoutEntry {
                     binding: 1,
                     visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::VERTEX,
                     ty: wgpu::BindingType::Buffer {
                         ty: wgpu::BufferBindingType::Uniform,
                         has_dynamic_offset: false,
-                        min_binding_size: wgpu::BufferSize::new(transform_bytes.len() as u64),
+                        min_binding_size: wgpu::BufferSize::new(32),
                     },
                     count: None,
                 },
             ],
         });
-        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("pixels_scaling_renderer_bind_group"),
-            layout: &bind_group_layout,
+        let size = (surface_size.width, surface_size.height);
+        let texture_view = create_provenance_view(device, size, render_texture_format);
+        let bind_group = create_intermediate_bind_group(
+            device,
+            "pixels_provenance_bind_group",
+            &bind_group_layout,
+            &texture_view,
+            &uniform_buffer,
+        );
+
+        // Create pipeline
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_provenance_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_provenance_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: surface_texture_format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            mark,
+            texture_view,
+            bind_group_layout,
+            bind_group,
+            uniform_buffer,
+            render_pipeline,
+            render_texture_format,
+            srgb: surface_texture_format.is_srgb(),
+            size,
+        }
+    }
+
+    /// The mark drawn on every frame.
+    pub(crate) fn mark(&self) -> ProvenanceMark {
+        self.mark
+    }
+
+    /// The intermediate render target.
+    pub(crate) fn texture_view(&self) -> &wgpu::TextureView {
+        &self.texture_view
+    }
+
+    /// Copy the intermediate texture to the render target, marked with `frame_index`.
+    pub(crate) fn render(
+        &self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        frame_index: u64,
+    ) {
+        let words = provenance::message(self.mark, frame_index);
+        let locals = [
+            words[0],
+            words[1],
+            words[2],
+            words[3],
+            u32::from(self.srgb),
+            0,
+            0,
+            0,
+        ];
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_provenance_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+        rpass.set_bind_group(0, &self.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+
+    /// Match the intermediate texture to a new surface size.
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if self.size == (width, height) {
+            return;
+        }
+
+        self.size = (width, height);
+        self.texture_view = create_provenance_view(device, self.size, self.render_texture_format);
+        self.bind_group = create_intermediate_bind_group(
+            device,
+            "pixels_provenance_bind_group",
+            &self.bind_group_layout,
+            &self.texture_view,
+            &self.uniform_buffer,
+        );
+    }
+}
+
+fn create_provenance_view(
+    device: &wgpu::Device,
+    size: (u32, u32),
+    format: wgpu::TextureFormat,
+) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_provenance_texture"),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// The largest number of frames averaged by the [`FrameAverager`].
+pub(crate) const MAX_AVERAGED_FRAMES: u32 = 16;
+
+/// Renders each frame into a layer of a texture array the size of the surface, then draws the
+/// average of the last frames to the render target.
+#[derive(Debug)]
+pub(crate) struct FrameAverager {
+    frames: u32,
+    // One view per layer, to render into, and a view of the whole array, to average
+    layer_views: Vec<wgpu::TextureView>,
+    history_view: wgpu::TextureView,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+    render_texture_format: wgpu::TextureFormat,
+    size: (u32, u32),
+    // The layer the next frame is rendered into, and the number of layers holding a frame
+    next: usize,
+    count: u32,
+    // The number of frames to render before every layer holds the same content
+    settling: u32,
+}
+
+impl FrameAverager {
+    /// Create a renderer averaging `frames` frames, clamped to `2..=MAX_AVERAGED_FRAMES`, for a
+    /// surface of the given size.
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        surface_size: &SurfaceSize,
+        render_texture_format: wgpu::TextureFormat,
+        frames: u32,
+    ) -> Self {
+        let frames = frames.clamp(2, MAX_AVERAGED_FRAMES);
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/averaging.wgsl"));
+
+        // Create uniform buffer
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("pixels_averaging_uniform_buffer"),
+            size: 16,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create bind group
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_averaging_bind_group_layout"),
             entries: &[
-                wgpu::BindGroupEntry {
+                wgpu::BindGroupLayoutEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(texture_view),
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                    },
+                    count: None,
                 },
-                wgpu::BindGroupEntry {
+                wgpu::BindGroupLayoutEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&sampler),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: uniform_buffer.as_entire_binding(),
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(16),
+                    },
+                    count: None,
                 },
             ],
         });
+        let size = (surface_size.width, surface_size.height);
+        let (layer_views, history_view) =
+            create_history_views(device, size, frames, render_texture_format);
+        let bind_group = create_intermediate_bind_group(
+            device,
+            "pixels_averaging_bind_group",
+            &bind_group_layout,
+            &history_view,
+            &uniform_buffer,
+        );
 
         // Create pipeline
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("pixels_scaling_renderer_pipeline_layout"),
+            label: Some("pixels_averaging_pipeline_layout"),
             bind_group_layouts: &[&bind_group_layout],
             push_constant_ranges: &[],
         });
         let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("pixels_scaling_renderer_pipeline"),
+            label: Some("pixels_averaging_pipeline"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &module,
                 entry_point: "vs_main",
-                buffers: &[vertex_buffer_layout],
-                compilation_options: Default::default()
+                buffers: &[],
+                compilation_options: Default::default(),
             },
             primitive: wgpu::PrimitiveState::default(),
             depth_stencil: None,
@@ -154,39 +4383,73 @@ impl ScalingRenderer {
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
                     format: render_texture_format,
-                    blend: Some(blend_state),
+                    blend: None,
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
-                compilation_options: Default::default()
+                compilation_options: Default::default(),
             }),
             multiview: None,
-            cache: None
+            cache: None,
         });
 
-        // Create clipping rectangle
-        let clip_rect = matrix.clip_rect();
-
         Self {
-            vertex_buffer,
-            uniform_buffer,
+            frames,
+            layer_views,
+            history_view,
+            bind_group_layout,
             bind_group,
+            uniform_buffer,
             render_pipeline,
-            clear_color,
-            width: texture_size.width as f32,
-            height: texture_size.height as f32,
-            clip_rect,
+            render_texture_format,
+            size,
+            next: 0,
+            count: 0,
+            settling: 0,
         }
     }
 
-    /// Draw the pixel buffer to the render target.
-    pub fn render(&self, encoder: &mut wgpu::CommandEncoder, render_target: &wgpu::TextureView) {
+    /// The number of frames averaged.
+    pub(crate) fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// The intermediate render target of the frame being rendered.
+    pub(crate) fn texture_view(&self) -> &wgpu::TextureView {
+        &self.layer_views[self.next]
+    }
+
+    /// Whether the average still changes on the next frames, without changes to their content.
+    pub(crate) fn is_settling(&self) -> bool {
+        self.settling > 0
+    }
+
+    /// Draw the average of the frames so far to the render target, and move on to the next layer.
+    ///
+    /// `changed` tells whether the content of the frame changed since the previous one, which
+    /// needs more frames before the average settles.
+    pub(crate) fn render(
+        &mut self,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        changed: bool,
+    ) {
+        self.count = (self.count + 1).min(self.frames);
+        self.settling = if changed {
+            self.frames - 1
+        } else {
+            self.settling.saturating_sub(1)
+        };
+        let locals = [self.count, 0, 0, 0];
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+
         let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("pixels_scaling_renderer_render_pass"),
+            label: Some("pixels_averaging_render_pass"),
             color_attachments: &[Some(wgpu::RenderPassColorAttachment {
                 view: render_target,
                 resolve_target: None,
                 ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(self.clear_color),
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
                     store: wgpu::StoreOp::Store,
                 },
             })],
@@ -196,85 +4459,273 @@ impl ScalingRenderer {
         });
         rpass.set_pipeline(&self.render_pipeline);
         rpass.set_bind_group(0, &self.bind_group, &[]);
-        rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-        rpass.set_scissor_rect(
-            self.clip_rect.0,
-            self.clip_rect.1,
-            self.clip_rect.2,
-            self.clip_rect.3,
-        );
         rpass.draw(0..3, 0..1);
+        drop(rpass);
+
+        self.next = (self.next + 1) % self.layer_views.len();
     }
 
-    /// Get the clipping rectangle for the scaling renderer.
-    ///
-    /// This rectangle defines the inner bounds of the surface texture, without the border.
-    pub fn clip_rect(&self) -> (u32, u32, u32, u32) {
-        self.clip_rect
+    /// Match the texture array to a new surface size, which starts the average over.
+    pub(crate) fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if self.size == (width, height) {
+            return;
+        }
+
+        self.size = (width, height);
+        (self.layer_views, self.history_view) =
+            create_history_views(device, self.size, self.frames, self.render_texture_format);
+        self.bind_group = create_intermediate_bind_group(
+            device,
+            "pixels_averaging_bind_group",
+            &self.bind_group_layout,
+            &self.history_view,
+            &self.uniform_buffer,
+        );
+        self.next = 0;
+        self.count = 0;
     }
+}
 
-    pub(crate) fn resize(&mut self, queue: &wgpu::Queue, width: u32, height: u32) {
-        let matrix = ScalingMatrix::new((self.width, self.height), (width as f32, height as f32));
-        let transform_bytes = matrix.as_bytes();
-        queue.write_buffer(&self.uniform_buffer, 0, transform_bytes);
+/// Create a texture array with a layer for each of `frames`, and return a view of each layer and
+/// of the `frames` layers together.
+fn create_history_views(
+    device: &wgpu::Device,
+    size: (u32, u32),
+    frames: u32,
+    format: wgpu::TextureFormat,
+) -> (Vec<wgpu::TextureView>, wgpu::TextureView) {
+    // The GL backend takes textures with a multiple of 6 layers for cube maps
+    let layers = if frames % 6 == 0 { frames + 1 } else { frames };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("pixels_averaging_texture"),
+        size: wgpu::Extent3d {
+            width: size.0,
+            height: size.1,
+            depth_or_array_layers: layers,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
 
-        self.clip_rect = matrix.clip_rect();
+    let layer_views = (0..frames)
+        .map(|layer| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("pixels_averaging_layer_view"),
+                dimension: Some(wgpu::TextureViewDimension::D2),
+                base_array_layer: layer,
+                array_layer_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
+    let history_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("pixels_averaging_history_view"),
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        array_layer_count: Some(frames),
+        ..Default::default()
+    });
+
+    (layer_views, history_view)
+}
+
+impl ScalingPipeline {
+    /// Create the pipeline for the full-screen triangle.
+    fn create_triangle(&self, device: &wgpu::Device) -> wgpu::RenderPipeline {
+        // The vertex buffer holds one position per vertex; the minimal pipeline has no buffer
+        let buffers = [wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: 0,
+                shader_location: 0,
+            }],
+        }];
+        let buffers: &[_] = if self.minimal { &[] } else { &buffers };
+
+        self.create(
+            device,
+            "pixels_scaling_renderer_pipeline",
+            "vs_main",
+            buffers,
+        )
+    }
+
+    /// Create the pipeline for a [`DistortionMesh`].
+    fn create_mesh(&self, device: &wgpu::Device) -> wgpu::RenderPipeline {
+        let vertex_buffer_layout = wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<MeshVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+        };
+
+        self.create(
+            device,
+            "pixels_scaling_renderer_mesh_pipeline",
+            "vs_mesh",
+            &[vertex_buffer_layout],
+        )
+    }
+
+    fn create(
+        &self,
+        device: &wgpu::Device,
+        label: &str,
+        vertex_entry: &str,
+        buffers: &[wgpu::VertexBufferLayout<'_>],
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(label),
+            layout: Some(&self.layout),
+            vertex: wgpu::VertexState {
+                module: &self.module,
+                entry_point: vertex_entry,
+                buffers,
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &self.module,
+                entry_point: self.fragment_entry,
+                targets: &[Some(self.color_target.clone())],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        })
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct ScalingMatrix {
     pub(crate) transform: Mat4,
+    /// The horizontal and vertical scale factors on the surface; integers in
+    /// [`ScalingMode::IntegerOnly`] unless the texture is downscaled.
+    pub(crate) scale: (f32, f32),
+    /// The top-left corner of the scaled texture on the surface in physical pixels.
+    pub(crate) origin: (i64, i64),
+    texture_to_window: Mat4,
     clip_rect: (u32, u32, u32, u32),
 }
 
 impl ScalingMatrix {
     // texture_size is the dimensions of the drawing texture
     // screen_size is the dimensions of the surface being drawn to
-    pub(crate) fn new(texture_size: (f32, f32), screen_size: (f32, f32)) -> Self {
-        let (texture_width, texture_height) = texture_size;
+    // fit selects the scaling mode, whether integer scaling shrinks textures larger than the
+    // surface to fit instead of cropping them, and the orientation of the texture
+    //
+    // All sizes and offsets are computed with integers so that tiny textures scaled to huge
+    // surfaces (e.g. 1x1 to 8K) place every edge exactly on a physical pixel. The scaled image is
+    // snapped to whole pixels even when centering it leaves an odd number of border pixels.
+    pub(crate) fn new(texture_size: (u32, u32), screen_size: (u32, u32), fit: Fit) -> Self {
+        // Sideways textures are fit with their width and height swapped
+        let (texture_width, texture_height) = if fit.rotation.is_sideways() {
+            (texture_size.1, texture_size.0)
+        } else {
+            texture_size
+        };
         let (screen_width, screen_height) = screen_size;
+        let larger = texture_width > screen_width || texture_height > screen_height;
+
+        let (scaled_width, scaled_height) = match fit.mode {
+            ScalingMode::Stretch => (screen_width.max(1) as u64, screen_height.max(1) as u64),
+            ScalingMode::IntegerOnly if !(fit.downscale && larger) => {
+                // Get smallest integer scale size
+                let scale = (screen_width / texture_width)
+                    .min(screen_height / texture_height)
+                    .max(1);
 
-        let width_ratio = (screen_width / texture_width).max(1.0);
-        let height_ratio = (screen_height / texture_height).max(1.0);
+                (
+                    texture_width as u64 * scale as u64,
+                    texture_height as u64 * scale as u64,
+                )
+            }
+            _ => {
+                // Get the largest fractional scale that fits, rounding the size down to whole
+                // pixels
+                let scale = (screen_width as f64 / texture_width as f64)
+                    .min(screen_height as f64 / texture_height as f64);
+                let scaled_width = ((texture_width as f64 * scale) as u64).max(1);
+                let scaled_height = ((texture_height as f64 * scale) as u64).max(1);
 
-        // Get smallest scale size
-        let scale = width_ratio.clamp(1.0, height_ratio).floor();
+                (scaled_width, scaled_height)
+            }
+        };
 
-        let scaled_width = texture_width * scale;
-        let scaled_height = texture_height * scale;
+        // Top-left corner of the scaled image in physical pixels; negative when it overflows
+        let x = (screen_width as i64 - scaled_width as i64).div_euclid(2);
+        let y = (screen_height as i64 - scaled_height as i64).div_euclid(2);
 
-        // Create a transformation matrix
-        let sw = scaled_width / screen_width;
-        let sh = scaled_height / screen_height;
-        let tx = (screen_width / 2.0).fract() / screen_width;
-        let ty = (screen_height / 2.0).fract() / screen_height;
+        // Create a transformation matrix from the full-screen triangle to the scaled image. The
+        // triangle is centered, so it is rotated and flipped in place; clip space has `y` up.
+        let [[o00, o01], [o10, o11]] = fit.orientation();
+        let (sw_px, sh_px) = (screen_width as f64, screen_height as f64);
+        let sw = scaled_width as f64 / sw_px;
+        let sh = scaled_height as f64 / sh_px;
+        let tx = (2 * x + scaled_width as i64) as f64 / sw_px - 1.0;
+        let ty = 1.0 - (2 * y + scaled_height as i64) as f64 / sh_px;
+        let (a, b) = ((sw * o00 as f64) as f32, (-sw * o01 as f64) as f32);
+        let (c, d) = ((-sh * o10 as f64) as f32, (sh * o11 as f64) as f32);
         #[rustfmt::skip]
         let transform: [f32; 16] = [
-            sw,  0.0, 0.0, 0.0,
-            0.0, sh,  0.0, 0.0,
-            0.0, 0.0, 1.0, 0.0,
-            tx,  ty,  0.0, 1.0,
+            a,         c,         0.0, 0.0,
+            b,         d,         0.0, 0.0,
+            0.0,       0.0,       1.0, 0.0,
+            tx as f32, ty as f32, 0.0, 1.0,
+        ];
+
+        // Maps texture pixel coordinates to physical window coordinates. Each texture axis maps to
+        // one surface axis, which starts at the far edge of the scaled image when it is reversed.
+        let scale_x = (scaled_width as f64 / texture_width as f64) as f32;
+        let scale_y = (scaled_height as f64 / texture_height as f64) as f32;
+        let (a, b) = (scale_x * o00 as f32, scale_x * o01 as f32);
+        let (c, d) = (scale_y * o10 as f32, scale_y * o11 as f32);
+        let x0 = x + if o00 + o01 < 0 {
+            scaled_width as i64
+        } else {
+            0
+        };
+        let y0 = y + if o10 + o11 < 0 {
+            scaled_height as i64
+        } else {
+            0
+        };
+        #[rustfmt::skip]
+        let texture_to_window: [f32; 16] = [
+            a,         c,         0.0, 0.0,
+            b,         d,         0.0, 0.0,
+            0.0,       0.0,       1.0, 0.0,
+            x0 as f32, y0 as f32, 0.0, 1.0,
         ];
 
         // Create a clipping rectangle
         let clip_rect = {
-            let scaled_width = scaled_width.min(screen_width);
-            let scaled_height = scaled_height.min(screen_height);
-            let x = ((screen_width - scaled_width) / 2.0) as u32;
-            let y = ((screen_height - scaled_height) / 2.0) as u32;
+            let x0 = x.max(0);
+            let y0 = y.max(0);
+            let x1 = (x + scaled_width as i64).min(screen_width as i64);
+            let y1 = (y + scaled_height as i64).min(screen_height as i64);
 
-            (x, y, scaled_width as u32, scaled_height as u32)
+            (x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32)
         };
 
         Self {
             transform: Mat4::from(transform),
+            scale: (scale_x, scale_y),
+            origin: (x, y),
+            texture_to_window: Mat4::from(texture_to_window),
             clip_rect,
         }
     }
 
-    fn as_bytes(&self) -> &[u8] {
-        self.transform.as_byte_slice()
+    /// The transformation from physical window coordinates to texture pixel coordinates.
+    pub(crate) fn window_to_texture(&self) -> Mat4 {
+        self.texture_to_window.inversed()
     }
 
     pub(crate) fn clip_rect(&self) -> (u32, u32, u32, u32) {
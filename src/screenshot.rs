@@ -0,0 +1,193 @@
+//! A screenshot hotkey which saves the next frame as a timestamped PNG file.
+//!
+//! [`ScreenshotHotkey`] watches the window events for its key, starts a
+//! [`Pixels::capture_frame`] when it is pressed, and writes the captured frame once the render
+//! which read it back has finished. By default, F12 captures the frame as presented, at the size
+//! of the window; [`CaptureSource::Frame`] captures the pixel buffer at 1x instead:
+//!
+//! ```no_run
+//! # use pixels::{Pixels, SurfaceTexture};
+//! # use winit::event::WindowEvent;
+//! use pixels::screenshot::ScreenshotHotkey;
+//!
+//! # let window = pixels_mocks::Window;
+//! # let surface_texture = SurfaceTexture::new(320, 240, &window);
+//! # let mut pixels = Pixels::new(320, 240, surface_texture)?;
+//! # let event = WindowEvent::RedrawRequested;
+//! let mut screenshots = ScreenshotHotkey::new("screenshots");
+//!
+//! // In `ApplicationHandler::window_event`
+//! screenshots.handle_event(&mut pixels, &event)?;
+//!
+//! // After rendering
+//! pixels.render()?;
+//! if let Some(path) = screenshots.save(&mut pixels)? {
+//!     println!("Saved {}", path.display());
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Files are named after the time of the key press in UTC, like
+//! `screenshot_2024-05-17_14-03-21.042.png`, with a numbered suffix in the rare case that the name
+//! is already taken.
+
+use crate::{CaptureSource, Pixels, ReadbackError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use winit::event::{ElementState, WindowEvent};
+use winit::keyboard::{KeyCode, PhysicalKey};
+
+/// Saves a screenshot to a directory whenever a key is pressed.
+#[derive(Clone, Debug)]
+pub struct ScreenshotHotkey {
+    key: KeyCode,
+    source: CaptureSource,
+    directory: PathBuf,
+    pending: Option<SystemTime>,
+}
+
+impl ScreenshotHotkey {
+    /// Create a hotkey which saves screenshots to `directory` when F12 is pressed.
+    ///
+    /// The directory is created with the first screenshot.
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            key: KeyCode::F12,
+            source: CaptureSource::Presented,
+            directory: directory.into(),
+            pending: None,
+        }
+    }
+
+    /// Take screenshots with `key` instead of F12.
+    ///
+    /// The key is matched by its physical location, so it does not depend on the keyboard layout.
+    pub fn with_key(mut self, key: KeyCode) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// Choose what is captured.
+    ///
+    /// [`CaptureSource::Presented`], the default, saves the window contents at the size of the
+    /// window, with scaling and effects applied. [`CaptureSource::Frame`] saves the pixel buffer at
+    /// its own size.
+    pub fn with_source(mut self, source: CaptureSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// The directory where screenshots are saved.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Whether a screenshot was taken which has not been saved yet.
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Take a screenshot if `event` is a press of the hotkey.
+    ///
+    /// Returns `true` when the event started a screenshot. Key repeats are ignored, so holding
+    /// the key down takes a single screenshot.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`ScreenshotHotkey::trigger`].
+    pub fn handle_event(
+        &mut self,
+        pixels: &mut Pixels<'_>,
+        event: &WindowEvent,
+    ) -> Result<bool, ReadbackError> {
+        match event {
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state == ElementState::Pressed
+                    && !event.repeat
+                    && event.physical_key == PhysicalKey::Code(self.key) =>
+            {
+                self.trigger(pixels)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Take a screenshot of the next rendered frame, as if the hotkey was pressed.
+    ///
+    /// Does nothing while an earlier screenshot has not been saved yet.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [`Pixels::capture_frame`].
+    pub fn trigger(&mut self, pixels: &mut Pixels<'_>) -> Result<(), ReadbackError> {
+        if self.pending.is_none() {
+            pixels.capture_frame(self.source)?;
+            self.pending = Some(SystemTime::now());
+        }
+
+        Ok(())
+    }
+
+    /// Save the screenshot once its frame has been read back.
+    ///
+    /// Call this after [`Pixels::render`]. It returns the path of the new file, or `None` if no
+    /// screenshot was taken or it is not ready yet; readbacks usually complete during the next
+    /// render, or the same one when the device is polled in between.
+    ///
+    /// # Errors
+    ///
+    /// The I/O errors from creating the directory or writing the file, and
+    /// [`io::ErrorKind::InvalidData`] when the frame has a format which
+    /// [`CapturedFrame::rgba8`](crate::CapturedFrame::rgba8) cannot convert. The screenshot is
+    /// dropped either way.
+    pub fn save(&mut self, pixels: &mut Pixels<'_>) -> io::Result<Option<PathBuf>> {
+        let Some(taken) = self.pending else {
+            return Ok(None);
+        };
+        let Some(frame) = pixels.captured_frame() else {
+            return Ok(None);
+        };
+        self.pending = None;
+
+        std::fs::create_dir_all(&self.directory)?;
+        let stem = format!("screenshot_{}", timestamp(taken));
+        let mut path = self.directory.join(format!("{stem}.png"));
+        let mut suffix = 1;
+        while path.exists() {
+            path = self.directory.join(format!("{stem}_{suffix}.png"));
+            suffix += 1;
+        }
+        crate::recorder::write_png(&path, &frame)?;
+
+        Ok(Some(path))
+    }
+}
+
+/// Format `time` as `YYYY-MM-DD_HH-MM-SS.mmm` in UTC, which sorts chronologically and is a valid
+/// file name on every platform.
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}_{:02}-{:02}-{:02}.{:03}",
+        secs_of_day / 3_600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis(),
+    )
+}
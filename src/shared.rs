@@ -0,0 +1,172 @@
+//! A triple-buffered frame for producers running on another thread.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Set on the middle slot index when it holds a frame which has not been read.
+const FRESH: usize = 0b100;
+
+/// A frame shared between one producer thread and the render thread, without either waiting on
+/// the other.
+///
+/// The frame is triple-buffered: the producer draws into one buffer, the render thread reads
+/// another, and the third holds the most recently published frame. Publishing and reading swap a
+/// buffer with the third one atomically, so the producer can run faster or slower than the
+/// display without tearing, and neither thread ever blocks on the other. Frames published faster
+/// than they are rendered are dropped, and only the newest is shown.
+///
+/// Share it with an [`Arc`](std::sync::Arc). The producer calls [`SharedFrame::write`], and the
+/// render thread presents the newest frame with
+/// [`Pixels::render_shared`](crate::Pixels::render_shared).
+///
+/// ```
+/// use pixels::SharedFrame;
+/// use std::sync::Arc;
+///
+/// let shared = Arc::new(SharedFrame::new(2 * 2 * 4));
+///
+/// // Producer
+/// let producer = Arc::clone(&shared);
+/// std::thread::spawn(move || {
+///     producer.write(|frame| frame.fill(0xff));
+/// })
+/// .join()
+/// .unwrap();
+///
+/// // Render thread
+/// let mut frame = [0; 16];
+/// assert!(shared.read(&mut frame));
+/// assert_eq!(frame, [0xff; 16]);
+/// assert!(!shared.read(&mut frame));
+/// ```
+#[derive(Debug)]
+pub struct SharedFrame {
+    slots: [Mutex<Box<[u8]>>; 3],
+    len: usize,
+    // The published slot, with `FRESH` set until it is read
+    middle: AtomicUsize,
+    // The slots owned by each side. These locks are only contended when a side is used from more
+    // than one thread at a time.
+    producer: Mutex<usize>,
+    consumer: Mutex<usize>,
+}
+
+impl SharedFrame {
+    /// Create a shared frame of `len` bytes, e.g. `width * height * 4` for an RGBA pixel buffer.
+    ///
+    /// All buffers start zeroed, and no frame is published.
+    pub fn new(len: usize) -> Self {
+        let slot = || Mutex::new(vec![0; len].into_boxed_slice());
+
+        Self {
+            slots: [slot(), slot(), slot()],
+            len,
+            middle: AtomicUsize::new(1),
+            producer: Mutex::new(0),
+            consumer: Mutex::new(2),
+        }
+    }
+
+    /// Get the length of the frame in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the frame has a length of 0 bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Draw a frame with `draw` and publish it. Called by the producer thread.
+    ///
+    /// Like [`Pixels::frame_mut`](crate::Pixels::frame_mut), the buffer passed to `draw` is _not_
+    /// cleared for you. It holds an older frame, not necessarily the one published last, so draw
+    /// the whole frame each time.
+    pub fn write<F>(&self, draw: F)
+    where
+        F: FnOnce(&mut [u8]),
+    {
+        let mut producer = self.producer.lock().unwrap();
+        draw(&mut self.slots[*producer].lock().unwrap());
+
+        let previous = self.middle.swap(*producer | FRESH, Ordering::AcqRel);
+        *producer = previous & !FRESH;
+    }
+
+    /// Copy `frame` and publish it. Called by the producer thread.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `frame` is not [`SharedFrame::len`] bytes long.
+    pub fn publish(&self, frame: &[u8]) {
+        self.write(|dst| dst.copy_from_slice(frame));
+    }
+
+    /// Copy the newest frame into `dst` if one was published since the last read. Returns `false`
+    /// and leaves `dst` alone otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `dst` is not [`SharedFrame::len`] bytes long.
+    pub fn read(&self, dst: &mut [u8]) -> bool {
+        let mut consumer = self.consumer.lock().unwrap();
+        if self.middle.load(Ordering::Acquire) & FRESH == 0 {
+            return false;
+        }
+
+        let previous = self.middle.swap(*consumer, Ordering::AcqRel);
+        *consumer = previous & !FRESH;
+        dst.copy_from_slice(&self.slots[*consumer].lock().unwrap());
+
+        true
+    }
+}
+
+/// An immutable view of the pixel buffer, taken with
+/// [`Pixels::frame_snapshot`](crate::Pixels::frame_snapshot).
+///
+/// Snapshots are cheap to take and to clone, and can be sent to other threads, e.g. for audio
+/// visualizers or video encoders which read the displayed frame while the next one is drawn and
+/// rendered. The pixel buffer is copy-on-write: the first change to the frame while a snapshot
+/// is alive copies the buffer once, and the snapshot keeps the old contents. Drop snapshots when
+/// they have been read, so that frames are not copied needlessly.
+#[derive(Clone, Debug)]
+pub struct FrameSnapshot {
+    frame: Arc<Vec<u8>>,
+    width: u32,
+    height: u32,
+}
+
+impl FrameSnapshot {
+    #[cfg(feature = "gpu")]
+    pub(crate) fn new(frame: Arc<Vec<u8>>, width: u32, height: u32) -> Self {
+        Self {
+            frame,
+            width,
+            height,
+        }
+    }
+
+    /// Get the width of the frame in pixels.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the height of the frame in pixels.
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the bytes of the frame, in the texture format of the pixel buffer.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.frame
+    }
+}
+
+impl std::ops::Deref for FrameSnapshot {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.frame
+    }
+}
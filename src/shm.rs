@@ -0,0 +1,268 @@
+//! A shared-memory frame source for producers running in another process.
+//!
+//! Emulator cores, sandboxed plugins, and other out-of-process producers can render into a region
+//! of shared memory which `pixels` presents. Access is coordinated with a seqlock: the writer makes
+//! the sequence number odd while it copies a frame, and even again when it is done. Readers never
+//! block the writer; they simply retry (or skip the frame) when a write raced with the read.
+//!
+//! The region is a slice of [`AtomicU32`] so that concurrent access from both processes is sound.
+//! Creating the mapping (e.g. with `memmap2` or `shared_memory`) and viewing it as
+//! `&[AtomicU32]` is left to the application, because it requires `unsafe` code which `pixels`
+//! forbids. The mapping must be 4-byte aligned and at least [`region_len`] words long.
+//!
+//! # Layout
+//!
+//! | Word | Contents                                 |
+//! |------|------------------------------------------|
+//! | 0    | Magic number `0x4853_5850` (`"PXSH"`)    |
+//! | 1    | Sequence number                          |
+//! | 2    | Width in pixels                          |
+//! | 3    | Height in pixels                         |
+//! | 4    | Frame length in bytes                    |
+//! | 5..8 | Reserved                                 |
+//! | 8..  | Frame bytes, packed in little-endian words |
+//!
+//! ```
+//! use pixels::shm::{region_len, SharedFrameReader, SharedFrameStatus, SharedFrameWriter};
+//! use std::sync::atomic::AtomicU32;
+//!
+//! let frame_len = 2 * 2 * 4;
+//! let region: Vec<AtomicU32> = (0..region_len(frame_len)).map(|_| AtomicU32::new(0)).collect();
+//!
+//! // Producer
+//! let writer = SharedFrameWriter::new(&region, 2, 2, frame_len)?;
+//! writer.write(&[0xff; 16]);
+//!
+//! // Consumer
+//! let mut reader = SharedFrameReader::new(&region)?;
+//! let mut frame = [0; 16];
+//! assert_eq!(reader.read(&mut frame)?, SharedFrameStatus::Updated);
+//! assert_eq!(frame, [0xff; 16]);
+//! assert_eq!(reader.read(&mut frame)?, SharedFrameStatus::Unchanged);
+//! # Ok::<(), pixels::shm::SharedFrameError>(())
+//! ```
+
+use std::sync::atomic::{fence, AtomicU32, Ordering};
+use thiserror::Error;
+
+/// Identifies a region initialized by [`SharedFrameWriter`].
+const MAGIC: u32 = u32::from_le_bytes(*b"PXSH");
+
+/// The number of words before the frame bytes.
+const HEADER_WORDS: usize = 8;
+
+const MAGIC_WORD: usize = 0;
+const SEQUENCE_WORD: usize = 1;
+const WIDTH_WORD: usize = 2;
+const HEIGHT_WORD: usize = 3;
+const LENGTH_WORD: usize = 4;
+
+/// How many times a read is retried when it races with a write.
+const READ_RETRIES: usize = 8;
+
+/// The producer side of a shared frame.
+#[derive(Debug)]
+pub struct SharedFrameWriter<'a> {
+    region: &'a [AtomicU32],
+    frame_len: usize,
+}
+
+/// The consumer side of a shared frame.
+///
+/// Pass the reader to [`Pixels::read_shared_frame`](crate::Pixels::read_shared_frame) to copy new
+/// frames into the pixel buffer.
+#[derive(Debug)]
+pub struct SharedFrameReader<'a> {
+    region: &'a [AtomicU32],
+    last_sequence: Option<u32>,
+}
+
+/// The outcome of [`SharedFrameReader::read`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SharedFrameStatus {
+    /// A new frame was copied.
+    Updated,
+    /// The writer has not published a frame since the last read; nothing was copied.
+    Unchanged,
+    /// The writer kept updating the frame during every attempt; the destination may contain a
+    /// partial frame and should not be presented.
+    Contended,
+}
+
+/// All the ways in which sharing a frame can fail.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum SharedFrameError {
+    /// The shared region is too small for the header and frame
+    #[error("Shared region is too small: expected at least {expected} words, got {actual}.")]
+    RegionTooSmall {
+        /// The minimum number of words.
+        expected: usize,
+        /// The actual number of words.
+        actual: usize,
+    },
+    /// The shared region has not been initialized by a [`SharedFrameWriter`]
+    #[error("Shared region has an invalid magic number: {0:#010x}")]
+    InvalidMagic(u32),
+    /// The frame is larger than the header can describe, 4 GiB or more
+    #[error("Shared frame is too large: {0} bytes.")]
+    FrameTooLarge(usize),
+    /// The shared frame does not match the size of the destination
+    #[error("Shared frame is {width}x{height} with {frame_len} bytes, expected {expected} bytes.")]
+    SizeMismatch {
+        /// The width of the shared frame.
+        width: u32,
+        /// The height of the shared frame.
+        height: u32,
+        /// The length of the shared frame.
+        frame_len: usize,
+        /// The length of the destination.
+        expected: usize,
+    },
+}
+
+/// The number of [`AtomicU32`] words needed to share a frame of `frame_len` bytes.
+pub const fn region_len(frame_len: usize) -> usize {
+    HEADER_WORDS + frame_len.div_ceil(4)
+}
+
+impl<'a> SharedFrameWriter<'a> {
+    /// Initialize the header of a shared region and create a writer for it.
+    ///
+    /// `frame_len` is the size of each frame in bytes, e.g. `width * height * 4` for RGBA.
+    ///
+    /// # Errors
+    ///
+    /// - [`SharedFrameError::RegionTooSmall`] when `region` is shorter than [`region_len`].
+    /// - [`SharedFrameError::FrameTooLarge`] when `frame_len` does not fit in the 32-bit length
+    ///   word of the header.
+    pub fn new(
+        region: &'a [AtomicU32],
+        width: u32,
+        height: u32,
+        frame_len: usize,
+    ) -> Result<Self, SharedFrameError> {
+        let len_word =
+            u32::try_from(frame_len).map_err(|_| SharedFrameError::FrameTooLarge(frame_len))?;
+        check_len(region, frame_len)?;
+
+        region[WIDTH_WORD].store(width, Ordering::Relaxed);
+        region[HEIGHT_WORD].store(height, Ordering::Relaxed);
+        region[LENGTH_WORD].store(len_word, Ordering::Relaxed);
+        region[MAGIC_WORD].store(MAGIC, Ordering::Release);
+
+        Ok(Self { region, frame_len })
+    }
+
+    /// Publish a frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `frame` is not exactly `frame_len` bytes long.
+    pub fn write(&self, frame: &[u8]) {
+        assert_eq!(frame.len(), self.frame_len);
+
+        let sequence = &self.region[SEQUENCE_WORD];
+        let start = sequence.load(Ordering::Relaxed);
+        sequence.store(start.wrapping_add(1) | 1, Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        let words = &self.region[HEADER_WORDS..];
+        for (word, bytes) in words.iter().zip(frame.chunks(4)) {
+            let mut le = [0; 4];
+            le[..bytes.len()].copy_from_slice(bytes);
+            word.store(u32::from_le_bytes(le), Ordering::Relaxed);
+        }
+
+        sequence.store((start | 1).wrapping_add(1), Ordering::Release);
+    }
+}
+
+impl<'a> SharedFrameReader<'a> {
+    /// Create a reader for a region initialized by a [`SharedFrameWriter`].
+    ///
+    /// # Errors
+    ///
+    /// - [`SharedFrameError::InvalidMagic`] when the header has not been initialized.
+    /// - [`SharedFrameError::RegionTooSmall`] when `region` cannot hold the frame described by the
+    ///   header.
+    pub fn new(region: &'a [AtomicU32]) -> Result<Self, SharedFrameError> {
+        check_len(region, 0)?;
+
+        let magic = region[MAGIC_WORD].load(Ordering::Acquire);
+        if magic != MAGIC {
+            return Err(SharedFrameError::InvalidMagic(magic));
+        }
+        check_len(region, region[LENGTH_WORD].load(Ordering::Relaxed) as usize)?;
+
+        Ok(Self {
+            region,
+            last_sequence: None,
+        })
+    }
+
+    /// The frame size published by the writer, in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (
+            self.region[WIDTH_WORD].load(Ordering::Relaxed),
+            self.region[HEIGHT_WORD].load(Ordering::Relaxed),
+        )
+    }
+
+    /// Copy the latest frame into `dst` if the writer published one since the last read.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SharedFrameError::SizeMismatch`] when the length of `dst` differs from the shared
+    /// frame.
+    pub fn read(&mut self, dst: &mut [u8]) -> Result<SharedFrameStatus, SharedFrameError> {
+        let frame_len = self.region[LENGTH_WORD].load(Ordering::Relaxed) as usize;
+        if dst.len() != frame_len {
+            let (width, height) = self.size();
+            return Err(SharedFrameError::SizeMismatch {
+                width,
+                height,
+                frame_len,
+                expected: dst.len(),
+            });
+        }
+
+        let sequence = &self.region[SEQUENCE_WORD];
+        for _ in 0..READ_RETRIES {
+            let start = sequence.load(Ordering::Acquire);
+            if start % 2 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            if self.last_sequence == Some(start) {
+                return Ok(SharedFrameStatus::Unchanged);
+            }
+
+            let words = &self.region[HEADER_WORDS..];
+            for (word, bytes) in words.iter().zip(dst.chunks_mut(4)) {
+                let le = word.load(Ordering::Relaxed).to_le_bytes();
+                bytes.copy_from_slice(&le[..bytes.len()]);
+            }
+
+            fence(Ordering::Acquire);
+            if sequence.load(Ordering::Relaxed) == start {
+                self.last_sequence = Some(start);
+                return Ok(SharedFrameStatus::Updated);
+            }
+        }
+
+        Ok(SharedFrameStatus::Contended)
+    }
+}
+
+fn check_len(region: &[AtomicU32], frame_len: usize) -> Result<(), SharedFrameError> {
+    let expected = region_len(frame_len);
+    if region.len() < expected {
+        return Err(SharedFrameError::RegionTooSmall {
+            expected,
+            actual: region.len(),
+        });
+    }
+
+    Ok(())
+}
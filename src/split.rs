@@ -0,0 +1,418 @@
+//! Two views drawn side by side or one above the other, for stereoscopic output and dual-screen
+//! emulators.
+
+use crate::renderers::{Fit, ScalingMatrix};
+use crate::{
+    DrawTarget, FilterMode, FrameInfo, Pixels, PixelsContext, PixelsRenderer, TextureError,
+};
+use bytemuck::{Pod, Zeroable};
+
+/// How the two views of a [`SplitRenderer`] are arranged, in the pixel buffer or on the render
+/// target.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum SplitLayout {
+    /// The first view on the left, the second on the right.
+    #[default]
+    SideBySide,
+    /// The first view at the top, the second at the bottom.
+    TopBottom,
+}
+
+impl SplitLayout {
+    /// Split a rectangle of `size` into two halves; the second one gets an odd column or row.
+    fn halves(self, size: (u32, u32)) -> [(u32, u32, u32, u32); 2] {
+        let (width, height) = size;
+        match self {
+            Self::SideBySide => {
+                let half = width / 2;
+                [(0, 0, half, height), (half, 0, width - half, height)]
+            }
+            Self::TopBottom => {
+                let half = height / 2;
+                [(0, 0, width, half), (0, half, width, height - half)]
+            }
+        }
+    }
+}
+
+/// A [`PixelsRenderer`] which draws two views into separate halves of the render target.
+///
+/// The views are either the two halves of the pixel buffer, or two [`DrawTarget`]s. Each one is
+/// scaled into its own half of the render target with the [scaling mode](Pixels::scaling_mode)
+/// of the pixel buffer, and centered there with its own border. This covers simple stereoscopic
+/// output, with one view per eye, and dual-screen handhelds which present both screens in one
+/// window.
+///
+/// The split renderer replaces the scaling pass, so it does not draw the pixel grid, selections,
+/// or [distortion meshes](crate::DistortionMesh), and buffers with a
+/// [palette](crate::PixelsBuilder::palette) or a
+/// [packed format](crate::PixelsBuilder::packed_format) show their raw values. Window
+/// coordinates of [`Pixels::window_pos_to_pixel`] follow the scaling pass, not the views.
+///
+/// ```no_run
+/// use pixels::{Pixels, SplitLayout, SplitRenderer};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(1024, 384, &window);
+/// // Both screens of a dual-screen handheld, one above the other in the pixel buffer
+/// let mut pixels = Pixels::new(256, 384, surface_texture)?;
+/// let mut screens = SplitRenderer::new(&pixels, SplitLayout::TopBottom, SplitLayout::SideBySide);
+///
+/// pixels.render_with_renderers(&mut [&mut screens])?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct SplitRenderer {
+    source: SplitSource,
+    layout: SplitLayout,
+    fit: Fit,
+    views: [SplitView; 2],
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    render_pipeline: wgpu::RenderPipeline,
+    size: (u32, u32),
+}
+
+/// The textures shown by a [`SplitRenderer`].
+#[derive(Debug)]
+enum SplitSource {
+    /// The halves of the pixel buffer texture, which is sampled anew every frame because the
+    /// texture ring rotates.
+    Frame(SplitLayout),
+    /// Two draw targets of these sizes, which are bound once when the renderer is created.
+    Targets([(u32, u32); 2]),
+}
+
+/// The GPU resources and placement of one view.
+#[derive(Debug)]
+struct SplitView {
+    uniform_buffer: wgpu::Buffer,
+    bind_group: Option<wgpu::BindGroup>,
+    viewport: (u32, u32, u32, u32),
+    scissor: (u32, u32, u32, u32),
+}
+
+/// The uniform buffer layout shared with `split.wgsl`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Pod, Zeroable)]
+struct SplitLocals {
+    transform: [f32; 16],
+    tex_rect: [f32; 4],
+}
+
+impl SplitRenderer {
+    /// Create a split renderer which shows the two halves of the pixel buffer, divided along
+    /// `split`, in two halves of the render target arranged by `layout`.
+    ///
+    /// When the pixel buffer has an odd width or height across the split, the extra column or
+    /// row belongs to the second view.
+    pub fn new(pixels: &Pixels<'_>, split: SplitLayout, layout: SplitLayout) -> Self {
+        Self::with_source(pixels, SplitSource::Frame(split), layout, None)
+    }
+
+    /// Create a split renderer which shows `first` and `second` in two halves of the render
+    /// target arranged by `layout`.
+    ///
+    /// The targets are sampled directly, so upload them with
+    /// [`Pixels::upload_target`](crate::Pixels::upload_target) after drawing on the CPU. They may
+    /// differ in size.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the texture format of a target does not match the pixel buffer.
+    pub fn with_targets(
+        pixels: &Pixels<'_>,
+        first: &DrawTarget,
+        second: &DrawTarget,
+        layout: SplitLayout,
+    ) -> Self {
+        let texture_format = pixels.context().texture_format;
+        assert_eq!(first.texture_format(), texture_format);
+        assert_eq!(second.texture_format(), texture_format);
+
+        let sizes = [first, second].map(|target| (target.width(), target.height()));
+        Self::with_source(
+            pixels,
+            SplitSource::Targets(sizes),
+            layout,
+            Some([first, second]),
+        )
+    }
+
+    fn with_source(
+        pixels: &Pixels<'_>,
+        source: SplitSource,
+        layout: SplitLayout,
+        targets: Option<[&DrawTarget; 2]>,
+    ) -> Self {
+        let device = pixels.device();
+        let module = device.create_shader_module(wgpu::include_wgsl!("../shaders/split.wgsl"));
+
+        let filter = match pixels.scaling_options.filter {
+            FilterMode::Linear => wgpu::FilterMode::Linear,
+            _ => wgpu::FilterMode::Nearest,
+        };
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("pixels_split_renderer_sampler"),
+            mag_filter: filter,
+            min_filter: filter,
+            ..Default::default()
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("pixels_split_renderer_bind_group_layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<SplitLocals>() as u64
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("pixels_split_renderer_pipeline_layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("pixels_split_renderer_pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: pixels.render_texture_format(),
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            multiview: None,
+            cache: None,
+        });
+
+        let views = [0, 1].map(|index| {
+            let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("pixels_split_renderer_uniform_buffer"),
+                size: std::mem::size_of::<SplitLocals>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = targets.map(|targets| {
+                let texture_view = targets[index]
+                    .texture()
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                create_bind_group(
+                    device,
+                    &bind_group_layout,
+                    &texture_view,
+                    &sampler,
+                    &uniform_buffer,
+                )
+            });
+
+            SplitView {
+                uniform_buffer,
+                bind_group,
+                viewport: (0, 0, 0, 0),
+                scissor: (0, 0, 0, 0),
+            }
+        });
+
+        Self {
+            source,
+            layout,
+            fit: pixels.scaling_options.fit(),
+            views,
+            bind_group_layout,
+            sampler,
+            render_pipeline,
+            size: (0, 0),
+        }
+    }
+
+    /// Get how the views are arranged on the render target.
+    pub fn layout(&self) -> SplitLayout {
+        self.layout
+    }
+
+    /// Change how the views are arranged on the render target, from the next frame.
+    ///
+    /// Dual-screen emulators use this to switch between stacked and side by side screens.
+    pub fn set_layout(&mut self, layout: SplitLayout) {
+        self.layout = layout;
+    }
+}
+
+impl PixelsRenderer for SplitRenderer {
+    fn prepare(&mut self, _info: &FrameInfo, context: &PixelsContext<'_>) {
+        let frame_size = (context.texture_extent.width, context.texture_extent.height);
+        let (texture_sizes, rects) = match self.source {
+            SplitSource::Frame(split) => ([frame_size; 2], split.halves(frame_size)),
+            SplitSource::Targets(sizes) => (sizes, sizes.map(|(w, h)| (0, 0, w, h))),
+        };
+        let frame_view = matches!(self.source, SplitSource::Frame(_)).then(|| {
+            context
+                .texture
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+
+        let outputs = self.layout.halves(self.size);
+        for (index, view) in self.views.iter_mut().enumerate() {
+            let (x, y, width, height) = rects[index];
+            let (texture_width, texture_height) = texture_sizes[index];
+            let (output_x, output_y, output_width, output_height) = outputs[index];
+
+            view.viewport = outputs[index];
+            view.scissor = (0, 0, 0, 0);
+            if width == 0 || height == 0 || output_width == 0 || output_height == 0 {
+                continue;
+            }
+
+            let matrix =
+                ScalingMatrix::new((width, height), (output_width, output_height), self.fit);
+            let (clip_x, clip_y, clip_width, clip_height) = matrix.clip_rect();
+            view.scissor = (
+                output_x + clip_x,
+                output_y + clip_y,
+                clip_width,
+                clip_height,
+            );
+
+            let locals = SplitLocals {
+                transform: *matrix.transform.as_array(),
+                tex_rect: [
+                    x as f32 / texture_width as f32,
+                    y as f32 / texture_height as f32,
+                    width as f32 / texture_width as f32,
+                    height as f32 / texture_height as f32,
+                ],
+            };
+            context
+                .queue
+                .write_buffer(&view.uniform_buffer, 0, bytemuck::bytes_of(&locals));
+
+            if let Some(frame_view) = &frame_view {
+                view.bind_group = Some(create_bind_group(
+                    &context.device,
+                    &self.bind_group_layout,
+                    frame_view,
+                    &self.sampler,
+                    &view.uniform_buffer,
+                ));
+            }
+        }
+    }
+
+    fn resize(
+        &mut self,
+        _context: &PixelsContext<'_>,
+        width: u32,
+        height: u32,
+    ) -> Result<(), TextureError> {
+        self.size = (width, height);
+        Ok(())
+    }
+
+    fn render(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        render_target: &wgpu::TextureView,
+        context: &PixelsContext<'_>,
+    ) {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("pixels_split_renderer_render_pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: render_target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(context.scaling_renderer.clear_color),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        rpass.set_pipeline(&self.render_pipeline);
+
+        for view in &self.views {
+            let Some(bind_group) = view.bind_group.as_ref() else {
+                continue;
+            };
+            let (x, y, width, height) = view.viewport;
+            let (clip_x, clip_y, clip_width, clip_height) = view.scissor;
+            if clip_width == 0 || clip_height == 0 {
+                continue;
+            }
+
+            rpass.set_viewport(x as f32, y as f32, width as f32, height as f32, 0.0, 1.0);
+            rpass.set_scissor_rect(clip_x, clip_y, clip_width, clip_height);
+            rpass.set_bind_group(0, bind_group, &[]);
+            rpass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Bind `texture_view` with the sampler and uniform buffer of one view.
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture_view: &wgpu::TextureView,
+    sampler: &wgpu::Sampler,
+    uniform_buffer: &wgpu::Buffer,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("pixels_split_renderer_bind_group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(texture_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
@@ -0,0 +1,72 @@
+//! Sprites drawn from an atlas on the GPU.
+
+/// A sprite drawn by [`Pixels::draw_sprites`](crate::Pixels::draw_sprites): a rectangle of the
+/// atlas, copied to a position in the pixel buffer.
+///
+/// ```
+/// use pixels::Sprite;
+///
+/// // The second 16x16 frame of a walk cycle, facing left
+/// let sprite = Sprite::new((100, 60), (16, 0, 16, 16)).flipped(true, false);
+/// assert!(sprite.flip_x);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct Sprite {
+    /// The position of the top-left corner in the pixel buffer, which may be outside of it.
+    pub position: (i32, i32),
+
+    /// The `(x, y, width, height)` of the sprite in the atlas, in pixels.
+    pub source: (u32, u32, u32, u32),
+
+    /// Mirror the sprite horizontally.
+    pub flip_x: bool,
+
+    /// Mirror the sprite vertically.
+    pub flip_y: bool,
+}
+
+impl Sprite {
+    /// Create a sprite showing `source` (x, y, width, height) of the atlas at `position`.
+    pub const fn new(position: (i32, i32), source: (u32, u32, u32, u32)) -> Self {
+        Self {
+            position,
+            source,
+            flip_x: false,
+            flip_y: false,
+        }
+    }
+
+    /// Mirror the sprite horizontally with `x`, and vertically with `y`.
+    pub const fn flipped(mut self, x: bool, y: bool) -> Self {
+        self.flip_x = x;
+        self.flip_y = y;
+        self
+    }
+
+    /// The `(x, y, width, height)` of the pixel buffer of `size` covered by the sprite, or
+    /// `None` when it is not visible.
+    pub(crate) fn clip_rect(&self, size: (u32, u32)) -> Option<(u32, u32, u32, u32)> {
+        let (x, y) = self.position;
+        let (_, _, width, height) = self.source;
+
+        clip_to_buffer((x, y, width, height), size)
+    }
+}
+
+/// Clip `rect` (x, y, width, height), which may start outside of a pixel buffer of `size`, to
+/// the buffer. Returns `None` when nothing of it is inside.
+pub(crate) fn clip_to_buffer(
+    rect: (i32, i32, u32, u32),
+    size: (u32, u32),
+) -> Option<(u32, u32, u32, u32)> {
+    let (x, y, width, height) = rect;
+    let (x, y) = (i64::from(x), i64::from(y));
+
+    let x0 = x.max(0);
+    let y0 = y.max(0);
+    let x1 = (x + i64::from(width)).min(i64::from(size.0));
+    let y1 = (y + i64::from(height)).min(i64::from(size.1));
+
+    (x0 < x1 && y0 < y1).then(|| (x0 as u32, y0 as u32, (x1 - x0) as u32, (y1 - y0) as u32))
+}
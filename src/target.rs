@@ -0,0 +1,159 @@
+//! Offscreen pixel buffers for render-to-texture workflows.
+
+/// Where [`Pixels::blit_target_gpu`](crate::Pixels::blit_target_gpu) draws a [`DrawTarget`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum BlitDestination<'a> {
+    /// The pixel buffer texture, after the pixel buffer is uploaded on the next render.
+    Frame,
+
+    /// The texture of another draw target.
+    Target(&'a DrawTarget),
+}
+
+/// An offscreen pixel buffer with the same texture format as a [`Pixels`](crate::Pixels) frame.
+///
+/// Draw into it on the CPU with [`DrawTarget::frame_mut`], then either copy it into the main
+/// frame with [`Pixels::blit_target`](crate::Pixels::blit_target), or upload it to its own
+/// texture with [`Pixels::upload_target`](crate::Pixels::upload_target) and composite it in a
+/// custom render pass. Useful for mirrors, portals, and cached UI panels in software renderers.
+///
+/// Create one with [`Pixels::create_target`](crate::Pixels::create_target).
+#[derive(Debug)]
+pub struct DrawTarget {
+    pixels: Vec<u8>,
+    texture: wgpu::Texture,
+    texture_extent: wgpu::Extent3d,
+    bytes_per_pixel: usize,
+}
+
+impl DrawTarget {
+    pub(crate) fn new(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+        texture_format: wgpu::TextureFormat,
+    ) -> Self {
+        assert_eq!(
+            texture_format.block_dimensions(),
+            (1, 1),
+            "draw targets do not support block compressed formats",
+        );
+
+        let texture_extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("pixels_draw_target_texture"),
+            size: texture_extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: texture_format,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC
+                | render_attachment_usage(device, texture_format),
+            view_formats: &[],
+        });
+        let bytes_per_pixel = texture_format.block_copy_size(None).unwrap_or(0) as usize;
+        let pixels = vec![0; width as usize * height as usize * bytes_per_pixel];
+
+        Self {
+            pixels,
+            texture,
+            texture_extent,
+            bytes_per_pixel,
+        }
+    }
+
+    /// Get the width of the draw target in pixels.
+    pub fn width(&self) -> u32 {
+        self.texture_extent.width
+    }
+
+    /// Get the height of the draw target in pixels.
+    pub fn height(&self) -> u32 {
+        self.texture_extent.height
+    }
+
+    /// Get the texture format of the draw target.
+    pub fn texture_format(&self) -> wgpu::TextureFormat {
+        self.texture.format()
+    }
+
+    /// Get an immutable byte slice for the draw target.
+    pub fn frame(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Get a mutable byte slice for the draw target.
+    ///
+    /// Like [`Pixels::frame_mut`](crate::Pixels::frame_mut), the buffer is _not_ cleared for you.
+    pub fn frame_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    /// Get the texture which [`Pixels::upload_target`](crate::Pixels::upload_target) copies the
+    /// draw target to.
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub(crate) fn upload(&self, queue: &wgpu::Queue) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 0, y: 0, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            &self.pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(self.texture_extent.width * self.bytes_per_pixel as u32),
+                rows_per_image: Some(self.texture_extent.height),
+            },
+            self.texture_extent,
+        );
+    }
+
+    /// Copy the draw target into `dst`, a frame of `dst_size` pixels, with its top-left corner at
+    /// `position`. Pixels outside of `dst` are skipped.
+    pub(crate) fn blit(&self, dst: &mut [u8], dst_size: (u32, u32), position: (i32, i32)) {
+        let bpp = self.bytes_per_pixel;
+        let (dst_width, dst_height) = (i64::from(dst_size.0), i64::from(dst_size.1));
+        let (x, y) = (i64::from(position.0), i64::from(position.1));
+        let (width, height) = (i64::from(self.width()), i64::from(self.height()));
+
+        // Clip the target to the destination frame
+        let x0 = x.max(0);
+        let y0 = y.max(0);
+        let x1 = (x + width).min(dst_width);
+        let y1 = (y + height).min(dst_height);
+        if x0 >= x1 || y0 >= y1 {
+            return;
+        }
+
+        let row_len = (x1 - x0) as usize * bpp;
+        for dst_y in y0..y1 {
+            let src_start = (((dst_y - y) * width + (x0 - x)) as usize) * bpp;
+            let dst_start = ((dst_y * dst_width + x0) as usize) * bpp;
+            dst[dst_start..dst_start + row_len]
+                .copy_from_slice(&self.pixels[src_start..src_start + row_len]);
+        }
+    }
+}
+
+/// The `RENDER_ATTACHMENT` usage when textures of the format can be drawn to, for GPU blits.
+pub(crate) fn render_attachment_usage(
+    device: &wgpu::Device,
+    texture_format: wgpu::TextureFormat,
+) -> wgpu::TextureUsages {
+    texture_format
+        .guaranteed_format_features(device.features())
+        .allowed_usages
+        & wgpu::TextureUsages::RENDER_ATTACHMENT
+}
@@ -0,0 +1,365 @@
+//! Character-cell text mode: grids of glyph indices with palette colors.
+
+/// A monospaced bitmap font, with one bit per pixel.
+///
+/// Glyphs are stored one after another, in rows from top to bottom, and every row is padded to
+/// whole bytes with the leftmost pixel in the most significant bit. This is the layout of VGA
+/// fonts and PC Screen Font (PSF) files after their header, so they can be loaded as they are.
+///
+/// ```
+/// use pixels::BitmapFont;
+///
+/// // Two 3x2 glyphs: a blank, and a frame
+/// let font = BitmapFont::new(3, 2, vec![0b000_00000, 0b000_00000, 0b111_00000, 0b101_00000]);
+/// assert_eq!(font.glyph_count(), 2);
+/// assert!(font.pixel(1, 0, 1));
+/// assert!(!font.pixel(1, 1, 1));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BitmapFont {
+    glyph_width: u32,
+    glyph_height: u32,
+    glyph_count: u32,
+    bitmap: Vec<u8>,
+}
+
+impl BitmapFont {
+    /// Create a font of `glyph_width` by `glyph_height` glyphs from the packed `bitmap`.
+    ///
+    /// Trailing bytes which do not make a whole glyph are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the glyph size is zero, or `bitmap` holds more than 65536 glyphs.
+    pub fn new(glyph_width: u32, glyph_height: u32, bitmap: Vec<u8>) -> Self {
+        assert!(glyph_width > 0 && glyph_height > 0);
+
+        let glyph_len = (glyph_width.div_ceil(8) * glyph_height) as usize;
+        let glyph_count = bitmap.len() / glyph_len;
+        assert!(glyph_count <= 1 << 16, "fonts hold up to 65536 glyphs");
+
+        Self {
+            glyph_width,
+            glyph_height,
+            glyph_count: glyph_count as u32,
+            bitmap,
+        }
+    }
+
+    /// Get the width of a glyph in pixels.
+    pub fn glyph_width(&self) -> u32 {
+        self.glyph_width
+    }
+
+    /// Get the height of a glyph in pixels.
+    pub fn glyph_height(&self) -> u32 {
+        self.glyph_height
+    }
+
+    /// Get the number of glyphs in the font.
+    pub fn glyph_count(&self) -> u32 {
+        self.glyph_count
+    }
+
+    /// Check if pixel `(x, y)` of `glyph` is set. Glyphs past the end of the font are blank.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `(x, y)` is outside of the glyph size.
+    pub fn pixel(&self, glyph: u16, x: u32, y: u32) -> bool {
+        assert!(x < self.glyph_width && y < self.glyph_height);
+        if u32::from(glyph) >= self.glyph_count {
+            return false;
+        }
+
+        let row_len = self.glyph_width.div_ceil(8);
+        let row = (u32::from(glyph) * self.glyph_height + y) * row_len;
+        let byte = self.bitmap[(row + x / 8) as usize];
+
+        byte & (0x80 >> (x % 8)) != 0
+    }
+}
+
+/// A character cell: a glyph with palette indices for its foreground and background.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TextCell {
+    /// The glyph index in the font.
+    pub glyph: u16,
+
+    /// The palette index of the set pixels of the glyph.
+    pub fg: u8,
+
+    /// The palette index of the other pixels of the cell.
+    pub bg: u8,
+}
+
+impl TextCell {
+    /// Create a cell showing `glyph` in `fg` on `bg`.
+    pub const fn new(glyph: u16, fg: u8, bg: u8) -> Self {
+        Self { glyph, fg, bg }
+    }
+}
+
+/// A grid of character cells, for terminal emulators, roguelikes, and other text-mode displays.
+///
+/// Update the cells, then draw the whole grid in one call: into an RGBA frame on the CPU with
+/// [`TextGrid::draw_rgba`], or on the GPU with
+/// [`Pixels::draw_text_grid`](crate::Pixels::draw_text_grid), which skips drawing glyphs pixel by
+/// pixel entirely. Both draw every cell opaquely in its background color, and look the colors
+/// up in a palette in the same way as [`blit_indexed_rgba`](crate::blit_indexed_rgba).
+///
+/// ```
+/// use pixels::{BitmapFont, TextCell, TextGrid};
+///
+/// // An 8x8 font with 256 glyphs, e.g. loaded from a file
+/// let font = BitmapFont::new(8, 8, vec![0; 8 * 256]);
+/// let palette = [[0, 0, 0, 0xff], [0xaa, 0xaa, 0xaa, 0xff], [0xff, 0xff, 0x55, 0xff]];
+///
+/// let mut grid = TextGrid::new(40, 25);
+/// grid.fill(TextCell::new(b' '.into(), 1, 0));
+/// grid.print(1, 1, "HP 12/20", 2, 0);
+/// assert_eq!(grid.cell(1, 1), TextCell::new(b'H'.into(), 2, 0));
+///
+/// let mut frame = vec![0; 320 * 200 * 4];
+/// grid.draw_rgba(&font, &palette, &mut frame, (320, 200), (0, 0));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextGrid {
+    columns: u32,
+    rows: u32,
+    cells: Vec<TextCell>,
+}
+
+impl TextGrid {
+    /// Create a grid of `columns` by `rows` default cells, showing glyph 0 in palette index 0.
+    pub fn new(columns: u32, rows: u32) -> Self {
+        Self {
+            columns,
+            rows,
+            cells: vec![TextCell::default(); columns as usize * rows as usize],
+        }
+    }
+
+    /// Get the number of columns.
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    /// Get the number of rows.
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    /// Get the cells, in rows from top to bottom.
+    pub fn cells(&self) -> &[TextCell] {
+        &self.cells
+    }
+
+    /// Get the cells mutably, in rows from top to bottom.
+    pub fn cells_mut(&mut self) -> &mut [TextCell] {
+        &mut self.cells
+    }
+
+    /// Get the cell at `(column, row)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `(column, row)` is outside of the grid.
+    pub fn cell(&self, column: u32, row: u32) -> TextCell {
+        self.cells[self.offset(column, row)]
+    }
+
+    /// Replace the cell at `(column, row)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `(column, row)` is outside of the grid.
+    pub fn set(&mut self, column: u32, row: u32, cell: TextCell) {
+        let offset = self.offset(column, row);
+        self.cells[offset] = cell;
+    }
+
+    /// Replace every cell with `cell`.
+    pub fn fill(&mut self, cell: TextCell) {
+        self.cells.fill(cell);
+    }
+
+    /// Write `text` from `(column, row)` to the right, in `fg` on `bg`.
+    ///
+    /// Each character selects the glyph of its code point, which matches fonts laid out in
+    /// ASCII or Latin-1 order; characters past glyph `0xffff` are written as `?`. Text past the
+    /// end of the row is cut off rather than wrapped.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `row` is outside of the grid.
+    pub fn print(&mut self, column: u32, row: u32, text: &str, fg: u8, bg: u8) {
+        assert!(row < self.rows);
+
+        for (column, c) in (column..self.columns).zip(text.chars()) {
+            let glyph = u16::try_from(u32::from(c)).unwrap_or(u16::from(b'?'));
+            self.set(column, row, TextCell::new(glyph, fg, bg));
+        }
+    }
+
+    /// Draw the grid with `font` into an RGBA frame of `dst_size` pixels, with its top-left
+    /// corner at `position`.
+    ///
+    /// This draws straight into a [`Pixels`](crate::Pixels) frame in an 8-bit RGBA format.
+    /// Pixels outside of `dst` are skipped. Indices past the end of `palette` are drawn as
+    /// transparent black.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `dst` is smaller than `dst_size`.
+    pub fn draw_rgba(
+        &self,
+        font: &BitmapFont,
+        palette: &[[u8; 4]],
+        dst: &mut [u8],
+        dst_size: (u32, u32),
+        position: (i32, i32),
+    ) {
+        assert!(dst.len() >= dst_size.0 as usize * dst_size.1 as usize * 4);
+
+        let (glyph_width, glyph_height) = (font.glyph_width(), font.glyph_height());
+        let (dst_width, dst_height) = (i64::from(dst_size.0), i64::from(dst_size.1));
+        let (x, y) = (i64::from(position.0), i64::from(position.1));
+        let width = i64::from(self.columns) * i64::from(glyph_width);
+        let height = i64::from(self.rows) * i64::from(glyph_height);
+        let color = |index: u8| palette.get(index as usize).copied().unwrap_or_default();
+
+        // Clip the grid to the destination frame
+        let x0 = x.max(0);
+        let y0 = y.max(0);
+        let x1 = (x + width).min(dst_width);
+        let y1 = (y + height).min(dst_height);
+
+        for dst_y in y0..y1 {
+            let grid_y = (dst_y - y) as u32;
+            let (row, glyph_y) = (grid_y / glyph_height, grid_y % glyph_height);
+            let dst_row = (dst_y * dst_width) as usize;
+            for dst_x in x0..x1 {
+                let grid_x = (dst_x - x) as u32;
+                let (column, glyph_x) = (grid_x / glyph_width, grid_x % glyph_width);
+                let cell = self.cell(column, row);
+                let index = if font.pixel(cell.glyph, glyph_x, glyph_y) {
+                    cell.fg
+                } else {
+                    cell.bg
+                };
+
+                let offset = (dst_row + dst_x as usize) * 4;
+                dst[offset..offset + 4].copy_from_slice(&color(index));
+            }
+        }
+    }
+
+    fn offset(&self, column: u32, row: u32) -> usize {
+        assert!(column < self.columns && row < self.rows);
+
+        row as usize * self.columns as usize + column as usize
+    }
+}
+
+/// A [`BitmapFont`] uploaded to the GPU, for
+/// [`Pixels::draw_text_grid`](crate::Pixels::draw_text_grid).
+///
+/// Create one with [`Pixels::create_font_atlas`](crate::Pixels::create_font_atlas), and keep it
+/// for as long as the font is used.
+#[cfg(feature = "gpu")]
+#[derive(Debug)]
+pub struct FontAtlas {
+    texture: wgpu::Texture,
+    glyph_size: (u32, u32),
+    glyph_count: u32,
+}
+
+#[cfg(feature = "gpu")]
+impl FontAtlas {
+    /// The number of glyphs in each row of the atlas texture.
+    pub(crate) const COLUMNS: u32 = 16;
+
+    /// Get the size of the atlas texture in pixels for `font`.
+    pub(crate) fn texture_size(font: &BitmapFont) -> (u32, u32) {
+        let columns = font.glyph_count().clamp(1, Self::COLUMNS);
+        let rows = font.glyph_count().div_ceil(Self::COLUMNS).max(1);
+
+        (columns * font.glyph_width(), rows * font.glyph_height())
+    }
+
+    pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue, font: &BitmapFont) -> Self {
+        let (width, height) = Self::texture_size(font);
+        let texture_extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("pixels_font_atlas_texture"),
+            size: texture_extent,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+
+        // Unpack the glyphs to one byte per pixel, 16 glyphs to a row
+        let (glyph_width, glyph_height) = (font.glyph_width(), font.glyph_height());
+        let mut pixels = vec![0; width as usize * height as usize];
+        for glyph in 0..font.glyph_count() {
+            let (row, column) = (glyph / Self::COLUMNS, glyph % Self::COLUMNS);
+            for y in 0..glyph_height {
+                let dst_y = row * glyph_height + y;
+                for x in 0..glyph_width {
+                    let dst_x = column * glyph_width + x;
+                    if font.pixel(glyph as u16, x, y) {
+                        pixels[(dst_y * width + dst_x) as usize] = 0xff;
+                    }
+                }
+            }
+        }
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(width),
+                rows_per_image: Some(height),
+            },
+            texture_extent,
+        );
+
+        Self {
+            texture,
+            glyph_size: (glyph_width, glyph_height),
+            glyph_count: font.glyph_count(),
+        }
+    }
+
+    /// Get the width of a glyph in pixels.
+    pub fn glyph_width(&self) -> u32 {
+        self.glyph_size.0
+    }
+
+    /// Get the height of a glyph in pixels.
+    pub fn glyph_height(&self) -> u32 {
+        self.glyph_size.1
+    }
+
+    /// Get the number of glyphs in the font.
+    pub fn glyph_count(&self) -> u32 {
+        self.glyph_count
+    }
+
+    pub(crate) fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+}
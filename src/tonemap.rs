@@ -0,0 +1,64 @@
+//! Mapping colors brighter than white into the range of the display.
+
+/// The curve which maps the colors of the scaled pixel buffer to the render target.
+///
+/// Float pixel buffers, e.g. `Rgba16Float`, can hold colors brighter than `1.0`. SDR render
+/// targets clamp them, which blows out highlights; a tonemapping curve compresses them into range
+/// instead. [HDR](crate::PixelsBuilder::hdr) render targets show them brighter than SDR white, so
+/// they look right without a curve.
+///
+/// Colors are multiplied by the [exposure](crate::Pixels::set_exposure) first. Both apply in the
+/// scaling pass, after the [shader hook](crate::PixelsBuilder::shader_hook) and before flashes and
+/// the [gamma ramp](crate::PixelsBuilder::gamma_ramp), to colors in linear light on sRGB and float
+/// render targets.
+///
+/// ```no_run
+/// use pixels::{PixelsBuilder, Tonemapping};
+///
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(320, 240, &window);
+/// // Light values up to 4x SDR white, shown on any display
+/// let pixels = PixelsBuilder::new(320, 240, surface_texture)
+///     .texture_format(pixels::wgpu::TextureFormat::Rgba16Float)
+///     .tonemapping(Tonemapping::Reinhard { white: 4.0 })
+///     .build()?;
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Tonemapping {
+    /// Keep colors as they are.
+    #[default]
+    None,
+
+    /// The extended Reinhard curve, applied to each channel, which maps `white` to `1.0`.
+    ///
+    /// Dark colors barely change, and colors approach white smoothly. Colors above `white` are
+    /// clamped.
+    Reinhard {
+        /// The brightest color to keep, which must be positive.
+        white: f32,
+    },
+
+    /// A fit of the filmic ACES curve, which adds contrast and desaturates the brightest colors.
+    ///
+    /// The curve darkens SDR white to about `0.8`; raise the exposure to compensate.
+    Aces,
+}
+
+impl Tonemapping {
+    /// The `tonemap` field of the `Locals` uniform in `scale.wgsl`.
+    pub(crate) fn uniform(self, exposure: f32) -> [f32; 4] {
+        match self {
+            Self::None => [exposure, 0.0, 1.0, 0.0],
+            Self::Reinhard { white } => [exposure, 1.0, white, 0.0],
+            Self::Aces => [exposure, 2.0, 1.0, 0.0],
+        }
+    }
+
+    /// Panics when the curve parameters are out of range.
+    pub(crate) fn validate(self) {
+        if let Self::Reinhard { white } = self {
+            assert!(white > 0.0, "the Reinhard white point must be positive");
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Mapping between window coordinates and pixel buffer coordinates.
+
+/// The mapping between physical window coordinates and pixels of the scaled pixel buffer.
+///
+/// This covers the letterbox border, the [scaling mode](crate::ScalingMode), the
+/// [rotation](crate::Pixels::set_rotation) and [flip](crate::Pixels::set_flip), the
+/// [view](crate::Pixels::view) of the pixel buffer, and
+/// [supersampling](crate::PixelsBuilder::supersampling), so mouse picking and overlays agree with
+/// what the scaling pass draws. Pixel coordinates are fractional, with `(0.0, 0.0)` at the
+/// top-left corner of the pixel buffer; the center of pixel `(x, y)` is at `(x + 0.5, y + 0.5)`.
+///
+/// A transform is a snapshot: get a new one with [`Pixels::transform`](crate::Pixels::transform)
+/// after resizing the buffer or the surface, or changing the scaling mode. Window scale factor
+/// changes resize the surface, and logical positions are converted with the current scale factor
+/// by the `winit` helpers.
+///
+/// ```no_run
+/// # use pixels::Pixels;
+/// # let window = pixels_mocks::Window;
+/// # let surface_texture = pixels::SurfaceTexture::new(640, 480, &window);
+/// let pixels = Pixels::new(320, 240, surface_texture)?;
+/// let transform = pixels.transform();
+///
+/// // Round trip the center of a pixel through window coordinates
+/// let center = transform.pixel_to_window_pos((10.5, 20.5));
+/// assert_eq!(transform.window_pos_to_pixel(center), Ok((10, 20)));
+/// # Ok::<(), pixels::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Transform {
+    // Between the render target, which is the surface scaled by `factor`, and texture pixels
+    window_to_pixel: ultraviolet::Mat4,
+    pixel_to_window: ultraviolet::Mat4,
+    factor: f32,
+    // The visible rectangle of the pixel buffer, see `Pixels::view`
+    view: (u32, u32, u32, u32),
+    // In pixels of the render target
+    clip_rect: (u32, u32, u32, u32),
+}
+
+impl Transform {
+    pub(crate) fn new(
+        window_to_pixel: ultraviolet::Mat4,
+        factor: u32,
+        view: (u32, u32, u32, u32),
+        clip_rect: (u32, u32, u32, u32),
+    ) -> Self {
+        Self {
+            window_to_pixel,
+            pixel_to_window: window_to_pixel.inversed(),
+            factor: factor as f32,
+            view,
+            clip_rect,
+        }
+    }
+
+    /// Calculate the pixel location from a physical location on the window.
+    ///
+    /// Returns an `Err` with the pixel coordinates outside of the pixel buffer when the position
+    /// is not on it, like [`Pixels::window_pos_to_pixel`](crate::Pixels::window_pos_to_pixel).
+    /// Pixels outside of the [view](crate::Pixels::view) are not on the surface, so they are
+    /// returned as errors as well.
+    pub fn window_pos_to_pixel(
+        &self,
+        physical_position: (f32, f32),
+    ) -> Result<(usize, usize), (isize, isize)> {
+        let pos = ultraviolet::Vec4::new(
+            physical_position.0 * self.factor,
+            physical_position.1 * self.factor,
+            0.0,
+            1.0,
+        );
+        let pos = self.window_to_pixel * pos;
+
+        let pixel_x = pos.x.floor() as isize;
+        let pixel_y = pos.y.floor() as isize;
+        let (x, y, width, height) = self.view;
+        let (x0, y0) = (x as isize, y as isize);
+        let (x1, y1) = (x0 + width as isize, y0 + height as isize);
+
+        if pixel_x < x0 || pixel_x >= x1 || pixel_y < y0 || pixel_y >= y1 {
+            Err((pixel_x, pixel_y))
+        } else {
+            Ok((pixel_x as usize, pixel_y as usize))
+        }
+    }
+
+    /// Calculate the physical location on the window of a position in pixel coordinates.
+    ///
+    /// Positions outside of the pixel buffer map to the border or outside of the window.
+    pub fn pixel_to_window_pos(&self, pixel_position: (f32, f32)) -> (f32, f32) {
+        let pos = ultraviolet::Vec4::new(pixel_position.0, pixel_position.1, 0.0, 1.0);
+        let pos = self.pixel_to_window * pos;
+
+        (pos.x / self.factor, pos.y / self.factor)
+    }
+
+    /// Calculate the physical `(x, y, width, height)` on the window of a rectangle of pixels, e.g.
+    /// a text caret drawn in the frame.
+    ///
+    /// The rectangle covers the whole pixels from `(x, y)` to `(x + width, y + height)`. The
+    /// window rectangle starts at its top-left corner, whichever corner of the pixel rectangle
+    /// it is after a rotation or a flip.
+    pub fn pixel_rect_to_window_rect(&self, rect: (u32, u32, u32, u32)) -> (f32, f32, f32, f32) {
+        let (x, y, width, height) = rect;
+        let (x0, y0) = self.pixel_to_window_pos((x as f32, y as f32));
+        let (x1, y1) =
+            self.pixel_to_window_pos((x as f32 + width as f32, y as f32 + height as f32));
+
+        (x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs())
+    }
+
+    /// Get the width and height of one pixel of the buffer in physical pixels of the window.
+    ///
+    /// When the buffer is rotated sideways, its width runs along the height of the window.
+    pub fn scale(&self) -> (f32, f32) {
+        let x_axis = self.pixel_to_window.cols[0];
+        let y_axis = self.pixel_to_window.cols[1];
+
+        (
+            x_axis.x.abs().max(x_axis.y.abs()) / self.factor,
+            y_axis.x.abs().max(y_axis.y.abs()) / self.factor,
+        )
+    }
+
+    /// Get the `(x, y, width, height)` of the visible part of the scaled pixel buffer, in
+    /// physical pixels of the window.
+    ///
+    /// With supersampling, the edges are rounded outward to whole physical pixels. See
+    /// [`Pixels::safe_area`](crate::Pixels::safe_area) for the exact area and the borders around
+    /// it.
+    pub fn clip_rect(&self) -> (u32, u32, u32, u32) {
+        let factor = self.factor as u32;
+        let (x, y, width, height) = self.clip_rect;
+        let (x0, y0) = (x / factor, y / factor);
+        let x1 = (x + width).div_ceil(factor);
+        let y1 = (y + height).div_ceil(factor);
+
+        (x0, y0, x1 - x0, y1 - y0)
+    }
+
+    /// Calculate the pixel location from a `winit` position, such as the cursor position of
+    /// [`WindowEvent::CursorMoved`](winit::event::WindowEvent::CursorMoved).
+    ///
+    /// Logical positions are converted with `scale_factor`, the current
+    /// [`Window::scale_factor`](winit::window::Window::scale_factor); it is ignored for physical
+    /// positions.
+    ///
+    /// ```no_run
+    /// use winit::event::WindowEvent;
+    ///
+    /// # use pixels::Pixels;
+    /// # let window = pixels_mocks::Window;
+    /// # let surface_texture = pixels::SurfaceTexture::new(640, 480, &window);
+    /// # let event: WindowEvent = unimplemented!();
+    /// let pixels = Pixels::new(320, 240, surface_texture)?;
+    ///
+    /// if let WindowEvent::CursorMoved { position, .. } = event {
+    ///     if let Ok((x, y)) = pixels.transform().position_to_pixel(position, 1.0) {
+    ///         println!("Hovering over pixel ({x}, {y})");
+    ///     }
+    /// }
+    /// # Ok::<(), pixels::Error>(())
+    /// ```
+    #[cfg(feature = "winit")]
+    pub fn position_to_pixel(
+        &self,
+        position: impl Into<winit::dpi::Position>,
+        scale_factor: f64,
+    ) -> Result<(usize, usize), (isize, isize)> {
+        let position = position.into().to_physical::<f32>(scale_factor);
+
+        self.window_pos_to_pixel((position.x, position.y))
+    }
+
+    /// Calculate the physical `winit` position of a position in pixel coordinates, e.g. to place
+    /// an IME candidate window with [`Window::set_ime_cursor_area`].
+    ///
+    /// Convert the result with [`PhysicalPosition::to_logical`] for logical coordinates.
+    ///
+    /// [`Window::set_ime_cursor_area`]: winit::window::Window::set_ime_cursor_area
+    /// [`PhysicalPosition::to_logical`]: winit::dpi::PhysicalPosition::to_logical
+    #[cfg(feature = "winit")]
+    pub fn pixel_to_position(
+        &self,
+        pixel_position: (f32, f32),
+    ) -> winit::dpi::PhysicalPosition<f64> {
+        let (x, y) = self.pixel_to_window_pos(pixel_position);
+
+        winit::dpi::PhysicalPosition::new(f64::from(x), f64::from(y))
+    }
+}
@@ -0,0 +1,84 @@
+//! Easing curves for animating effect parameters.
+
+use web_time::{Duration, Instant};
+
+/// How a [tween](crate::Pixels::tween_effect_param) moves from its start value to its end value.
+///
+/// ```
+/// use pixels::Easing;
+///
+/// assert_eq!(Easing::Linear.apply(0.25), 0.25);
+/// assert!(Easing::EaseIn.apply(0.25) < 0.25);
+/// assert!(Easing::EaseOut.apply(0.25) > 0.25);
+/// assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+/// assert_eq!(Easing::EaseInOut.apply(1.0), 1.0);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Easing {
+    /// At a constant rate.
+    Linear,
+    /// Starting slowly, then speeding up.
+    EaseIn,
+    /// Starting quickly, then slowing down to a stop.
+    EaseOut,
+    /// Starting and stopping slowly.
+    #[default]
+    EaseInOut,
+}
+
+impl Easing {
+    /// Map the `progress` of a tween, from 0 to 1, to how far it is from the start value to the
+    /// end value, also from 0 to 1. Progress outside of that range is clamped.
+    pub fn apply(self, progress: f32) -> f32 {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A parameter of an effect moving to a value over time.
+#[derive(Debug)]
+pub(crate) struct ParamTween {
+    param: String,
+    from: f32,
+    to: f32,
+    duration: Duration,
+    easing: Easing,
+    start: Instant,
+}
+
+impl ParamTween {
+    /// Move `param` from `from` to `to` over `duration`, starting now.
+    pub(crate) fn new(param: &str, from: f32, to: f32, duration: Duration, easing: Easing) -> Self {
+        Self {
+            param: param.to_string(),
+            from,
+            to,
+            duration,
+            easing,
+            start: Instant::now(),
+        }
+    }
+
+    pub(crate) fn param(&self) -> &str {
+        &self.param
+    }
+
+    /// Get the value for a frame drawn at `now`, and whether the tween has ended with it.
+    pub(crate) fn value(&self, now: Instant) -> (f32, bool) {
+        let elapsed = now.saturating_duration_since(self.start);
+        if elapsed >= self.duration {
+            return (self.to, true);
+        }
+
+        let progress = elapsed.as_secs_f32() / self.duration.as_secs_f32();
+        let value = self.from + (self.to - self.from) * self.easing.apply(progress);
+
+        (value, false)
+    }
+}
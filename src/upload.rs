@@ -0,0 +1,192 @@
+//! Uploading the pixel buffer through a ring of staging buffers.
+
+use std::sync::{Arc, Mutex};
+
+/// The number of staging buffers in the ring: one being written, and two in flight.
+const RING_SIZE: usize = 3;
+
+/// The result of mapping a staging buffer again, set by the `map_async` callback.
+type MapResult = Arc<Mutex<Option<Result<(), wgpu::BufferAsyncError>>>>;
+
+/// How the pixel buffer is copied to its texture on each render.
+///
+/// See [`PixelsBuilder::upload_strategy`](crate::PixelsBuilder::upload_strategy).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum UploadStrategy {
+    /// Copy with [`wgpu::Queue::write_texture`], which stages the data in memory allocated by
+    /// `wgpu` on every upload.
+    #[default]
+    WriteTexture,
+
+    /// Write into a ring of staging buffers which stay mapped between uploads, and copy them to
+    /// the texture with the commands of the render.
+    ///
+    /// Each render writes to a buffer which the GPU is not reading, while the copies of previous
+    /// frames are still in flight, so uploads overlap with rendering and nothing is allocated
+    /// per frame once the ring is full. This pays off for very large pixel buffers, e.g. 1080p
+    /// and up. A buffer is mapped again as soon as its copy is submitted; when all of them are
+    /// still in flight, the render falls back to [`UploadStrategy::WriteTexture`].
+    ///
+    /// Block compressed texture formats are always uploaded with
+    /// [`UploadStrategy::WriteTexture`].
+    StagingRing,
+}
+
+/// Staging buffers which are mapped for writing while they are not in use by the GPU.
+#[derive(Debug, Default)]
+pub(crate) struct StagingRing {
+    // Mapped buffers, ready to be written
+    free: Vec<wgpu::Buffer>,
+    // The buffer written by the frame being rendered, unmapped before it is submitted
+    current: Option<wgpu::Buffer>,
+    // Buffers which are mapped again after their copies were submitted
+    mapping: Vec<(wgpu::Buffer, MapResult)>,
+}
+
+impl StagingRing {
+    /// Copy `rect` (x, y, width, height) of `pixels`, a frame with the size of `texture`, through
+    /// the current staging buffer. Returns `false` without recording a copy when every staging
+    /// buffer is in flight.
+    pub(crate) fn write(
+        &mut self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+        pixels: &[u8],
+        rect: (u32, u32, u32, u32),
+    ) -> bool {
+        let Some(bytes_per_pixel) = texture.format().block_copy_size(None) else {
+            return false;
+        };
+        let row_len = texture.width() * bytes_per_pixel;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_row_len = row_len.div_ceil(align) * align;
+        let size = u64::from(padded_row_len) * u64::from(texture.height());
+
+        let Some(buffer) = self.acquire(device, size) else {
+            return false;
+        };
+
+        // Rows keep their layout in the frame, padded to the copy alignment
+        let (x, y, width, rows) = rect;
+        let (padded_row_len, row_len) = (u64::from(padded_row_len), row_len as usize);
+        let start = u64::from(y) * padded_row_len;
+        let end = start + u64::from(rows) * padded_row_len;
+        let (offset, len) = (
+            (x * bytes_per_pixel) as usize,
+            (width * bytes_per_pixel) as usize,
+        );
+        let mut view = buffer.slice(start..end).get_mapped_range_mut();
+        for (dst, src) in view
+            .chunks_mut(padded_row_len as usize)
+            .zip(pixels[y as usize * row_len..].chunks(row_len))
+        {
+            dst[offset..offset + len].copy_from_slice(&src[offset..offset + len]);
+        }
+        drop(view);
+
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: start + offset as u64,
+                    bytes_per_row: Some(padded_row_len as u32),
+                    rows_per_image: Some(rows),
+                },
+            },
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x, y, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width,
+                height: rows,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        true
+    }
+
+    /// Get the staging buffer of the frame being rendered, taking a mapped one from the ring.
+    fn acquire(&mut self, device: &wgpu::Device, size: u64) -> Option<&wgpu::Buffer> {
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|buffer| buffer.size() != size)
+        {
+            self.current = None;
+        }
+        if self.current.is_none() {
+            self.reclaim(device);
+
+            // Buffers of other sizes are left from before a resize
+            self.free.retain(|buffer| buffer.size() == size);
+            let buffer = match self.free.pop() {
+                Some(buffer) => buffer,
+                None if self.free.len() + self.mapping.len() < RING_SIZE => {
+                    device.create_buffer(&wgpu::BufferDescriptor {
+                        label: Some("pixels_staging_ring_buffer"),
+                        size,
+                        usage: wgpu::BufferUsages::MAP_WRITE | wgpu::BufferUsages::COPY_SRC,
+                        mapped_at_creation: true,
+                    })
+                }
+                None => return None,
+            };
+            self.current = Some(buffer);
+        }
+
+        self.current.as_ref()
+    }
+
+    /// Return the buffers which were mapped again to the ring.
+    fn reclaim(&mut self, device: &wgpu::Device) {
+        if self.mapping.is_empty() {
+            return;
+        }
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut i = 0;
+        while i < self.mapping.len() {
+            let result = self.mapping[i].1.lock().unwrap().take();
+            match result {
+                Some(result) => {
+                    let (buffer, _) = self.mapping.swap_remove(i);
+                    // Buffers which failed to map, e.g. on a lost device, are dropped
+                    if result.is_ok() {
+                        self.free.push(buffer);
+                    }
+                }
+                None => i += 1,
+            }
+        }
+    }
+
+    /// Unmap the staging buffer of the frame being rendered, before its copies are submitted.
+    pub(crate) fn unmap(&mut self) {
+        if let Some(buffer) = &self.current {
+            buffer.unmap();
+        }
+    }
+
+    /// Start mapping the staging buffer of the frame again, after its copies were submitted.
+    pub(crate) fn map(&mut self) {
+        let Some(buffer) = self.current.take() else {
+            return;
+        };
+
+        let mapped = MapResult::default();
+        let result = Arc::clone(&mapped);
+        buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Write, move |mapped| {
+                *result.lock().unwrap() = Some(mapped);
+            });
+        self.mapping.push((buffer, mapped));
+    }
+}
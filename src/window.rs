@@ -0,0 +1,111 @@
+//! Window sizes at exact integer multiples of the pixel buffer.
+//!
+//! The scaling renderer works in physical pixels: at an integer scale, every pixel of the buffer
+//! covers the same square of physical pixels, and no border is drawn. Windows sized in logical
+//! units miss that size on displays with fractional scale factors, e.g. a 320x240 buffer at 3x
+//! on a 1.25 display is 768x576 logical but 960x720 physical. The helpers here compute and
+//! request the physical size directly, so the buffer fills the window exactly.
+//!
+//! ```
+//! use pixels::window::integer_size;
+//!
+//! assert_eq!(integer_size(320, 240, 3), (960, 720));
+//! ```
+
+/// Get the physical window size which shows a `width` by `height` pixel buffer at the integer
+/// `scale`, saturating at [`u32::MAX`].
+///
+/// # Panics
+///
+/// Panics when `scale` is 0.
+pub fn integer_size(width: u32, height: u32, scale: u32) -> (u32, u32) {
+    assert!(scale > 0);
+
+    (width.saturating_mul(scale), height.saturating_mul(scale))
+}
+
+/// Get the integer multiple of a `width` by `height` pixel buffer which is closest to the physical
+/// `window_size`, at least 1x.
+///
+/// This is the size a window snaps to when its aspect ratio is locked to the buffer, see
+/// [`Pixels::lock_window_aspect`](crate::Pixels::lock_window_aspect). The scale is rounded from
+/// the smaller of the horizontal and vertical ratios, so that the snapped window is never much
+/// larger than the requested one.
+///
+/// ```
+/// use pixels::window::nearest_integer_size;
+///
+/// assert_eq!(nearest_integer_size(320, 240, (960, 720)), (960, 720));
+/// assert_eq!(nearest_integer_size(320, 240, (1000, 1000)), (960, 720));
+/// assert_eq!(nearest_integer_size(320, 240, (1200, 500)), (640, 480));
+/// assert_eq!(nearest_integer_size(320, 240, (100, 100)), (320, 240));
+/// ```
+///
+/// # Panics
+///
+/// Panics when `width` or `height` is 0.
+pub fn nearest_integer_size(width: u32, height: u32, window_size: (u32, u32)) -> (u32, u32) {
+    assert!(width > 0 && height > 0);
+
+    let ratio = (f64::from(window_size.0) / f64::from(width))
+        .min(f64::from(window_size.1) / f64::from(height));
+
+    integer_size(width, height, ratio.round().max(1.0) as u32)
+}
+
+/// Get the integer scale closest to `logical_scale` logical pixels per buffer pixel on a display
+/// with the given `scale_factor`, at least 1.
+///
+/// Use it to keep pixels about the same apparent size across displays, then pass the result to
+/// [`integer_size`] or [`request_integer_size`].
+///
+/// ```
+/// use pixels::window::scale_for_display;
+///
+/// assert_eq!(scale_for_display(3, 1.0), 3);
+/// assert_eq!(scale_for_display(3, 1.25), 4);
+/// assert_eq!(scale_for_display(1, 0.5), 1);
+/// ```
+pub fn scale_for_display(logical_scale: u32, scale_factor: f64) -> u32 {
+    (f64::from(logical_scale) * scale_factor).round().max(1.0) as u32
+}
+
+/// Request the physical inner size which shows a `width` by `height` pixel buffer at the integer
+/// `scale`.
+///
+/// When `snap` is true, resize increments are set to the buffer size as well, so that interactive
+/// resizing steps from one integer scale to the next; when false, they are cleared. Not every
+/// platform supports resize increments, and window managers are free to ignore both requests.
+///
+/// Returns the new size when it was applied immediately, like
+/// [`winit::window::Window::request_inner_size`]. Otherwise the size arrives with a
+/// [`winit::event::WindowEvent::Resized`] event, which should be handled as usual with
+/// [`Pixels::resize_surface`](crate::Pixels::resize_surface).
+///
+/// ```no_run
+/// use pixels::window::{request_integer_size, scale_for_display};
+/// use winit::window::Window;
+///
+/// fn set_scale(window: &Window, logical_scale: u32) {
+///     let scale = scale_for_display(logical_scale, window.scale_factor());
+///     request_integer_size(window, 320, 240, scale, true);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics when `scale` is 0.
+#[cfg(feature = "winit")]
+pub fn request_integer_size(
+    window: &winit::window::Window,
+    width: u32,
+    height: u32,
+    scale: u32,
+    snap: bool,
+) -> Option<winit::dpi::PhysicalSize<u32>> {
+    let (window_width, window_height) = integer_size(width, height, scale);
+    let increments = snap.then(|| winit::dpi::PhysicalSize::new(width, height));
+    window.set_resize_increments(increments);
+
+    window.request_inner_size(winit::dpi::PhysicalSize::new(window_width, window_height))
+}
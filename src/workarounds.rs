@@ -0,0 +1,108 @@
+//! Workarounds for known driver issues, selected by adapter.
+//!
+//! Some GPU drivers misbehave in ways that `wgpu` cannot detect, such as showing a black screen
+//! with certain present modes. [`KNOWN_WORKAROUNDS`] lists the issues `pixels` knows about by
+//! adapter vendor and device id. When a pixel buffer is built, [`detect`] picks the entries that
+//! match the adapter, and they are applied automatically.
+//!
+//! Applications can inspect the result with [`Pixels::workarounds`], and add or remove
+//! workarounds with [`PixelsBuilder::workaround_override`], e.g. from a setting that end users
+//! can change when they hit a device-specific issue.
+//!
+//! ```
+//! use pixels::wgpu::{AdapterInfo, Backend, DeviceType};
+//! use pixels::workarounds::{detect, Workaround};
+//!
+//! let info = AdapterInfo {
+//!     name: "Mali-G78".to_string(),
+//!     vendor: 0x13b5,
+//!     device: 0,
+//!     device_type: DeviceType::IntegratedGpu,
+//!     driver: String::new(),
+//!     driver_info: String::new(),
+//!     backend: Backend::Vulkan,
+//! };
+//! assert_eq!(detect(&info), [Workaround::FifoPresentMode]);
+//! ```
+//!
+//! [`Pixels::workarounds`]: crate::Pixels::workarounds
+//! [`PixelsBuilder::workaround_override`]: crate::PixelsBuilder::workaround_override
+
+/// A change to how `pixels` drives the GPU, which avoids a driver issue.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Workaround {
+    /// Always present with [`wgpu::PresentMode::Fifo`], ignoring the requested present mode.
+    FifoPresentMode,
+    /// Upload every frame to a single texture instead of a
+    /// [texture ring](crate::PixelsBuilder::texture_ring_size).
+    SingleTexture,
+    /// Use the [minimal pipeline](crate::Pixels::minimal_pipeline).
+    MinimalPipeline,
+}
+
+/// An entry in [`KNOWN_WORKAROUNDS`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct KnownWorkaround {
+    /// The PCI vendor id of the adapter, as in [`wgpu::AdapterInfo::vendor`].
+    pub vendor: u32,
+
+    /// The device id of the adapter, or `None` for all devices of the vendor.
+    pub device: Option<u32>,
+
+    /// The backend with the issue, or `None` for all backends.
+    pub backend: Option<wgpu::Backend>,
+
+    /// The workaround to apply.
+    pub workaround: Workaround,
+
+    /// A short description of the issue.
+    pub reason: &'static str,
+}
+
+impl KnownWorkaround {
+    /// Check if the entry applies to an adapter.
+    pub fn matches(&self, info: &wgpu::AdapterInfo) -> bool {
+        self.vendor == info.vendor
+            && self.device.map_or(true, |device| device == info.device)
+            && self.backend.map_or(true, |backend| backend == info.backend)
+    }
+}
+
+/// Arm (Mali GPUs).
+const VENDOR_ARM: u32 = 0x13b5;
+
+/// Imagination Technologies (PowerVR GPUs).
+const VENDOR_IMAGINATION: u32 = 0x1010;
+
+/// The driver issues known to `pixels`.
+pub const KNOWN_WORKAROUNDS: &[KnownWorkaround] = &[
+    KnownWorkaround {
+        vendor: VENDOR_ARM,
+        device: None,
+        backend: Some(wgpu::Backend::Vulkan),
+        workaround: Workaround::FifoPresentMode,
+        reason: "mailbox and immediate presentation can leave the surface black on Android",
+    },
+    KnownWorkaround {
+        vendor: VENDOR_IMAGINATION,
+        device: None,
+        backend: Some(wgpu::Backend::Vulkan),
+        workaround: Workaround::FifoPresentMode,
+        reason: "mailbox and immediate presentation can stall or show a black screen on Android",
+    },
+];
+
+/// Get the workarounds from [`KNOWN_WORKAROUNDS`] which apply to an adapter, without duplicates.
+pub fn detect(info: &wgpu::AdapterInfo) -> Vec<Workaround> {
+    let mut workarounds = Vec::new();
+    for known in KNOWN_WORKAROUNDS.iter().filter(|known| known.matches(info)) {
+        if !workarounds.contains(&known.workaround) {
+            workarounds.push(known.workaround);
+        }
+    }
+
+    workarounds
+}